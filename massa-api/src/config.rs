@@ -13,6 +13,12 @@ pub struct APIConfig {
     pub bind_private: SocketAddr,
     /// bind for the public API
     pub bind_public: SocketAddr,
+    /// bind for the public WebSocket subscription API, disabled if `None`
+    pub bind_public_ws: Option<SocketAddr>,
+    /// max number of simultaneous WebSocket connections
+    pub max_ws_connections: usize,
+    /// max number of active subscriptions per WebSocket connection
+    pub max_subscriptions_per_connection: usize,
     /// max argument count
     pub max_arguments: u64,
     /// max datastore value length
@@ -19,6 +19,10 @@ pub enum ApiError {
     PoolError(#[from] PoolError),
     /// too many arguments error: {0}
     TooManyArguments(String),
+    /// too many subscriptions on this connection (max {0})
+    TooManySubscriptions(usize),
+    /// subscriber lagged too far behind, {0} notifications were dropped
+    SubscriberLagged(u64),
     /// send channel error: {0}
     SendChannelError(String),
     /// receive channel error: {0}
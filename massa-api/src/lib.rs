@@ -8,20 +8,28 @@ use error::ApiError;
 use jsonrpc_core::{BoxFuture, IoHandler, Value};
 use jsonrpc_derive::rpc;
 use jsonrpc_http_server::{CloseHandle, ServerBuilder};
+use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
+use massa_archive::BlockArchive;
+use massa_consensus_exports::settings::ConsensusBroadcasts;
 use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
-use massa_execution_exports::ExecutionController;
+use massa_execution_exports::{ExecutionChannels, ExecutionController};
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressHistoryPage, AddressHistoryRequest, AddressInfo, BlockInfo, BlockNotification,
+    BlockSummary, DatastoreEntryInput, DatastoreEntryOutput, EndorsementInfo, EstimateOperation,
+    EventFilter, EventPage, EventPageRequest, LedgerEntryAtSlotInput, LedgerEntryAtSlotOutput,
+    LedgerProofInput, LedgerProofOutput, NodeStatus, OperationInfo, OperationInput,
+    OperationNotification, ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
-use massa_models::execution::ExecuteReadOnlyResponse;
+use massa_models::execution::{
+    ExecuteReadOnlyResponse, OperationEstimation, OperationTrace, OperationsSimulation,
+};
 use massa_models::node::NodeId;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
+use massa_models::stats::PeerStats;
 use massa_models::{
     address::Address,
     block::{Block, BlockId},
@@ -36,18 +44,21 @@ use massa_protocol_exports::ProtocolCommandSender;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use massa_wallet::Wallet;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
 use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tracing::{info, warn};
 
 mod config;
 mod error;
 mod private;
 mod public;
+mod pubsub;
 pub use config::APIConfig;
 
 /// Public API component
@@ -64,6 +75,8 @@ pub struct Public {
     pub protocol_command_sender: ProtocolCommandSender,
     /// Massa storage
     pub storage: Storage,
+    /// archive of final blocks, if the node runs in archive mode
+    pub archive: Option<BlockArchive>,
     /// consensus configuration (TODO: remove it, can be retrieved via an endpoint)
     pub consensus_config: ConsensusConfig,
     /// API settings
@@ -98,6 +111,25 @@ pub struct Private {
     pub node_wallet: Arc<RwLock<Wallet>>,
 }
 
+/// Active subscriptions: the connection they belong to and their stop signal
+type Subscriptions = HashMap<SubscriptionId, (usize, oneshot::Sender<()>)>;
+
+/// Public WebSocket subscription API content
+pub struct PubSub {
+    /// block graph notifications from the consensus component
+    pub consensus_broadcasts: ConsensusBroadcasts,
+    /// notifications from the execution component
+    pub execution_channels: ExecutionChannels,
+    /// API settings
+    pub api_settings: APIConfig,
+    /// active subscriptions
+    subscriptions: Arc<Mutex<Subscriptions>>,
+    /// next subscription id
+    next_subscription_id: AtomicU64,
+    /// runtime on which subscriptions are driven
+    runtime: tokio::runtime::Handle,
+}
+
 /// The API wrapper
 pub struct API<T>(T);
 
@@ -117,7 +149,7 @@ fn serve(api: impl Endpoints, url: &SocketAddr) -> StopHandle {
         .start_http(url)
         .expect("Unable to start RPC server");
 
-    let close_handle = server.close_handle();
+    let close_handle = ServerCloseHandle::Http(server.close_handle());
     let join_handle = thread::spawn(|| server.wait());
 
     StopHandle {
@@ -126,16 +158,27 @@ fn serve(api: impl Endpoints, url: &SocketAddr) -> StopHandle {
     }
 }
 
+/// Close handle of either kind of server
+enum ServerCloseHandle {
+    /// HTTP server
+    Http(CloseHandle),
+    /// WebSocket server
+    Ws(jsonrpc_ws_server::CloseHandle),
+}
+
 /// Used to be able to stop the API
 pub struct StopHandle {
-    close_handle: CloseHandle,
+    close_handle: ServerCloseHandle,
     join_handle: JoinHandle<()>,
 }
 
 impl StopHandle {
     /// stop the API gracefully
     pub fn stop(self) {
-        self.close_handle.close();
+        match self.close_handle {
+            ServerCloseHandle::Http(close_handle) => close_handle.close(),
+            ServerCloseHandle::Ws(close_handle) => close_handle.close(),
+        }
         if let Err(err) = self.join_handle.join() {
             warn!("API thread panicked: {:?}", err);
         } else {
@@ -175,6 +218,22 @@ pub trait Endpoints {
         _: Vec<ReadOnlyCall>,
     ) -> BoxFuture<Result<Vec<ExecuteReadOnlyResponse>, ApiError>>;
 
+    /// Estimate the gas cost, effects and fee of operations by executing them in read-only mode
+    /// against the latest candidate state.
+    #[rpc(name = "estimate_operation")]
+    fn estimate_operation(
+        &self,
+        _: Vec<EstimateOperation>,
+    ) -> BoxFuture<Result<Vec<OperationEstimation>, ApiError>>;
+
+    /// Execute signed operations in read-only mode, in order, as if they were included in a block,
+    /// and return their effects. The operations are not broadcast and must all be emitted from the same thread.
+    #[rpc(name = "simulate_operations")]
+    fn simulate_operations(
+        &self,
+        _: Vec<OperationInput>,
+    ) -> BoxFuture<Result<OperationsSimulation, ApiError>>;
+
     /// Remove a vector of addresses used to stake.
     /// No confirmation to expect.
     #[rpc(name = "remove_staking_addresses")]
@@ -216,6 +275,11 @@ pub trait Endpoints {
     #[rpc(name = "node_unban_by_id")]
     fn node_unban_by_id(&self, _: Vec<NodeId>) -> BoxFuture<Result<(), ApiError>>;
 
+    /// Traffic stats of every active connection: bytes and messages exchanged,
+    /// round-trip latency, connection age and bandwidth throttle hits.
+    #[rpc(name = "get_peers_stats")]
+    fn get_peers_stats(&self) -> BoxFuture<Result<Vec<PeerStats>, ApiError>>;
+
     /// Summary of the current state: time, last final blocks (hash, thread, slot, timestamp), clique count, connected nodes count.
     #[rpc(name = "get_status")]
     fn get_status(&self) -> BoxFuture<Result<NodeStatus, ApiError>>;
@@ -264,10 +328,49 @@ pub trait Endpoints {
         _: Vec<DatastoreEntryInput>,
     ) -> BoxFuture<Result<Vec<DatastoreEntryOutput>, ApiError>>;
 
+    /// Get final ledger entries (balances, bytecode and requested datastore values)
+    /// as they were after the execution of past final slots.
+    /// Only available if the node retains the ledger history of the requested slots.
+    #[rpc(name = "get_ledger_entries_at_slot")]
+    fn get_ledger_entries_at_slot(
+        &self,
+        _: Vec<LedgerEntryAtSlotInput>,
+    ) -> BoxFuture<Result<Vec<LedgerEntryAtSlotOutput>, ApiError>>;
+
+    /// Get final ledger values (balances, bytecode or datastore values)
+    /// along with their Merkle proof against the root of the final ledger at the current final slot,
+    /// or at a past final slot retained in the ledger history.
+    /// A missing value comes with a proof of non-inclusion.
+    /// The root does not commit to the async pool nor to the PoS state.
+    #[rpc(name = "get_ledger_proofs")]
+    fn get_ledger_proofs(
+        &self,
+        _: Vec<LedgerProofInput>,
+    ) -> BoxFuture<Result<Vec<LedgerProofOutput>, ApiError>>;
+
     /// Get addresses.
     #[rpc(name = "get_addresses")]
     fn get_addresses(&self, _: Vec<Address>) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>>;
 
+    /// Get a page of the final history of an address: executed operations sent or received,
+    /// roll buys and sells, and received async messages.
+    /// The `next_cursor` of a page can be given as `after` to get the next page.
+    #[rpc(name = "get_address_history")]
+    fn get_address_history(
+        &self,
+        _: AddressHistoryRequest,
+    ) -> BoxFuture<Result<AddressHistoryPage, ApiError>>;
+
+    /// Get the traces of the calls made by smart contracts to the node
+    /// during the execution of operations in final or candidate slots.
+    /// Only available if execution tracing is enabled on the node.
+    /// Gas is reported for the whole execution of each operation, not per nested call.
+    #[rpc(name = "get_operation_traces")]
+    fn get_operation_traces(
+        &self,
+        _: Vec<OperationId>,
+    ) -> BoxFuture<Result<Vec<OperationTrace>, ApiError>>;
+
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
     #[rpc(name = "send_operations")]
     fn send_operations(
@@ -286,6 +389,115 @@ pub trait Endpoints {
         &self,
         _: EventFilter,
     ) -> BoxFuture<Result<Vec<SCOutputEvent>, ApiError>>;
+
+    /// Get a page of the final events stored in the node's event archive,
+    /// filtered in the same way as `get_filtered_sc_output_event`.
+    /// The `next_cursor` of a page can be given as `after` to get the next page.
+    #[rpc(name = "get_archived_sc_output_events")]
+    fn get_archived_sc_output_events(
+        &self,
+        _: EventPageRequest,
+    ) -> BoxFuture<Result<EventPage, ApiError>>;
+}
+
+/// Exposed WebSocket subscription endpoints.
+/// Each subscription can be cancelled with the matching `unsubscribe_*` method.
+#[rpc(server)]
+pub trait PubSubEndpoints {
+    /// Connection metadata
+    type Metadata;
+
+    /// Subscribe to the blocks that become the head of their thread in the blockclique.
+    #[pubsub(
+        subscription = "new_blockclique_heads",
+        subscribe,
+        name = "subscribe_new_blockclique_heads"
+    )]
+    fn subscribe_new_blockclique_heads(&self, _: Self::Metadata, _: Subscriber<BlockNotification>);
+
+    /// Unsubscribe from blockclique heads.
+    #[pubsub(
+        subscription = "new_blockclique_heads",
+        unsubscribe,
+        name = "unsubscribe_new_blockclique_heads"
+    )]
+    fn unsubscribe_new_blockclique_heads(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+
+    /// Subscribe to the blocks that become final.
+    #[pubsub(
+        subscription = "new_final_blocks",
+        subscribe,
+        name = "subscribe_new_final_blocks"
+    )]
+    fn subscribe_new_final_blocks(&self, _: Self::Metadata, _: Subscriber<BlockNotification>);
+
+    /// Unsubscribe from final blocks.
+    #[pubsub(
+        subscription = "new_final_blocks",
+        unsubscribe,
+        name = "unsubscribe_new_final_blocks"
+    )]
+    fn unsubscribe_new_final_blocks(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+
+    /// Subscribe to the smart contract output events matching a filter.
+    /// Candidate events are sent when generated, and sent again once final.
+    #[pubsub(
+        subscription = "new_filtered_sc_output_events",
+        subscribe,
+        name = "subscribe_new_filtered_sc_output_events"
+    )]
+    fn subscribe_new_filtered_sc_output_events(
+        &self,
+        _: Self::Metadata,
+        _: Subscriber<SCOutputEvent>,
+        _: EventFilter,
+    );
+
+    /// Unsubscribe from smart contract output events.
+    #[pubsub(
+        subscription = "new_filtered_sc_output_events",
+        unsubscribe,
+        name = "unsubscribe_new_filtered_sc_output_events"
+    )]
+    fn unsubscribe_new_filtered_sc_output_events(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+
+    /// Subscribe to the inclusion of the given operations in blockclique blocks,
+    /// and to the finality of these blocks.
+    #[pubsub(
+        subscription = "operations_status",
+        subscribe,
+        name = "subscribe_operations_status"
+    )]
+    fn subscribe_operations_status(
+        &self,
+        _: Self::Metadata,
+        _: Subscriber<OperationNotification>,
+        _: Vec<OperationId>,
+    );
+
+    /// Unsubscribe from operations status.
+    #[pubsub(
+        subscription = "operations_status",
+        unsubscribe,
+        name = "unsubscribe_operations_status"
+    )]
+    fn unsubscribe_operations_status(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
 }
 
 fn wrong_api<T>() -> BoxFuture<Result<T, ApiError>> {
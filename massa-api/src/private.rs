@@ -10,16 +10,21 @@ use jsonrpc_http_server::tokio::sync::mpsc;
 use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
 use massa_execution_exports::ExecutionController;
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressHistoryPage, AddressHistoryRequest, AddressInfo, BlockInfo, BlockSummary,
+    DatastoreEntryInput, DatastoreEntryOutput, EndorsementInfo, EstimateOperation, EventFilter,
+    EventPage, EventPageRequest, LedgerEntryAtSlotInput, LedgerEntryAtSlotOutput, LedgerProofInput,
+    LedgerProofOutput, NodeStatus, OperationInfo, OperationInput, ReadOnlyBytecodeExecution,
+    ReadOnlyCall, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
-use massa_models::execution::ExecuteReadOnlyResponse;
+use massa_models::execution::{
+    ExecuteReadOnlyResponse, OperationEstimation, OperationTrace, OperationsSimulation,
+};
 use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
+use massa_models::stats::PeerStats;
 use massa_models::{
     address::Address,
     block::{Block, BlockId},
@@ -110,6 +115,20 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<_>()
     }
 
+    fn estimate_operation(
+        &self,
+        _reqs: Vec<EstimateOperation>,
+    ) -> BoxFuture<Result<Vec<OperationEstimation>, ApiError>> {
+        crate::wrong_api::<_>()
+    }
+
+    fn simulate_operations(
+        &self,
+        _: Vec<OperationInput>,
+    ) -> BoxFuture<Result<OperationsSimulation, ApiError>> {
+        crate::wrong_api::<_>()
+    }
+
     fn remove_staking_addresses(&self, addresses: Vec<Address>) -> BoxFuture<Result<(), ApiError>> {
         let node_wallet = self.0.node_wallet.clone();
         let closure = async move || {
@@ -150,6 +169,12 @@ impl Endpoints for API<Private> {
         Box::pin(closure())
     }
 
+    fn get_peers_stats(&self) -> BoxFuture<Result<Vec<PeerStats>, ApiError>> {
+        let network_command_sender = self.0.network_command_sender.clone();
+        let closure = async move || Ok(network_command_sender.get_peers_stats().await?);
+        Box::pin(closure())
+    }
+
     fn get_status(&self) -> BoxFuture<Result<NodeStatus, ApiError>> {
         crate::wrong_api::<NodeStatus>()
     }
@@ -198,10 +223,38 @@ impl Endpoints for API<Private> {
         crate::wrong_api()
     }
 
+    fn get_ledger_entries_at_slot(
+        &self,
+        _: Vec<LedgerEntryAtSlotInput>,
+    ) -> BoxFuture<Result<Vec<LedgerEntryAtSlotOutput>, ApiError>> {
+        crate::wrong_api::<Vec<LedgerEntryAtSlotOutput>>()
+    }
+
+    fn get_ledger_proofs(
+        &self,
+        _: Vec<LedgerProofInput>,
+    ) -> BoxFuture<Result<Vec<LedgerProofOutput>, ApiError>> {
+        crate::wrong_api::<Vec<LedgerProofOutput>>()
+    }
+
     fn get_addresses(&self, _: Vec<Address>) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>> {
         crate::wrong_api::<Vec<AddressInfo>>()
     }
 
+    fn get_address_history(
+        &self,
+        _: AddressHistoryRequest,
+    ) -> BoxFuture<Result<AddressHistoryPage, ApiError>> {
+        crate::wrong_api::<AddressHistoryPage>()
+    }
+
+    fn get_operation_traces(
+        &self,
+        _: Vec<OperationId>,
+    ) -> BoxFuture<Result<Vec<OperationTrace>, ApiError>> {
+        crate::wrong_api::<Vec<OperationTrace>>()
+    }
+
     fn send_operations(
         &self,
         _: Vec<OperationInput>,
@@ -216,6 +269,13 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<Vec<SCOutputEvent>>()
     }
 
+    fn get_archived_sc_output_events(
+        &self,
+        _: EventPageRequest,
+    ) -> BoxFuture<Result<EventPage, ApiError>> {
+        crate::wrong_api::<EventPage>()
+    }
+
     fn node_whitelist(&self, ips: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>> {
         let network_command_sender = self.0.network_command_sender.clone();
         let closure = async move || Ok(network_command_sender.whitelist(ips).await?);
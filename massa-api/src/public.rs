@@ -4,18 +4,22 @@ use crate::config::APIConfig;
 use crate::error::ApiError;
 use crate::{Endpoints, Public, RpcServer, StopHandle, API};
 use jsonrpc_core::BoxFuture;
+use massa_archive::BlockArchive;
 use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
 use massa_execution_exports::{
     ExecutionController, ExecutionStackElement, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
 };
 use massa_graph::DiscardReason;
 use massa_models::api::{
-    BlockGraphStatus, DatastoreEntryInput, DatastoreEntryOutput, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, SlotAmount,
+    BlockGraphStatus, DatastoreEntryInput, DatastoreEntryOutput, EstimateOperation,
+    LedgerEntryAtSlotInput, LedgerEntryAtSlotOutput, LedgerProofInput, LedgerProofOutput,
+    OperationInput, ReadOnlyBytecodeExecution, ReadOnlyCall, SlotAmount,
 };
 use massa_models::execution::ReadOnlyResult;
-use massa_models::operation::OperationDeserializer;
-use massa_models::wrapped::WrappedDeserializer;
+use massa_models::operation::{
+    Operation, OperationDeserializer, OperationSerializer, OperationType,
+};
+use massa_models::wrapped::{WrappedContent, WrappedDeserializer};
 use massa_models::{
     block::Block, endorsement::WrappedEndorsement, error::ModelsError, operation::WrappedOperation,
     timeslots,
@@ -27,21 +31,27 @@ use massa_serialization::{DeserializeError, Deserializer};
 use itertools::{izip, Itertools};
 use massa_models::{
     address::Address,
+    amount::Amount,
     api::{
-        AddressInfo, BlockInfo, BlockInfoContent, BlockSummary, EndorsementInfo, EventFilter,
-        NodeStatus, OperationInfo, TimeInterval,
+        AddressHistoryPage, AddressHistoryRequest, AddressInfo, BlockInfo, BlockInfoContent,
+        BlockSummary, EndorsementInfo, EventFilter, EventPage, EventPageRequest, NodeStatus,
+        OperationInfo, TimeInterval,
     },
     block::BlockId,
     clique::Clique,
     composite::PubkeySig,
     config::CompactConfig,
     endorsement::EndorsementId,
-    execution::ExecuteReadOnlyResponse,
+    execution::{
+        ExecuteReadOnlyResponse, OperationEstimation, OperationExecutionStatus, OperationTrace,
+        OperationsSimulation,
+    },
     node::NodeId,
     operation::OperationId,
     output_event::SCOutputEvent,
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
+    stats::PeerStats,
     timeslots::{get_latest_block_slot_at_timestamp, time_range_to_slot_range},
     version::Version,
 };
@@ -68,6 +78,7 @@ impl API<Public> {
         compensation_millis: i64,
         node_id: NodeId,
         storage: Storage,
+        archive: Option<BlockArchive>,
     ) -> Self {
         API(Public {
             consensus_command_sender,
@@ -83,6 +94,7 @@ impl API<Public> {
             execution_controller,
             selector_controller,
             storage,
+            archive,
         })
     }
 }
@@ -152,12 +164,15 @@ impl Endpoints for API<Public> {
 
             // map result
             let result = ExecuteReadOnlyResponse {
-                executed_at: result.as_ref().map_or_else(|_| Slot::new(0, 0), |v| v.slot),
+                executed_at: result
+                    .as_ref()
+                    .map_or_else(|_| Slot::new(0, 0), |v| v.out.slot),
                 result: result.as_ref().map_or_else(
                     |err| ReadOnlyResult::Error(format!("readonly call failed: {}", err)),
                     |_| ReadOnlyResult::Ok,
                 ),
-                output_events: result.map_or_else(|_| Default::default(), |mut v| v.events.take()),
+                output_events: result
+                    .map_or_else(|_| Default::default(), |mut v| v.out.events.take()),
             };
 
             res.push(result);
@@ -226,12 +241,15 @@ impl Endpoints for API<Public> {
 
             // map result
             let result = ExecuteReadOnlyResponse {
-                executed_at: result.as_ref().map_or_else(|_| Slot::new(0, 0), |v| v.slot),
+                executed_at: result
+                    .as_ref()
+                    .map_or_else(|_| Slot::new(0, 0), |v| v.out.slot),
                 result: result.as_ref().map_or_else(
                     |err| ReadOnlyResult::Error(format!("readonly call failed: {}", err)),
                     |_| ReadOnlyResult::Ok,
                 ),
-                output_events: result.map_or_else(|_| Default::default(), |mut v| v.events.take()),
+                output_events: result
+                    .map_or_else(|_| Default::default(), |mut v| v.out.events.take()),
             };
 
             res.push(result);
@@ -242,6 +260,223 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn estimate_operation(
+        &self,
+        reqs: Vec<EstimateOperation>,
+    ) -> BoxFuture<Result<Vec<OperationEstimation>, ApiError>> {
+        if reqs.len() as u64 > self.0.api_settings.max_arguments {
+            let closure =
+                async move || Err(ApiError::TooManyArguments("too many arguments".into()));
+            return Box::pin(closure());
+        }
+
+        let mut res: Vec<OperationEstimation> = Vec::with_capacity(reqs.len());
+        for EstimateOperation { op, caller_address } in reqs {
+            let caller_address = caller_address.unwrap_or_else(|| {
+                // if no addr provided, use a random one
+                Address::from_public_key(&KeyPair::generate().get_public_key())
+            });
+
+            // suggest a fee for the largest serialized size the operation can have, and its max gas
+            let content = Operation {
+                fee: Amount::MAX,
+                expire_period: u64::MAX,
+                op: op.clone(),
+            };
+            let (op_size, op_max_gas) = match Operation::new_wrapped(
+                content,
+                OperationSerializer::new(),
+                &KeyPair::generate(),
+            ) {
+                Ok(wrapped_op) => (wrapped_op.serialized_size(), wrapped_op.get_gas_usage()),
+                Err(e) => {
+                    let closure = async move || Err(ApiError::from(e));
+                    return Box::pin(closure());
+                }
+            };
+            let suggested_fee = self.0.pool_command_sender.get_suggested_fee(
+                caller_address.get_thread(self.0.consensus_config.thread_count),
+                op_size,
+                op_max_gas,
+            );
+
+            // translate request
+            let req = match op {
+                OperationType::ExecuteSC {
+                    data,
+                    max_gas,
+                    coins,
+                    gas_price,
+                } => ReadOnlyExecutionRequest {
+                    max_gas,
+                    simulated_gas_price: gas_price,
+                    target: ReadOnlyExecutionTarget::BytecodeExecution(data),
+                    call_stack: vec![ExecutionStackElement {
+                        address: caller_address,
+                        coins,
+                        owned_addresses: vec![caller_address],
+                    }],
+                },
+                OperationType::CallSC {
+                    target_addr,
+                    target_func,
+                    param,
+                    max_gas,
+                    sequential_coins,
+                    parallel_coins,
+                    gas_price,
+                } => ReadOnlyExecutionRequest {
+                    max_gas,
+                    simulated_gas_price: gas_price,
+                    target: ReadOnlyExecutionTarget::FunctionCall {
+                        target_func,
+                        target_addr,
+                        parameter: param,
+                    },
+                    call_stack: vec![
+                        ExecutionStackElement {
+                            address: caller_address,
+                            coins: Default::default(),
+                            owned_addresses: vec![caller_address],
+                        },
+                        ExecutionStackElement {
+                            address: target_addr,
+                            coins: sequential_coins.saturating_add(parallel_coins),
+                            owned_addresses: vec![target_addr],
+                        },
+                    ],
+                },
+                _ => {
+                    // nothing to execute
+                    res.push(OperationEstimation {
+                        executed_at: Slot::new(0, 0),
+                        result: ReadOnlyResult::Error(
+                            "only ExecuteSC and CallSC operations can be executed".into(),
+                        ),
+                        gas_cost: 0,
+                        ledger_changes: Vec::new(),
+                        output_events: Default::default(),
+                        suggested_fee,
+                    });
+                    continue;
+                }
+            };
+
+            // run
+            let max_gas = req.max_gas;
+            let result = self.0.execution_controller.execute_readonly_request(req);
+
+            // map result
+            let estimation = match result {
+                Ok(mut output) => OperationEstimation {
+                    executed_at: output.out.slot,
+                    result: ReadOnlyResult::Ok,
+                    gas_cost: output.gas_cost,
+                    ledger_changes: output
+                        .out
+                        .state_changes
+                        .ledger_changes
+                        .get_address_changes(),
+                    output_events: output.out.events.take(),
+                    suggested_fee,
+                },
+                Err(err) => OperationEstimation {
+                    executed_at: Slot::new(0, 0),
+                    result: ReadOnlyResult::Error(format!("readonly call failed: {}", err)),
+                    // the runtime does not report the gas used before a failure,
+                    // and the whole max gas of a failed operation is paid for
+                    gas_cost: max_gas,
+                    ledger_changes: Vec::new(),
+                    output_events: Default::default(),
+                    suggested_fee,
+                },
+            };
+
+            res.push(estimation);
+        }
+
+        // return result
+        let closure = async move || Ok(res);
+        Box::pin(closure())
+    }
+
+    fn simulate_operations(
+        &self,
+        ops: Vec<OperationInput>,
+    ) -> BoxFuture<Result<OperationsSimulation, ApiError>> {
+        if ops.len() as u64 > self.0.api_settings.max_arguments {
+            let closure =
+                async move || Err(ApiError::TooManyArguments("too many arguments".into()));
+            return Box::pin(closure());
+        }
+        let operations = match deserialize_operations(ops, &self.0.api_settings) {
+            Ok(operations) => operations,
+            Err(e) => {
+                let closure = async move || Err(e);
+                return Box::pin(closure());
+            }
+        };
+        let operation_ids: Vec<OperationId> = operations.iter().map(|op| op.id).collect();
+
+        // run the operations as if they were included in a block, nothing is broadcast
+        let req = ReadOnlyExecutionRequest {
+            max_gas: self.0.consensus_config.max_gas_per_block,
+            simulated_gas_price: Amount::zero(),
+            call_stack: Vec::new(),
+            target: ReadOnlyExecutionTarget::Operations(operations),
+        };
+        let result = self.0.execution_controller.execute_readonly_request(req);
+
+        // map result
+        let simulation = match result {
+            Ok(mut output) => OperationsSimulation {
+                executed_at: output.out.slot,
+                result: ReadOnlyResult::Ok,
+                operation_ids,
+                ledger_changes: output
+                    .out
+                    .state_changes
+                    .ledger_changes
+                    .get_address_changes(),
+                roll_changes: output
+                    .out
+                    .state_changes
+                    .pos_changes
+                    .roll_changes
+                    .into_iter()
+                    .sorted()
+                    .collect(),
+                deferred_credits: output
+                    .out
+                    .state_changes
+                    .pos_changes
+                    .deferred_credits
+                    .0
+                    .into_iter()
+                    .flat_map(|(slot, credits)| {
+                        credits
+                            .into_iter()
+                            .map(move |(address, amount)| (slot, address, amount))
+                            .sorted()
+                    })
+                    .collect(),
+                output_events: output.out.events.take(),
+            },
+            Err(err) => OperationsSimulation {
+                executed_at: Slot::new(0, 0),
+                result: ReadOnlyResult::Error(format!("simulation failed: {}", err)),
+                operation_ids,
+                ledger_changes: Vec::new(),
+                roll_changes: Vec::new(),
+                deferred_credits: Vec::new(),
+                output_events: Default::default(),
+            },
+        };
+
+        let closure = async move || Ok(simulation);
+        Box::pin(closure())
+    }
+
     fn remove_staking_addresses(&self, _: Vec<Address>) -> BoxFuture<Result<(), ApiError>> {
         crate::wrong_api::<()>()
     }
@@ -266,6 +501,10 @@ impl Endpoints for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    fn get_peers_stats(&self) -> BoxFuture<Result<Vec<PeerStats>, ApiError>> {
+        crate::wrong_api::<Vec<PeerStats>>()
+    }
+
     fn get_status(&self) -> BoxFuture<Result<NodeStatus, ApiError>> {
         let execution_controller = self.0.execution_controller.clone();
         let consensus_command_sender = self.0.consensus_command_sender.clone();
@@ -383,16 +622,39 @@ impl Endpoints for API<Public> {
                 .collect()
         };
 
+        // the ops missing from storage may have been pruned: they will be looked for in the archive
+        let archive = self.0.archive.clone();
+        let missing_ops: Vec<OperationId> = ops
+            .iter()
+            .filter(|id| !storage_info.iter().any(|(op, _)| &op.id == *id))
+            .copied()
+            .collect();
+
         // keep only the ops found in storage
+        let requested_ops = ops;
         let ops: Vec<OperationId> = storage_info.iter().map(|(op, _)| op.id).collect();
 
         // ask pool whether it carries the operations
         let in_pool = self.0.pool_command_sender.contains_operations(&ops);
 
+        // ask execution for the status of the operations that were executed
+        let execution_statuses: PreHashMap<OperationId, OperationExecutionStatus> = {
+            let all_ops: Vec<OperationId> = ops.iter().chain(missing_ops.iter()).copied().collect();
+            let statuses = self
+                .0
+                .execution_controller
+                .get_operation_execution_statuses(&all_ops);
+            all_ops
+                .into_iter()
+                .zip(statuses)
+                .filter_map(|(id, status)| status.map(|status| (id, status)))
+                .collect()
+        };
+
         let api_cfg = self.0.api_settings;
         let consensus_command_sender = self.0.consensus_command_sender.clone();
         let closure = async move || {
-            if ops.len() as u64 > api_cfg.max_arguments {
+            if requested_ops.len() as u64 > api_cfg.max_arguments {
                 return Err(ApiError::TooManyArguments("too many arguments".into()));
             }
 
@@ -420,8 +682,9 @@ impl Endpoints for API<Public> {
                     .collect()
             };
 
-            // gather all values into a vector of OperationInfo instances
-            let mut res: Vec<OperationInfo> = Vec::with_capacity(ops.len());
+            // gather all values by id
+            let mut res: PreHashMap<OperationId, OperationInfo> =
+                PreHashMap::with_capacity(requested_ops.len());
             let zipped_iterator = izip!(
                 ops.into_iter(),
                 storage_info.into_iter(),
@@ -429,17 +692,44 @@ impl Endpoints for API<Public> {
                 is_final.into_iter()
             );
             for (id, (operation, in_blocks), in_pool, is_final) in zipped_iterator {
-                res.push(OperationInfo {
+                res.insert(
                     id,
-                    operation,
-                    in_pool,
-                    is_final,
-                    in_blocks: in_blocks.into_iter().collect(),
-                });
+                    OperationInfo {
+                        id,
+                        operation,
+                        in_pool,
+                        is_final,
+                        in_blocks: in_blocks.into_iter().collect(),
+                        execution_status: execution_statuses.get(&id).cloned(),
+                    },
+                );
             }
 
-            // return values in the right order
-            Ok(res)
+            // add the archived ops, which are final by definition
+            if let Some(archive) = archive {
+                for (operation, block_id) in missing_ops
+                    .iter()
+                    .filter_map(|id| archive.get_operation(id))
+                {
+                    res.insert(
+                        operation.id,
+                        OperationInfo {
+                            id: operation.id,
+                            execution_status: execution_statuses.get(&operation.id).cloned(),
+                            operation,
+                            in_pool: false,
+                            is_final: true,
+                            in_blocks: vec![block_id],
+                        },
+                    );
+                }
+            }
+
+            // return values in the order of the request
+            Ok(requested_ops
+                .iter()
+                .filter_map(|id| res.get(id).cloned())
+                .collect())
         };
         Box::pin(closure())
     }
@@ -467,7 +757,16 @@ impl Endpoints for API<Public> {
                 .collect()
         };
 
-        // keep only the ops found in storage
+        // the endorsements missing from storage may have been pruned: they will be looked for in the archive
+        let archive = self.0.archive.clone();
+        let missing_eds: Vec<EndorsementId> = eds
+            .iter()
+            .filter(|id| !storage_info.iter().any(|(ed, _)| &ed.id == *id))
+            .copied()
+            .collect();
+
+        // keep only the endorsements found in storage
+        let requested_eds = eds;
         let eds: Vec<EndorsementId> = storage_info.iter().map(|(ed, _)| ed.id).collect();
 
         // ask pool whether it carries the operations
@@ -476,7 +775,7 @@ impl Endpoints for API<Public> {
         let consensus_command_sender = self.0.consensus_command_sender.clone();
         let api_cfg = self.0.api_settings;
         let closure = async move || {
-            if eds.len() as u64 > api_cfg.max_arguments {
+            if requested_eds.len() as u64 > api_cfg.max_arguments {
                 return Err(ApiError::TooManyArguments("too many arguments".into()));
             }
 
@@ -504,8 +803,9 @@ impl Endpoints for API<Public> {
                     .collect()
             };
 
-            // gather all values into a vector of EndorsementInfo instances
-            let mut res: Vec<EndorsementInfo> = Vec::with_capacity(eds.len());
+            // gather all values by id
+            let mut res: PreHashMap<EndorsementId, EndorsementInfo> =
+                PreHashMap::with_capacity(requested_eds.len());
             let zipped_iterator = izip!(
                 eds.into_iter(),
                 storage_info.into_iter(),
@@ -513,31 +813,67 @@ impl Endpoints for API<Public> {
                 is_final.into_iter()
             );
             for (id, (endorsement, in_blocks), in_pool, is_final) in zipped_iterator {
-                res.push(EndorsementInfo {
+                res.insert(
                     id,
-                    endorsement,
-                    in_pool,
-                    is_final,
-                    in_blocks: in_blocks.into_iter().collect(),
-                });
+                    EndorsementInfo {
+                        id,
+                        endorsement,
+                        in_pool,
+                        is_final,
+                        in_blocks: in_blocks.into_iter().collect(),
+                    },
+                );
             }
 
-            // return values in the right order
-            Ok(res)
+            // add the archived endorsements, which are final by definition
+            if let Some(archive) = archive {
+                for (endorsement, block_id) in missing_eds
+                    .iter()
+                    .filter_map(|id| archive.get_endorsement(id))
+                {
+                    res.insert(
+                        endorsement.id,
+                        EndorsementInfo {
+                            id: endorsement.id,
+                            endorsement,
+                            in_pool: false,
+                            is_final: true,
+                            in_blocks: vec![block_id],
+                        },
+                    );
+                }
+            }
+
+            // return values in the order of the request
+            Ok(requested_eds
+                .iter()
+                .filter_map(|id| res.get(id).cloned())
+                .collect())
         };
         Box::pin(closure())
     }
 
     /// gets a block. Returns None if not found
-    /// only active blocks are returned
+    /// only active blocks are returned, and archived final blocks if the node runs in archive mode
     fn get_block(&self, id: BlockId) -> BoxFuture<Result<BlockInfo, ApiError>> {
         let consensus_command_sender = self.0.consensus_command_sender.clone();
         let storage = self.0.storage.clone_without_refs();
+        let archive = self.0.archive.clone();
         let closure = async move || {
             let block = match storage.read_blocks().get(&id).cloned() {
                 Some(b) => b.content,
                 None => {
-                    return Ok(BlockInfo { id, content: None });
+                    // the block may have been pruned after becoming final
+                    let content = archive
+                        .and_then(|archive| archive.get_block(&id))
+                        .map(|block| BlockInfoContent {
+                            is_final: true,
+                            is_in_blockclique: false,
+                            is_candidate: false,
+                            is_discarded: false,
+                            block: block.content,
+                        });
+                    return Ok(BlockInfo { id, content });
                 }
             };
 
@@ -596,6 +932,7 @@ impl Endpoints for API<Public> {
     ) -> BoxFuture<Result<Vec<BlockSummary>, ApiError>> {
         let consensus_command_sender = self.0.consensus_command_sender.clone();
         let consensus_settings = self.0.consensus_config.clone();
+        let archive = self.0.archive.clone();
         let closure = async move || {
             // filter blocks from graph_export
             let (start_slot, end_slot) = time_range_to_slot_range(
@@ -638,6 +975,33 @@ impl Endpoints for API<Public> {
                     });
                 }
             }
+
+            // add the final blocks that were pruned from the graph,
+            // as long as the total count stays within `max_item_return_count`
+            let max_count = consensus_settings.max_item_return_count;
+            if let Some(archive) = archive.filter(|_| res.len() < max_count) {
+                let known_blocks: PreHashSet<BlockId> = res.iter().map(|b| b.id).collect();
+                // at most `res.len()` of them are skipped because they are still in the graph
+                let archived_blocks =
+                    archive.get_blocks_in_slot_range(start_slot, end_slot, max_count);
+                for block in archived_blocks {
+                    if res.len() >= max_count {
+                        break;
+                    }
+                    if known_blocks.contains(&block.id) {
+                        continue;
+                    }
+                    res.push(BlockSummary {
+                        id: block.id,
+                        is_final: true,
+                        is_stale: false,
+                        is_in_blockclique: false,
+                        slot: block.content.header.content.slot,
+                        creator: block.creator_address,
+                        parents: block.content.header.content.parents,
+                    });
+                }
+            }
             Ok(res)
         };
         Box::pin(closure())
@@ -666,6 +1030,37 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_ledger_entries_at_slot(
+        &self,
+        inputs: Vec<LedgerEntryAtSlotInput>,
+    ) -> BoxFuture<Result<Vec<LedgerEntryAtSlotOutput>, ApiError>> {
+        let api_cfg = self.0.api_settings;
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || {
+            let key_count: usize = inputs.iter().map(|input| input.datastore_keys.len()).sum();
+            if (inputs.len() + key_count) as u64 > api_cfg.max_arguments {
+                return Err(ApiError::TooManyArguments("too many arguments".into()));
+            }
+            Ok(execution_controller.get_final_ledger_entries_at_slot(inputs)?)
+        };
+        Box::pin(closure())
+    }
+
+    fn get_ledger_proofs(
+        &self,
+        inputs: Vec<LedgerProofInput>,
+    ) -> BoxFuture<Result<Vec<LedgerProofOutput>, ApiError>> {
+        let api_cfg = self.0.api_settings;
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || {
+            if inputs.len() as u64 > api_cfg.max_arguments {
+                return Err(ApiError::TooManyArguments("too many arguments".into()));
+            }
+            Ok(execution_controller.get_final_ledger_proofs(inputs)?)
+        };
+        Box::pin(closure())
+    }
+
     fn get_addresses(
         &self,
         addresses: Vec<Address>,
@@ -806,6 +1201,37 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_address_history(
+        &self,
+        request: AddressHistoryRequest,
+    ) -> BoxFuture<Result<AddressHistoryPage, ApiError>> {
+        if request.limit > self.0.api_settings.max_arguments {
+            let closure = async move || {
+                Err(ApiError::TooManyArguments(
+                    "too many history entries requested".into(),
+                ))
+            };
+            return Box::pin(closure());
+        }
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || Ok(execution_controller.get_address_history(request)?);
+        Box::pin(closure())
+    }
+
+    fn get_operation_traces(
+        &self,
+        ops: Vec<OperationId>,
+    ) -> BoxFuture<Result<Vec<OperationTrace>, ApiError>> {
+        if ops.len() as u64 > self.0.api_settings.max_arguments {
+            let closure =
+                async move || Err(ApiError::TooManyArguments("too many arguments".into()));
+            return Box::pin(closure());
+        }
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || Ok(execution_controller.get_operation_traces(&ops)?);
+        Box::pin(closure())
+    }
+
     fn send_operations(
         &self,
         ops: Vec<OperationInput>,
@@ -818,39 +1244,7 @@ impl Endpoints for API<Public> {
             if ops.len() as u64 > api_cfg.max_arguments {
                 return Err(ApiError::TooManyArguments("too many arguments".into()));
             }
-            let operation_deserializer = WrappedDeserializer::new(OperationDeserializer::new(
-                api_cfg.max_datastore_value_length,
-                api_cfg.max_function_name_length,
-                api_cfg.max_parameter_size,
-            ));
-            let verified_ops = ops
-                .into_iter()
-                .map(|op_input| {
-                    let mut op_serialized = Vec::new();
-                    op_serialized.extend(op_input.signature.to_bytes());
-                    op_serialized.extend(op_input.creator_public_key.to_bytes());
-                    op_serialized.extend(op_input.serialized_content);
-                    let (rest, op): (&[u8], WrappedOperation) = operation_deserializer
-                        .deserialize::<DeserializeError>(&op_serialized)
-                        .map_err(|err| {
-                            ApiError::ModelsError(ModelsError::DeserializeError(err.to_string()))
-                        })?;
-                    if rest.is_empty() {
-                        Ok(op)
-                    } else {
-                        Err(ApiError::ModelsError(ModelsError::DeserializeError(
-                            "There is data left after operation deserialization".to_owned(),
-                        )))
-                    }
-                })
-                .map(|op| match op {
-                    Ok(operation) => {
-                        operation.verify_signature()?;
-                        Ok(operation)
-                    }
-                    Err(e) => Err(e),
-                })
-                .collect::<Result<Vec<WrappedOperation>, ApiError>>()?;
+            let verified_ops = deserialize_operations(ops, &api_cfg)?;
             to_send.store_operations(verified_ops.clone());
             let ids: Vec<OperationId> = verified_ops.iter().map(|op| op.id).collect();
             cmd_sender.add_operations(to_send.clone());
@@ -880,6 +1274,24 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_archived_sc_output_events(
+        &self,
+        request: EventPageRequest,
+    ) -> BoxFuture<Result<EventPage, ApiError>> {
+        if request.limit > self.0.api_settings.max_arguments {
+            let closure = async move || {
+                Err(ApiError::TooManyArguments(
+                    "too many events requested".into(),
+                ))
+            };
+            return Box::pin(closure());
+        }
+        let execution_controller = self.0.execution_controller.clone();
+        let closure =
+            async move || Ok(execution_controller.get_archived_sc_output_events(request)?);
+        Box::pin(closure())
+    }
+
     fn node_whitelist(&self, _: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>> {
         crate::wrong_api::<()>()
     }
@@ -888,3 +1300,47 @@ impl Endpoints for API<Public> {
         crate::wrong_api::<()>()
     }
 }
+
+/// Deserializes operations received through the API and checks their signatures
+fn deserialize_operations(
+    ops: Vec<OperationInput>,
+    api_cfg: &APIConfig,
+) -> Result<Vec<WrappedOperation>, ApiError> {
+    let operation_deserializer = WrappedDeserializer::new(OperationDeserializer::new(
+        api_cfg.max_datastore_value_length,
+        api_cfg.max_function_name_length,
+        api_cfg.max_parameter_size,
+    ));
+    ops.into_iter()
+        .map(|op_input| {
+            let mut op_serialized = Vec::new();
+            Operation::serialize(
+                &op_input.signature,
+                &op_input.creator_public_key,
+                &op_input.multisig,
+                &op_input.serialized_content,
+                &mut op_serialized,
+            )
+            .map_err(ModelsError::from)?;
+            let (rest, op): (&[u8], WrappedOperation) = operation_deserializer
+                .deserialize::<DeserializeError>(&op_serialized)
+                .map_err(|err| {
+                    ApiError::ModelsError(ModelsError::DeserializeError(err.to_string()))
+                })?;
+            if rest.is_empty() {
+                Ok(op)
+            } else {
+                Err(ApiError::ModelsError(ModelsError::DeserializeError(
+                    "There is data left after operation deserialization".to_owned(),
+                )))
+            }
+        })
+        .map(|op| match op {
+            Ok(operation) => {
+                operation.verify_signature()?;
+                Ok(operation)
+            }
+            Err(e) => Err(e),
+        })
+        .collect::<Result<Vec<WrappedOperation>, ApiError>>()
+}
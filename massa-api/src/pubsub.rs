@@ -0,0 +1,386 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::config::APIConfig;
+use crate::error::ApiError;
+use crate::{PubSub, PubSubEndpoints, ServerCloseHandle, StopHandle, API};
+
+use jsonrpc_core::MetaIoHandler;
+use jsonrpc_pubsub::{typed::Subscriber, PubSubHandler, Session, SubscriptionId};
+use jsonrpc_ws_server::{RequestContext, ServerBuilder};
+use massa_consensus_exports::settings::ConsensusBroadcasts;
+use massa_execution_exports::ExecutionChannels;
+use massa_models::api::{BlockNotification, EventFilter, OperationNotification};
+use massa_models::operation::OperationId;
+use massa_models::output_event::SCOutputEvent;
+use massa_models::prehash::PreHashSet;
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::oneshot;
+use tracing::warn;
+
+impl API<PubSub> {
+    /// generate a new WebSocket subscription API
+    pub fn new(
+        consensus_broadcasts: ConsensusBroadcasts,
+        execution_channels: ExecutionChannels,
+        api_settings: APIConfig,
+    ) -> Self {
+        API(PubSub {
+            consensus_broadcasts,
+            execution_channels,
+            api_settings,
+            subscriptions: Default::default(),
+            next_subscription_id: Default::default(),
+            runtime: tokio::runtime::Handle::current(),
+        })
+    }
+
+    /// Start the WebSocket subscription API
+    pub fn serve(self, url: &SocketAddr) -> StopHandle {
+        let max_ws_connections = self.0.api_settings.max_ws_connections;
+        let mut io = PubSubHandler::new(MetaIoHandler::default());
+        io.extend_with(self.to_delegate());
+
+        let server = ServerBuilder::with_meta_extractor(io, |context: &RequestContext| {
+            Arc::new(Session::new(context.sender()))
+        })
+        .event_loop_executor(tokio::runtime::Handle::current())
+        .max_connections(max_ws_connections)
+        .max_payload(50 * 1024 * 1024)
+        .start(url)
+        .expect("Unable to start WebSocket RPC server");
+
+        let close_handle = ServerCloseHandle::Ws(server.close_handle());
+        let join_handle = thread::spawn(|| {
+            if let Err(err) = server.wait() {
+                warn!("WebSocket API server error: {}", err);
+            }
+        });
+
+        StopHandle {
+            close_handle,
+            join_handle,
+        }
+    }
+
+    /// Register a subscription and spawn the task forwarding its notifications.
+    ///
+    /// Notifications come from `receiver` (and `second_receiver` if any)
+    /// and are turned into the items sent to the subscriber by `filter_map`.
+    ///
+    /// The subscription ends when the subscriber unsubscribes, when its connection is closed,
+    /// when the connection cannot accept more outgoing messages,
+    /// or when the subscriber lags so far behind that notifications were dropped.
+    fn subscribe<N, T, F>(
+        &self,
+        session: Arc<Session>,
+        subscriber: Subscriber<T>,
+        mut receiver: broadcast::Receiver<N>,
+        mut second_receiver: Option<broadcast::Receiver<N>>,
+        mut filter_map: F,
+    ) where
+        N: Clone + Send + 'static,
+        T: Serialize + Send + 'static,
+        F: FnMut(N) -> Vec<T> + Send + 'static,
+    {
+        let max_subscriptions = self.0.api_settings.max_subscriptions_per_connection;
+        let session_key = Arc::as_ptr(&session) as usize;
+
+        // register the subscription, within the per-connection limit
+        let (id, mut stop_rx) = {
+            let mut subscriptions = self.0.subscriptions.lock();
+            let session_subscriptions = subscriptions
+                .values()
+                .filter(|(key, _stop)| *key == session_key)
+                .count();
+            if session_subscriptions >= max_subscriptions {
+                let _ = subscriber.reject(ApiError::TooManySubscriptions(max_subscriptions).into());
+                return;
+            }
+            let id =
+                SubscriptionId::Number(self.0.next_subscription_id.fetch_add(1, Ordering::Relaxed));
+            let (stop_tx, stop_rx) = oneshot::channel();
+            subscriptions.insert(id.clone(), (session_key, stop_tx));
+            (id, stop_rx)
+        };
+        let sink = match subscriber.assign_id(id.clone()) {
+            Ok(sink) => sink,
+            Err(_) => {
+                self.0.subscriptions.lock().remove(&id);
+                return;
+            }
+        };
+
+        // stop the subscription when its connection is closed
+        let subscriptions = self.0.subscriptions.clone();
+        let session_id = id.clone();
+        session.on_drop(move || {
+            if let Some((_key, stop_tx)) = subscriptions.lock().remove(&session_id) {
+                let _ = stop_tx.send(());
+            }
+        });
+
+        let subscriptions = self.0.subscriptions.clone();
+        self.0.runtime.spawn(async move {
+            loop {
+                let received = tokio::select! {
+                    _ = &mut stop_rx => break,
+                    received = recv_any(&mut receiver, &mut second_receiver) => received,
+                };
+                match received {
+                    Ok(notification) => {
+                        // the connection is closed or cannot accept more messages
+                        if filter_map(notification)
+                            .into_iter()
+                            .any(|item| sink.notify(Ok(item)).is_err())
+                        {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(count)) => {
+                        let _ = sink.notify(Err(ApiError::SubscriberLagged(count).into()));
+                        break;
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            subscriptions.lock().remove(&id);
+        });
+    }
+
+    /// Stop a subscription, returns false if it was not active
+    fn unsubscribe(&self, id: SubscriptionId) -> jsonrpc_core::Result<bool> {
+        match self.0.subscriptions.lock().remove(&id) {
+            Some((_key, stop_tx)) => {
+                let _ = stop_tx.send(());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// Receive a notification from the first receiver, or from the second one if any
+async fn recv_any<N: Clone>(
+    receiver: &mut broadcast::Receiver<N>,
+    second_receiver: &mut Option<broadcast::Receiver<N>>,
+) -> Result<N, RecvError> {
+    match second_receiver {
+        Some(second_receiver) => tokio::select! {
+            received = receiver.recv() => received,
+            received = second_receiver.recv() => received,
+        },
+        None => receiver.recv().await,
+    }
+}
+
+#[doc(hidden)]
+impl PubSubEndpoints for API<PubSub> {
+    type Metadata = Arc<Session>;
+
+    fn subscribe_new_blockclique_heads(
+        &self,
+        session: Self::Metadata,
+        subscriber: Subscriber<BlockNotification>,
+    ) {
+        let receiver = self
+            .0
+            .consensus_broadcasts
+            .blockclique_head_sender
+            .subscribe();
+        self.subscribe(session, subscriber, receiver, None, |block| vec![block]);
+    }
+
+    fn unsubscribe_new_blockclique_heads(
+        &self,
+        _: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn subscribe_new_final_blocks(
+        &self,
+        session: Self::Metadata,
+        subscriber: Subscriber<BlockNotification>,
+    ) {
+        let receiver = self.0.consensus_broadcasts.final_block_sender.subscribe();
+        self.subscribe(session, subscriber, receiver, None, |block| vec![block]);
+    }
+
+    fn unsubscribe_new_final_blocks(
+        &self,
+        _: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn subscribe_new_filtered_sc_output_events(
+        &self,
+        session: Self::Metadata,
+        subscriber: Subscriber<SCOutputEvent>,
+        filter: EventFilter,
+    ) {
+        let receiver = self.0.execution_channels.sc_output_event_sender.subscribe();
+        self.subscribe(session, subscriber, receiver, None, move |event| {
+            if filter.matches(&event) {
+                vec![event]
+            } else {
+                Vec::new()
+            }
+        });
+    }
+
+    fn unsubscribe_new_filtered_sc_output_events(
+        &self,
+        _: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.unsubscribe(id)
+    }
+
+    fn subscribe_operations_status(
+        &self,
+        session: Self::Metadata,
+        subscriber: Subscriber<OperationNotification>,
+        ops: Vec<OperationId>,
+    ) {
+        if ops.len() as u64 > self.0.api_settings.max_arguments {
+            let _ =
+                subscriber.reject(ApiError::TooManyArguments("too many arguments".into()).into());
+            return;
+        }
+        let ops: PreHashSet<OperationId> = ops.into_iter().collect();
+        let blockclique_receiver = self
+            .0
+            .consensus_broadcasts
+            .blockclique_block_sender
+            .subscribe();
+        let final_receiver = self.0.consensus_broadcasts.final_block_sender.subscribe();
+        self.subscribe(
+            session,
+            subscriber,
+            blockclique_receiver,
+            Some(final_receiver),
+            move |block: BlockNotification| {
+                block
+                    .operations
+                    .iter()
+                    .filter(|id| ops.contains(*id))
+                    .map(|id| OperationNotification {
+                        id: *id,
+                        block_id: block.id,
+                        slot: block.slot,
+                        is_final: block.is_final,
+                    })
+                    .collect()
+            },
+        );
+    }
+
+    fn unsubscribe_operations_status(
+        &self,
+        _: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.unsubscribe(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::futures::{channel::mpsc, StreamExt};
+    use massa_hash::Hash;
+    use massa_models::address::Address;
+    use massa_models::block::BlockId;
+    use massa_models::slot::Slot;
+    use massa_signature::KeyPair;
+    use std::time::Duration;
+
+    fn block_notification(seed: &str) -> BlockNotification {
+        BlockNotification {
+            id: BlockId(Hash::compute_from(seed.as_bytes())),
+            slot: Slot::new(1, 0),
+            creator: Address::from_public_key(&KeyPair::generate().get_public_key()),
+            parents: Vec::new(),
+            operations: Vec::new(),
+            is_final: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_unsubscribe_new_blockclique_heads() {
+        let consensus_broadcasts = ConsensusBroadcasts::new(16);
+        let api_settings = APIConfig {
+            draw_lookahead_period_count: 10,
+            bind_private: "127.0.0.1:0".parse().unwrap(),
+            bind_public: "127.0.0.1:0".parse().unwrap(),
+            bind_public_ws: None,
+            max_ws_connections: 1,
+            max_subscriptions_per_connection: 4,
+            max_arguments: 128,
+            max_datastore_value_length: 1_000_000,
+            max_function_name_length: 1000,
+            max_parameter_size: 10_000_000,
+        };
+        let api = API::<PubSub>::new(
+            consensus_broadcasts.clone(),
+            ExecutionChannels::new(16),
+            api_settings,
+        );
+        let (session_sender, _session_receiver) = mpsc::unbounded();
+        let session = Arc::new(Session::new(session_sender));
+
+        let (subscriber, id_receiver, mut notifications) =
+            Subscriber::new_test("new_blockclique_heads");
+        // the session is kept alive as an open connection would
+        api.subscribe_new_blockclique_heads(session.clone(), subscriber);
+        let id = id_receiver
+            .await
+            .expect("subscription id not sent")
+            .expect("subscription rejected");
+
+        // the subscriber receives the new heads sent by consensus
+        let block = block_notification("head");
+        consensus_broadcasts
+            .blockclique_head_sender
+            .send(block.clone())
+            .expect("the subscription does not listen to new heads");
+        let message = tokio::time::timeout(Duration::from_secs(1), notifications.next())
+            .await
+            .expect("no notification received")
+            .expect("subscription ended");
+        assert!(message.contains(&block.id.to_string()));
+
+        // once unsubscribed it stops listening and receives nothing more
+        assert!(api
+            .unsubscribe_new_blockclique_heads(None, id.clone())
+            .unwrap());
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while consensus_broadcasts
+                .blockclique_head_sender
+                .receiver_count()
+                > 0
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the subscription still listens to new heads");
+        assert!(consensus_broadcasts
+            .blockclique_head_sender
+            .send(block_notification("other head"))
+            .is_err());
+        let ended = tokio::time::timeout(Duration::from_secs(1), notifications.next())
+            .await
+            .expect("the subscription was not closed");
+        assert!(ended.is_none());
+        assert!(!api.unsubscribe_new_blockclique_heads(None, id).unwrap());
+    }
+}
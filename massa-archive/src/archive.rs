@@ -0,0 +1,312 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module provides an on-disk archive of final blocks.
+//!
+//! Blocks are stored in the `blocks` column family under their id.
+//! The `slots` column family maps the slot of each final block to its id.
+//! Operations are stored in the `operations` column family under their id, prefixed by the id of the including block.
+//! The `endorsements` column family maps each endorsement id to the id of the including block,
+//! the endorsement itself being read from the header of that block.
+
+use crate::ArchiveConfig;
+use massa_models::block::{BlockDeserializer, BlockId, WrappedBlock, BLOCK_ID_SIZE_BYTES};
+use massa_models::endorsement::{EndorsementId, WrappedEndorsement};
+use massa_models::operation::{OperationDeserializer, OperationId, WrappedOperation};
+use massa_models::slot::Slot;
+use massa_models::wrapped::{WrappedDeserializer, WrappedSerializer};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use std::sync::Arc;
+use tracing::warn;
+
+const BLOCKS_CF: &str = "blocks";
+const SLOTS_CF: &str = "slots";
+const OPERATIONS_CF: &str = "operations";
+const ENDORSEMENTS_CF: &str = "endorsements";
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+
+/// Handle on the on-disk archive of final blocks.
+/// Clones share the same database.
+#[derive(Clone)]
+pub struct BlockArchive {
+    db: Arc<DB>,
+    config: ArchiveConfig,
+}
+
+impl BlockArchive {
+    /// Open the archive, creating it if missing
+    pub fn new(config: ArchiveConfig) -> Self {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+
+        let db = DB::open_cf_descriptors(
+            &db_opts,
+            &config.archive_path,
+            vec![
+                ColumnFamilyDescriptor::new(BLOCKS_CF, Options::default()),
+                ColumnFamilyDescriptor::new(SLOTS_CF, Options::default()),
+                ColumnFamilyDescriptor::new(OPERATIONS_CF, Options::default()),
+                ColumnFamilyDescriptor::new(ENDORSEMENTS_CF, Options::default()),
+            ],
+        )
+        .expect(OPEN_ERROR);
+
+        BlockArchive {
+            db: Arc::new(db),
+            config,
+        }
+    }
+
+    /// Archive a final block along with its operations and endorsements
+    ///
+    /// # Arguments
+    /// * `block`: the final block
+    /// * `operations`: the operations of the block
+    pub fn store_final_block(&self, block: &WrappedBlock, operations: &[WrappedOperation]) {
+        let blocks_handle = self.db.cf_handle(BLOCKS_CF).expect(CF_ERROR);
+        let slots_handle = self.db.cf_handle(SLOTS_CF).expect(CF_ERROR);
+        let operations_handle = self.db.cf_handle(OPERATIONS_CF).expect(CF_ERROR);
+        let endorsements_handle = self.db.cf_handle(ENDORSEMENTS_CF).expect(CF_ERROR);
+        let serializer = WrappedSerializer::new();
+
+        let mut batch = WriteBatch::default();
+        let mut block_bytes = Vec::new();
+        if let Err(err) = serializer.serialize(block, &mut block_bytes) {
+            warn!(
+                "could not serialize block {} for archiving: {}",
+                block.id, err
+            );
+            return;
+        }
+        batch.put_cf(blocks_handle, block.id.to_bytes(), block_bytes);
+        batch.put_cf(
+            slots_handle,
+            block.content.header.content.slot.to_bytes_key(),
+            block.id.to_bytes(),
+        );
+        for operation in operations {
+            let mut operation_bytes = block.id.to_bytes().to_vec();
+            if let Err(err) = serializer.serialize(operation, &mut operation_bytes) {
+                warn!(
+                    "could not serialize operation {} for archiving: {}",
+                    operation.id, err
+                );
+                continue;
+            }
+            batch.put_cf(operations_handle, operation.id.to_bytes(), operation_bytes);
+        }
+        for endorsement in block.content.header.content.endorsements.iter() {
+            batch.put_cf(
+                endorsements_handle,
+                endorsement.id.to_bytes(),
+                block.id.to_bytes(),
+            );
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Deserialize an archived block
+    fn read_block(&self, bytes: &[u8]) -> Option<WrappedBlock> {
+        let deserializer = WrappedDeserializer::new(BlockDeserializer::new(
+            self.config.thread_count,
+            self.config.max_operations_per_block,
+            self.config.endorsement_count,
+        ));
+        match deserializer.deserialize::<DeserializeError>(bytes) {
+            Ok((_rest, block)) => Some(block),
+            Err(err) => {
+                warn!("could not deserialize archived block: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Get an archived block
+    pub fn get_block(&self, block_id: &BlockId) -> Option<WrappedBlock> {
+        let handle = self.db.cf_handle(BLOCKS_CF).expect(CF_ERROR);
+        let bytes = self
+            .db
+            .get_cf(handle, block_id.to_bytes())
+            .expect(CRUD_ERROR)?;
+        self.read_block(&bytes)
+    }
+
+    /// Get an archived operation along with the id of the final block including it
+    pub fn get_operation(&self, operation_id: &OperationId) -> Option<(WrappedOperation, BlockId)> {
+        let handle = self.db.cf_handle(OPERATIONS_CF).expect(CF_ERROR);
+        let bytes = self
+            .db
+            .get_cf(handle, operation_id.to_bytes())
+            .expect(CRUD_ERROR)?;
+        let block_id = BlockId::from_bytes(bytes.get(..BLOCK_ID_SIZE_BYTES)?.try_into().ok()?);
+        let deserializer = WrappedDeserializer::new(OperationDeserializer::new(
+            self.config.max_datastore_value_length,
+            self.config.max_function_name_length,
+            self.config.max_parameters_size,
+        ));
+        match deserializer.deserialize::<DeserializeError>(&bytes[BLOCK_ID_SIZE_BYTES..]) {
+            Ok((_rest, operation)) => Some((operation, block_id)),
+            Err(err) => {
+                warn!(
+                    "could not deserialize archived operation {}: {}",
+                    operation_id, err
+                );
+                None
+            }
+        }
+    }
+
+    /// Get an archived endorsement along with the id of the final block including it
+    pub fn get_endorsement(
+        &self,
+        endorsement_id: &EndorsementId,
+    ) -> Option<(WrappedEndorsement, BlockId)> {
+        let handle = self.db.cf_handle(ENDORSEMENTS_CF).expect(CF_ERROR);
+        let bytes = self
+            .db
+            .get_cf(handle, endorsement_id.to_bytes())
+            .expect(CRUD_ERROR)?;
+        let block_id = BlockId::from_bytes(bytes.as_slice().try_into().ok()?);
+        let endorsement = self
+            .get_block(&block_id)?
+            .content
+            .header
+            .content
+            .endorsements
+            .into_iter()
+            .find(|endorsement| &endorsement.id == endorsement_id)?;
+        Some((endorsement, block_id))
+    }
+
+    /// Get the archived blocks in a slot range, sorted by slot
+    ///
+    /// # Arguments
+    /// * `start`: optional start slot (included)
+    /// * `end`: optional end slot (excluded)
+    /// * `max_count`: maximum number of returned blocks
+    pub fn get_blocks_in_slot_range(
+        &self,
+        start: Option<Slot>,
+        end: Option<Slot>,
+        max_count: usize,
+    ) -> Vec<WrappedBlock> {
+        let slots_handle = self.db.cf_handle(SLOTS_CF).expect(CF_ERROR);
+        let blocks_handle = self.db.cf_handle(BLOCKS_CF).expect(CF_ERROR);
+        let start_key = start.map(|slot| slot.to_bytes_key());
+        let mode = match &start_key {
+            Some(key) => IteratorMode::From(key, Direction::Forward),
+            None => IteratorMode::Start,
+        };
+        let end_key = end.map(|slot| slot.to_bytes_key());
+
+        let mut blocks = Vec::new();
+        for (slot_key, block_id) in self.db.iterator_cf(slots_handle, mode).flatten() {
+            if blocks.len() >= max_count {
+                break;
+            }
+            if let Some(end_key) = &end_key {
+                if slot_key.as_ref() >= end_key.as_slice() {
+                    break;
+                }
+            }
+            if let Some(block) = self
+                .db
+                .get_cf(blocks_handle, &block_id)
+                .expect(CRUD_ERROR)
+                .and_then(|bytes| self.read_block(&bytes))
+            {
+                blocks.push(block);
+            }
+        }
+        blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockArchive;
+    use crate::ArchiveConfig;
+    use massa_hash::Hash;
+    use massa_models::block::{
+        Block, BlockHeader, BlockHeaderSerializer, BlockId, BlockSerializer, WrappedBlock,
+    };
+    use massa_models::endorsement::{Endorsement, EndorsementSerializer};
+    use massa_models::slot::Slot;
+    use massa_models::wrapped::WrappedContent;
+    use massa_signature::KeyPair;
+    use tempfile::TempDir;
+
+    fn create_block(keypair: &KeyPair, slot: Slot) -> WrappedBlock {
+        let endorsement = Endorsement::new_wrapped(
+            Endorsement {
+                slot,
+                index: 0,
+                endorsed_block: BlockId(Hash::compute_from("blk".as_bytes())),
+            },
+            EndorsementSerializer::new(),
+            keypair,
+        )
+        .unwrap();
+        let header = BlockHeader::new_wrapped(
+            BlockHeader {
+                slot,
+                parents: Vec::new(),
+                operation_merkle_root: Hash::compute_from(&Vec::new()),
+                endorsements: vec![endorsement],
+            },
+            BlockHeaderSerializer::new(),
+            keypair,
+        )
+        .unwrap();
+        Block::new_wrapped(
+            Block {
+                header,
+                operations: Vec::new(),
+            },
+            BlockSerializer::new(),
+            keypair,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_block_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = BlockArchive::new(ArchiveConfig {
+            archive_path: temp_dir.path().to_path_buf(),
+            thread_count: 2,
+            endorsement_count: 1,
+            max_operations_per_block: 10,
+            max_datastore_value_length: 1_000,
+            max_function_name_length: 100,
+            max_parameters_size: 1_000,
+        });
+        let keypair = KeyPair::generate();
+        let blocks: Vec<WrappedBlock> = (1..5)
+            .map(|period| create_block(&keypair, Slot::new(period, 0)))
+            .collect();
+        for block in blocks.iter() {
+            archive.store_final_block(block, &[]);
+        }
+
+        // get a block and one of its endorsements by id
+        let block = archive.get_block(&blocks[1].id).unwrap();
+        assert_eq!(block.serialized_data, blocks[1].serialized_data);
+        let endorsement = &blocks[1].content.header.content.endorsements[0];
+        let (archived, block_id) = archive.get_endorsement(&endorsement.id).unwrap();
+        assert_eq!(archived.id, endorsement.id);
+        assert_eq!(block_id, blocks[1].id);
+
+        // get blocks in a slot range
+        let ids: Vec<_> = archive
+            .get_blocks_in_slot_range(Some(Slot::new(2, 0)), Some(Slot::new(4, 0)), 10)
+            .into_iter()
+            .map(|block| block.id)
+            .collect();
+        assert_eq!(ids, vec![blocks[1].id, blocks[2].id]);
+        assert_eq!(archive.get_blocks_in_slot_range(None, None, 3).len(), 3);
+    }
+}
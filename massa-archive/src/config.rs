@@ -0,0 +1,24 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines a configuration structure containing all settings for the block archive
+
+use std::path::PathBuf;
+
+/// Block archive configuration
+#[derive(Debug, Clone)]
+pub struct ArchiveConfig {
+    /// archive db directory
+    pub archive_path: PathBuf,
+    /// thread count
+    pub thread_count: u8,
+    /// endorsement count
+    pub endorsement_count: u32,
+    /// max operations per block
+    pub max_operations_per_block: u32,
+    /// max datastore value length
+    pub max_datastore_value_length: u64,
+    /// max function name length
+    pub max_function_name_length: u16,
+    /// max parameters size
+    pub max_parameters_size: u32,
+}
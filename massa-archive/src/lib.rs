@@ -0,0 +1,27 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! # General description
+//!
+//! This crate implements an on-disk archive of final blocks, along with their operations and endorsements.
+//!
+//! Final blocks are pruned from the block graph and from storage after `force_keep_final_periods`.
+//! When the node runs in archive mode, consensus writes every new final block into the archive
+//! so that the API can keep serving them after they were pruned.
+//!
+//! # Architecture
+//!
+//! ## `archive.rs`
+//! Defines the `BlockArchive`, a cheaply clonable handle on a RocksDB database
+//! storing final blocks by id, indexed by slot, and their operations and endorsements by id.
+//!
+//! ## `config.rs`
+//! Contains the configuration of the archive.
+
+#![warn(missing_docs)]
+#![warn(unused_crate_dependencies)]
+
+mod archive;
+mod config;
+
+pub use archive::BlockArchive;
+pub use config::ArchiveConfig;
@@ -8,6 +8,7 @@ use crate::{
     message::{AsyncMessage, AsyncMessageId, AsyncMessageIdDeserializer, AsyncMessageIdSerializer},
     AsyncMessageDeserializer, AsyncMessageSerializer,
 };
+use massa_hash::{Hash, HASH_SIZE_BYTES};
 use massa_models::{error::ModelsError, slot::Slot};
 use massa_serialization::{Deserializer, Serializer};
 use nom::{multi::many0, sequence::tuple};
@@ -130,6 +131,31 @@ impl AsyncPool {
             .collect()
     }
 
+    /// Get the number of messages in the pool
+    pub fn get_message_count(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Compute a hash committing to every message of the pool, in the order of their IDs.
+    /// Used to check the integrity of a bootstrapped pool.
+    pub fn compute_hash(&self) -> Hash {
+        let id_async_message_serializer = AsyncMessageIdSerializer::new();
+        let async_message_serializer = AsyncMessageSerializer::new();
+        let mut message_hashes = Vec::with_capacity(self.messages.len() * HASH_SIZE_BYTES);
+        for (id, message) in self.messages.iter() {
+            let mut bytes = Vec::new();
+            // the messages of the pool were serializable when they entered it
+            id_async_message_serializer
+                .serialize(id, &mut bytes)
+                .expect("could not serialize async message id");
+            async_message_serializer
+                .serialize(message, &mut bytes)
+                .expect("could not serialize async message");
+            message_hashes.extend(Hash::compute_from(&bytes).to_bytes());
+        }
+        Hash::compute_from(&message_hashes)
+    }
+
     /// Used for bootstrap
     /// Take a part of the async pool starting from the next element after `last_id` and with a max length of the constant `ASYNC_POOL_PART_SIZE_MESSAGE_BYTES`.
     /// Should always follow the same behavior as the `get_ledger_part` from `FinalLedger`method.
@@ -1,10 +1,19 @@
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+use std::{collections::HashMap, net::SocketAddr, path::Path, sync::Arc, time::Duration};
 
+use futures::future::try_join_all;
 use massa_final_state::{ExecutedOpsStreamingStep, FinalState};
-use massa_ledger_exports::get_address_from_key;
+use massa_graph::{BootstrapableGraphDeserializer, BootstrapableGraphSerializer};
+use massa_hash::Hash;
+use massa_ledger_exports::{get_address_from_key, seq_balance_key, SEQ_BALANCE_IDENT};
 use massa_logging::massa_trace;
-use massa_models::version::Version;
+use massa_models::{
+    address::{Address, ADDRESS_SIZE_BYTES},
+    block::BlockId,
+    slot::Slot,
+    version::Version,
+};
 use massa_pos_exports::PoSCycleStreamingStep;
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use nom::AsBytes;
@@ -26,6 +35,9 @@ use crate::{
 /// This function will send the starting point to receive a stream of the ledger and will receive and process each part until receive a `BootstrapServerMessage::FinalStateFinished` message from the server.
 /// `next_bootstrap_message` passed as parameter must be `BootstrapClientMessage::AskFinalStatePart` enum's variant.
 /// `next_bootstrap_message` will be updated after receiving each part so that in case of connection lost we can restart from the last message we processed.
+/// The partial state is kept along with it, so the stream resumes from those cursors with the same or another server,
+/// which sends the changes that happened since the cursor slot, or `SlotTooOld` if they left its history.
+/// In the latter case the partial state is reset and `next_bootstrap_message` asks for the whole state again.
 async fn stream_final_state(
     cfg: &BootstrapConfig,
     client: &mut BootstrapClientBinder,
@@ -119,18 +131,38 @@ async fn stream_final_state(
                         last_exec_ops_step,
                     };
                 }
-                BootstrapServerMessage::FinalStateFinished => {
-                    info!("State bootstrap complete");
+                BootstrapServerMessage::FinalStateFinished {
+                    slot,
+                    final_state_hash,
+                } => {
                     // Prune executed operations
                     let mut write_final_state = global_bootstrap_state.final_state.write();
-                    let slot = write_final_state.slot;
+                    if write_final_state.slot != slot {
+                        return Err(BootstrapError::GeneralError(format!(
+                            "final state streamed up to slot {} but the server announced slot {}",
+                            write_final_state.slot, slot
+                        )));
+                    }
                     write_final_state.executed_ops.prune(slot);
+                    // Check the integrity of the received state
+                    let actual = write_final_state.compute_state_hash();
+                    if actual != final_state_hash {
+                        return Err(BootstrapError::FinalStateHashMismatch {
+                            slot,
+                            expected: final_state_hash,
+                            actual,
+                        });
+                    }
+                    info!("State bootstrap complete, final state hash {}", actual);
                     // Set next bootstrap message
                     *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPeers;
                     return Ok(());
                 }
                 BootstrapServerMessage::SlotTooOld => {
+                    // The changes since our cursor left the history of the server:
+                    // the partial state cannot be completed, drop it and ask the state again
                     info!("Slot is too old retry bootstrap from scratch");
+                    global_bootstrap_state.final_state.write().reset();
                     *next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
                         last_slot: None,
                         last_key: None,
@@ -139,7 +171,7 @@ async fn stream_final_state(
                         last_credits_slot: None,
                         last_exec_ops_step: ExecutedOpsStreamingStep::Started,
                     };
-                    panic!("Bootstrap failed, try to bootstrap again.");
+                    return Ok(());
                 }
                 _ => {
                     return Err(
@@ -166,7 +198,18 @@ async fn bootstrap_from_server(
     our_version: Version,
 ) -> Result<(), BootstrapError> {
     massa_trace!("bootstrap.lib.bootstrap_from_server", {});
+    global_bootstrap_state.compensation_millis = server_handshake(cfg, client, our_version).await?;
+    request_state(cfg, client, next_bootstrap_message, global_bootstrap_state).await
+}
 
+/// Reads the error the server may send at connection, then does the handshake and syncs the clocks.
+/// Returns the clock compensation with the server.
+/// needs to be CANCELLABLE
+async fn server_handshake(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    our_version: Version,
+) -> Result<i64, BootstrapError> {
     // read error (if sent by the server)
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
     match tokio::time::timeout(cfg.read_error_timeout.into(), client.next()).await {
@@ -207,32 +250,33 @@ async fn bootstrap_from_server(
 
     // First, clock and version.
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
-    let server_time = match tokio::time::timeout(cfg.read_timeout.into(), client.next()).await {
-        Err(_) => {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::TimedOut,
-                "bootstrap clock sync read timed out",
-            )
-            .into())
-        }
-        Ok(Err(e)) => return Err(e),
-        Ok(Ok(BootstrapServerMessage::BootstrapTime {
-            server_time,
-            version,
-        })) => {
-            if !our_version.is_compatible(&version) {
-                return Err(BootstrapError::IncompatibleVersionError(format!(
-                    "remote is running incompatible version: {} (local node version: {})",
-                    version, our_version
-                )));
+    let (server_time, server_version) =
+        match tokio::time::timeout(cfg.read_timeout.into(), client.next()).await {
+            Err(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "bootstrap clock sync read timed out",
+                )
+                .into())
             }
-            server_time
-        }
-        Ok(Ok(BootstrapServerMessage::BootstrapError { error })) => {
-            return Err(BootstrapError::ReceivedError(error))
-        }
-        Ok(Ok(msg)) => return Err(BootstrapError::UnexpectedServerMessage(msg)),
-    };
+            Ok(Err(e)) => return Err(e),
+            Ok(Ok(BootstrapServerMessage::BootstrapTime {
+                server_time,
+                version,
+            })) => {
+                if !our_version.is_compatible(&version) {
+                    return Err(BootstrapError::IncompatibleVersionError(format!(
+                        "remote is running incompatible version: {} (local node version: {})",
+                        version, our_version
+                    )));
+                }
+                (server_time, version)
+            }
+            Ok(Ok(BootstrapServerMessage::BootstrapError { error })) => {
+                return Err(BootstrapError::ReceivedError(error))
+            }
+            Ok(Ok(msg)) => return Err(BootstrapError::UnexpectedServerMessage(msg)),
+        };
 
     let recv_time_uncompensated = MassaTime::now(0)?;
 
@@ -266,8 +310,32 @@ async fn bootstrap_from_server(
         0
     };
 
-    global_bootstrap_state.compensation_millis = compensation_millis;
+    // the stream is encrypted from now on if both sides agree on it
+    match tokio::time::timeout(
+        cfg.read_timeout.into(),
+        client.negotiate_encryption(our_version, server_version),
+    )
+    .await
+    {
+        Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "bootstrap encryption negotiation timed out",
+        )
+        .into()),
+        Ok(Err(e)) => Err(e),
+        Ok(Ok(_)) => Ok(()),
+    }?;
+    Ok(compensation_millis)
+}
 
+/// Asks the server the data still missing from `global_bootstrap_state`, starting with `next_bootstrap_message`
+/// needs to be CANCELLABLE
+async fn request_state(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    next_bootstrap_message: &mut BootstrapClientMessage,
+    global_bootstrap_state: &mut GlobalBootstrapState,
+) -> Result<(), BootstrapError> {
     let write_timeout: std::time::Duration = cfg.write_timeout.into();
     // Loop to ask data to the server depending on the last message we sent
     loop {
@@ -338,12 +406,341 @@ async fn bootstrap_from_server(
             BootstrapClientMessage::BootstrapError { error: _ } => {
                 panic!("The next message to send shouldn't be BootstrapError");
             }
+            BootstrapClientMessage::AskLedgerRangePart { .. }
+            | BootstrapClientMessage::AskLedgerRangeChanges { .. }
+            | BootstrapClientMessage::AskStateSummary => {
+                return Err(BootstrapError::GeneralError(format!(
+                    "Try to request the state but the message to send to the server was {:#?}",
+                    next_bootstrap_message
+                )));
+            }
         };
     }
     info!("Successful bootstrap");
     Ok(())
 }
 
+/// Splits the address space into `count` ranges of addresses, by their first byte.
+/// `count` must be between 1 and 256.
+fn ledger_ranges(count: usize) -> Vec<(Option<Address>, Option<Address>)> {
+    let bound = |index: usize| {
+        let mut bytes = [0u8; ADDRESS_SIZE_BYTES];
+        bytes[0] = (256 * index / count) as u8;
+        Address(Hash::from_bytes(&bytes))
+    };
+    (0..count)
+        .map(|index| {
+            (
+                (index > 0).then(|| bound(index)),
+                (index + 1 < count).then(|| bound(index + 1)),
+            )
+        })
+        .collect()
+}
+
+/// Receives a range of the ledger from a server, along with the changes of the range that happened meanwhile.
+/// Returns the slot at which the range is final, and the greatest address received.
+async fn stream_ledger_range(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    range_start: Option<Address>,
+    range_end: Option<Address>,
+    final_state: &Arc<RwLock<FinalState>>,
+) -> Result<(Slot, Option<Address>), BootstrapError> {
+    match tokio::time::timeout(
+        cfg.write_timeout.into(),
+        client.send(&BootstrapClientMessage::AskLedgerRangePart {
+            range_start,
+            range_end,
+            last_slot: None,
+            last_key: None,
+        }),
+    )
+    .await
+    {
+        Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "bootstrap ask ledger range part send timed out",
+        )
+        .into()),
+        Ok(Err(e)) => Err(e),
+        Ok(Ok(_)) => Ok(()),
+    }?;
+    let mut last_address = None;
+    loop {
+        let msg = match tokio::time::timeout(cfg.read_timeout.into(), client.next()).await {
+            Err(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "ledger range bootstrap read timed out",
+                )
+                .into());
+            }
+            Ok(Err(e)) => return Err(e),
+            Ok(Ok(msg)) => msg,
+        };
+        match msg {
+            BootstrapServerMessage::LedgerRangePart {
+                slot,
+                ledger_data,
+                ledger_changes,
+            } => {
+                let mut write_final_state = final_state.write();
+                if let Some(last_key) = write_final_state.ledger.set_ledger_part(ledger_data)? {
+                    last_address = get_address_from_key(&last_key);
+                }
+                for (changes_slot, changes) in ledger_changes {
+                    write_final_state
+                        .ledger
+                        .apply_changes(changes, changes_slot);
+                }
+                debug!(
+                    "Received ledger range batch from {:?} to {:?} for slot: {}",
+                    range_start, last_address, slot
+                );
+            }
+            BootstrapServerMessage::LedgerRangeFinished { slot } => {
+                return Ok((slot, last_address));
+            }
+            BootstrapServerMessage::SlotTooOld => {
+                return Err(BootstrapError::GeneralError(
+                    "ledger range changes left the history of the server".to_string(),
+                ))
+            }
+            BootstrapServerMessage::BootstrapError { error } => {
+                return Err(BootstrapError::ReceivedError(error))
+            }
+            other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+        }
+    }
+}
+
+/// Checks the state received from a server against the summary sent by another one:
+/// the final blocks both know about must be the same, and every complete PoS cycle of the state
+/// must be in the summary with the same hash.
+fn cross_check_state(
+    global_bootstrap_state: &GlobalBootstrapState,
+    final_blocks: &[(Slot, BlockId)],
+    cycle_hashes: &[(u64, Hash)],
+) -> Result<(), BootstrapError> {
+    let graph_blocks: HashMap<Slot, BlockId> = global_bootstrap_state
+        .graph
+        .as_ref()
+        .map(|graph| {
+            graph
+                .final_blocks
+                .iter()
+                .map(|export_block| {
+                    (
+                        export_block.block.content.header.content.slot,
+                        export_block.block.id,
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut common_blocks = 0;
+    for (slot, block_id) in final_blocks {
+        if let Some(graph_block_id) = graph_blocks.get(slot) {
+            if graph_block_id != block_id {
+                return Err(BootstrapError::CrossCheckError(format!(
+                    "final block {} at slot {} instead of {}",
+                    graph_block_id, slot, block_id
+                )));
+            }
+            common_blocks += 1;
+        }
+    }
+    if common_blocks == 0 {
+        return Err(BootstrapError::CrossCheckError(
+            "no final block in common".to_string(),
+        ));
+    }
+    let final_state = global_bootstrap_state.final_state.read();
+    for cycle_info in final_state.pos_state.cycle_history.iter() {
+        if !cycle_info.complete {
+            continue;
+        }
+        match cycle_hashes
+            .iter()
+            .find(|(cycle, _)| *cycle == cycle_info.cycle)
+        {
+            Some((_, hash)) if cycle_info.compute_hash() == *hash => {}
+            Some(_) => {
+                return Err(BootstrapError::CrossCheckError(format!(
+                    "different PoS history for cycle {}",
+                    cycle_info.cycle
+                )))
+            }
+            None => {
+                return Err(BootstrapError::CrossCheckError(format!(
+                    "complete PoS cycle {} is missing",
+                    cycle_info.cycle
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Gets the state from several bootstrap servers at once (internal private function):
+/// * each server sends a range of the ledger, at the slot it reached
+/// * the server that reached the latest slot sends the changes that bring every other range to that slot,
+///   then the rest of the state as in a single-server bootstrap
+/// * the consensus graph and the PoS cycle history it sent are cross-checked with the other servers
+///
+/// needs to be CANCELLABLE
+async fn get_state_in_parallel(
+    cfg: &BootstrapConfig,
+    final_state: Arc<RwLock<FinalState>>,
+    establisher: &mut Establisher,
+    our_version: Version,
+    servers: &[(SocketAddr, PublicKey)],
+) -> Result<GlobalBootstrapState, BootstrapError> {
+    massa_trace!("bootstrap.lib.get_state_in_parallel", {});
+    let write_timeout: Duration = cfg.write_timeout.into();
+    let read_timeout: Duration = cfg.read_timeout.into();
+    let mut global_bootstrap_state = GlobalBootstrapState::new(final_state.clone());
+
+    let mut clients = Vec::with_capacity(servers.len());
+    let mut compensations = Vec::with_capacity(servers.len());
+    for (addr, pub_key) in servers {
+        info!("Start bootstrapping from {}", addr);
+        let mut client = connect_to_server(establisher, cfg, addr, pub_key).await?;
+        compensations.push(server_handshake(cfg, &mut client, our_version).await?);
+        clients.push(client);
+    }
+
+    // download the ledger ranges
+    let ranges = ledger_ranges(clients.len());
+    let finished_ranges = try_join_all(clients.iter_mut().zip(ranges.iter()).map(
+        |(client, (range_start, range_end))| {
+            stream_ledger_range(cfg, client, *range_start, *range_end, &final_state)
+        },
+    ))
+    .await?;
+    let (primary, last_slot) = finished_ranges
+        .iter()
+        .enumerate()
+        .map(|(index, (slot, _))| (index, *slot))
+        .max_by_key(|(_, slot)| *slot)
+        .expect("no bootstrap server to get the state from");
+    let mut last_address = finished_ranges
+        .iter()
+        .filter_map(|(_, last_address)| *last_address)
+        .max();
+
+    // the other servers are only asked what is needed to cross-check the primary one
+    let mut summaries = Vec::with_capacity(clients.len() - 1);
+    for (index, client) in clients.iter_mut().enumerate() {
+        if index == primary {
+            continue;
+        }
+        match send_client_message(
+            &BootstrapClientMessage::AskStateSummary,
+            client,
+            write_timeout,
+            read_timeout,
+            "ask state summary timed out",
+        )
+        .await?
+        {
+            BootstrapServerMessage::StateSummary {
+                final_blocks,
+                cycle_hashes,
+            } => summaries.push((servers[index].0, final_blocks, cycle_hashes)),
+            BootstrapServerMessage::BootstrapError { error } => {
+                return Err(BootstrapError::ReceivedError(error))
+            }
+            other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+        }
+        match tokio::time::timeout(
+            write_timeout,
+            client.send(&BootstrapClientMessage::BootstrapSuccess),
+        )
+        .await
+        {
+            Err(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "send bootstrap success timed out",
+            )
+            .into()),
+            Ok(Err(e)) => Err(e),
+            Ok(Ok(_)) => Ok(()),
+        }?;
+    }
+
+    // bring every range to the slot of the primary server
+    let primary_client = &mut clients[primary];
+    for ((range_slot, _), (range_start, range_end)) in finished_ranges.iter().zip(ranges.iter()) {
+        if *range_slot == last_slot {
+            continue;
+        }
+        let ledger_changes = match send_client_message(
+            &BootstrapClientMessage::AskLedgerRangeChanges {
+                range_start: *range_start,
+                range_end: *range_end,
+                last_slot: *range_slot,
+                until_slot: last_slot,
+            },
+            primary_client,
+            write_timeout,
+            read_timeout,
+            "ask ledger range changes timed out",
+        )
+        .await?
+        {
+            BootstrapServerMessage::LedgerRangeChanges { ledger_changes } => ledger_changes,
+            BootstrapServerMessage::SlotTooOld => {
+                return Err(BootstrapError::GeneralError(
+                    "ledger range changes left the history of the server".to_string(),
+                ))
+            }
+            BootstrapServerMessage::BootstrapError { error } => {
+                return Err(BootstrapError::ReceivedError(error))
+            }
+            other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+        };
+        let mut write_final_state = final_state.write();
+        for (changes_slot, changes) in ledger_changes {
+            last_address = last_address.max(changes.0.keys().max().copied());
+            write_final_state
+                .ledger
+                .apply_changes(changes, changes_slot);
+        }
+    }
+
+    // the primary server streams the changes of the whole ledger since its slot, and the rest of the state
+    global_bootstrap_state.compensation_millis = compensations[primary];
+    let mut next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
+        last_slot: Some(last_slot),
+        last_key: last_address.map(|address| seq_balance_key!(address)),
+        last_async_message_id: None,
+        last_cycle_step: PoSCycleStreamingStep::Started,
+        last_credits_slot: None,
+        last_exec_ops_step: ExecutedOpsStreamingStep::Started,
+    };
+    request_state(
+        cfg,
+        primary_client,
+        &mut next_bootstrap_message,
+        &mut global_bootstrap_state,
+    )
+    .await?;
+
+    for (addr, final_blocks, cycle_hashes) in summaries {
+        cross_check_state(&global_bootstrap_state, &final_blocks, &cycle_hashes).map_err(
+            |err| {
+                BootstrapError::CrossCheckError(format!(
+                    "{} and {} disagree: {}",
+                    servers[primary].0, addr, err
+                ))
+            },
+        )?;
+    }
+    Ok(global_bootstrap_state)
+}
+
 async fn send_client_message(
     message_to_send: &BootstrapClientMessage,
     client: &mut BootstrapClientBinder,
@@ -378,6 +775,7 @@ async fn connect_to_server(
         socket,
         *pub_key,
         bootstrap_config.max_bytes_read_write,
+        bootstrap_config.enable_encryption,
         bootstrap_config.max_bootstrap_message_size,
         bootstrap_config.endorsement_count,
         bootstrap_config.max_advertise_length,
@@ -399,6 +797,81 @@ async fn connect_to_server(
     ))
 }
 
+/// Writes a bootstrapped state to a snapshot file:
+/// the final state along with the final blocks of the consensus graph at the same slot.
+/// The peers are not part of the snapshot.
+///
+/// # Returns
+/// The hash of the snapshot
+pub fn export_state_to_snapshot(
+    bootstrap_state: &GlobalBootstrapState,
+    snapshot_path: &Path,
+) -> Result<Hash, BootstrapError> {
+    massa_trace!("bootstrap.lib.export_state_to_snapshot", {});
+    let graph = match &bootstrap_state.graph {
+        Some(graph) => {
+            let mut graph_bytes = Vec::new();
+            BootstrapableGraphSerializer::new().serialize(graph, &mut graph_bytes)?;
+            Some(graph_bytes)
+        }
+        None => None,
+    };
+    let hash = bootstrap_state
+        .final_state
+        .read()
+        .export_snapshot(snapshot_path, graph.as_deref())?;
+    Ok(hash)
+}
+
+/// Gets the state from a final state snapshot file instead of a bootstrap server.
+/// The snapshot holds the final state and the final blocks of the consensus graph,
+/// so that the node resumes the graph from there and asks its peers for the blocks that follow.
+/// The peers are not part of the snapshot: the node connects to the ones of its peers file.
+pub fn get_state_from_snapshot(
+    bootstrap_config: &BootstrapConfig,
+    final_state: Arc<RwLock<FinalState>>,
+    snapshot_path: &Path,
+) -> Result<GlobalBootstrapState, BootstrapError> {
+    massa_trace!("bootstrap.lib.get_state_from_snapshot", {});
+    let (hash, graph_bytes) = final_state.write().import_snapshot(snapshot_path)?;
+    let graph = match graph_bytes {
+        Some(graph_bytes) => {
+            let (rest, graph) = BootstrapableGraphDeserializer::new(
+                bootstrap_config.thread_count,
+                bootstrap_config.endorsement_count,
+                bootstrap_config.max_bootstrap_blocks_length,
+                bootstrap_config.max_datastore_value_length,
+                bootstrap_config.max_function_name_length,
+                bootstrap_config.max_parameters_size,
+                bootstrap_config.max_operations_per_blocks,
+            )
+            .deserialize::<DeserializeError>(&graph_bytes)
+            .map_err(|err| {
+                BootstrapError::GeneralError(format!(
+                    "could not deserialize the snapshot graph: {}",
+                    err
+                ))
+            })?;
+            if !rest.is_empty() {
+                return Err(BootstrapError::GeneralError(
+                    "trailing bytes after the snapshot graph".to_string(),
+                ));
+            }
+            Some(graph)
+        }
+        None => None,
+    };
+    info!(
+        "Final state loaded from snapshot {} (hash {}) at slot {}",
+        snapshot_path.display(),
+        hash,
+        final_state.read().slot
+    );
+    let mut global_bootstrap_state = GlobalBootstrapState::new(final_state);
+    global_bootstrap_state.graph = graph;
+    Ok(global_bootstrap_state)
+}
+
 /// Gets the state from a bootstrap server
 /// needs to be CANCELLABLE
 pub async fn get_state(
@@ -438,6 +911,35 @@ pub async fn get_state(
     }
     let mut shuffled_list = bootstrap_config.bootstrap_list.clone();
     shuffled_list.shuffle(&mut StdRng::from_entropy());
+    let parallel_servers = bootstrap_config
+        .parallel_bootstrap_servers
+        .min(shuffled_list.len())
+        .min(256);
+    if parallel_servers > 1 {
+        match get_state_in_parallel(
+            bootstrap_config,
+            final_state.clone(),
+            &mut establisher,
+            version,
+            &shuffled_list[..parallel_servers],
+        )
+        .await
+        {
+            Ok(global_bootstrap_state) => {
+                info!("Successful bootstrap from {} servers", parallel_servers);
+                return Ok(global_bootstrap_state);
+            }
+            Err(e @ BootstrapError::FinalStateHashMismatch { .. })
+            | Err(e @ BootstrapError::CrossCheckError(_)) => return Err(e),
+            Err(e) => {
+                warn!(
+                    "Error while bootstrapping from {} servers: {}. Your node will bootstrap from a single server.",
+                    parallel_servers, e
+                );
+                final_state.write().reset();
+            }
+        }
+    }
     let mut next_bootstrap_message: BootstrapClientMessage =
         BootstrapClientMessage::AskFinalStatePart {
             last_slot: None,
@@ -462,8 +964,16 @@ pub async fn get_state(
                     .await  // cancellable
                     {
                         Err(BootstrapError::ReceivedError(error)) => warn!("Error received from bootstrap server: {}", error),
+                        Err(e @ BootstrapError::FinalStateHashMismatch { .. }) => {
+                            // the received parts are already applied: the local state cannot be trusted anymore
+                            let _ = tokio::time::timeout(bootstrap_config.write_error_timeout.into(), client.send(&BootstrapClientMessage::BootstrapError { error: e.to_string() })).await;
+                            return Err(e);
+                        }
                         Err(e) => {
                             warn!("Error while bootstrapping: {}", e);
+                            if let BootstrapClientMessage::AskFinalStatePart { last_slot: Some(slot), .. } = &next_bootstrap_message {
+                                info!("The final state received up to slot {} is kept, the bootstrap will resume from there", slot);
+                            }
                             // We allow unused result because we don't care if an error is thrown when sending the error message to the server we will close the socket anyway.
                             let _ = tokio::time::timeout(bootstrap_config.write_error_timeout.into(), client.send(&BootstrapClientMessage::BootstrapError { error: e.to_string() })).await;
                         }
@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::encryption::{read_encrypted_frame, write_encrypted_frame};
 use crate::error::BootstrapError;
 use crate::establisher::types::Duplex;
 use crate::messages::{
@@ -8,12 +9,14 @@ use crate::messages::{
 };
 use async_speed_limit::clock::StandardClock;
 use async_speed_limit::{Limiter, Resource};
+use massa_cipher::{EphemeralKey, SessionCipher, EPHEMERAL_KEY_SIZE, TAG_SIZE};
 use massa_hash::{Hash, HASH_SIZE_BYTES};
 use massa_models::serialization::{DeserializeMinBEInt, SerializeMinBEInt};
 use massa_models::version::{Version, VersionSerializer};
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_signature::{PublicKey, Signature, SIGNATURE_SIZE_BYTES};
 use rand::{rngs::StdRng, RngCore, SeedableRng};
+use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 
@@ -25,6 +28,8 @@ pub struct BootstrapClientBinder {
     duplex: Resource<Duplex, StandardClock>,
     prev_message: Option<Hash>,
     version_serializer: VersionSerializer,
+    enable_encryption: bool,
+    session: Option<SessionCipher>,
     endorsement_count: u32,
     max_advertise_length: u32,
     max_bootstrap_blocks: u32,
@@ -50,11 +55,13 @@ impl BootstrapClientBinder {
     /// # Argument
     /// * duplex: duplex stream.
     /// * limit: limit max bytes per second (up and down)
+    /// * enable_encryption: ask the server to encrypt the stream, if it supports it
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         duplex: Duplex,
         remote_pubkey: PublicKey,
         limit: f64,
+        enable_encryption: bool,
         max_bootstrap_message_size: u32,
         endorsement_count: u32,
         max_advertise_length: u32,
@@ -82,6 +89,8 @@ impl BootstrapClientBinder {
             duplex: <Limiter>::new(limit).limit(duplex),
             prev_message: None,
             version_serializer: VersionSerializer::new(),
+            enable_encryption,
+            session: None,
             endorsement_count,
             max_advertise_length,
             max_bootstrap_blocks,
@@ -125,19 +134,88 @@ impl BootstrapClientBinder {
         Ok(())
     }
 
+    /// Negotiates the encryption of the stream with a server running `server_version`,
+    /// asking for it if encryption is enabled.
+    /// Must be called right after receiving the `BootstrapMessage::BootstrapTime` of the server.
+    /// The stream stays plain if either side is too old to take part in the negotiation.
+    /// NOT cancel-safe
+    pub async fn negotiate_encryption(
+        &mut self,
+        our_version: Version,
+        server_version: Version,
+    ) -> Result<(), BootstrapError> {
+        if !our_version.supports_encryption() || !server_version.supports_encryption() {
+            return Ok(());
+        }
+        let prev_message = self.prev_message.ok_or_else(|| {
+            BootstrapError::GeneralError("bootstrap encryption negotiated too early".to_string())
+        })?;
+
+        // send whether we want the stream encrypted, and our ephemeral key if we do
+        let ephemeral_key = self.enable_encryption.then(EphemeralKey::generate);
+        let mut request = Vec::with_capacity(1 + EPHEMERAL_KEY_SIZE);
+        match &ephemeral_key {
+            Some(ephemeral_key) => {
+                request.push(1u8);
+                request.extend(ephemeral_key.public_bytes());
+            }
+            None => request.push(0u8),
+        }
+        self.duplex.write_all(&request).await?;
+
+        // read the ephemeral key of the server, signed with its node key
+        if let Some(ephemeral_key) = ephemeral_key {
+            let mut server_ephemeral_key = [0u8; EPHEMERAL_KEY_SIZE];
+            self.duplex.read_exact(&mut server_ephemeral_key).await?;
+            let mut sig_bytes = [0u8; SIGNATURE_SIZE_BYTES];
+            self.duplex.read_exact(&mut sig_bytes).await?;
+            let handshake_hash = Hash::compute_from(
+                &[
+                    &prev_message.to_bytes()[..],
+                    &request[..],
+                    &server_ephemeral_key[..],
+                    &self.remote_pubkey.to_bytes()[..],
+                ]
+                .concat(),
+            );
+            self.remote_pubkey
+                .verify_signature(&handshake_hash, &Signature::from_bytes(&sig_bytes)?)?;
+            self.session =
+                Some(ephemeral_key.into_session(server_ephemeral_key, &handshake_hash, true)?);
+        }
+
+        Ok(())
+    }
+
     /// Reads the next message. NOT cancel-safe
     pub async fn next(&mut self) -> Result<BootstrapServerMessage, BootstrapError> {
+        // when the stream is encrypted, read and decrypt the whole frame first
+        let frame;
+        let mut frame_reader: &[u8];
+        let reader: &mut (dyn AsyncRead + Unpin + Send) = match &mut self.session {
+            Some(session) => {
+                let max_frame_len = SIGNATURE_SIZE_BYTES
+                    + self.size_field_len
+                    + self.max_bootstrap_message_size as usize
+                    + TAG_SIZE;
+                frame = read_encrypted_frame(&mut self.duplex, session, max_frame_len).await?;
+                frame_reader = &frame[..];
+                &mut frame_reader
+            }
+            None => &mut self.duplex,
+        };
+
         // read signature
         let sig = {
             let mut sig_bytes = [0u8; SIGNATURE_SIZE_BYTES];
-            self.duplex.read_exact(&mut sig_bytes).await?;
+            reader.read_exact(&mut sig_bytes).await?;
             Signature::from_bytes(&sig_bytes)?
         };
 
         // read message length
         let msg_len = {
             let mut msg_len_bytes = vec![0u8; self.size_field_len];
-            self.duplex.read_exact(&mut msg_len_bytes[..]).await?;
+            reader.read_exact(&mut msg_len_bytes[..]).await?;
             u32::from_be_bytes_min(&msg_len_bytes, self.max_bootstrap_message_size)?.0
         };
 
@@ -165,7 +243,7 @@ impl BootstrapClientBinder {
                 self.prev_message = Some(Hash::compute_from(&sig.to_bytes()));
                 let mut sig_msg_bytes = vec![0u8; HASH_SIZE_BYTES + (msg_len as usize)];
                 sig_msg_bytes[..HASH_SIZE_BYTES].copy_from_slice(prev_message.to_bytes());
-                self.duplex
+                reader
                     .read_exact(&mut sig_msg_bytes[HASH_SIZE_BYTES..])
                     .await?;
                 let msg_hash = Hash::compute_from(&sig_msg_bytes);
@@ -177,7 +255,7 @@ impl BootstrapClientBinder {
             } else {
                 self.prev_message = Some(Hash::compute_from(&sig.to_bytes()));
                 let mut sig_msg_bytes = vec![0u8; msg_len as usize];
-                self.duplex.read_exact(&mut sig_msg_bytes[..]).await?;
+                reader.read_exact(&mut sig_msg_bytes[..]).await?;
                 let msg_hash = Hash::compute_from(&sig_msg_bytes);
                 self.remote_pubkey.verify_signature(&msg_hash, &sig)?;
                 let (_, msg) = message_deserializer
@@ -199,6 +277,7 @@ impl BootstrapClientBinder {
             BootstrapError::GeneralError(format!("bootstrap message too large to encode: {}", e))
         })?;
 
+        let mut frame = Vec::new();
         if let Some(prev_message) = self.prev_message {
             // there was a previous message
             let prev_message = prev_message.to_bytes();
@@ -211,7 +290,7 @@ impl BootstrapClientBinder {
             self.prev_message = Some(Hash::compute_from(&hash_data));
 
             // send old previous message
-            self.duplex.write_all(prev_message).await?;
+            frame.extend(prev_message);
         } else {
             // there was no previous message
 
@@ -222,11 +301,15 @@ impl BootstrapClientBinder {
         // send message length
         {
             let msg_len_bytes = msg_len.to_be_bytes_min(self.max_bootstrap_message_size)?;
-            self.duplex.write_all(&msg_len_bytes).await?;
+            frame.extend(msg_len_bytes);
         }
 
         // send message
-        self.duplex.write_all(&msg_bytes).await?;
+        frame.extend(msg_bytes);
+        match &mut self.session {
+            Some(session) => write_encrypted_frame(&mut self.duplex, session, &frame).await?,
+            None => self.duplex.write_all(&frame).await?,
+        }
         Ok(())
     }
 }
@@ -0,0 +1,50 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Optional encryption of the bootstrap stream.
+//!
+//! Right after the server sent its `BootstrapTime`, a client that knows the version of the server
+//! sends a flag byte telling if it wants the stream encrypted, followed by its ephemeral key if it does.
+//! The server answers with its own ephemeral key signed with its node key, then every frame is encrypted.
+//! Peers whose version does not support encryption do not exchange anything and keep a plain stream.
+
+use crate::error::BootstrapError;
+use crate::establisher::types::Duplex;
+use async_speed_limit::{clock::StandardClock, Resource};
+use massa_cipher::SessionCipher;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Reads a frame of an encrypted stream: its length on 4 bytes, then the encrypted data.
+/// Returns the decrypted frame.
+pub(crate) async fn read_encrypted_frame(
+    duplex: &mut Resource<Duplex, StandardClock>,
+    session: &mut SessionCipher,
+    max_frame_len: usize,
+) -> Result<Vec<u8>, BootstrapError> {
+    let mut frame_len_bytes = [0u8; 4];
+    duplex.read_exact(&mut frame_len_bytes).await?;
+    let frame_len = u32::from_be_bytes(frame_len_bytes) as usize;
+    if frame_len > max_frame_len {
+        return Err(BootstrapError::GeneralError(format!(
+            "encrypted bootstrap frame too large: {} bytes",
+            frame_len
+        )));
+    }
+    let mut encrypted_frame = vec![0u8; frame_len];
+    duplex.read_exact(&mut encrypted_frame).await?;
+    Ok(session.decrypt(&encrypted_frame)?)
+}
+
+/// Writes a frame of an encrypted stream: its length on 4 bytes, then the encrypted data
+pub(crate) async fn write_encrypted_frame(
+    duplex: &mut Resource<Duplex, StandardClock>,
+    session: &mut SessionCipher,
+    frame: &[u8],
+) -> Result<(), BootstrapError> {
+    let encrypted_frame = session.encrypt(frame)?;
+    let frame_len: u32 = encrypted_frame.len().try_into().map_err(|e| {
+        BootstrapError::GeneralError(format!("bootstrap frame too large to encode: {}", e))
+    })?;
+    duplex.write_all(&frame_len.to_be_bytes()).await?;
+    duplex.write_all(&encrypted_frame).await?;
+    Ok(())
+}
@@ -4,7 +4,8 @@ use crate::messages::{BootstrapClientMessage, BootstrapServerMessage};
 use displaydoc::Display;
 use massa_consensus_exports::error::ConsensusError;
 use massa_final_state::FinalStateError;
-use massa_hash::MassaHashError;
+use massa_hash::{Hash, MassaHashError};
+use massa_models::slot::Slot;
 use massa_network_exports::NetworkError;
 use massa_pos_exports::PosError;
 use massa_serialization::SerializeError;
@@ -28,6 +29,8 @@ pub enum BootstrapError {
     UnexpectedClientMessage(BootstrapClientMessage),
     /// connection with bootstrap node dropped
     UnexpectedConnectionDrop,
+    /// cipher error: {0}
+    CipherError(#[from] massa_cipher::CipherError),
     /// `massa_hash` error: {0}
     MassaHashError(#[from] MassaHashError),
     /// massa_signature error {0}
@@ -50,4 +53,15 @@ pub enum BootstrapError {
     IncompatibleVersionError(String),
     /// Received error: {0}
     ReceivedError(String),
+    /// cross-check with another bootstrap server failed: {0}
+    CrossCheckError(String),
+    /// final state hash mismatch at slot {slot}: the server announced {expected} but the received state hashes to {actual}
+    FinalStateHashMismatch {
+        /// final slot of the received state
+        slot: Slot,
+        /// hash announced by the server
+        expected: Hash,
+        /// hash of the received state
+        actual: Hash,
+    },
 }
@@ -23,19 +23,20 @@ use std::sync::Arc;
 
 mod client;
 mod client_binder;
+mod encryption;
 mod error;
 mod establisher;
 mod messages;
 mod server;
 mod server_binder;
 mod settings;
-pub use client::get_state;
+pub use client::{export_state_to_snapshot, get_state, get_state_from_snapshot};
 pub use establisher::types;
 pub use messages::{
     BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapClientMessageSerializer,
     BootstrapServerMessage, BootstrapServerMessageDeserializer, BootstrapServerMessageSerializer,
 };
-pub use server::{start_bootstrap_server, BootstrapManager};
+pub use server::{start_bootstrap_server, BootstrapManager, BootstrapSessionCounters};
 pub use settings::BootstrapConfig;
 
 #[cfg(test)]
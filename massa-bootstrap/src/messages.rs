@@ -9,7 +9,14 @@ use massa_final_state::{
 use massa_graph::{
     BootstrapableGraph, BootstrapableGraphDeserializer, BootstrapableGraphSerializer,
 };
-use massa_ledger_exports::{KeyDeserializer, KeySerializer};
+use massa_hash::{Hash, HashDeserializer};
+use massa_ledger_exports::{
+    KeyDeserializer, KeySerializer, LedgerChanges, LedgerChangesDeserializer,
+    LedgerChangesSerializer,
+};
+use massa_models::address::{Address, AddressDeserializer, AddressSerializer};
+use massa_models::block::BlockId;
+use massa_models::config::POS_SAVED_CYCLES;
 use massa_models::serialization::{VecU8Deserializer, VecU8Serializer};
 use massa_models::slot::SlotDeserializer;
 use massa_models::{
@@ -23,7 +30,7 @@ use massa_pos_exports::{
 };
 use massa_serialization::{
     Deserializer, OptionDeserializer, OptionSerializer, SerializeError, Serializer,
-    U32VarIntDeserializer, U32VarIntSerializer,
+    U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
 use massa_time::{MassaTime, MassaTimeDeserializer, MassaTimeSerializer};
 use nom::error::context;
@@ -77,7 +84,12 @@ pub enum BootstrapServerMessage {
         final_state_changes: Vec<(Slot, StateChanges)>,
     },
     /// Message sent when there is no state part left
-    FinalStateFinished,
+    FinalStateFinished {
+        /// Final slot of the streamed state
+        slot: Slot,
+        /// Hash of the final state at that slot, see `FinalState::compute_state_hash`
+        final_state_hash: Hash,
+    },
     /// Slot sent to get state changes is too old
     SlotTooOld,
     /// Bootstrap error
@@ -85,6 +97,32 @@ pub enum BootstrapServerMessage {
         /// Error message
         error: String,
     },
+    /// Part of a ledger address range
+    LedgerRangePart {
+        /// Slot the ledger changes are attached to
+        slot: Slot,
+        /// Part of the execution ledger sent in a serialized way
+        ledger_data: Vec<u8>,
+        /// Ledger changes for the addresses of the range already streamed, since the slot of the client message
+        ledger_changes: Vec<(Slot, LedgerChanges)>,
+    },
+    /// Message sent when there is no part left in the ledger address range
+    LedgerRangeFinished {
+        /// Final slot of the streamed range
+        slot: Slot,
+    },
+    /// Ledger changes of an address range between two slots
+    LedgerRangeChanges {
+        /// Ledger changes of the range, by slot
+        ledger_changes: Vec<(Slot, LedgerChanges)>,
+    },
+    /// Summary of the server state, used to cross-check another bootstrap server
+    StateSummary {
+        /// Latest final block of each thread
+        final_blocks: Vec<(Slot, BlockId)>,
+        /// Hashes of the latest complete Proof of Stake cycles
+        cycle_hashes: Vec<(u64, Hash)>,
+    },
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -97,6 +135,10 @@ enum MessageServerTypeId {
     FinalStateFinished = 4u32,
     SlotTooOld = 5u32,
     BootstrapError = 6u32,
+    LedgerRangePart = 7u32,
+    LedgerRangeFinished = 8u32,
+    LedgerRangeChanges = 9u32,
+    StateSummary = 10u32,
 }
 
 /// Serializer for `BootstrapServerMessage`
@@ -110,6 +152,8 @@ pub struct BootstrapServerMessageSerializer {
     bootstrapable_graph_serializer: BootstrapableGraphSerializer,
     vec_u8_serializer: VecU8Serializer,
     slot_serializer: SlotSerializer,
+    ledger_changes_serializer: LedgerChangesSerializer,
+    u64_serializer: U64VarIntSerializer,
 }
 
 impl BootstrapServerMessageSerializer {
@@ -124,6 +168,8 @@ impl BootstrapServerMessageSerializer {
             bootstrapable_graph_serializer: BootstrapableGraphSerializer::new(),
             vec_u8_serializer: VecU8Serializer::new(),
             slot_serializer: SlotSerializer::new(),
+            ledger_changes_serializer: LedgerChangesSerializer::new(),
+            u64_serializer: U64VarIntSerializer::new(),
         }
     }
 }
@@ -196,9 +242,14 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
                         .serialize(state_changes, buffer)?;
                 }
             }
-            BootstrapServerMessage::FinalStateFinished => {
+            BootstrapServerMessage::FinalStateFinished {
+                slot,
+                final_state_hash,
+            } => {
                 self.u32_serializer
                     .serialize(&u32::from(MessageServerTypeId::FinalStateFinished), buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+                buffer.extend(final_state_hash.to_bytes());
             }
             BootstrapServerMessage::SlotTooOld => {
                 self.u32_serializer
@@ -215,6 +266,62 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
                 )?;
                 buffer.extend(error.as_bytes())
             }
+            BootstrapServerMessage::LedgerRangePart {
+                slot,
+                ledger_data,
+                ledger_changes,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::LedgerRangePart), buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+                self.vec_u8_serializer.serialize(ledger_data, buffer)?;
+                self.serialize_ledger_changes(ledger_changes, buffer)?;
+            }
+            BootstrapServerMessage::LedgerRangeFinished { slot } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::LedgerRangeFinished), buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+            }
+            BootstrapServerMessage::LedgerRangeChanges { ledger_changes } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::LedgerRangeChanges), buffer)?;
+                self.serialize_ledger_changes(ledger_changes, buffer)?;
+            }
+            BootstrapServerMessage::StateSummary {
+                final_blocks,
+                cycle_hashes,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::StateSummary), buffer)?;
+                self.u32_serializer
+                    .serialize(&(final_blocks.len() as u32), buffer)?;
+                for (slot, block_id) in final_blocks {
+                    self.slot_serializer.serialize(slot, buffer)?;
+                    buffer.extend(block_id.0.to_bytes());
+                }
+                self.u32_serializer
+                    .serialize(&(cycle_hashes.len() as u32), buffer)?;
+                for (cycle, hash) in cycle_hashes {
+                    self.u64_serializer.serialize(cycle, buffer)?;
+                    buffer.extend(hash.to_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BootstrapServerMessageSerializer {
+    fn serialize_ledger_changes(
+        &self,
+        ledger_changes: &[(Slot, LedgerChanges)],
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.u32_serializer
+            .serialize(&(ledger_changes.len() as u32), buffer)?;
+        for (slot, changes) in ledger_changes {
+            self.slot_serializer.serialize(slot, buffer)?;
+            self.ledger_changes_serializer.serialize(changes, buffer)?;
         }
         Ok(())
     }
@@ -232,6 +339,11 @@ pub struct BootstrapServerMessageDeserializer {
     final_state_parts_deserializer: VecU8Deserializer,
     length_bootstrap_error: U32VarIntDeserializer,
     slot_deserializer: SlotDeserializer,
+    hash_deserializer: HashDeserializer,
+    ledger_changes_deserializer: LedgerChangesDeserializer,
+    length_final_blocks: U32VarIntDeserializer,
+    length_cycle_hashes: U32VarIntDeserializer,
+    cycle_deserializer: U64VarIntDeserializer,
 }
 
 impl BootstrapServerMessageDeserializer {
@@ -297,6 +409,22 @@ impl BootstrapServerMessageDeserializer {
                 Included(0),
                 Included(max_bootstrap_error_length),
             ),
+            hash_deserializer: HashDeserializer::new(),
+            ledger_changes_deserializer: LedgerChangesDeserializer::new(
+                max_ledger_changes_count,
+                max_datastore_key_length,
+                max_datastore_value_length,
+                max_datastore_entry_count,
+            ),
+            length_final_blocks: U32VarIntDeserializer::new(
+                Included(0),
+                Included(max_bootstrap_blocks),
+            ),
+            length_cycle_hashes: U32VarIntDeserializer::new(
+                Included(0),
+                Included(POS_SAVED_CYCLES as u32),
+            ),
+            cycle_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
         }
     }
 }
@@ -430,9 +558,21 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     },
                 )
                 .parse(input),
-                MessageServerTypeId::FinalStateFinished => {
-                    Ok((input, BootstrapServerMessage::FinalStateFinished))
-                }
+                MessageServerTypeId::FinalStateFinished => tuple((
+                    context("Failed slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                    context("Failed final_state_hash deserialization", |input| {
+                        self.hash_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(
+                    |(slot, final_state_hash)| BootstrapServerMessage::FinalStateFinished {
+                        slot,
+                        final_state_hash,
+                    },
+                )
+                .parse(input),
                 MessageServerTypeId::SlotTooOld => Ok((input, BootstrapServerMessage::SlotTooOld)),
                 MessageServerTypeId::BootstrapError => context(
                     "Failed BootstrapError deserialization",
@@ -444,12 +584,104 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     error: String::from_utf8_lossy(error).into_owned(),
                 })
                 .parse(input),
+                MessageServerTypeId::LedgerRangePart => tuple((
+                    context("Failed slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                    context("Failed ledger_data deserialization", |input| {
+                        self.final_state_parts_deserializer.deserialize(input)
+                    }),
+                    context("Failed ledger_changes deserialization", |input| {
+                        self.deserialize_ledger_changes(input)
+                    }),
+                ))
+                .map(|(slot, ledger_data, ledger_changes)| {
+                    BootstrapServerMessage::LedgerRangePart {
+                        slot,
+                        ledger_data,
+                        ledger_changes,
+                    }
+                })
+                .parse(input),
+                MessageServerTypeId::LedgerRangeFinished => {
+                    context("Failed slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    })
+                    .map(|slot| BootstrapServerMessage::LedgerRangeFinished { slot })
+                    .parse(input)
+                }
+                MessageServerTypeId::LedgerRangeChanges => {
+                    context("Failed ledger_changes deserialization", |input| {
+                        self.deserialize_ledger_changes(input)
+                    })
+                    .map(
+                        |ledger_changes| BootstrapServerMessage::LedgerRangeChanges {
+                            ledger_changes,
+                        },
+                    )
+                    .parse(input)
+                }
+                MessageServerTypeId::StateSummary => tuple((
+                    context(
+                        "Failed final_blocks deserialization",
+                        length_count(
+                            context("Failed length deserialization", |input| {
+                                self.length_final_blocks.deserialize(input)
+                            }),
+                            tuple((
+                                |input| self.slot_deserializer.deserialize(input),
+                                |input| {
+                                    self.hash_deserializer
+                                        .deserialize(input)
+                                        .map(|(rest, hash)| (rest, BlockId(hash)))
+                                },
+                            )),
+                        ),
+                    ),
+                    context(
+                        "Failed cycle_hashes deserialization",
+                        length_count(
+                            context("Failed length deserialization", |input| {
+                                self.length_cycle_hashes.deserialize(input)
+                            }),
+                            tuple((
+                                |input| self.cycle_deserializer.deserialize(input),
+                                |input| self.hash_deserializer.deserialize(input),
+                            )),
+                        ),
+                    ),
+                ))
+                .map(
+                    |(final_blocks, cycle_hashes)| BootstrapServerMessage::StateSummary {
+                        final_blocks,
+                        cycle_hashes,
+                    },
+                )
+                .parse(input),
             }
         })
         .parse(buffer)
     }
 }
 
+impl BootstrapServerMessageDeserializer {
+    fn deserialize_ledger_changes<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Vec<(Slot, LedgerChanges)>, E> {
+        length_count(
+            context("Failed length deserialization", |input| {
+                self.length_state_changes.deserialize(input)
+            }),
+            tuple((
+                |input| self.slot_deserializer.deserialize(input),
+                |input| self.ledger_changes_deserializer.deserialize(input),
+            )),
+        )
+        .parse(buffer)
+    }
+}
+
 /// Messages used during bootstrap by client
 #[derive(Debug, Clone)]
 pub enum BootstrapClientMessage {
@@ -479,6 +711,30 @@ pub enum BootstrapClientMessage {
     },
     /// Bootstrap succeed
     BootstrapSuccess,
+    /// Ask for a part of a ledger address range
+    AskLedgerRangePart {
+        /// First address of the range, unbounded if `None`
+        range_start: Option<Address>,
+        /// Address excluded from the end of the range, unbounded if `None`
+        range_end: Option<Address>,
+        /// Slot we are attached to for changes
+        last_slot: Option<Slot>,
+        /// Last key of the range we received from the server
+        last_key: Option<Vec<u8>>,
+    },
+    /// Ask for the ledger changes of an address range between two slots
+    AskLedgerRangeChanges {
+        /// First address of the range, unbounded if `None`
+        range_start: Option<Address>,
+        /// Address excluded from the end of the range, unbounded if `None`
+        range_end: Option<Address>,
+        /// Slot the range is attached to
+        last_slot: Slot,
+        /// Slot up to which the changes are wanted
+        until_slot: Slot,
+    },
+    /// Ask for a summary of the server state
+    AskStateSummary,
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -489,17 +745,21 @@ enum MessageClientTypeId {
     AskFinalStatePart = 2u32,
     BootstrapError = 3u32,
     BootstrapSuccess = 4u32,
+    AskLedgerRangePart = 5u32,
+    AskLedgerRangeChanges = 6u32,
+    AskStateSummary = 7u32,
 }
 
 /// Serializer for `BootstrapClientMessage`
 pub struct BootstrapClientMessageSerializer {
     u32_serializer: U32VarIntSerializer,
-    slot_serializer: SlotSerializer,
-    async_message_id_serializer: AsyncMessageIdSerializer,
-    key_serializer: KeySerializer,
+    opt_async_message_id_serializer: OptionSerializer<AsyncMessageId, AsyncMessageIdSerializer>,
+    opt_key_serializer: OptionSerializer<Vec<u8>, KeySerializer>,
     cycle_step_serializer: PoSCycleStreamingStepSerializer,
     opt_slot_serializer: OptionSerializer<Slot, SlotSerializer>,
     exec_ops_step_serializer: ExecutedOpsStreamingStepSerializer,
+    opt_address_serializer: OptionSerializer<Address, AddressSerializer>,
+    slot_serializer: SlotSerializer,
 }
 
 impl BootstrapClientMessageSerializer {
@@ -507,12 +767,13 @@ impl BootstrapClientMessageSerializer {
     pub fn new() -> Self {
         Self {
             u32_serializer: U32VarIntSerializer::new(),
-            slot_serializer: SlotSerializer::new(),
-            async_message_id_serializer: AsyncMessageIdSerializer::new(),
-            key_serializer: KeySerializer::new(),
+            opt_async_message_id_serializer: OptionSerializer::new(AsyncMessageIdSerializer::new()),
+            opt_key_serializer: OptionSerializer::new(KeySerializer::new()),
             cycle_step_serializer: PoSCycleStreamingStepSerializer::new(),
             opt_slot_serializer: OptionSerializer::new(SlotSerializer::new()),
             exec_ops_step_serializer: ExecutedOpsStreamingStepSerializer::new(),
+            opt_address_serializer: OptionSerializer::new(AddressSerializer::new()),
+            slot_serializer: SlotSerializer::new(),
         }
     }
 }
@@ -561,15 +822,18 @@ impl Serializer<BootstrapClientMessage> for BootstrapClientMessageSerializer {
             } => {
                 self.u32_serializer
                     .serialize(&u32::from(MessageClientTypeId::AskFinalStatePart), buffer)?;
-                // If we have a cursor we must have also a slot
-                if let Some(key) = last_key && let Some(slot) = last_slot && let Some(last_async_message_id) = last_async_message_id  {
-                    self.key_serializer.serialize(key, buffer)?;
-                    self.slot_serializer.serialize(slot, buffer)?;
-                    self.async_message_id_serializer.serialize(last_async_message_id, buffer)?;
-                    self.cycle_step_serializer.serialize(last_cycle_step, buffer)?;
-                    self.opt_slot_serializer.serialize(last_credits_slot, buffer)?;
-                    self.exec_ops_step_serializer.serialize(last_exec_ops_step, buffer)?;
-                }
+                // Every cursor is sent, even when some streams have not started yet,
+                // so that an interrupted bootstrap can be resumed
+                self.opt_key_serializer.serialize(last_key, buffer)?;
+                self.opt_slot_serializer.serialize(last_slot, buffer)?;
+                self.opt_async_message_id_serializer
+                    .serialize(last_async_message_id, buffer)?;
+                self.cycle_step_serializer
+                    .serialize(last_cycle_step, buffer)?;
+                self.opt_slot_serializer
+                    .serialize(last_credits_slot, buffer)?;
+                self.exec_ops_step_serializer
+                    .serialize(last_exec_ops_step, buffer)?;
             }
             BootstrapClientMessage::BootstrapError { error } => {
                 self.u32_serializer
@@ -586,6 +850,38 @@ impl Serializer<BootstrapClientMessage> for BootstrapClientMessageSerializer {
                 self.u32_serializer
                     .serialize(&u32::from(MessageClientTypeId::BootstrapSuccess), buffer)?;
             }
+            BootstrapClientMessage::AskLedgerRangePart {
+                range_start,
+                range_end,
+                last_slot,
+                last_key,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageClientTypeId::AskLedgerRangePart), buffer)?;
+                self.opt_address_serializer.serialize(range_start, buffer)?;
+                self.opt_address_serializer.serialize(range_end, buffer)?;
+                self.opt_slot_serializer.serialize(last_slot, buffer)?;
+                self.opt_key_serializer.serialize(last_key, buffer)?;
+            }
+            BootstrapClientMessage::AskLedgerRangeChanges {
+                range_start,
+                range_end,
+                last_slot,
+                until_slot,
+            } => {
+                self.u32_serializer.serialize(
+                    &u32::from(MessageClientTypeId::AskLedgerRangeChanges),
+                    buffer,
+                )?;
+                self.opt_address_serializer.serialize(range_start, buffer)?;
+                self.opt_address_serializer.serialize(range_end, buffer)?;
+                self.slot_serializer.serialize(last_slot, buffer)?;
+                self.slot_serializer.serialize(until_slot, buffer)?;
+            }
+            BootstrapClientMessage::AskStateSummary => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageClientTypeId::AskStateSummary), buffer)?;
+            }
         }
         Ok(())
     }
@@ -594,13 +890,15 @@ impl Serializer<BootstrapClientMessage> for BootstrapClientMessageSerializer {
 /// Deserializer for `BootstrapClientMessage`
 pub struct BootstrapClientMessageDeserializer {
     id_deserializer: U32VarIntDeserializer,
-    slot_deserializer: SlotDeserializer,
-    async_message_id_deserializer: AsyncMessageIdDeserializer,
+    opt_async_message_id_deserializer:
+        OptionDeserializer<AsyncMessageId, AsyncMessageIdDeserializer>,
     length_error_deserializer: U32VarIntDeserializer,
-    key_deserializer: KeyDeserializer,
+    opt_key_deserializer: OptionDeserializer<Vec<u8>, KeyDeserializer>,
     cycle_step_deserializer: PoSCycleStreamingStepDeserializer,
     opt_slot_deserializer: OptionDeserializer<Slot, SlotDeserializer>,
     exec_ops_step_serializer: ExecutedOpsStreamingStepDeserializer,
+    opt_address_deserializer: OptionDeserializer<Address, AddressDeserializer>,
+    slot_deserializer: SlotDeserializer,
 }
 
 impl BootstrapClientMessageDeserializer {
@@ -608,12 +906,12 @@ impl BootstrapClientMessageDeserializer {
     pub fn new(thread_count: u8, max_datastore_key_length: u8) -> Self {
         Self {
             id_deserializer: U32VarIntDeserializer::new(Included(0), Included(u32::MAX)),
-            slot_deserializer: SlotDeserializer::new(
-                (Included(0), Included(u64::MAX)),
-                (Included(0), Excluded(thread_count)),
+            opt_async_message_id_deserializer: OptionDeserializer::new(
+                AsyncMessageIdDeserializer::new(thread_count),
             ),
-            async_message_id_deserializer: AsyncMessageIdDeserializer::new(thread_count),
-            key_deserializer: KeyDeserializer::new(max_datastore_key_length),
+            opt_key_deserializer: OptionDeserializer::new(KeyDeserializer::new(
+                max_datastore_key_length,
+            )),
             length_error_deserializer: U32VarIntDeserializer::new(Included(0), Included(100000)),
             cycle_step_deserializer: PoSCycleStreamingStepDeserializer::new(),
             opt_slot_deserializer: OptionDeserializer::new(SlotDeserializer::new(
@@ -621,6 +919,11 @@ impl BootstrapClientMessageDeserializer {
                 (Included(0), Excluded(thread_count)),
             )),
             exec_ops_step_serializer: ExecutedOpsStreamingStepDeserializer::new(),
+            opt_address_deserializer: OptionDeserializer::new(AddressDeserializer::new()),
+            slot_deserializer: SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(thread_count)),
+            ),
         }
     }
 }
@@ -670,62 +973,46 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                 MessageClientTypeId::AskConsensusState => {
                     Ok((input, BootstrapClientMessage::AskConsensusState))
                 }
-                MessageClientTypeId::AskFinalStatePart => {
-                    if input.is_empty() {
-                        Ok((
-                            input,
-                            BootstrapClientMessage::AskFinalStatePart {
-                                last_slot: None,
-                                last_key: None,
-                                last_async_message_id: None,
-                                last_cycle_step: PoSCycleStreamingStep::Started,
-                                last_credits_slot: None,
-                                last_exec_ops_step: ExecutedOpsStreamingStep::Started,
-                            },
-                        ))
-                    } else {
-                        tuple((
-                            context("Faild key deserialization", |input| {
-                                self.key_deserializer.deserialize(input)
-                            }),
-                            context("Failed slot deserialization", |input| {
-                                self.slot_deserializer.deserialize(input)
-                            }),
-                            context("Failed async_message_id deserialization", |input| {
-                                self.async_message_id_deserializer.deserialize(input)
-                            }),
-                            context("Failed cycle_step deserialization", |input| {
-                                self.cycle_step_deserializer.deserialize(input)
-                            }),
-                            context("Failed credits_slot deserialization", |input| {
-                                self.opt_slot_deserializer.deserialize(input)
-                            }),
-                            context("Failed exec_ops_step deserialization", |input| {
-                                self.exec_ops_step_serializer.deserialize(input)
-                            }),
-                        ))
-                        .map(
-                            |(
-                                last_key,
-                                last_slot,
-                                last_async_message_id,
-                                last_cycle_step,
-                                last_credits_slot,
-                                last_exec_ops_step,
-                            )| {
-                                BootstrapClientMessage::AskFinalStatePart {
-                                    last_slot: Some(last_slot),
-                                    last_key: Some(last_key),
-                                    last_async_message_id: Some(last_async_message_id),
-                                    last_cycle_step,
-                                    last_credits_slot,
-                                    last_exec_ops_step, // TODO: update this
-                                }
-                            },
-                        )
-                        .parse(input)
-                    }
-                }
+                MessageClientTypeId::AskFinalStatePart => tuple((
+                    context("Failed key deserialization", |input| {
+                        self.opt_key_deserializer.deserialize(input)
+                    }),
+                    context("Failed slot deserialization", |input| {
+                        self.opt_slot_deserializer.deserialize(input)
+                    }),
+                    context("Failed async_message_id deserialization", |input| {
+                        self.opt_async_message_id_deserializer.deserialize(input)
+                    }),
+                    context("Failed cycle_step deserialization", |input| {
+                        self.cycle_step_deserializer.deserialize(input)
+                    }),
+                    context("Failed credits_slot deserialization", |input| {
+                        self.opt_slot_deserializer.deserialize(input)
+                    }),
+                    context("Failed exec_ops_step deserialization", |input| {
+                        self.exec_ops_step_serializer.deserialize(input)
+                    }),
+                ))
+                .map(
+                    |(
+                        last_key,
+                        last_slot,
+                        last_async_message_id,
+                        last_cycle_step,
+                        last_credits_slot,
+                        last_exec_ops_step,
+                    )| {
+                        BootstrapClientMessage::AskFinalStatePart {
+                            last_slot,
+                            last_key,
+                            last_async_message_id,
+                            last_cycle_step,
+                            last_credits_slot,
+                            last_exec_ops_step,
+                        }
+                    },
+                )
+                .parse(input),
                 MessageClientTypeId::BootstrapError => context(
                     "Failed BootstrapError deserialization",
                     length_data(context("Failed length deserialization", |input| {
@@ -739,6 +1026,55 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                 MessageClientTypeId::BootstrapSuccess => {
                     Ok((input, BootstrapClientMessage::BootstrapSuccess))
                 }
+                MessageClientTypeId::AskLedgerRangePart => tuple((
+                    context("Failed range_start deserialization", |input| {
+                        self.opt_address_deserializer.deserialize(input)
+                    }),
+                    context("Failed range_end deserialization", |input| {
+                        self.opt_address_deserializer.deserialize(input)
+                    }),
+                    context("Failed slot deserialization", |input| {
+                        self.opt_slot_deserializer.deserialize(input)
+                    }),
+                    context("Failed key deserialization", |input| {
+                        self.opt_key_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(|(range_start, range_end, last_slot, last_key)| {
+                    BootstrapClientMessage::AskLedgerRangePart {
+                        range_start,
+                        range_end,
+                        last_slot,
+                        last_key,
+                    }
+                })
+                .parse(input),
+                MessageClientTypeId::AskLedgerRangeChanges => tuple((
+                    context("Failed range_start deserialization", |input| {
+                        self.opt_address_deserializer.deserialize(input)
+                    }),
+                    context("Failed range_end deserialization", |input| {
+                        self.opt_address_deserializer.deserialize(input)
+                    }),
+                    context("Failed last_slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                    context("Failed until_slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(|(range_start, range_end, last_slot, until_slot)| {
+                    BootstrapClientMessage::AskLedgerRangeChanges {
+                        range_start,
+                        range_end,
+                        last_slot,
+                        until_slot,
+                    }
+                })
+                .parse(input),
+                MessageClientTypeId::AskStateSummary => {
+                    Ok((input, BootstrapClientMessage::AskStateSummary))
+                }
             }
         })
         .parse(buffer)
@@ -1,7 +1,11 @@
 use std::{
     collections::{hash_map, HashMap},
     net::{IpAddr, SocketAddr},
-    sync::Arc,
+    ops::Bound,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
@@ -11,9 +15,12 @@ use massa_async_pool::AsyncMessageId;
 use massa_consensus_exports::ConsensusCommandSender;
 use massa_final_state::{ExecutedOpsStreamingStep, FinalState};
 use massa_graph::BootstrapableGraph;
+use massa_hash::Hash;
 use massa_ledger_exports::get_address_from_key;
 use massa_logging::massa_trace;
-use massa_models::{slot::Slot, version::Version};
+use massa_models::{
+    address::Address, block::BlockId, config::POS_SAVED_CYCLES, slot::Slot, version::Version,
+};
 use massa_network_exports::{BootstrapPeers, NetworkCommandSender};
 use massa_pos_exports::PoSCycleStreamingStep;
 use massa_signature::KeyPair;
@@ -29,13 +36,47 @@ use crate::{
     BootstrapConfig, Establisher,
 };
 
+/// counters of the bootstrap sessions handled by the server
+#[derive(Debug, Default)]
+pub struct BootstrapSessionCounters {
+    /// number of sessions currently running
+    active: AtomicU64,
+    /// number of sessions started since the server was launched
+    started: AtomicU64,
+    /// number of bootstrap attempts refused since the server was launched
+    refused: AtomicU64,
+}
+
+impl BootstrapSessionCounters {
+    /// number of sessions currently running
+    pub fn get_active_count(&self) -> u64 {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// number of sessions started since the server was launched
+    pub fn get_started_count(&self) -> u64 {
+        self.started.load(Ordering::Relaxed)
+    }
+
+    /// number of bootstrap attempts refused since the server was launched
+    pub fn get_refused_count(&self) -> u64 {
+        self.refused.load(Ordering::Relaxed)
+    }
+}
+
 /// handle on the bootstrap server
 pub struct BootstrapManager {
     join_handle: JoinHandle<Result<(), BootstrapError>>,
     manager_tx: mpsc::Sender<()>,
+    session_counters: Arc<BootstrapSessionCounters>,
 }
 
 impl BootstrapManager {
+    /// get the counters of the bootstrap sessions handled by the server
+    pub fn get_session_counters(&self) -> Arc<BootstrapSessionCounters> {
+        self.session_counters.clone()
+    }
+
     /// stop the bootstrap server
     pub async fn stop(self) -> Result<(), BootstrapError> {
         massa_trace!("bootstrap.lib.stop", {});
@@ -65,6 +106,8 @@ pub async fn start_bootstrap_server(
     massa_trace!("bootstrap.lib.start_bootstrap_server", {});
     if let Some(bind) = bootstrap_config.bind {
         let (manager_tx, manager_rx) = mpsc::channel::<()>(1);
+        let session_counters = Arc::new(BootstrapSessionCounters::default());
+        let server_session_counters = session_counters.clone();
         let join_handle = tokio::spawn(async move {
             BootstrapServer {
                 consensus_command_sender,
@@ -78,6 +121,7 @@ pub async fn start_bootstrap_server(
                 version,
                 ip_hist_map: HashMap::with_capacity(bootstrap_config.ip_list_max_size),
                 bootstrap_config,
+                session_counters: server_session_counters,
             }
             .run()
             .await
@@ -85,6 +129,7 @@ pub async fn start_bootstrap_server(
         Ok(Some(BootstrapManager {
             join_handle,
             manager_tx,
+            session_counters,
         }))
     } else {
         Ok(None)
@@ -103,6 +148,7 @@ struct BootstrapServer {
     compensation_millis: i64,
     version: Version,
     ip_hist_map: HashMap<IpAddr, Instant>,
+    session_counters: Arc<BootstrapSessionCounters>,
 }
 
 impl BootstrapServer {
@@ -146,6 +192,7 @@ impl BootstrapServer {
 
                 // bootstrap session finished
                 Some(_) = bootstrap_sessions.next() => {
+                    self.session_counters.active.store(bootstrap_sessions.len() as u64, Ordering::Relaxed);
                     massa_trace!("bootstrap.session.finished", {"active_count": bootstrap_sessions.len()});
                 }
 
@@ -179,6 +226,7 @@ impl BootstrapServer {
                                     Ok(Ok(_)) => Ok(()),
                                 };
                                 // in list, non-expired => refuse
+                                self.session_counters.refused.fetch_add(1, Ordering::Relaxed);
                                 massa_trace!("bootstrap.lib.run.select.accept.refuse_limit", {"remote_addr": remote_addr});
                                 continue;
                             } else {
@@ -245,6 +293,8 @@ impl BootstrapServer {
                         }
 
                     });
+                    self.session_counters.started.fetch_add(1, Ordering::Relaxed);
+                    self.session_counters.active.store(bootstrap_sessions.len() as u64, Ordering::Relaxed);
                     massa_trace!("bootstrap.session.started", {"active_count": bootstrap_sessions.len()});
                 } else {
                     let config = self.bootstrap_config.clone();
@@ -256,6 +306,7 @@ impl BootstrapServer {
                         Ok(Err(e)) => Err(e),
                         Ok(Ok(_)) => Ok(()),
                     };
+                    self.session_counters.refused.fetch_add(1, Ordering::Relaxed);
                     debug!("did not bootstrap {}: no available slots", remote_addr);
                 }
             }
@@ -286,6 +337,36 @@ pub async fn send_final_state_stream(
             tokio::time::sleep(Duration::from_millis(500)).await;
         }
 
+        // A client resuming its stream needs every change since its cursor slot:
+        // if they left the changes history, it has to restart from scratch.
+        let slot_too_old = if let Some(slot) = last_slot {
+            let final_state_read = final_state.read();
+            slot < final_state_read.slot
+                && final_state_read
+                    .changes_history
+                    .front()
+                    .map_or(true, |(first_slot, _)| slot < *first_slot)
+        } else {
+            false
+        };
+        if slot_too_old {
+            match tokio::time::timeout(
+                write_timeout,
+                server.send(BootstrapServerMessage::SlotTooOld),
+            )
+            .await
+            {
+                Err(_) => Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "bootstrap slot too old send timed out",
+                )
+                .into()),
+                Ok(Err(e)) => Err(e),
+                Ok(Ok(_)) => Ok(()),
+            }?;
+            return Ok(());
+        }
+
         let current_slot;
         let ledger_data;
         let async_pool_data;
@@ -293,6 +374,7 @@ pub async fn send_final_state_stream(
         let pos_credits_data;
         let exec_ops_data;
         let final_state_changes;
+        let final_state_hash;
 
         // Scope of the final state read
         {
@@ -373,15 +455,42 @@ pub async fn send_final_state_stream(
             }
             last_slot = Some(final_state_read.slot);
             current_slot = final_state_read.slot;
+
+            // when the client is up to date, hash the state it should now have
+            final_state_hash = if ledger_data.is_empty()
+                && async_pool_data.is_empty()
+                && pos_cycle_data.is_empty()
+                && pos_credits_data.is_empty()
+                && exec_ops_data.is_empty()
+                && final_state_changes.is_empty()
+            {
+                Some(final_state_read.compute_state_hash())
+            } else {
+                None
+            };
         }
 
-        if !ledger_data.is_empty()
-            || !async_pool_data.is_empty()
-            || !pos_cycle_data.is_empty()
-            || !pos_credits_data.is_empty()
-            || !exec_ops_data.is_empty()
-            || !final_state_changes.is_empty()
-        {
+        if let Some(final_state_hash) = final_state_hash {
+            // There is no ledger data nor async pool data.
+            match tokio::time::timeout(
+                write_timeout,
+                server.send(BootstrapServerMessage::FinalStateFinished {
+                    slot: current_slot,
+                    final_state_hash,
+                }),
+            )
+            .await
+            {
+                Err(_) => Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "bootstrap ask ledger part send timed out",
+                )
+                .into()),
+                Ok(Err(e)) => Err(e),
+                Ok(Ok(_)) => Ok(()),
+            }?;
+            break;
+        } else {
             match tokio::time::timeout(
                 write_timeout,
                 server.send(BootstrapServerMessage::FinalStatePart {
@@ -404,30 +513,192 @@ pub async fn send_final_state_stream(
                 Ok(Err(e)) => Err(e),
                 Ok(Ok(_)) => Ok(()),
             }?;
+        }
+    }
+    Ok(())
+}
+
+/// Bounds of the addresses of a ledger range, `range_end` being excluded
+fn ledger_range_bounds(
+    range_start: Option<Address>,
+    range_end: Option<Address>,
+) -> (Bound<Address>, Bound<Address>) {
+    (
+        range_start.map_or(Bound::Unbounded, Bound::Included),
+        range_end.map_or(Bound::Unbounded, Bound::Excluded),
+    )
+}
+
+/// Stream a range of the ledger, along with the changes of the range that happen meanwhile,
+/// until the client has the whole range at the current final slot.
+/// Used by clients that download the ledger from several servers in parallel.
+pub async fn send_ledger_range_stream(
+    server: &mut BootstrapServerBinder,
+    final_state: Arc<RwLock<FinalState>>,
+    range_start: Option<Address>,
+    range_end: Option<Address>,
+    mut last_slot: Option<Slot>,
+    mut last_key: Option<Vec<u8>>,
+    write_timeout: Duration,
+) -> Result<(), BootstrapError> {
+    // addresses are shorter than ledger keys so they are never taken as a key themselves
+    let start_key = range_start.map(|address| address.to_bytes().to_vec());
+    let end_key = range_end.map(|address| address.to_bytes().to_vec());
+    loop {
+        let slot_too_old = if let Some(slot) = last_slot {
+            let final_state_read = final_state.read();
+            slot < final_state_read.slot
+                && final_state_read
+                    .changes_history
+                    .front()
+                    .map_or(true, |(first_slot, _)| slot < *first_slot)
         } else {
-            // There is no ledger data nor async pool data.
+            false
+        };
+        if slot_too_old {
             match tokio::time::timeout(
                 write_timeout,
-                server.send(BootstrapServerMessage::FinalStateFinished),
+                server.send(BootstrapServerMessage::SlotTooOld),
             )
             .await
             {
                 Err(_) => Err(std::io::Error::new(
                     std::io::ErrorKind::TimedOut,
-                    "bootstrap ask ledger part send timed out",
+                    "bootstrap slot too old send timed out",
+                )
+                .into()),
+                Ok(Err(e)) => Err(e),
+                Ok(Ok(_)) => Ok(()),
+            }?;
+            return Ok(());
+        }
+
+        let current_slot;
+        let ledger_data;
+        let ledger_changes;
+
+        // Scope of the final state read
+        {
+            let final_state_read = final_state.read();
+            let (data, new_last_key) = final_state_read
+                .ledger
+                .get_ledger_range_part(
+                    if last_key.is_some() {
+                        &last_key
+                    } else {
+                        &start_key
+                    },
+                    end_key.as_deref(),
+                )
+                .map_err(|_| {
+                    BootstrapError::GeneralError(
+                        "Error on fetching ledger range part of execution".to_string(),
+                    )
+                })?;
+            ledger_data = data;
+
+            // only the addresses already streamed need the changes since the cursor slot
+            ledger_changes = match (last_slot, &last_key) {
+                (Some(slot), Some(key)) if slot != final_state_read.slot => {
+                    if slot > final_state_read.slot {
+                        return Err(BootstrapError::GeneralError(
+                            "Bootstrap cursor set to future slot".to_string(),
+                        ));
+                    }
+                    let last_address = get_address_from_key(key).ok_or_else(|| {
+                        BootstrapError::GeneralError("Malformed key in slot changes".to_string())
+                    })?;
+                    final_state_read.get_ledger_changes_part(
+                        slot,
+                        None,
+                        (
+                            ledger_range_bounds(range_start, None).0,
+                            Bound::Included(last_address),
+                        ),
+                    )?
+                }
+                _ => Vec::new(),
+            };
+
+            if new_last_key.is_some() || !ledger_data.is_empty() {
+                last_key = new_last_key;
+            }
+            last_slot = Some(final_state_read.slot);
+            current_slot = final_state_read.slot;
+        }
+
+        if ledger_data.is_empty() && ledger_changes.is_empty() {
+            match tokio::time::timeout(
+                write_timeout,
+                server.send(BootstrapServerMessage::LedgerRangeFinished { slot: current_slot }),
+            )
+            .await
+            {
+                Err(_) => Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "bootstrap ledger range finished send timed out",
                 )
                 .into()),
                 Ok(Err(e)) => Err(e),
                 Ok(Ok(_)) => Ok(()),
             }?;
             break;
+        } else {
+            match tokio::time::timeout(
+                write_timeout,
+                server.send(BootstrapServerMessage::LedgerRangePart {
+                    slot: current_slot,
+                    ledger_data,
+                    ledger_changes,
+                }),
+            )
+            .await
+            {
+                Err(_) => Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "bootstrap ledger range part send timed out",
+                )
+                .into()),
+                Ok(Err(e)) => Err(e),
+                Ok(Ok(_)) => Ok(()),
+            }?;
         }
     }
     Ok(())
 }
 
+/// Latest final blocks of the graph, and hashes of the latest complete cycles,
+/// sent to a client cross-checking the state it got from another server
+fn get_state_summary(
+    data_graph: &BootstrapableGraph,
+    final_state: &Arc<RwLock<FinalState>>,
+) -> (Vec<(Slot, BlockId)>, Vec<(u64, Hash)>) {
+    let final_blocks = data_graph
+        .final_blocks
+        .iter()
+        .map(|export_block| {
+            (
+                export_block.block.content.header.content.slot,
+                export_block.block.id,
+            )
+        })
+        .collect();
+    let final_state_read = final_state.read();
+    let mut cycle_hashes: Vec<(u64, Hash)> = final_state_read
+        .pos_state
+        .cycle_history
+        .iter()
+        .rev()
+        .filter(|cycle_info| cycle_info.complete)
+        .take(POS_SAVED_CYCLES as usize)
+        .map(|cycle_info| (cycle_info.cycle, cycle_info.compute_hash()))
+        .collect();
+    cycle_hashes.reverse();
+    (final_blocks, cycle_hashes)
+}
+
 #[allow(clippy::too_many_arguments)]
-async fn manage_bootstrap(
+pub async fn manage_bootstrap(
     bootstrap_config: &BootstrapConfig,
     server: &mut BootstrapServerBinder,
     mut data_graph: BootstrapableGraph,
@@ -467,6 +738,9 @@ async fn manage_bootstrap(
 
     let write_timeout: std::time::Duration = bootstrap_config.write_timeout.into();
 
+    // The graph final blocks are dropped once sent, keep what a cross-checking client needs
+    let (summary_final_blocks, summary_cycle_hashes) = get_state_summary(&data_graph, &final_state);
+
     // Sync clocks.
     let server_time = MassaTime::now(compensation_millis)?;
 
@@ -488,6 +762,21 @@ async fn manage_bootstrap(
         Ok(Ok(_)) => Ok(()),
     }?;
 
+    match tokio::time::timeout(
+        bootstrap_config.read_timeout.into(),
+        server.negotiate_encryption(version),
+    )
+    .await
+    {
+        Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "bootstrap encryption negotiation timed out",
+        )
+        .into()),
+        Ok(Err(e)) => Err(e),
+        Ok(Ok(_)) => Ok(()),
+    }?;
+
     loop {
         match tokio::time::timeout(bootstrap_config.read_timeout.into(), server.next()).await {
             Err(_) => break Ok(()),
@@ -553,6 +842,77 @@ async fn manage_bootstrap(
                         }
                     }?;
                 }
+                BootstrapClientMessage::AskLedgerRangePart {
+                    range_start,
+                    range_end,
+                    last_slot,
+                    last_key,
+                } => {
+                    send_ledger_range_stream(
+                        server,
+                        final_state.clone(),
+                        range_start,
+                        range_end,
+                        last_slot,
+                        last_key,
+                        write_timeout,
+                    )
+                    .await?;
+                }
+                BootstrapClientMessage::AskLedgerRangeChanges {
+                    range_start,
+                    range_end,
+                    last_slot,
+                    until_slot,
+                } => {
+                    let (changes, final_slot) = {
+                        let final_state_read = final_state.read();
+                        (
+                            final_state_read.get_ledger_changes_part(
+                                last_slot,
+                                Some(until_slot),
+                                ledger_range_bounds(range_start, range_end),
+                            ),
+                            final_state_read.slot,
+                        )
+                    };
+                    let message = match changes {
+                        Ok(ledger_changes) => {
+                            BootstrapServerMessage::LedgerRangeChanges { ledger_changes }
+                        }
+                        // the until slot is final, so the last slot left the changes history
+                        Err(_) if until_slot <= final_slot => BootstrapServerMessage::SlotTooOld,
+                        Err(err) => break Err(err.into()),
+                    };
+                    match tokio::time::timeout(write_timeout, server.send(message)).await {
+                        Err(_) => Err(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            "bootstrap ledger range changes send timed out",
+                        )
+                        .into()),
+                        Ok(Err(e)) => Err(e),
+                        Ok(Ok(_)) => Ok(()),
+                    }?;
+                }
+                BootstrapClientMessage::AskStateSummary => {
+                    match tokio::time::timeout(
+                        write_timeout,
+                        server.send(BootstrapServerMessage::StateSummary {
+                            final_blocks: summary_final_blocks.clone(),
+                            cycle_hashes: summary_cycle_hashes.clone(),
+                        }),
+                    )
+                    .await
+                    {
+                        Err(_) => Err(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            "bootstrap state summary send timed out",
+                        )
+                        .into()),
+                        Ok(Err(e)) => Err(e),
+                        Ok(Ok(_)) => Ok(()),
+                    }?;
+                }
                 BootstrapClientMessage::BootstrapSuccess => break Ok(()),
                 BootstrapClientMessage::BootstrapError { error } => {
                     break Err(BootstrapError::ReceivedError(error));
@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::encryption::{read_encrypted_frame, write_encrypted_frame};
 use crate::error::BootstrapError;
 use crate::establisher::types::Duplex;
 use crate::messages::{
@@ -8,6 +9,7 @@ use crate::messages::{
 };
 use async_speed_limit::clock::StandardClock;
 use async_speed_limit::{Limiter, Resource};
+use massa_cipher::{EphemeralKey, SessionCipher, EPHEMERAL_KEY_SIZE, TAG_SIZE};
 use massa_hash::Hash;
 use massa_hash::HASH_SIZE_BYTES;
 use massa_models::serialization::{DeserializeMinBEInt, SerializeMinBEInt};
@@ -15,7 +17,7 @@ use massa_models::version::{Version, VersionDeserializer, VersionSerializer};
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_signature::KeyPair;
 use std::convert::TryInto;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 
 /// Bootstrap server binder
 pub struct BootstrapServerBinder {
@@ -29,6 +31,8 @@ pub struct BootstrapServerBinder {
     prev_message: Option<Hash>,
     version_serializer: VersionSerializer,
     version_deserializer: VersionDeserializer,
+    session: Option<SessionCipher>,
+    client_version: Option<Version>,
 }
 
 impl BootstrapServerBinder {
@@ -59,6 +63,8 @@ impl BootstrapServerBinder {
             randomness_size_bytes,
             version_serializer: VersionSerializer::new(),
             version_deserializer: VersionDeserializer::new(),
+            session: None,
+            client_version: None,
         }
     }
 }
@@ -66,7 +72,7 @@ impl BootstrapServerBinder {
 impl BootstrapServerBinder {
     /// Performs a handshake. Should be called after connection
     /// NOT cancel-safe
-    /// MUST always be followed by a send of the BootstrapMessage::BootstrapTime
+    /// MUST always be followed by a send of the BootstrapMessage::BootstrapTime, then by `negotiate_encryption`
     pub async fn handshake(&mut self, version: Version) -> Result<(), BootstrapError> {
         // read version and random bytes, send signature
         let msg_hash = {
@@ -82,6 +88,7 @@ impl BootstrapServerBinder {
             if !received_version.is_compatible(&version) {
                 return Err(BootstrapError::IncompatibleVersionError(format!("Received a bad incompatible version in handshake. (excepted: {}, received: {})", version, received_version)));
             }
+            self.client_version = Some(received_version);
             Hash::compute_from(&msg_bytes)
         };
 
@@ -91,6 +98,64 @@ impl BootstrapServerBinder {
         Ok(())
     }
 
+    /// Negotiates the encryption of the stream, if the client asks for it.
+    /// Must be called right after sending the `BootstrapMessage::BootstrapTime`.
+    /// The stream stays plain if either side is too old to take part in the negotiation.
+    /// NOT cancel-safe
+    pub async fn negotiate_encryption(&mut self, version: Version) -> Result<(), BootstrapError> {
+        let (prev_message, client_version) = match (self.prev_message, self.client_version) {
+            (Some(prev_message), Some(client_version)) => (prev_message, client_version),
+            _ => {
+                return Err(BootstrapError::GeneralError(
+                    "bootstrap encryption negotiated too early".to_string(),
+                ))
+            }
+        };
+        if !version.supports_encryption() || !client_version.supports_encryption() {
+            return Ok(());
+        }
+
+        // the client tells whether it wants the stream encrypted, and sends its ephemeral key if it does
+        let mut encryption_flag = [0u8; 1];
+        self.duplex.read_exact(&mut encryption_flag).await?;
+        match encryption_flag[0] {
+            0 => {}
+            1 => {
+                let mut client_ephemeral_key = [0u8; EPHEMERAL_KEY_SIZE];
+                self.duplex.read_exact(&mut client_ephemeral_key).await?;
+
+                // answer with our ephemeral key, signed with our node key to authenticate it
+                let ephemeral_key = EphemeralKey::generate();
+                let server_ephemeral_key = ephemeral_key.public_bytes();
+                let handshake_hash = Hash::compute_from(
+                    &[
+                        &prev_message.to_bytes()[..],
+                        &encryption_flag[..],
+                        &client_ephemeral_key[..],
+                        &server_ephemeral_key[..],
+                        &self.local_keypair.get_public_key().to_bytes()[..],
+                    ]
+                    .concat(),
+                );
+                let sig = self.local_keypair.sign(&handshake_hash)?;
+                self.duplex.write_all(&server_ephemeral_key).await?;
+                self.duplex.write_all(&sig.to_bytes()).await?;
+                self.session = Some(ephemeral_key.into_session(
+                    client_ephemeral_key,
+                    &handshake_hash,
+                    false,
+                )?);
+            }
+            _ => {
+                return Err(BootstrapError::GeneralError(
+                    "Received an invalid encryption flag in handshake".to_string(),
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
     /// Writes the next message. NOT cancel-safe
     pub async fn send(&mut self, msg: BootstrapServerMessage) -> Result<(), BootstrapError> {
         // serialize message
@@ -116,16 +181,20 @@ impl BootstrapServerBinder {
         };
 
         // send signature
-        self.duplex.write_all(&sig.to_bytes()).await?;
+        let mut frame = sig.to_bytes().to_vec();
 
         // send message length
         {
             let msg_len_bytes = msg_len.to_be_bytes_min(self.max_bootstrap_message_size)?;
-            self.duplex.write_all(&msg_len_bytes).await?;
+            frame.extend(msg_len_bytes);
         }
 
         // send message
-        self.duplex.write_all(&msg_bytes).await?;
+        frame.extend(msg_bytes);
+        match &mut self.session {
+            Some(session) => write_encrypted_frame(&mut self.duplex, session, &frame).await?,
+            None => self.duplex.write_all(&frame).await?,
+        }
 
         // save prev sig
         self.prev_message = Some(Hash::compute_from(&sig.to_bytes()));
@@ -136,11 +205,27 @@ impl BootstrapServerBinder {
     #[allow(dead_code)]
     /// Read a message sent from the client (not signed). NOT cancel-safe
     pub async fn next(&mut self) -> Result<BootstrapClientMessage, BootstrapError> {
+        // when the stream is encrypted, read and decrypt the whole frame first
+        let frame;
+        let mut frame_reader: &[u8];
+        let reader: &mut (dyn AsyncRead + Unpin + Send) = match &mut self.session {
+            Some(session) => {
+                let max_frame_len = HASH_SIZE_BYTES
+                    + self.size_field_len
+                    + self.max_bootstrap_message_size as usize
+                    + TAG_SIZE;
+                frame = read_encrypted_frame(&mut self.duplex, session, max_frame_len).await?;
+                frame_reader = &frame[..];
+                &mut frame_reader
+            }
+            None => &mut self.duplex,
+        };
+
         // read prev hash
         let received_prev_hash = {
             if self.prev_message.is_some() {
                 let mut hash_bytes = [0u8; HASH_SIZE_BYTES];
-                self.duplex.read_exact(&mut hash_bytes).await?;
+                reader.read_exact(&mut hash_bytes).await?;
                 Some(Hash::from_bytes(&hash_bytes))
             } else {
                 None
@@ -150,13 +235,13 @@ impl BootstrapServerBinder {
         // read message length
         let msg_len = {
             let mut msg_len_bytes = vec![0u8; self.size_field_len];
-            self.duplex.read_exact(&mut msg_len_bytes[..]).await?;
+            reader.read_exact(&mut msg_len_bytes[..]).await?;
             u32::from_be_bytes_min(&msg_len_bytes, self.max_bootstrap_message_size)?.0
         };
 
         // read message
         let mut msg_bytes = vec![0u8; msg_len as usize];
-        self.duplex.read_exact(&mut msg_bytes).await?;
+        reader.read_exact(&mut msg_bytes).await?;
 
         // check previous hash
         if received_prev_hash != self.prev_message {
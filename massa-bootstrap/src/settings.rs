@@ -24,10 +24,14 @@ pub struct BootstrapConfig {
     pub write_error_timeout: MassaTime,
     /// Time we wait before retrying a bootstrap
     pub retry_delay: MassaTime,
+    /// Number of servers of the bootstrap list the ledger is downloaded from in parallel, 1 to disable
+    pub parallel_bootstrap_servers: usize,
     /// Max ping delay.
     pub max_ping: MassaTime,
     /// Enable clock synchronization
     pub enable_clock_synchronization: bool,
+    /// Ask the bootstrap servers to encrypt the stream with a key agreed on during the handshake
+    pub enable_encryption: bool,
     /// Cache duration
     pub cache_duration: MassaTime,
     /// Max simultaneous bootstraps
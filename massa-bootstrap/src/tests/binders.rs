@@ -45,6 +45,7 @@ async fn test_binders() {
         client,
         bootstrap_config.bootstrap_list[0].1,
         f64::INFINITY,
+        false,
         MAX_BOOTSTRAP_MESSAGE_SIZE,
         ENDORSEMENT_COUNT,
         MAX_ADVERTISE_LENGTH,
@@ -72,7 +73,7 @@ async fn test_binders() {
             peers: BootstrapPeers(vector_peers.clone()),
         };
 
-        let version: Version = Version::from_str("TEST.1.10").unwrap();
+        let version: Version = Version::from_str("TEST.14.10").unwrap();
 
         server.handshake(version).await.unwrap();
         server.send(test_peers_message.clone()).await.unwrap();
@@ -102,7 +103,7 @@ async fn test_binders() {
         // Test message 1
         let vector_peers = vec![bootstrap_config.bootstrap_list[0].0.ip()];
 
-        let version: Version = Version::from_str("TEST.1.10").unwrap();
+        let version: Version = Version::from_str("TEST.14.10").unwrap();
 
         client.handshake(version).await.unwrap();
         let message = client.next().await.unwrap();
@@ -159,6 +160,7 @@ async fn test_binders_double_send_server_works() {
         client,
         bootstrap_config.bootstrap_list[0].1,
         f64::INFINITY,
+        false,
         MAX_BOOTSTRAP_MESSAGE_SIZE,
         ENDORSEMENT_COUNT,
         MAX_ADVERTISE_LENGTH,
@@ -186,7 +188,7 @@ async fn test_binders_double_send_server_works() {
             peers: BootstrapPeers(vector_peers.clone()),
         };
 
-        let version: Version = Version::from_str("TEST.1.10").unwrap();
+        let version: Version = Version::from_str("TEST.14.10").unwrap();
 
         server.handshake(version).await.unwrap();
         server.send(test_peers_message.clone()).await.unwrap();
@@ -208,7 +210,7 @@ async fn test_binders_double_send_server_works() {
         // Test message 1
         let vector_peers = vec![bootstrap_config.bootstrap_list[0].0.ip()];
 
-        let version: Version = Version::from_str("TEST.1.10").unwrap();
+        let version: Version = Version::from_str("TEST.14.10").unwrap();
 
         client.handshake(version).await.unwrap();
         let message = client.next().await.unwrap();
@@ -258,6 +260,7 @@ async fn test_binders_try_double_send_client_works() {
         client,
         bootstrap_config.bootstrap_list[0].1,
         f64::INFINITY,
+        false,
         MAX_BOOTSTRAP_MESSAGE_SIZE,
         ENDORSEMENT_COUNT,
         MAX_ADVERTISE_LENGTH,
@@ -284,7 +287,7 @@ async fn test_binders_try_double_send_client_works() {
         let test_peers_message = BootstrapServerMessage::BootstrapPeers {
             peers: BootstrapPeers(vector_peers.clone()),
         };
-        let version: Version = Version::from_str("TEST.1.10").unwrap();
+        let version: Version = Version::from_str("TEST.14.10").unwrap();
 
         server.handshake(version).await.unwrap();
         server.send(test_peers_message.clone()).await.unwrap();
@@ -311,7 +314,7 @@ async fn test_binders_try_double_send_client_works() {
     let client_thread = tokio::spawn(async move {
         // Test message 1
         let vector_peers = vec![bootstrap_config.bootstrap_list[0].0.ip()];
-        let version: Version = Version::from_str("TEST.1.10").unwrap();
+        let version: Version = Version::from_str("TEST.14.10").unwrap();
 
         client.handshake(version).await.unwrap();
         let message = client.next().await.unwrap();
@@ -350,3 +353,182 @@ async fn test_binders_try_double_send_client_works() {
     server_thread.await.unwrap();
     client_thread.await.unwrap();
 }
+
+/// The server and the client will agree on a key after the handshake and then send encrypted messages in both ways
+#[tokio::test]
+#[serial]
+async fn test_binders_encrypted() {
+    let (bootstrap_config, server_keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+    let (client, server) = duplex(1000000);
+    let mut server = BootstrapServerBinder::new(
+        server,
+        server_keypair.clone(),
+        f64::INFINITY,
+        MAX_BOOTSTRAP_MESSAGE_SIZE,
+        THREAD_COUNT,
+        MAX_DATASTORE_KEY_LENGTH,
+        BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+    );
+    let mut client = BootstrapClientBinder::new(
+        client,
+        bootstrap_config.bootstrap_list[0].1,
+        f64::INFINITY,
+        true,
+        MAX_BOOTSTRAP_MESSAGE_SIZE,
+        ENDORSEMENT_COUNT,
+        MAX_ADVERTISE_LENGTH,
+        MAX_BOOTSTRAP_BLOCKS,
+        MAX_OPERATIONS_PER_BLOCK,
+        THREAD_COUNT,
+        BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+        MAX_BOOTSTRAP_ASYNC_POOL_CHANGES,
+        MAX_BOOTSTRAP_ERROR_LENGTH,
+        MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE,
+        MAX_DATASTORE_ENTRY_COUNT,
+        MAX_DATASTORE_KEY_LENGTH,
+        MAX_DATASTORE_VALUE_LENGTH,
+        MAX_DATA_ASYNC_MESSAGE,
+        MAX_FUNCTION_NAME_LENGTH,
+        MAX_PARAMETERS_SIZE,
+        MAX_LEDGER_CHANGES_COUNT,
+        1000,
+    );
+
+    let server_thread = tokio::spawn(async move {
+        let vector_peers = vec![bootstrap_config.bootstrap_list[0].0.ip()];
+        let test_peers_message = BootstrapServerMessage::BootstrapPeers {
+            peers: BootstrapPeers(vector_peers.clone()),
+        };
+
+        let version: Version = Version::from_str("TEST.14.10").unwrap();
+
+        server.handshake(version).await.unwrap();
+        server.negotiate_encryption(version).await.unwrap();
+        server.send(test_peers_message.clone()).await.unwrap();
+
+        let message = server.next().await.unwrap();
+        match message {
+            BootstrapClientMessage::BootstrapError { error } => {
+                assert_eq!(error, "test error");
+            }
+            _ => panic!("Bad message receive: Expected an error message"),
+        }
+    });
+
+    let client_thread = tokio::spawn(async move {
+        let vector_peers = vec![bootstrap_config.bootstrap_list[0].0.ip()];
+
+        let version: Version = Version::from_str("TEST.14.10").unwrap();
+
+        client.handshake(version).await.unwrap();
+        client.negotiate_encryption(version, version).await.unwrap();
+        let message = client.next().await.unwrap();
+        match message {
+            BootstrapServerMessage::BootstrapPeers { peers } => {
+                assert_eq!(vector_peers, peers.0);
+            }
+            _ => panic!("Bad message receive: Expected a peers list message"),
+        }
+
+        client
+            .send(&BootstrapClientMessage::BootstrapError {
+                error: "test error".to_string(),
+            })
+            .await
+            .unwrap();
+    });
+
+    server_thread.await.unwrap();
+    client_thread.await.unwrap();
+}
+
+/// The client asks for encryption but the server is too old to negotiate it: messages are sent in plain in both ways
+#[tokio::test]
+#[serial]
+async fn test_binders_encryption_older_server() {
+    let (bootstrap_config, server_keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+    let (client, server) = duplex(1000000);
+    let mut server = BootstrapServerBinder::new(
+        server,
+        server_keypair.clone(),
+        f64::INFINITY,
+        MAX_BOOTSTRAP_MESSAGE_SIZE,
+        THREAD_COUNT,
+        MAX_DATASTORE_KEY_LENGTH,
+        BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+    );
+    let mut client = BootstrapClientBinder::new(
+        client,
+        bootstrap_config.bootstrap_list[0].1,
+        f64::INFINITY,
+        true,
+        MAX_BOOTSTRAP_MESSAGE_SIZE,
+        ENDORSEMENT_COUNT,
+        MAX_ADVERTISE_LENGTH,
+        MAX_BOOTSTRAP_BLOCKS,
+        MAX_OPERATIONS_PER_BLOCK,
+        THREAD_COUNT,
+        BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+        MAX_BOOTSTRAP_ASYNC_POOL_CHANGES,
+        MAX_BOOTSTRAP_ERROR_LENGTH,
+        MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE,
+        MAX_DATASTORE_ENTRY_COUNT,
+        MAX_DATASTORE_KEY_LENGTH,
+        MAX_DATASTORE_VALUE_LENGTH,
+        MAX_DATA_ASYNC_MESSAGE,
+        MAX_FUNCTION_NAME_LENGTH,
+        MAX_PARAMETERS_SIZE,
+        MAX_LEDGER_CHANGES_COUNT,
+        1000,
+    );
+
+    let server_thread = tokio::spawn(async move {
+        let vector_peers = vec![bootstrap_config.bootstrap_list[0].0.ip()];
+        let test_peers_message = BootstrapServerMessage::BootstrapPeers {
+            peers: BootstrapPeers(vector_peers.clone()),
+        };
+
+        let version: Version = Version::from_str("TEST.14.7").unwrap();
+
+        server.handshake(version).await.unwrap();
+        server.negotiate_encryption(version).await.unwrap();
+        server.send(test_peers_message.clone()).await.unwrap();
+
+        let message = server.next().await.unwrap();
+        match message {
+            BootstrapClientMessage::BootstrapError { error } => {
+                assert_eq!(error, "test error");
+            }
+            _ => panic!("Bad message receive: Expected an error message"),
+        }
+    });
+
+    let client_thread = tokio::spawn(async move {
+        let vector_peers = vec![bootstrap_config.bootstrap_list[0].0.ip()];
+
+        let version: Version = Version::from_str("TEST.14.10").unwrap();
+
+        client.handshake(version).await.unwrap();
+        client
+            .negotiate_encryption(version, Version::from_str("TEST.14.7").unwrap())
+            .await
+            .unwrap();
+        let message = client.next().await.unwrap();
+        match message {
+            BootstrapServerMessage::BootstrapPeers { peers } => {
+                assert_eq!(vector_peers, peers.0);
+            }
+            _ => panic!("Bad message receive: Expected a peers list message"),
+        }
+
+        client
+            .send(&BootstrapClientMessage::BootstrapError {
+                error: "test error".to_string(),
+            })
+            .await
+            .unwrap();
+    });
+
+    server_thread.await.unwrap();
+    client_thread.await.unwrap();
+}
@@ -3,29 +3,53 @@
 use super::{
     mock_establisher,
     tools::{
-        bridge_mock_streams, get_boot_state, get_peers, get_random_final_state_bootstrap,
-        get_random_ledger_changes, wait_consensus_command, wait_network_command,
+        accept_bootstrap_connection, accept_bootstrap_session, apply_random_changes,
+        bridge_mock_streams, get_boot_state, get_first_final_state_part, get_peers,
+        get_random_address, get_random_final_state_bootstrap, get_random_ledger_changes,
+        get_test_pos_state, read_final_state_part_request, send_requested_final_state_stream,
+        serve_bootstrap_end, spawn_get_state, start_test_selector_worker, wait_consensus_command,
+        wait_network_command, FinalStatePartRequest,
     },
 };
-use crate::tests::tools::{
-    get_random_async_pool_changes, get_random_executed_ops, get_random_pos_changes,
-};
-use crate::BootstrapConfig;
+use crate::error::BootstrapError;
+use crate::messages::{BootstrapClientMessage, BootstrapServerMessage};
+use crate::server::manage_bootstrap;
+use crate::server_binder::BootstrapServerBinder;
+use crate::tests::tools::BASE_BOOTSTRAP_IP;
 use crate::{
-    get_state, start_bootstrap_server,
+    export_state_to_snapshot, get_state_from_snapshot, start_bootstrap_server,
     tests::tools::{assert_eq_bootstrap_graph, get_bootstrap_config},
 };
+use crate::{BootstrapConfig, GlobalBootstrapState};
 use massa_consensus_exports::{commands::ConsensusCommand, ConsensusCommandSender};
-use massa_final_state::{test_exports::assert_eq_final_state, FinalState, StateChanges};
-use massa_models::{address::Address, slot::Slot, version::Version};
+use massa_final_state::{
+    test_exports::assert_eq_final_state, ExecutedOpsStreamingStep, FinalState,
+};
+use massa_graph::{
+    create_genesis_block, export_active_block::ExportActiveBlock, settings::GraphConfig,
+    BlockGraph, BootstrapableGraph,
+};
+use massa_hash::Hash;
+use massa_models::{
+    address::Address,
+    api::BlockGraphStatus,
+    block::{Block, BlockHeader, BlockHeaderSerializer, BlockId, BlockSerializer, WrappedBlock},
+    slot::Slot,
+    version::Version,
+    wrapped::WrappedContent,
+};
 use massa_network_exports::{NetworkCommand, NetworkCommandSender};
-use massa_pos_exports::{test_exports::assert_eq_pos_selection, PoSFinalState, SelectorConfig};
-use massa_pos_worker::start_selector_worker;
+use massa_pos_exports::{
+    test_exports::{
+        assert_eq_pos_selection, MockSelectorController, MockSelectorControllerMessage,
+    },
+    PoSCycleStreamingStep, Selection, SelectorController,
+};
 use massa_signature::KeyPair;
-use massa_time::MassaTime;
+use massa_storage::Storage;
 use parking_lot::RwLock;
 use serial_test::serial;
-use std::{path::PathBuf, str::FromStr, sync::Arc, time::Duration};
+use std::{collections::HashMap, net::SocketAddr, str::FromStr, sync::Arc, time::Duration};
 use tokio::sync::mpsc;
 
 lazy_static::lazy_static! {
@@ -40,37 +64,16 @@ lazy_static::lazy_static! {
 async fn test_bootstrap_server() {
     let (bootstrap_config, keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
 
-    let rolls_path = PathBuf::from_str("../massa-node/base_config/initial_rolls.json").unwrap();
     let genesis_address = Address::from_public_key(&KeyPair::generate().get_public_key());
     let (mut server_selector_manager, server_selector_controller) =
-        start_selector_worker(SelectorConfig {
-            thread_count: 2,
-            periods_per_cycle: 2,
-            genesis_address,
-            ..Default::default()
-        })
-        .expect("could not start server selector controller");
+        start_test_selector_worker(genesis_address);
     let (mut client_selector_manager, client_selector_controller) =
-        start_selector_worker(SelectorConfig {
-            thread_count: 2,
-            periods_per_cycle: 2,
-            genesis_address,
-            ..Default::default()
-        })
-        .expect("could not start client selector controller");
+        start_test_selector_worker(genesis_address);
 
     let (consensus_cmd_tx, mut consensus_cmd_rx) = mpsc::channel::<ConsensusCommand>(5);
     let (network_cmd_tx, mut network_cmd_rx) = mpsc::channel::<NetworkCommand>(5);
-    let final_state_bootstrap = get_random_final_state_bootstrap(
-        PoSFinalState::new(
-            &"".to_string(),
-            &rolls_path,
-            2,
-            2,
-            server_selector_controller.clone(),
-        )
-        .unwrap(),
-    );
+    let final_state_bootstrap =
+        get_random_final_state_bootstrap(get_test_pos_state(server_selector_controller.clone()));
     let final_state = Arc::new(RwLock::new(final_state_bootstrap));
 
     let (bootstrap_establisher, bootstrap_interface) = mock_establisher::new();
@@ -82,39 +85,23 @@ async fn test_bootstrap_server() {
         bootstrap_establisher,
         keypair.clone(),
         0,
-        Version::from_str("TEST.1.10").unwrap(),
+        Version::from_str("TEST.14.10").unwrap(),
     )
     .await
     .unwrap()
     .unwrap();
 
     let final_state_client = Arc::new(RwLock::new(FinalState::default_with_pos(
-        PoSFinalState::new(
-            &"".to_string(),
-            &rolls_path,
-            2,
-            2,
-            client_selector_controller.clone(),
-        )
-        .unwrap(),
+        get_test_pos_state(client_selector_controller.clone()),
     )));
-    let final_state_client_clone = final_state_client.clone();
     let final_state_clone = final_state.clone();
 
     // launch the get_state process
-    let (remote_establisher, mut remote_interface) = mock_establisher::new();
-    let get_state_h = tokio::spawn(async move {
-        get_state(
-            bootstrap_config,
-            final_state_client_clone,
-            remote_establisher,
-            Version::from_str("TEST.1.10").unwrap(),
-            MassaTime::now(0).unwrap().saturating_sub(1000.into()),
-            None,
-        )
-        .await
-        .unwrap()
-    });
+    let (get_state_h, mut remote_interface) = spawn_get_state(
+        bootstrap_config.clone(),
+        final_state_client.clone(),
+        Version::from_str("TEST.14.10").unwrap(),
+    );
 
     // accept connection attempt from remote
     let (remote_rw, conn_addr, resp) = tokio::time::timeout(
@@ -186,32 +173,18 @@ async fn test_bootstrap_server() {
     let (sent_peers, sent_graph) = tokio::join!(wait_peers(), wait_graph());
 
     // launch the modifier thread
-    let list_changes: Arc<RwLock<Vec<(Slot, StateChanges)>>> = Arc::new(RwLock::new(Vec::new()));
-    let list_changes_clone = list_changes.clone();
     std::thread::spawn(move || {
         for _ in 0..10 {
             std::thread::sleep(Duration::from_millis(500));
-            let mut final_write = final_state_clone.write();
-            let next = final_write.slot.get_next_slot(2).unwrap();
-            final_write.slot = next;
-            let changes = StateChanges {
-                pos_changes: get_random_pos_changes(10),
-                ledger_changes: get_random_ledger_changes(10),
-                async_pool_changes: get_random_async_pool_changes(10),
-                executed_ops: get_random_executed_ops(10),
-            };
-            final_write
-                .changes_history
-                .push_back((next, changes.clone()));
-            let mut list_changes_write = list_changes_clone.write();
-            list_changes_write.push((next, changes));
+            apply_random_changes(&mut final_state_clone.write());
         }
     });
 
     // wait for get_state
     let bootstrap_res = get_state_h
         .await
-        .expect("error while waiting for get_state to finish");
+        .expect("error while waiting for get_state to finish")
+        .expect("bootstrap failed");
 
     // wait for bridge
     bridge.await.expect("bridge join failed");
@@ -223,28 +196,6 @@ async fn test_bootstrap_server() {
         "mismatch between sent and received peers"
     );
 
-    // apply the changes to the server state before matching with the client
-    {
-        let mut final_state_write = final_state.write();
-        let list_changes_read = list_changes.read().clone();
-        // note: skip the first change to match the update loop behaviour
-        for (slot, change) in list_changes_read.iter().skip(1) {
-            final_state_write
-                .pos_state
-                .apply_changes(change.pos_changes.clone(), *slot, false)
-                .unwrap();
-            final_state_write
-                .ledger
-                .apply_changes(change.ledger_changes.clone(), *slot);
-            final_state_write
-                .async_pool
-                .apply_changes_unchecked(&change.async_pool_changes);
-            final_state_write
-                .executed_ops
-                .extend(change.executed_ops.clone());
-        }
-    }
-
     // check final states
     assert_eq_final_state(&final_state.read(), &final_state_client.read());
 
@@ -270,3 +221,619 @@ async fn test_bootstrap_server() {
     server_selector_manager.stop();
     client_selector_manager.stop();
 }
+
+/// Random final state for a bootstrap server played by the test, and an empty one for the client
+fn get_scripted_final_states(
+    selector_controller: Box<dyn SelectorController>,
+) -> (Arc<RwLock<FinalState>>, Arc<RwLock<FinalState>>) {
+    let final_state =
+        get_random_final_state_bootstrap(get_test_pos_state(selector_controller.clone()));
+    let final_state_client = FinalState::default_with_pos(get_test_pos_state(selector_controller));
+    (
+        Arc::new(RwLock::new(final_state)),
+        Arc::new(RwLock::new(final_state_client)),
+    )
+}
+
+#[tokio::test]
+#[serial]
+async fn test_bootstrap_resume_interrupted_stream() {
+    let (bootstrap_config, keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+    let version = Version::from_str("TEST.14.10").unwrap();
+    let (mut selector_manager, selector_controller) =
+        start_test_selector_worker(get_random_address());
+    let (final_state, final_state_client) = get_scripted_final_states(selector_controller);
+    apply_random_changes(&mut final_state.write());
+
+    // launch the get_state process
+    let (get_state_h, mut remote_interface) = spawn_get_state(
+        bootstrap_config.clone(),
+        final_state_client.clone(),
+        version,
+    );
+
+    // the first session is cut right after the first part of the final state
+    let mut server =
+        accept_bootstrap_session(&mut remote_interface, bootstrap_config, keypair, version).await;
+    let request = read_final_state_part_request(&mut server).await;
+    assert!(
+        request.last_slot.is_none() && request.last_key.is_none(),
+        "unexpected first request: {:?}",
+        request
+    );
+    let part_slot = final_state.read().slot;
+    let first_part = get_first_final_state_part(&final_state.read());
+    server.send(first_part).await.unwrap();
+    drop(server);
+
+    // the final state moves on before the client comes back
+    apply_random_changes(&mut final_state.write());
+
+    // the client resumes from the part it got, which it kept
+    let mut server =
+        accept_bootstrap_session(&mut remote_interface, bootstrap_config, keypair, version).await;
+    let request = read_final_state_part_request(&mut server).await;
+    assert_eq!(request.last_slot, Some(part_slot), "wrong resume slot");
+    assert!(request.last_key.is_some(), "the ledger cursor was lost");
+    assert!(
+        !final_state_client
+            .read()
+            .ledger
+            .get_ledger_part(&None)
+            .unwrap()
+            .0
+            .is_empty(),
+        "the partial final state was dropped"
+    );
+    send_requested_final_state_stream(&mut server, final_state.clone(), request, bootstrap_config)
+        .await;
+    serve_bootstrap_end(&mut server, get_peers(), get_boot_state()).await;
+
+    get_state_h
+        .await
+        .expect("error while waiting for get_state to finish")
+        .expect("bootstrap failed");
+    assert_eq_final_state(&final_state.read(), &final_state_client.read());
+
+    selector_manager.stop();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_bootstrap_slot_too_old() {
+    let (bootstrap_config, keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+    let version = Version::from_str("TEST.14.10").unwrap();
+    let (mut selector_manager, selector_controller) =
+        start_test_selector_worker(get_random_address());
+    let (final_state, final_state_client) = get_scripted_final_states(selector_controller);
+    apply_random_changes(&mut final_state.write());
+
+    // launch the get_state process
+    let (get_state_h, mut remote_interface) = spawn_get_state(
+        bootstrap_config.clone(),
+        final_state_client.clone(),
+        version,
+    );
+
+    // the first session is cut right after the first part of the final state
+    let mut server =
+        accept_bootstrap_session(&mut remote_interface, bootstrap_config, keypair, version).await;
+    server.next().await.unwrap();
+    let first_part = get_first_final_state_part(&final_state.read());
+    server.send(first_part).await.unwrap();
+    drop(server);
+
+    // the changes since the slot of the client leave the history of the server
+    {
+        let mut final_write = final_state.write();
+        apply_random_changes(&mut final_write);
+        final_write.changes_history.pop_front();
+    }
+
+    // the server tells the client its slot is too old
+    let mut server =
+        accept_bootstrap_session(&mut remote_interface, bootstrap_config, keypair, version).await;
+    let request = read_final_state_part_request(&mut server).await;
+    assert!(
+        request.last_slot.is_some(),
+        "the client did not resume its stream"
+    );
+    send_requested_final_state_stream(&mut server, final_state.clone(), request, bootstrap_config)
+        .await;
+
+    // the client drops its partial state and asks the whole state again
+    let request = read_final_state_part_request(&mut server).await;
+    assert!(
+        matches!(
+            request,
+            FinalStatePartRequest {
+                last_slot: None,
+                last_key: None,
+                last_async_message_id: None,
+                last_cycle_step: PoSCycleStreamingStep::Started,
+                last_credits_slot: None,
+                last_exec_ops_step: ExecutedOpsStreamingStep::Started,
+            }
+        ),
+        "the client did not restart its stream: {:?}",
+        request
+    );
+    assert!(
+        final_state_client
+            .read()
+            .ledger
+            .get_ledger_part(&None)
+            .unwrap()
+            .0
+            .is_empty(),
+        "the partial final state was not reset"
+    );
+    send_requested_final_state_stream(&mut server, final_state.clone(), request, bootstrap_config)
+        .await;
+    serve_bootstrap_end(&mut server, get_peers(), get_boot_state()).await;
+
+    get_state_h
+        .await
+        .expect("error while waiting for get_state to finish")
+        .expect("bootstrap failed");
+    assert_eq_final_state(&final_state.read(), &final_state_client.read());
+
+    selector_manager.stop();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_bootstrap_final_state_hash_mismatch() {
+    let (bootstrap_config, keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+    let version = Version::from_str("TEST.14.10").unwrap();
+    let (mut selector_manager, selector_controller) =
+        start_test_selector_worker(get_random_address());
+    let (final_state, final_state_client) = get_scripted_final_states(selector_controller);
+
+    // launch the get_state process
+    let (get_state_h, mut remote_interface) =
+        spawn_get_state(bootstrap_config.clone(), final_state_client, version);
+
+    // the server streams a part that does not match the state it announces at the end
+    let mut server =
+        accept_bootstrap_session(&mut remote_interface, bootstrap_config, keypair, version).await;
+    server.next().await.unwrap();
+    let corrupted_part = get_first_final_state_part(&final_state.read());
+    server.send(corrupted_part).await.unwrap();
+    let (slot, final_state_hash) = {
+        let mut final_write = final_state.write();
+        let slot = final_write.slot;
+        final_write
+            .ledger
+            .apply_changes(get_random_ledger_changes(1), slot);
+        (slot, final_write.compute_state_hash())
+    };
+    server
+        .send(BootstrapServerMessage::FinalStateFinished {
+            slot,
+            final_state_hash,
+        })
+        .await
+        .unwrap();
+
+    // the client rejects the state and tells the server why
+    match server.next().await.unwrap() {
+        BootstrapClientMessage::BootstrapError { .. } => {}
+        other => panic!("unexpected client message: {:?}", other),
+    }
+    match get_state_h
+        .await
+        .expect("error while waiting for get_state to finish")
+    {
+        Err(BootstrapError::FinalStateHashMismatch {
+            slot: mismatch_slot,
+            expected,
+            ..
+        }) => {
+            assert_eq!(mismatch_slot, slot);
+            assert_eq!(expected, final_state_hash);
+        }
+        Err(err) => panic!("unexpected bootstrap error: {}", err),
+        Ok(_) => panic!("a corrupted final state was accepted"),
+    }
+
+    selector_manager.stop();
+}
+
+/// Copy of a final state, history included, through a snapshot
+fn copy_final_state(
+    final_state: &FinalState,
+    selector_controller: Box<dyn SelectorController>,
+) -> Arc<RwLock<FinalState>> {
+    let snapshot_file = tempfile::NamedTempFile::new().expect("cannot create temp file");
+    final_state
+        .export_snapshot(snapshot_file.path(), None)
+        .expect("could not export snapshot");
+    let mut copy = FinalState::default_with_pos(get_test_pos_state(selector_controller));
+    copy.import_snapshot(snapshot_file.path())
+        .expect("could not import snapshot");
+    copy.changes_history = final_state.changes_history.clone();
+    Arc::new(RwLock::new(copy))
+}
+
+/// Bootstraps `final_state_client` from two servers at once, played by the test with their own final state and graph
+async fn bootstrap_in_parallel(
+    servers: [(Arc<RwLock<FinalState>>, BootstrapableGraph); 2],
+    final_state_client: Arc<RwLock<FinalState>>,
+) -> Result<GlobalBootstrapState, BootstrapError> {
+    let (_, keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+    let version = Version::from_str("TEST.14.10").unwrap();
+    let mut bootstrap_config = get_bootstrap_config(keypair.get_public_key());
+    bootstrap_config.bootstrap_list.push((
+        SocketAddr::new(BASE_BOOTSTRAP_IP, 17),
+        keypair.get_public_key(),
+    ));
+    bootstrap_config.parallel_bootstrap_servers = 2;
+    let mut servers: HashMap<SocketAddr, _> = bootstrap_config
+        .bootstrap_list
+        .iter()
+        .map(|(addr, _)| *addr)
+        .zip(servers)
+        .collect();
+
+    // launch the get_state process
+    let (get_state_h, mut remote_interface) =
+        spawn_get_state(bootstrap_config.clone(), final_state_client, version);
+
+    // serve the session of each server as the bootstrap server does
+    let mut sessions = Vec::new();
+    for _ in 0..2 {
+        let (remote_rw, conn_addr) = accept_bootstrap_connection(&mut remote_interface).await;
+        let (final_state, graph) = servers
+            .remove(&conn_addr)
+            .expect("client connected to an unknown bootstrap server");
+        let config = bootstrap_config.clone();
+        let keypair = keypair.clone();
+        sessions.push(tokio::spawn(async move {
+            let mut server = BootstrapServerBinder::new(
+                remote_rw,
+                keypair,
+                config.max_bytes_read_write,
+                config.max_bootstrap_message_size,
+                config.thread_count,
+                config.max_datastore_key_length,
+                config.randomness_size_bytes,
+            );
+            manage_bootstrap(
+                &config,
+                &mut server,
+                graph,
+                get_peers(),
+                final_state,
+                0,
+                version,
+            )
+            .await
+        }));
+    }
+
+    let bootstrap_res = tokio::time::timeout(Duration::from_secs(10), get_state_h)
+        .await
+        .expect("timeout waiting for get_state to finish")
+        .expect("error while waiting for get_state to finish");
+    for session in sessions {
+        let _ = session.await.expect("bootstrap session panicked");
+    }
+    bootstrap_res
+}
+
+#[tokio::test]
+#[serial]
+async fn test_bootstrap_parallel_servers() {
+    let (mut selector_manager, selector_controller) =
+        start_test_selector_worker(get_random_address());
+    let (final_state, final_state_client) = get_scripted_final_states(selector_controller.clone());
+    apply_random_changes(&mut final_state.write());
+    let final_state_copy = copy_final_state(&final_state.read(), selector_controller);
+    let graph = get_boot_state();
+
+    // each server sends a range of the ledger, and one of them the rest of the state
+    let bootstrap_res = bootstrap_in_parallel(
+        [
+            (final_state.clone(), graph.clone()),
+            (final_state_copy, graph.clone()),
+        ],
+        final_state_client.clone(),
+    )
+    .await
+    .expect("parallel bootstrap failed");
+    assert_eq!(get_peers().0, bootstrap_res.peers.unwrap().0);
+    assert_eq_bootstrap_graph(&graph, &bootstrap_res.graph.unwrap());
+    assert_eq_final_state(&final_state.read(), &final_state_client.read());
+
+    selector_manager.stop();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_bootstrap_parallel_servers_catch_up() {
+    let (mut selector_manager, selector_controller) =
+        start_test_selector_worker(get_random_address());
+    let (final_state, final_state_client) = get_scripted_final_states(selector_controller.clone());
+    apply_random_changes(&mut final_state.write());
+    let late_final_state = copy_final_state(&final_state.read(), selector_controller);
+    let graph = get_boot_state();
+
+    // one server is a slot ahead: the range sent by the other is brought to that slot with its changes
+    apply_random_changes(&mut final_state.write());
+    bootstrap_in_parallel(
+        [
+            (late_final_state, graph.clone()),
+            (final_state.clone(), graph),
+        ],
+        final_state_client.clone(),
+    )
+    .await
+    .expect("parallel bootstrap failed");
+    assert_eq_final_state(&final_state.read(), &final_state_client.read());
+
+    selector_manager.stop();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_bootstrap_parallel_servers_cross_check() {
+    let (mut selector_manager, selector_controller) =
+        start_test_selector_worker(get_random_address());
+    let (final_state, final_state_client) = get_scripted_final_states(selector_controller.clone());
+    apply_random_changes(&mut final_state.write());
+    let other_final_state = copy_final_state(&final_state.read(), selector_controller);
+    let graph = get_boot_state();
+
+    // the server sending the state claims a complete cycle the other one does not have
+    {
+        let mut final_write = final_state.write();
+        apply_random_changes(&mut final_write);
+        final_write.pos_state.cycle_history[0].complete = true;
+    }
+    match bootstrap_in_parallel(
+        [
+            (final_state.clone(), graph.clone()),
+            (other_final_state, graph),
+        ],
+        final_state_client,
+    )
+    .await
+    {
+        Err(BootstrapError::CrossCheckError(_)) => {}
+        Err(err) => panic!("unexpected bootstrap error: {}", err),
+        Ok(_) => panic!("the states of the servers were not cross-checked"),
+    }
+
+    selector_manager.stop();
+}
+
+#[test]
+#[serial]
+fn test_final_state_snapshot() {
+    let genesis_address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let (mut selector_manager, selector_controller) = start_test_selector_worker(genesis_address);
+    let final_state =
+        get_random_final_state_bootstrap(get_test_pos_state(selector_controller.clone()));
+
+    // export the final state
+    let snapshot_file = tempfile::NamedTempFile::new().expect("cannot create temp file");
+    let export_hash = final_state
+        .export_snapshot(snapshot_file.path(), None)
+        .expect("could not export snapshot");
+
+    // import it into an empty final state
+    let imported_final_state = Arc::new(RwLock::new(FinalState::default_with_pos(
+        get_test_pos_state(selector_controller.clone()),
+    )));
+    let (import_hash, graph) = imported_final_state
+        .write()
+        .import_snapshot(snapshot_file.path())
+        .expect("could not import snapshot");
+    assert_eq!(export_hash, import_hash);
+    assert!(graph.is_none());
+    assert_eq_final_state(&final_state, &imported_final_state.read());
+
+    // a corrupted snapshot is rejected before anything is loaded
+    let mut bytes = std::fs::read(snapshot_file.path()).unwrap();
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 0xff;
+    std::fs::write(snapshot_file.path(), bytes).unwrap();
+    let empty_final_state = Arc::new(RwLock::new(FinalState::default_with_pos(
+        get_test_pos_state(selector_controller.clone()),
+    )));
+    assert!(get_state_from_snapshot(
+        &BOOTSTRAP_CONFIG_KEYPAIR.0,
+        empty_final_state,
+        snapshot_file.path()
+    )
+    .is_err());
+
+    selector_manager.stop();
+}
+
+/// Creates an empty block of `slot` with the given parents
+fn create_test_block(slot: Slot, parents: Vec<BlockId>, keypair: &KeyPair) -> WrappedBlock {
+    let header = BlockHeader::new_wrapped(
+        BlockHeader {
+            slot,
+            parents,
+            operation_merkle_root: Hash::compute_from(&Vec::new()),
+            endorsements: Vec::new(),
+        },
+        BlockHeaderSerializer::new(),
+        keypair,
+    )
+    .unwrap();
+    Block::new_wrapped(
+        Block {
+            header,
+            operations: Default::default(),
+        },
+        BlockSerializer::new(),
+        keypair,
+    )
+    .unwrap()
+}
+
+/// Feeds a block built on top of the final blocks of a snapshot to a graph started from that snapshot,
+/// and to a graph started from genesis which does not know them.
+#[tokio::test]
+#[serial]
+async fn test_node_from_snapshot_accepts_child_of_final_block() {
+    let bootstrap_config = BootstrapConfig {
+        thread_count: 2,
+        ..BOOTSTRAP_CONFIG_KEYPAIR.0.clone()
+    };
+    let graph_config = GraphConfig {
+        thread_count: 2,
+        genesis_key: KeyPair::generate(),
+        max_discarded_blocks: 100,
+        future_block_processing_max_periods: 100,
+        max_future_processing_blocks: 100,
+        max_dependency_blocks: 100,
+        delta_f0: 32,
+        operation_validity_periods: 10,
+        periods_per_cycle: 2,
+        force_keep_final_periods: 10,
+        endorsement_count: bootstrap_config.endorsement_count,
+        max_item_return_count: 100,
+    };
+
+    // every block is produced by the same address
+    let creator = KeyPair::generate();
+    let creator_address = Address::from_public_key(&creator.get_public_key());
+    let (selector_controller, selector_receiver) = MockSelectorController::new_with_receiver();
+    let endorsement_count = graph_config.endorsement_count as usize;
+    let selector_responder = std::thread::spawn(move || {
+        while let Ok(message) = selector_receiver.recv() {
+            match message {
+                MockSelectorControllerMessage::GetProducer { response_tx, .. } => {
+                    let _ = response_tx.send(Ok(creator_address));
+                }
+                MockSelectorControllerMessage::GetSelection { response_tx, .. } => {
+                    let _ = response_tx.send(Ok(Selection {
+                        producer: creator_address,
+                        endorsements: vec![creator_address; endorsement_count],
+                    }));
+                }
+                _ => {}
+            }
+        }
+    });
+
+    // final blocks of the snapshot: the genesis blocks, then one block per thread at period 1
+    let genesis: Vec<WrappedBlock> = (0..graph_config.thread_count)
+        .map(|thread| create_genesis_block(&graph_config, thread).unwrap().1)
+        .collect();
+    let final_block_0 = create_test_block(
+        Slot::new(1, 0),
+        vec![genesis[0].id, genesis[1].id],
+        &creator,
+    );
+    let final_block_1 = create_test_block(
+        Slot::new(1, 1),
+        vec![final_block_0.id, genesis[1].id],
+        &creator,
+    );
+    let export_block = |block: &WrappedBlock, parents: Vec<(BlockId, u64)>| ExportActiveBlock {
+        block: block.clone(),
+        parents,
+        operations: Vec::new(),
+        is_final: true,
+    };
+    let graph = BootstrapableGraph {
+        final_blocks: vec![
+            export_block(&genesis[0], Vec::new()),
+            export_block(&genesis[1], Vec::new()),
+            export_block(&final_block_0, vec![(genesis[0].id, 0), (genesis[1].id, 0)]),
+            export_block(
+                &final_block_1,
+                vec![(final_block_0.id, 1), (genesis[1].id, 0)],
+            ),
+        ],
+    };
+
+    // export the state and its graph, then start a node from the snapshot
+    let (mut selector_manager, pos_selector_controller) =
+        start_test_selector_worker(creator_address);
+    let mut exported_state = GlobalBootstrapState::new(Arc::new(RwLock::new(
+        get_random_final_state_bootstrap(get_test_pos_state(pos_selector_controller.clone())),
+    )));
+    exported_state.graph = Some(graph.clone());
+    let snapshot_file = tempfile::NamedTempFile::new().expect("cannot create temp file");
+    export_state_to_snapshot(&exported_state, snapshot_file.path())
+        .expect("could not export snapshot");
+    let imported_state = get_state_from_snapshot(
+        &bootstrap_config,
+        Arc::new(RwLock::new(FinalState::default_with_pos(
+            get_test_pos_state(pos_selector_controller.clone()),
+        ))),
+        snapshot_file.path(),
+    )
+    .expect("could not load snapshot");
+    assert_eq_final_state(
+        &exported_state.final_state.read(),
+        &imported_state.final_state.read(),
+    );
+    let imported_graph = imported_state
+        .graph
+        .expect("the snapshot graph was not loaded");
+    assert_eq_bootstrap_graph(&graph, &imported_graph);
+
+    // a child of the final blocks is accepted by the graph started from the snapshot
+    let child = create_test_block(
+        Slot::new(2, 0),
+        vec![final_block_0.id, final_block_1.id],
+        &creator,
+    );
+    let storage = Storage::create_root();
+    let mut block_graph = BlockGraph::new(
+        graph_config.clone(),
+        Some(imported_graph),
+        storage.clone(),
+        selector_controller.clone_box(),
+    )
+    .await
+    .expect("could not start the graph from the snapshot");
+    let mut child_storage = storage.clone_without_refs();
+    child_storage.store_block(child.clone());
+    block_graph
+        .incoming_block(
+            child.id,
+            child.content.header.content.slot,
+            Some(Slot::new(2, 0)),
+            child_storage,
+        )
+        .unwrap();
+    assert_eq!(
+        block_graph.get_block_status(&child.id),
+        BlockGraphStatus::ActiveInBlockclique
+    );
+
+    // while a graph started from genesis waits for its parents
+    let storage = Storage::create_root();
+    let mut genesis_graph =
+        BlockGraph::new(graph_config, None, storage.clone(), selector_controller)
+            .await
+            .expect("could not start the graph from genesis");
+    let mut child_storage = storage.clone_without_refs();
+    child_storage.store_block(child.clone());
+    genesis_graph
+        .incoming_block(
+            child.id,
+            child.content.header.content.slot,
+            Some(Slot::new(2, 0)),
+            child_storage,
+        )
+        .unwrap();
+    assert_eq!(
+        genesis_graph.get_block_status(&child.id),
+        BlockGraphStatus::WaitingForDependencies
+    );
+
+    drop(block_graph);
+    drop(genesis_graph);
+    selector_responder.join().unwrap();
+    selector_manager.stop();
+}
@@ -1,13 +1,19 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use super::mock_establisher::Duplex;
+use super::mock_establisher::{self, Duplex, MockEstablisherInterface};
+use crate::error::BootstrapError;
+use crate::messages::{BootstrapClientMessage, BootstrapServerMessage};
+use crate::server::send_final_state_stream;
+use crate::server_binder::BootstrapServerBinder;
 use crate::settings::BootstrapConfig;
+use crate::{get_state, GlobalBootstrapState};
 use bitvec::vec::BitVec;
 use massa_async_pool::test_exports::{create_async_pool, get_random_message};
+use massa_async_pool::AsyncMessageId;
 use massa_async_pool::{AsyncPoolChanges, Change};
 use massa_consensus_exports::commands::ConsensusCommand;
 use massa_final_state::test_exports::create_final_state;
-use massa_final_state::{ExecutedOps, FinalState};
+use massa_final_state::{ExecutedOps, ExecutedOpsStreamingStep, FinalState, StateChanges};
 use massa_graph::export_active_block::ExportActiveBlockSerializer;
 use massa_graph::{export_active_block::ExportActiveBlock, BootstrapableGraph};
 use massa_graph::{BootstrapableGraphDeserializer, BootstrapableGraphSerializer};
@@ -29,26 +35,36 @@ use massa_models::{
     block::{Block, BlockHeader, BlockHeaderSerializer, BlockId},
     endorsement::Endorsement,
     endorsement::EndorsementSerializer,
+    execution::OperationExecutionStatus,
     operation::OperationId,
     prehash::PreHashMap,
     slot::Slot,
+    version::Version,
     wrapped::Id,
     wrapped::WrappedContent,
 };
 use massa_network_exports::{BootstrapPeers, NetworkCommand};
-use massa_pos_exports::{CycleInfo, DeferredCredits, PoSChanges, PoSFinalState, ProductionStats};
+use massa_pos_exports::{
+    CycleInfo, DeferredCredits, PoSChanges, PoSCycleStreamingStep, PoSFinalState, ProductionStats,
+    SelectorConfig, SelectorController, SelectorManager,
+};
+use massa_pos_worker::start_selector_worker;
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_signature::{KeyPair, PublicKey, Signature};
 use massa_time::MassaTime;
+use parking_lot::RwLock;
 use rand::Rng;
 use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::{
     collections::BTreeMap,
     net::{IpAddr, Ipv4Addr, SocketAddr},
 };
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
+use tokio::task::JoinHandle;
 use tokio::{sync::mpsc::Receiver, time::sleep};
 
 pub const BASE_BOOTSTRAP_IP: IpAddr = IpAddr::V4(Ipv4Addr::new(169, 202, 0, 10));
@@ -192,18 +208,54 @@ pub fn get_random_async_pool_changes(r_limit: u64) -> AsyncPoolChanges {
 
 pub fn get_random_executed_ops(r_limit: u64) -> ExecutedOps {
     let mut ops = ExecutedOps::default();
-    for _ in 0..r_limit {
+    for i in 0..r_limit {
+        let op_id = OperationId::new(Hash::compute_from(&get_some_random_bytes()));
         ops.insert(
-            OperationId::new(Hash::compute_from(&get_some_random_bytes())),
+            op_id,
             Slot {
                 period: 500,
                 thread: 0,
             },
         );
+        let status = match i % 4 {
+            0 => OperationExecutionStatus::Success,
+            1 => OperationExecutionStatus::OutOfGas,
+            2 => OperationExecutionStatus::InsufficientBalance,
+            _ => OperationExecutionStatus::vm_error("runtime error".to_string()),
+        };
+        ops.set_status(&op_id, status);
     }
     ops
 }
 
+/// Moves `final_state` to its next slot with random changes, keeping them in its changes history.
+/// The changes are settled as `FinalState::finalize` would, without feeding the selector.
+pub fn apply_random_changes(final_state: &mut FinalState) {
+    let next = final_state.slot.get_next_slot(2).unwrap();
+    final_state.slot = next;
+    let changes = StateChanges {
+        pos_changes: get_random_pos_changes(10),
+        ledger_changes: get_random_ledger_changes(10),
+        async_pool_changes: get_random_async_pool_changes(10),
+        executed_ops: get_random_executed_ops(10),
+    };
+    final_state
+        .ledger
+        .apply_changes(changes.ledger_changes.clone(), next);
+    final_state
+        .async_pool
+        .apply_changes_unchecked(&changes.async_pool_changes);
+    final_state
+        .pos_state
+        .apply_changes(changes.pos_changes.clone(), next, false)
+        .unwrap();
+    final_state
+        .executed_ops
+        .extend(changes.executed_ops.clone());
+    final_state.executed_ops.prune(next);
+    final_state.changes_history.push_back((next, changes));
+}
+
 /// generates a random bootstrap state for the final state
 pub fn get_random_final_state_bootstrap(pos: PoSFinalState) -> FinalState {
     let r_limit: u64 = 50;
@@ -259,6 +311,7 @@ pub fn get_bootstrap_config(bootstrap_public_key: PublicKey) -> BootstrapConfig
         bind: Some("0.0.0.0:31244".parse().unwrap()),
         connect_timeout: 200.into(),
         retry_delay: 200.into(),
+        parallel_bootstrap_servers: 1,
         max_ping: MassaTime::from_millis(500),
         read_timeout: 1000.into(),
         write_timeout: 1000.into(),
@@ -266,6 +319,7 @@ pub fn get_bootstrap_config(bootstrap_public_key: PublicKey) -> BootstrapConfig
         write_error_timeout: 200.into(),
         bootstrap_list: vec![(SocketAddr::new(BASE_BOOTSTRAP_IP, 16), bootstrap_public_key)],
         enable_clock_synchronization: true,
+        enable_encryption: true,
         cache_duration: 10000.into(),
         max_simultaneous_bootstraps: 2,
         ip_list_max_size: 10,
@@ -473,3 +527,204 @@ pub async fn bridge_mock_streams(mut side1: Duplex, mut side2: Duplex) {
         }
     }
 }
+
+/// Accepts the next connection attempt of the client, returning the stream and the address it connected to
+pub async fn accept_bootstrap_connection(
+    remote_interface: &mut MockEstablisherInterface,
+) -> (Duplex, SocketAddr) {
+    let (remote_rw, conn_addr, resp) = tokio::time::timeout(
+        std::time::Duration::from_millis(1000),
+        remote_interface.wait_connection_attempt_from_controller(),
+    )
+    .await
+    .expect("timeout waiting for connection attempt from remote")
+    .expect("error receiving connection attempt from remote");
+    resp.send(true)
+        .expect("could not send connection accept to remote");
+    (remote_rw, conn_addr)
+}
+
+/// Accepts the next connection of the client to the bootstrap server,
+/// and plays the server side of the handshake, of the clock synchronization and of the encryption negotiation
+pub async fn accept_bootstrap_session(
+    remote_interface: &mut MockEstablisherInterface,
+    bootstrap_config: &BootstrapConfig,
+    keypair: &KeyPair,
+    version: Version,
+) -> BootstrapServerBinder {
+    let (remote_rw, _) = accept_bootstrap_connection(remote_interface).await;
+    let mut server = BootstrapServerBinder::new(
+        remote_rw,
+        keypair.clone(),
+        bootstrap_config.max_bytes_read_write,
+        bootstrap_config.max_bootstrap_message_size,
+        bootstrap_config.thread_count,
+        bootstrap_config.max_datastore_key_length,
+        bootstrap_config.randomness_size_bytes,
+    );
+    server
+        .handshake(version)
+        .await
+        .expect("bootstrap handshake failed");
+    server
+        .send(BootstrapServerMessage::BootstrapTime {
+            server_time: MassaTime::now(0).unwrap(),
+            version,
+        })
+        .await
+        .expect("could not send bootstrap time");
+    server
+        .negotiate_encryption(version)
+        .await
+        .expect("bootstrap encryption negotiation failed");
+    server
+}
+
+/// First part of the final state, as streamed to a client that has none of it yet
+pub fn get_first_final_state_part(final_state: &FinalState) -> BootstrapServerMessage {
+    let (ledger_data, _) = final_state.ledger.get_ledger_part(&None).unwrap();
+    let (async_pool_part, _) = final_state.async_pool.get_pool_part(None).unwrap();
+    let (pos_cycle_part, _) = final_state
+        .pos_state
+        .get_cycle_history_part(PoSCycleStreamingStep::Started)
+        .unwrap();
+    let (pos_credits_part, _) = final_state
+        .pos_state
+        .get_deferred_credits_part(None)
+        .unwrap();
+    let (exec_ops_part, _) = final_state
+        .executed_ops
+        .get_executed_ops_part(ExecutedOpsStreamingStep::Started)
+        .unwrap();
+    BootstrapServerMessage::FinalStatePart {
+        ledger_data,
+        async_pool_part,
+        pos_cycle_part,
+        pos_credits_part,
+        exec_ops_part,
+        slot: final_state.slot,
+        final_state_changes: Vec::new(),
+    }
+}
+
+/// Answers the requests of a client that got the final state, until it reports its success
+pub async fn serve_bootstrap_end(
+    server: &mut BootstrapServerBinder,
+    peers: BootstrapPeers,
+    graph: BootstrapableGraph,
+) {
+    loop {
+        let message = match server.next().await.expect("could not read client message") {
+            BootstrapClientMessage::AskBootstrapPeers => BootstrapServerMessage::BootstrapPeers {
+                peers: peers.clone(),
+            },
+            BootstrapClientMessage::AskConsensusState => BootstrapServerMessage::ConsensusState {
+                graph: graph.clone(),
+            },
+            BootstrapClientMessage::BootstrapSuccess => return,
+            other => panic!("unexpected client message: {:?}", other),
+        };
+        server.send(message).await.expect("could not send message");
+    }
+}
+
+/// Starts a selector worker with the thread count and cycle length of the test final states
+pub fn start_test_selector_worker(
+    genesis_address: Address,
+) -> (Box<dyn SelectorManager>, Box<dyn SelectorController>) {
+    start_selector_worker(SelectorConfig {
+        thread_count: 2,
+        periods_per_cycle: 2,
+        genesis_address,
+        ..Default::default()
+    })
+    .expect("could not start selector controller")
+}
+
+/// PoS state holding the initial rolls of the node configuration, for the test final states
+pub fn get_test_pos_state(selector_controller: Box<dyn SelectorController>) -> PoSFinalState {
+    let rolls_path = PathBuf::from_str("../massa-node/base_config/initial_rolls.json").unwrap();
+    PoSFinalState::new(&"".to_string(), &rolls_path, 2, 2, selector_controller).unwrap()
+}
+
+/// Launches the bootstrap of `final_state` by a client,
+/// returning its handle and the interface through which the test accepts its connections
+pub fn spawn_get_state(
+    bootstrap_config: BootstrapConfig,
+    final_state: Arc<RwLock<FinalState>>,
+    version: Version,
+) -> (
+    JoinHandle<Result<GlobalBootstrapState, BootstrapError>>,
+    MockEstablisherInterface,
+) {
+    let (remote_establisher, remote_interface) = mock_establisher::new();
+    let get_state_h = tokio::spawn(async move {
+        get_state(
+            &bootstrap_config,
+            final_state,
+            remote_establisher,
+            version,
+            MassaTime::now(0).unwrap().saturating_sub(1000.into()),
+            None,
+        )
+        .await
+    });
+    (get_state_h, remote_interface)
+}
+
+/// Position in the final state stream sent by a client asking for a part of the final state
+#[derive(Debug)]
+pub struct FinalStatePartRequest {
+    pub last_slot: Option<Slot>,
+    pub last_key: Option<Vec<u8>>,
+    pub last_async_message_id: Option<AsyncMessageId>,
+    pub last_cycle_step: PoSCycleStreamingStep,
+    pub last_credits_slot: Option<Slot>,
+    pub last_exec_ops_step: ExecutedOpsStreamingStep,
+}
+
+/// Reads the next message of the client, which must ask for a part of the final state
+pub async fn read_final_state_part_request(
+    server: &mut BootstrapServerBinder,
+) -> FinalStatePartRequest {
+    match server.next().await.expect("could not read client message") {
+        BootstrapClientMessage::AskFinalStatePart {
+            last_slot,
+            last_key,
+            last_async_message_id,
+            last_cycle_step,
+            last_credits_slot,
+            last_exec_ops_step,
+        } => FinalStatePartRequest {
+            last_slot,
+            last_key,
+            last_async_message_id,
+            last_cycle_step,
+            last_credits_slot,
+            last_exec_ops_step,
+        },
+        other => panic!("the client did not ask for the final state: {:?}", other),
+    }
+}
+
+/// Streams the rest of the final state to a client, from the position it asked for
+pub async fn send_requested_final_state_stream(
+    server: &mut BootstrapServerBinder,
+    final_state: Arc<RwLock<FinalState>>,
+    request: FinalStatePartRequest,
+    bootstrap_config: &BootstrapConfig,
+) {
+    send_final_state_stream(
+        server,
+        final_state,
+        request.last_slot,
+        request.last_key,
+        request.last_async_message_id,
+        request.last_cycle_step,
+        request.last_credits_slot,
+        request.last_exec_ops_step,
+        bootstrap_config.write_timeout.into(),
+    )
+    .await
+    .expect("could not stream the final state");
+}
@@ -6,8 +6,14 @@
 
 use pbkdf2::Params;
 
-/// Cipher version
-pub const VERSION: u32 = 0;
+/// Version of the format using PBKDF2 to hash the password
+pub const PBKDF2_VERSION: u32 = 0;
+
+/// Version of the format using Argon2id to hash the password, with its parameters stored in the data
+pub const ARGON2ID_VERSION: u32 = 1;
+
+/// Cipher version used by `encrypt`
+pub const VERSION: u32 = ARGON2ID_VERSION;
 
 /// AES-GCM-SIV nonce size.
 ///
@@ -18,6 +24,23 @@ pub const NONCE_SIZE: usize = 12;
 /// PBKDF2 salt size.
 pub const SALT_SIZE: usize = 12;
 
+/// Argon2id salt size.
+pub const ARGON2ID_SALT_SIZE: usize = 16;
+
+/// Maximal Argon2id memory cost accepted when decrypting, in KiB (4 GiB).
+///
+/// The parameters are read from the data: the caps prevent a crafted file from exhausting the resources of the node.
+pub const ARGON2ID_MAX_MEMORY_COST: u32 = 4 * 1024 * 1024;
+
+/// Maximal number of Argon2id passes accepted when decrypting
+pub const ARGON2ID_MAX_ITERATIONS: u32 = 64;
+
+/// Maximal Argon2id degree of parallelism accepted when decrypting
+pub const ARGON2ID_MAX_PARALLELISM: u32 = 16;
+
+/// Size of the key derived from the password
+pub const KEY_SIZE: usize = 32;
+
 /// PBKDF2 hash parameters.
 pub const HASH_PARAMS: Params = Params {
     rounds: 10_000,
@@ -6,67 +6,199 @@
 
 use aes_gcm::aead::Aead;
 use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
-use pbkdf2::{
-    password_hash::{PasswordHasher, SaltString},
-    Pbkdf2,
-};
+use pbkdf2::password_hash::SaltString;
 
-use crate::constants::{HASH_PARAMS, NONCE_SIZE, SALT_SIZE};
+use crate::constants::{
+    ARGON2ID_SALT_SIZE, ARGON2ID_VERSION, NONCE_SIZE, PBKDF2_VERSION, SALT_SIZE,
+};
 use crate::error::CipherError;
+use crate::kdf::{argon2id_key, pbkdf2_key, Argon2Params};
 use massa_serialization::{DeserializeError, Deserializer, U32VarIntDeserializer};
 
 use std::ops::Bound::Included;
 
 /// Decryption function using AES-GCM cipher.
+/// Returns the cipher version the data was encrypted with, along with the decrypted data:
+/// data encrypted with an older version than `VERSION` should be encrypted again.
 ///
 /// Read `lib.rs` module documentation for more information.
 pub fn decrypt(password: &str, data: &[u8]) -> Result<(u32, Vec<u8>), CipherError> {
     // parse cipher version
-    let (rest, version) = U32VarIntDeserializer::new(Included(0), Included(u32::MAX))
-        .deserialize::<DeserializeError>(data)
-        .map_err(|_| {
-            CipherError::DecryptionError(
-                "wallet file truncated: version missing or incomplete".to_string(),
-            )
-        })?;
+    let (rest, version) = parse_version(data)?;
 
-    // parse PBKDF2 salt
-    let salt_data = rest.get(..SALT_SIZE).ok_or_else(|| {
-        CipherError::DecryptionError(
-            "wallet file truncated: salt missing or incomplete".to_string(),
-        )
-    })?;
-    let salt = SaltString::new(std::str::from_utf8(salt_data)?)
-        .map_err(|e| CipherError::DecryptionError(e.to_string()))?;
-
-    // compute PBKDF2 password hash
-    let password_hash = Pbkdf2
-        .hash_password_customized(password.as_bytes(), None, None, HASH_PARAMS, &salt)
-        .map_err(|e| CipherError::DecryptionError(e.to_string()))?
-        .hash
-        .expect("content is missing after a successful hash");
+    // compute the password hash with the function of this version
+    let (rest, password_hash) = match version {
+        PBKDF2_VERSION => {
+            // parse PBKDF2 salt
+            let salt_data = rest.get(..SALT_SIZE).ok_or_else(|| {
+                CipherError::DecryptionError(
+                    "wallet file truncated: salt missing or incomplete".to_string(),
+                )
+            })?;
+            let salt = SaltString::new(std::str::from_utf8(salt_data)?)
+                .map_err(|e| CipherError::DecryptionError(e.to_string()))?;
+            (&rest[SALT_SIZE..], pbkdf2_key(password, &salt)?)
+        }
+        ARGON2ID_VERSION => {
+            // parse Argon2id parameters and salt
+            let (rest, params) = parse_argon2_params(rest)?;
+            let salt = rest.get(..ARGON2ID_SALT_SIZE).ok_or_else(|| {
+                CipherError::DecryptionError(
+                    "wallet file truncated: salt missing or incomplete".to_string(),
+                )
+            })?;
+            (
+                &rest[ARGON2ID_SALT_SIZE..],
+                argon2id_key(password, salt, &params)?,
+            )
+        }
+        _ => {
+            return Err(CipherError::DecryptionError(format!(
+                "unsupported cipher version {}",
+                version
+            )))
+        }
+    };
 
     // parse AES-GCM nonce
-    let nonce_end_index = SALT_SIZE + NONCE_SIZE;
-    let nonce = Nonce::from_slice(rest.get(SALT_SIZE..nonce_end_index).ok_or_else(|| {
+    let nonce = Nonce::from_slice(rest.get(..NONCE_SIZE).ok_or_else(|| {
         CipherError::DecryptionError(
             "wallet file truncated: nonce missing or incomplete".to_string(),
         )
     })?);
 
     // decrypt the data
-    let cipher = Aes256Gcm::new_from_slice(password_hash.as_bytes()).expect("invalid size key");
-    let decrypted_bytes = cipher
-        .decrypt(
-            nonce,
-            rest.get(nonce_end_index..).ok_or_else(|| {
+    let cipher = Aes256Gcm::new_from_slice(&password_hash).expect("invalid size key");
+    let decrypted_bytes = cipher.decrypt(nonce, &rest[NONCE_SIZE..]).map_err(|_| {
+        CipherError::DecryptionError("wrong password or corrupted data".to_string())
+    })?;
+    Ok((version, decrypted_bytes))
+}
+
+/// Returns the Argon2id parameters encrypted data was encrypted with,
+/// or `None` if it was encrypted with an older version that does not use Argon2id.
+/// The password is not needed: the parameters are stored in clear next to the encrypted data.
+pub fn encryption_params(data: &[u8]) -> Result<Option<Argon2Params>, CipherError> {
+    let (rest, version) = parse_version(data)?;
+    match version {
+        ARGON2ID_VERSION => Ok(Some(parse_argon2_params(rest)?.1)),
+        _ => Ok(None),
+    }
+}
+
+/// Parses the cipher version at the start of encrypted data
+fn parse_version(data: &[u8]) -> Result<(&[u8], u32), CipherError> {
+    U32VarIntDeserializer::new(Included(0), Included(u32::MAX))
+        .deserialize::<DeserializeError>(data)
+        .map_err(|_| {
+            CipherError::DecryptionError(
+                "wallet file truncated: version missing or incomplete".to_string(),
+            )
+        })
+}
+
+/// Parses the Argon2id parameters following the cipher version
+fn parse_argon2_params(data: &[u8]) -> Result<(&[u8], Argon2Params), CipherError> {
+    let u32_deserializer = U32VarIntDeserializer::new(Included(0), Included(u32::MAX));
+    let mut rest = data;
+    let mut costs = [0u32; 3];
+    for cost in costs.iter_mut() {
+        (rest, *cost) = u32_deserializer
+            .deserialize::<DeserializeError>(rest)
+            .map_err(|_| {
                 CipherError::DecryptionError(
-                    "wallet file truncated: encrypted data missing or incomplete".to_string(),
+                    "wallet file truncated: Argon2id parameters missing or incomplete".to_string(),
                 )
-            })?,
-        )
-        .map_err(|_| {
-            CipherError::DecryptionError("wrong password or corrupted data".to_string())
-        })?;
-    Ok((version, decrypted_bytes))
+            })?;
+    }
+    let params = Argon2Params {
+        memory_cost: costs[0],
+        iterations: costs[1],
+        parallelism: costs[2],
+    };
+    Ok((rest, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{
+        ARGON2ID_MAX_ITERATIONS, ARGON2ID_MAX_MEMORY_COST, ARGON2ID_MAX_PARALLELISM,
+    };
+    use crate::encrypt::encrypt_with_params;
+    use massa_serialization::{Serializer, U32VarIntSerializer};
+
+    /// Rewrites the Argon2id parameters of data encrypted with `encrypt_with_params`
+    fn set_params(data: &[u8], params: &Argon2Params) -> Vec<u8> {
+        let u32_deserializer = U32VarIntDeserializer::new(Included(0), Included(u32::MAX));
+        let mut rest = data;
+        for _ in 0..4 {
+            (rest, _) = u32_deserializer
+                .deserialize::<DeserializeError>(rest)
+                .unwrap();
+        }
+        let mut result = Vec::new();
+        let u32_serializer = U32VarIntSerializer::new();
+        for value in [
+            ARGON2ID_VERSION,
+            params.memory_cost,
+            params.iterations,
+            params.parallelism,
+        ] {
+            u32_serializer.serialize(&value, &mut result).unwrap();
+        }
+        result.extend(rest);
+        result
+    }
+
+    #[test]
+    fn test_argon2id_params_bounds() {
+        let params = Argon2Params {
+            memory_cost: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let data = encrypt_with_params("password", b"secret", &params).unwrap();
+        assert_eq!(
+            decrypt("password", &data).unwrap(),
+            (ARGON2ID_VERSION, b"secret".to_vec())
+        );
+        assert_eq!(encryption_params(&data).unwrap(), Some(params));
+
+        let out_of_range = [
+            Argon2Params {
+                memory_cost: ARGON2ID_MAX_MEMORY_COST + 1,
+                ..params
+            },
+            Argon2Params {
+                memory_cost: 8 * 2 - 1,
+                parallelism: 2,
+                ..params
+            },
+            Argon2Params {
+                iterations: 0,
+                ..params
+            },
+            Argon2Params {
+                iterations: ARGON2ID_MAX_ITERATIONS + 1,
+                ..params
+            },
+            Argon2Params {
+                parallelism: 0,
+                ..params
+            },
+            Argon2Params {
+                parallelism: ARGON2ID_MAX_PARALLELISM + 1,
+                memory_cost: 8 * (ARGON2ID_MAX_PARALLELISM + 1),
+                ..params
+            },
+        ];
+        for bad_params in out_of_range {
+            assert!(matches!(
+                decrypt("password", &set_params(&data, &bad_params)),
+                Err(CipherError::KeyDerivationError(_))
+            ));
+            assert!(encrypt_with_params("password", b"secret", &bad_params).is_err());
+        }
+    }
 }
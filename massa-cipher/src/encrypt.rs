@@ -6,32 +6,34 @@
 
 use aes_gcm::aead::Aead;
 use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
-use pbkdf2::password_hash::Salt;
-use pbkdf2::{password_hash::PasswordHasher, Pbkdf2};
-use rand::{distributions::Alphanumeric, thread_rng, Rng, RngCore};
+use rand::{thread_rng, RngCore};
 
-use crate::constants::{HASH_PARAMS, NONCE_SIZE, SALT_SIZE, VERSION};
+use crate::constants::{ARGON2ID_SALT_SIZE, NONCE_SIZE, VERSION};
 use crate::error::CipherError;
+use crate::kdf::{argon2id_key, Argon2Params};
 use massa_serialization::{Serializer, U32VarIntSerializer};
 
-/// Encryption function using AES-GCM cipher.
+/// Encryption function using AES-GCM cipher, with the default Argon2id parameters.
 ///
 /// Read `lib.rs` module documentation for more information.
 pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
-    // generate the PBKDF2 salt
-    let raw_salt: String = thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(SALT_SIZE)
-        .map(char::from)
-        .collect();
-    let salt = Salt::new(&raw_salt).expect("salt creation failed");
-
-    // compute PBKDF2 password hash
-    let password_hash = Pbkdf2
-        .hash_password_customized(password.as_bytes(), None, None, HASH_PARAMS, salt)
-        .map_err(|e| CipherError::EncryptionError(e.to_string()))?
-        .hash
-        .expect("content is missing after a successful hash");
+    encrypt_with_params(password, data, &Argon2Params::default())
+}
+
+/// Encryption function using AES-GCM cipher, with the given Argon2id parameters.
+///
+/// Read `lib.rs` module documentation for more information.
+pub fn encrypt_with_params(
+    password: &str,
+    data: &[u8],
+    params: &Argon2Params,
+) -> Result<Vec<u8>, CipherError> {
+    // generate the Argon2id salt
+    let mut salt = [0u8; ARGON2ID_SALT_SIZE];
+    thread_rng().fill_bytes(&mut salt);
+
+    // compute Argon2id password hash
+    let password_hash = argon2id_key(password, &salt, params)?;
 
     // generate the AES-GCM nonce
     let mut nonce_bytes = [0u8; NONCE_SIZE];
@@ -39,17 +41,25 @@ pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
     let nonce = Nonce::from_slice(&nonce_bytes);
 
     // encrypt the data
-    let cipher = Aes256Gcm::new_from_slice(password_hash.as_bytes()).expect("invalid key length");
+    let cipher = Aes256Gcm::new_from_slice(&password_hash).expect("invalid key length");
     let encrypted_bytes = cipher
         .encrypt(nonce, data.as_ref())
         .map_err(|e| CipherError::EncryptionError(e.to_string()))?;
 
     // build the encryption result
     let mut content = Vec::new();
-    U32VarIntSerializer::new()
-        .serialize(&VERSION, &mut content)
-        .map_err(|err| CipherError::EncryptionError(err.to_string()))?;
-    content.extend(salt.as_bytes());
+    let u32_serializer = U32VarIntSerializer::new();
+    for value in [
+        VERSION,
+        params.memory_cost,
+        params.iterations,
+        params.parallelism,
+    ] {
+        u32_serializer
+            .serialize(&value, &mut content)
+            .map_err(|err| CipherError::EncryptionError(err.to_string()))?;
+    }
+    content.extend(salt);
     content.extend(nonce_bytes);
     content.extend(encrypted_bytes);
     Ok(content)
@@ -12,6 +12,8 @@ pub enum CipherError {
     EncryptionError(String),
     /// Decryption error: {0}
     DecryptionError(String),
+    /// Key derivation error: {0}
+    KeyDerivationError(String),
     /// Utf8 error: {0}
     Utf8Error(#[from] std::str::Utf8Error),
 }
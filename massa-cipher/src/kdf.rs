@@ -0,0 +1,104 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! massa-cipher password hashing module.
+//!
+//! Derives the AES-GCM key from the password, with the function of each cipher version.
+//!
+//! Read `lib.rs` module documentation for more information.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use pbkdf2::{
+    password_hash::{PasswordHasher, SaltString},
+    Pbkdf2,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    ARGON2ID_MAX_ITERATIONS, ARGON2ID_MAX_MEMORY_COST, ARGON2ID_MAX_PARALLELISM, HASH_PARAMS,
+    KEY_SIZE,
+};
+use crate::error::CipherError;
+
+/// Argon2id cost parameters.
+///
+/// They are stored next to the encrypted data, so that they can be tuned without breaking older files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Argon2Params {
+    /// memory cost, in KiB
+    pub memory_cost: u32,
+    /// number of passes over the memory
+    pub iterations: u32,
+    /// degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    /// Minimal parameters recommended by OWASP for Argon2id: 19 MiB of memory, 2 passes
+    fn default() -> Self {
+        Argon2Params {
+            memory_cost: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl Argon2Params {
+    /// Checks that the parameters are between the Argon2 minimums and the caps of `constants`:
+    /// at least one pass and one lane, and at least 8 KiB of memory per lane.
+    pub fn check(&self) -> Result<(), CipherError> {
+        if !(1..=ARGON2ID_MAX_PARALLELISM).contains(&self.parallelism) {
+            return Err(CipherError::KeyDerivationError(format!(
+                "Argon2id parallelism {} is out of range 1..={}",
+                self.parallelism, ARGON2ID_MAX_PARALLELISM
+            )));
+        }
+        if !(1..=ARGON2ID_MAX_ITERATIONS).contains(&self.iterations) {
+            return Err(CipherError::KeyDerivationError(format!(
+                "Argon2id iterations {} is out of range 1..={}",
+                self.iterations, ARGON2ID_MAX_ITERATIONS
+            )));
+        }
+        let min_memory_cost = 8 * self.parallelism;
+        if !(min_memory_cost..=ARGON2ID_MAX_MEMORY_COST).contains(&self.memory_cost) {
+            return Err(CipherError::KeyDerivationError(format!(
+                "Argon2id memory cost {} KiB is out of range {}..={}",
+                self.memory_cost, min_memory_cost, ARGON2ID_MAX_MEMORY_COST
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Derive the key of the PBKDF2 cipher version
+pub(crate) fn pbkdf2_key(password: &str, salt: &SaltString) -> Result<Vec<u8>, CipherError> {
+    Ok(Pbkdf2
+        .hash_password_customized(password.as_bytes(), None, None, HASH_PARAMS, salt)
+        .map_err(|e| CipherError::KeyDerivationError(e.to_string()))?
+        .hash
+        .expect("content is missing after a successful hash")
+        .as_bytes()
+        .to_vec())
+}
+
+/// Derive the key of the Argon2id cipher version.
+/// Fails if the parameters are out of the range accepted by `Argon2Params::check`.
+pub(crate) fn argon2id_key(
+    password: &str,
+    salt: &[u8],
+    params: &Argon2Params,
+) -> Result<Vec<u8>, CipherError> {
+    params.check()?;
+    let params = Params::new(
+        params.memory_cost,
+        params.iterations,
+        params.parallelism,
+        Some(KEY_SIZE),
+    )
+    .map_err(|e| CipherError::KeyDerivationError(e.to_string()))?;
+    let mut key = vec![0u8; KEY_SIZE];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| CipherError::KeyDerivationError(e.to_string()))?;
+    Ok(key)
+}
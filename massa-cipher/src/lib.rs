@@ -7,16 +7,32 @@
 //! AES-GCM is a state-of-the-art high-performance Authenticated Encryption with Associated Data (AEAD)
 //! that provides confidentiality and authenticity.
 //!
-//! To hash the password before using it as a cipher key, we use the PBKDF2 key derivation function
-//! as specified in [RFC 2898](https://datatracker.ietf.org/doc/html/rfc2898).
+//! To hash the password before using it as a cipher key, we use the Argon2id key derivation function
+//! as specified in [RFC 9106](https://datatracker.ietf.org/doc/html/rfc9106).
+//! Its cost parameters are stored in the encrypted data, so that they can be tuned with `encrypt_with_params`
+//! and read back with `encryption_params`.
+//!
+//! The encrypted data starts with the version of its format. `decrypt` still reads version 0,
+//! which uses the PBKDF2 key derivation function as specified in [RFC 2898](https://datatracker.ietf.org/doc/html/rfc2898),
+//! and returns the version so that older data can be encrypted again with the current `VERSION`.
 //!
 //! The AES-GCM crate we use has received one security audit by NCC Group, with no significant findings.
+//!
+//! Streams of messages between nodes are encrypted with session keys agreed on with ephemeral X25519 keys,
+//! see the `session` module.
 
 mod constants;
 mod decrypt;
 mod encrypt;
 mod error;
+mod kdf;
+mod session;
 
-pub use decrypt::decrypt;
-pub use encrypt::encrypt;
+pub use constants::VERSION;
+pub use decrypt::{decrypt, encryption_params};
+pub use encrypt::{encrypt, encrypt_with_params};
 pub use error::CipherError;
+pub use kdf::Argon2Params;
+pub use session::{
+    EphemeralKey, SessionCipher, SessionDecrypter, SessionEncrypter, EPHEMERAL_KEY_SIZE, TAG_SIZE,
+};
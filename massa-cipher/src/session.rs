@@ -0,0 +1,159 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! massa-cipher session module.
+//!
+//! Encrypts a stream of messages between two nodes. Each side generates an ephemeral X25519 key
+//! and the session keys are derived from the Diffie-Hellman shared secret and a hash of the handshake.
+//! Each direction has its own AES-GCM key and uses a message counter as nonce,
+//! so that a message that is altered, replayed, reordered or dropped fails to decrypt.
+//!
+//! Read `lib.rs` module documentation for more information.
+
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use massa_hash::Hash;
+use rand::{thread_rng, RngCore};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::constants::NONCE_SIZE;
+use crate::error::CipherError;
+
+/// Size of an ephemeral public key, in bytes
+pub const EPHEMERAL_KEY_SIZE: usize = 32;
+
+/// Size of the authentication tag added to each encrypted message, in bytes
+pub const TAG_SIZE: usize = 16;
+
+/// Ephemeral X25519 key of one side of a session
+pub struct EphemeralKey {
+    secret: StaticSecret,
+    public: PublicKey,
+}
+
+impl EphemeralKey {
+    /// Generate a new random ephemeral key
+    pub fn generate() -> Self {
+        let mut secret_bytes = [0u8; EPHEMERAL_KEY_SIZE];
+        thread_rng().fill_bytes(&mut secret_bytes);
+        let secret = StaticSecret::from(secret_bytes);
+        let public = PublicKey::from(&secret);
+        EphemeralKey { secret, public }
+    }
+
+    /// Public part of the key, to send to the remote side
+    pub fn public_bytes(&self) -> [u8; EPHEMERAL_KEY_SIZE] {
+        self.public.to_bytes()
+    }
+
+    /// Derive the session from the public ephemeral key of the remote side.
+    ///
+    /// # Arguments
+    /// * `remote_public`: public ephemeral key received from the remote side
+    /// * `handshake_hash`: hash committing to the whole handshake, including the identity of the nodes
+    /// * `initiator`: whether this side initiated the handshake
+    pub fn into_session(
+        self,
+        remote_public: [u8; EPHEMERAL_KEY_SIZE],
+        handshake_hash: &Hash,
+        initiator: bool,
+    ) -> Result<SessionCipher, CipherError> {
+        let shared_secret = self.secret.diffie_hellman(&PublicKey::from(remote_public));
+        // a low order remote key would give a shared secret known by anyone
+        if shared_secret.as_bytes() == &[0u8; EPHEMERAL_KEY_SIZE] {
+            return Err(CipherError::EncryptionError(
+                "invalid remote ephemeral key".to_string(),
+            ));
+        }
+        let derive_key = |label: &[u8]| {
+            let key = Hash::compute_from(
+                &[shared_secret.as_bytes(), handshake_hash.to_bytes(), label].concat(),
+            );
+            Aes256Gcm::new_from_slice(key.to_bytes()).expect("invalid key length")
+        };
+        let initiator_cipher = derive_key(b"initiator");
+        let responder_cipher = derive_key(b"responder");
+        let (send_cipher, receive_cipher) = if initiator {
+            (initiator_cipher, responder_cipher)
+        } else {
+            (responder_cipher, initiator_cipher)
+        };
+        Ok(SessionCipher {
+            encrypter: SessionEncrypter {
+                cipher: send_cipher,
+                counter: 0,
+            },
+            decrypter: SessionDecrypter {
+                cipher: receive_cipher,
+                counter: 0,
+            },
+        })
+    }
+}
+
+/// Keys and message counters of an encrypted session
+pub struct SessionCipher {
+    encrypter: SessionEncrypter,
+    decrypter: SessionDecrypter,
+}
+
+/// Sending half of an encrypted session
+pub struct SessionEncrypter {
+    cipher: Aes256Gcm,
+    counter: u64,
+}
+
+/// Receiving half of an encrypted session
+pub struct SessionDecrypter {
+    cipher: Aes256Gcm,
+    counter: u64,
+}
+
+/// Nonce of the message of the given index
+fn counter_nonce(counter: u64) -> [u8; NONCE_SIZE] {
+    let mut nonce_bytes = [0u8; NONCE_SIZE];
+    nonce_bytes[NONCE_SIZE - 8..].copy_from_slice(&counter.to_be_bytes());
+    nonce_bytes
+}
+
+impl SessionCipher {
+    /// Encrypt the next message sent to the remote side
+    pub fn encrypt(&mut self, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+        self.encrypter.encrypt(data)
+    }
+
+    /// Decrypt the next message received from the remote side
+    pub fn decrypt(&mut self, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+        self.decrypter.decrypt(data)
+    }
+
+    /// Split the session, to send and receive from different tasks
+    pub fn split(self) -> (SessionEncrypter, SessionDecrypter) {
+        (self.encrypter, self.decrypter)
+    }
+}
+
+impl SessionEncrypter {
+    /// Encrypt the next message sent to the remote side
+    pub fn encrypt(&mut self, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+        let nonce_bytes = counter_nonce(self.counter);
+        self.counter = self.counter.checked_add(1).ok_or_else(|| {
+            CipherError::EncryptionError("session message counter overflow".to_string())
+        })?;
+        self.cipher
+            .encrypt(Nonce::from_slice(&nonce_bytes), data)
+            .map_err(|e| CipherError::EncryptionError(e.to_string()))
+    }
+}
+
+impl SessionDecrypter {
+    /// Decrypt the next message received from the remote side
+    pub fn decrypt(&mut self, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+        let nonce_bytes = counter_nonce(self.counter);
+        self.counter = self.counter.checked_add(1).ok_or_else(|| {
+            CipherError::DecryptionError("session message counter overflow".to_string())
+        })?;
+        self.cipher
+            .decrypt(Nonce::from_slice(&nonce_bytes), data)
+            .map_err(|e| CipherError::DecryptionError(e.to_string()))
+    }
+}
@@ -3,10 +3,13 @@
 use crate::repl::Output;
 use anyhow::{anyhow, bail, Result};
 use console::style;
+use dialoguer::Password;
 use massa_models::api::{
-    AddressInfo, CompactAddressInfo, DatastoreEntryInput, EventFilter, OperationInput,
+    AddressHistoryCursor, AddressHistoryRequest, AddressInfo, CompactAddressInfo,
+    DatastoreEntryInput, EventFilter, LedgerEntryAtSlotInput, LedgerProofInput, LedgerProofKey,
+    OperationInput,
 };
-use massa_models::api::{ReadOnlyBytecodeExecution, ReadOnlyCall};
+use massa_models::api::{EstimateOperation, ReadOnlyBytecodeExecution, ReadOnlyCall};
 use massa_models::node::NodeId;
 use massa_models::prehash::PreHashMap;
 use massa_models::timeslots::get_current_latest_block_slot;
@@ -15,14 +18,15 @@ use massa_models::{
     amount::Amount,
     block::BlockId,
     endorsement::EndorsementId,
-    operation::{Operation, OperationId, OperationType},
+    multisig::{MultisigPolicy, MultisigSignatures},
+    operation::{Operation, OperationId, OperationType, WrappedOperation},
     slot::Slot,
 };
 use massa_sdk::Client;
-use massa_signature::KeyPair;
+use massa_signature::{KeyPair, PublicKey};
 use massa_time::MassaTime;
-use massa_wallet::Wallet;
-use serde::Serialize;
+use massa_wallet::{SeedPhrase, Wallet};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Write as _;
 use std::fmt::{Debug, Display};
@@ -113,6 +117,12 @@ pub enum Command {
     )]
     node_remove_from_whitelist,
 
+    #[strum(
+        ascii_case_insensitive,
+        message = "show the traffic stats of each connected peer (bytes and messages exchanged, latency, connection age, throttling)"
+    )]
+    node_peers,
+
     #[strum(
         ascii_case_insensitive,
         message = "show the status of the node (reachable? number of peers connected, consensus, version, config parameter summary...)"
@@ -126,6 +136,13 @@ pub enum Command {
     )]
     get_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address limit=Number after_slot=Slot after_index=Number"),
+        message = "show a page of the final history of an address (operations, roll buys and sells, received async messages), after the given position if any"
+    )]
+    get_address_history,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address Key"),
@@ -133,6 +150,20 @@ pub enum Command {
     )]
     get_datastore_entry,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address Slot Key1 Key2 ..."),
+        message = "get the balances, bytecode size and given datastore values (keys must be UTF-8) of an address as they were at a past final slot (slot format: period,thread)"
+    )]
+    get_ledger_entry_at_slot,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address Key1 Key2 ..."),
+        message = "get the final balances, bytecode and given datastore values (keys must be UTF-8) of an address along with their Merkle proof against the final ledger root"
+    )]
+    get_ledger_proofs,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "BlockId"),
@@ -154,6 +185,13 @@ pub enum Command {
     )]
     get_operations,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "OperationId1 OperationId2 ..."),
+        message = "show the calls made by smart contracts to the node during the execution of a list of operations (execution tracing must be enabled on the node)"
+    )]
+    get_operation_traces,
+
     #[strum(
         ascii_case_insensitive,
         props(
@@ -171,10 +209,30 @@ pub enum Command {
 
     #[strum(
         ascii_case_insensitive,
-        message = "generate a secret key and add it into the wallet"
+        message = "generate a secret key and add it into the wallet (derived from the seed phrase of the wallet, if any)"
     )]
     wallet_generate_secret_key,
 
+    #[strum(
+        ascii_case_insensitive,
+        message = "generate a seed phrase from which the next secret keys of the wallet will be derived"
+    )]
+    wallet_generate_seed_phrase,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[AccountCount]"),
+        message = "restore the accounts derived from a seed phrase, asked interactively (without AccountCount, the accounts used on the network are restored)"
+    )]
+    wallet_restore_seed_phrase,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[NewPassword]"),
+        message = "encrypt the wallet file with a new password (asked interactively if not given)"
+    )]
+    wallet_change_password,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "SecretKey1 SecretKey2 ..."),
@@ -189,6 +247,27 @@ pub enum Command {
     )]
     wallet_remove_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ..."),
+        message = "add a list of addresses to follow in the wallet without their secret keys"
+    )]
+    wallet_add_watch_only_addresses,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address Label"),
+        message = "give a label to an address of the wallet, to use instead of the address in commands"
+    )]
+    wallet_set_label,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Label"),
+        message = "remove a label from the wallet"
+    )]
+    wallet_remove_label,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address string"),
@@ -233,6 +312,41 @@ pub enum Command {
     )]
     call_smart_contract,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "PathToUnsignedOperation ExpirePeriod Command Parameters..."),
+        message = "write an unsigned operation to a file, to be signed with sign_operation. Command is buy_rolls, sell_rolls, send_transaction, send_smart_contract or call_smart_contract, followed by its parameters"
+    )]
+    prepare_operation,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "PathToUnsignedOperation PathToSignedOperation"),
+        message = "sign an operation prepared with prepare_operation and write it to a file. Does not need a running node. For a multisig sender, adds the signatures of the wallet keys to the unsigned operation file, and writes the signed operation once the threshold is reached"
+    )]
+    sign_operation,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Threshold PublicKey1 PublicKey2 ..."),
+        message = "show the address of the multisig account controlled by Threshold of the given public keys"
+    )]
+    multisig_address,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "PathToUnsignedOperation Threshold PublicKey1 PublicKey2 ..."),
+        message = "set the public keys and threshold of the multisig sender of an unsigned operation, so that its signers can sign it in turn with sign_operation"
+    )]
+    set_operation_multisig,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "PathToSignedOperation1 PathToSignedOperation2 ..."),
+        message = "send operations signed with sign_operation"
+    )]
+    broadcast_operation,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "PathToBytecode MaxGas GasPrice Address",),
@@ -247,6 +361,20 @@ pub enum Command {
     )]
     read_only_call,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "PathToBytecode MaxGas GasPrice Coins SenderAddress",),
+        message = "estimate the gas cost, effects and fee of an operation containing byte code, sender address is optional. Nothing is really executed on chain"
+    )]
+    estimate_smart_contract,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "TargetAddress FunctionName Parameter MaxGas GasPrice Coins SenderAddress",),
+        message = "estimate the gas cost, effects and fee of an operation calling a function of a smart contract, sender address is optional. Nothing is really executed on chain"
+    )]
+    estimate_call,
+
     #[strum(
         ascii_case_insensitive,
         message = "show time remaining to end of current episode"
@@ -277,20 +405,41 @@ macro_rules! client_warning {
     };
 }
 
+/// Operation written to a file by `prepare_operation`, to be signed by `sign_operation`
+#[derive(Debug, Serialize, Deserialize)]
+struct UnsignedOperation {
+    /// address whose keypair signs the operation
+    sender_address: Address,
+    /// content of the operation
+    operation: Operation,
+    /// signatures collected so far, if the sender is a multisig address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    multisig: Option<MultisigSignatures>,
+}
+
 /// Used to have a shinny json output
 /// TODO re-factor me
 #[derive(Debug, Serialize)]
 struct ExtendedWalletEntry {
-    /// the keypair
-    pub keypair: KeyPair,
+    /// the label of the address, if any
+    pub label: Option<String>,
+    /// the keypair, none for watch-only addresses
+    pub keypair: Option<KeyPair>,
     /// address and balance information
     pub address_info: CompactAddressInfo,
 }
 
 impl Display for ExtendedWalletEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "Secret key: {}", self.keypair)?;
-        writeln!(f, "Public key: {}", self.keypair.get_public_key())?;
+        if let Some(label) = &self.label {
+            writeln!(f, "Label: {}", label)?;
+        }
+        if let Some(keypair) = &self.keypair {
+            writeln!(f, "Secret key: {}", keypair)?;
+            writeln!(f, "Public key: {}", keypair.get_public_key())?;
+        } else {
+            writeln!(f, "Watch-only address")?;
+        }
         writeln!(f, "{}", self.address_info)?;
         writeln!(f, "\n=====\n")?;
         Ok(())
@@ -309,14 +458,15 @@ impl ExtendedWallet {
             addresses_info
                 .iter()
                 .map(|x| {
-                    let keypair = wallet
-                        .keys
-                        .get(&x.address)
-                        .ok_or_else(|| anyhow!("missing key"))?;
+                    let keypair = wallet.keys.get(&x.address).cloned();
+                    if keypair.is_none() && !wallet.watch_only.contains(&x.address) {
+                        bail!("missing key");
+                    }
                     Ok((
                         x.address,
                         ExtendedWalletEntry {
-                            keypair: keypair.clone(),
+                            label: wallet.labels.get(&x.address).cloned(),
+                            keypair,
                             address_info: x.compact(),
                         },
                     ))
@@ -468,7 +618,7 @@ impl Command {
             }
 
             Command::node_remove_staking_addresses => {
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = parse_addresses(wallet, parameters)?;
                 match client.private.remove_staking_addresses(addresses).await {
                     Ok(()) => {
                         if !json {
@@ -498,7 +648,7 @@ impl Command {
                     bail!("wrong number of parameters");
                 }
                 // parse
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = wallet.resolve_address(&parameters[0])?;
                 let msg = parameters[1].as_bytes().to_vec();
                 // get address signature
                 if let Some(addr_sig) = wallet.sign_message(&addr, msg.clone()) {
@@ -530,18 +680,55 @@ impl Command {
             },
 
             Command::get_addresses => {
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = parse_addresses(wallet, parameters)?;
                 match client.public.get_addresses(addresses).await {
                     Ok(addresses_info) => Ok(Box::new(addresses_info)),
                     Err(e) => rpc_error!(e),
                 }
             }
 
+            Command::get_address_history => {
+                if parameters.is_empty() {
+                    bail!("wrong number of parameters");
+                }
+                let address = wallet.resolve_address(&parameters[0])?;
+                let p_list: [&str; 3] = ["limit", "after_slot", "after_index"];
+                let mut p: HashMap<&str, &str> = HashMap::new();
+                for v in &parameters[1..] {
+                    let s: Vec<&str> = v.split('=').collect();
+                    if s.len() == 2 && p_list.contains(&s[0]) {
+                        p.insert(s[0], s[1]);
+                    } else {
+                        bail!("invalid parameter");
+                    }
+                }
+                let after = match (
+                    parse_key_value(&p, p_list[1]),
+                    parse_key_value(&p, p_list[2]),
+                ) {
+                    (Some(slot), Some(index_in_slot)) => Some(AddressHistoryCursor {
+                        slot,
+                        index_in_slot,
+                    }),
+                    (None, None) => None,
+                    _ => bail!("after_slot and after_index must be given together"),
+                };
+                let request = AddressHistoryRequest {
+                    address,
+                    after,
+                    limit: parse_key_value(&p, p_list[0]).unwrap_or(20),
+                };
+                match client.public.get_address_history(request).await {
+                    Ok(page) => Ok(Box::new(page)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_datastore_entry => {
                 if parameters.len() != 2 {
                     bail!("invalid number of parameters");
                 }
-                let address = parameters[0].parse::<Address>()?;
+                let address = wallet.resolve_address(&parameters[0])?;
                 let key = parameters[1].as_bytes().to_vec();
                 match client
                     .public
@@ -553,6 +740,53 @@ impl Command {
                 }
             }
 
+            Command::get_ledger_entry_at_slot => {
+                if parameters.len() < 2 {
+                    bail!("invalid number of parameters");
+                }
+                let input = LedgerEntryAtSlotInput {
+                    address: wallet.resolve_address(&parameters[0])?,
+                    slot: parameters[1].parse::<Slot>()?,
+                    datastore_keys: parameters[2..]
+                        .iter()
+                        .map(|key| key.as_bytes().to_vec())
+                        .collect(),
+                };
+                match client.public.get_ledger_entries_at_slot(vec![input]).await {
+                    Ok(result) => Ok(Box::new(result)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::get_ledger_proofs => {
+                if parameters.is_empty() {
+                    bail!("invalid number of parameters");
+                }
+                let address = wallet.resolve_address(&parameters[0])?;
+                let keys = [
+                    LedgerProofKey::SequentialBalance,
+                    LedgerProofKey::ParallelBalance,
+                    LedgerProofKey::Bytecode,
+                ]
+                .into_iter()
+                .chain(
+                    parameters[1..]
+                        .iter()
+                        .map(|key| LedgerProofKey::Datastore(key.as_bytes().to_vec())),
+                );
+                let inputs = keys
+                    .map(|key| LedgerProofInput {
+                        address,
+                        key,
+                        slot: None,
+                    })
+                    .collect();
+                match client.public.get_ledger_proofs(inputs).await {
+                    Ok(result) => Ok(Box::new(result)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_block => {
                 if parameters.len() != 1 {
                     bail!("wrong param numbers")
@@ -580,6 +814,14 @@ impl Command {
                 }
             }
 
+            Command::get_operation_traces => {
+                let operations = parse_vec::<OperationId>(parameters)?;
+                match client.public.get_operation_traces(operations).await {
+                    Ok(operation_traces) => Ok(Box::new(operation_traces)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_filtered_sc_output_event => {
                 let p_list: [&str; 6] = [
                     "start",
@@ -601,8 +843,8 @@ impl Command {
                 let filter = EventFilter {
                     start: parse_key_value(&p, p_list[0]),
                     end: parse_key_value(&p, p_list[1]),
-                    emitter_address: parse_key_value(&p, p_list[2]),
-                    original_caller_address: parse_key_value(&p, p_list[3]),
+                    emitter_address: parse_key_address(wallet, &p, p_list[2]),
+                    original_caller_address: parse_key_address(wallet, &p, p_list[3]),
                     original_operation_id: parse_key_value(&p, p_list[4]),
                     is_final: parse_key_value(&p, p_list[5]),
                 };
@@ -618,7 +860,7 @@ impl Command {
                 }
                 match client
                     .public
-                    .get_addresses(wallet.get_full_wallet().keys().copied().collect())
+                    .get_addresses(wallet.get_all_addresses().into_iter().collect())
                     .await
                 {
                     Ok(addresses_info) => {
@@ -629,8 +871,15 @@ impl Command {
             }
 
             Command::wallet_generate_secret_key => {
-                let key = KeyPair::generate();
-                let ad = wallet.add_keypairs(vec![key])?[0];
+                let ad = if wallet.seed_phrase.is_some() {
+                    wallet.derive_next_account()?
+                } else {
+                    if !json {
+                        client_warning!("this key is not derived from a seed phrase: back up your wallet file, or use 'wallet_generate_seed_phrase' first");
+                    }
+                    let key = KeyPair::generate();
+                    wallet.add_keypairs(vec![key])?[0]
+                };
                 if json {
                     Ok(Box::new(ad.to_string()))
                 } else {
@@ -640,6 +889,64 @@ impl Command {
                 }
             }
 
+            Command::wallet_generate_seed_phrase => {
+                let seed_phrase = wallet.generate_seed_phrase()?;
+                if json {
+                    Ok(Box::new(seed_phrase))
+                } else {
+                    client_warning!("write down this seed phrase and do not share it: anyone knowing it can spend the coins of the accounts derived from it");
+                    println!("{}", seed_phrase);
+                    println!("Type `wallet_generate_secret_key` to derive a new account from this seed phrase.\n");
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::wallet_change_password => {
+                let new_password = match parameters {
+                    [] => Password::new()
+                        .with_prompt("Enter new password for wallet")
+                        .with_confirmation("Confirm password", "Passwords mismatching")
+                        .interact()?,
+                    [new_password] => new_password.clone(),
+                    _ => bail!("wrong number of parameters"),
+                };
+                wallet.change_password(new_password)?;
+                if !json {
+                    println!("Wallet password changed");
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::wallet_restore_seed_phrase => {
+                // the seed phrase is not taken from the parameters
+                // so that it is neither echoed nor kept in the command history
+                let account_count = match parameters {
+                    [] => None,
+                    [account_count] => Some(account_count.parse::<u32>()?),
+                    _ => bail!("wrong number of parameters"),
+                };
+                let phrase = Password::new()
+                    .with_prompt("Enter the seed phrase")
+                    .interact()?;
+                let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+                if phrase.is_empty() {
+                    bail!("missing seed phrase")
+                }
+                let account_count = match account_count {
+                    Some(account_count) => account_count,
+                    None => find_used_account_count(client, &phrase).await?,
+                };
+                let addresses = wallet.restore_seed_phrase(&phrase, account_count)?;
+                if json {
+                    return Ok(Box::new(addresses));
+                } else {
+                    for address in addresses {
+                        println!("Restored address {} to the wallet.", address);
+                    }
+                }
+                Ok(Box::new(()))
+            }
+
             Command::wallet_add_secret_keys => {
                 let keypairs = parse_vec::<KeyPair>(parameters)?;
                 let addresses = wallet.add_keypairs(keypairs)?;
@@ -656,7 +963,7 @@ impl Command {
 
             Command::wallet_remove_addresses => {
                 let mut res = "".to_string();
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = parse_addresses(wallet, parameters)?;
                 match wallet.remove_addresses(&addresses) {
                     Ok(_) => {
                         let _ = writeln!(res, "Addresses removed from the wallet");
@@ -672,20 +979,16 @@ impl Command {
             }
 
             Command::buy_rolls => {
-                if parameters.len() != 3 {
-                    bail!("wrong number of parameters");
-                }
-                let addr = parameters[0].parse::<Address>()?;
-                let roll_count = parameters[1].parse::<u64>()?;
-                let fee = parameters[2].parse::<Amount>()?;
+                let (addr, op, fee) =
+                    parse_operation(wallet, Command::buy_rolls, parameters).await?;
 
-                if !json {
+                if let (false, OperationType::RollBuy { roll_count }) = (json, &op) {
                     let roll_price = match client.public.get_status().await {
                         Err(e) => bail!("RpcError: {}", e),
                         Ok(status) => status.config.roll_price,
                     };
                     match roll_price
-                        .checked_mul_u64(roll_count)
+                        .checked_mul_u64(*roll_count)
                         .and_then(|x| x.checked_add(fee))
                     {
                         Some(total) => {
@@ -714,31 +1017,19 @@ impl Command {
                         }
                     }
                 }
-                send_operation(
-                    client,
-                    wallet,
-                    OperationType::RollBuy { roll_count },
-                    fee,
-                    addr,
-                    json,
-                )
-                .await
+                send_operation(client, wallet, op, fee, addr, json).await
             }
 
             Command::sell_rolls => {
-                if parameters.len() != 3 {
-                    bail!("wrong number of parameters");
-                }
-                let addr = parameters[0].parse::<Address>()?;
-                let roll_count = parameters[1].parse::<u64>()?;
-                let fee = parameters[2].parse::<Amount>()?;
+                let (addr, op, fee) =
+                    parse_operation(wallet, Command::sell_rolls, parameters).await?;
 
-                if !json {
+                if let (false, OperationType::RollSell { roll_count }) = (json, &op) {
                     if let Ok(addresses_info) = client.public.get_addresses(vec![addr]).await {
                         match addresses_info.get(0) {
                             Some(info) => {
                                 if info.candidate_sequential_balance < fee
-                                    || roll_count > info.candidate_roll_count
+                                    || *roll_count > info.candidate_roll_count
                                 {
                                     client_warning!("this operation may be rejected due to insufficient balance or roll count");
                                 }
@@ -748,25 +1039,12 @@ impl Command {
                     }
                 }
 
-                send_operation(
-                    client,
-                    wallet,
-                    OperationType::RollSell { roll_count },
-                    fee,
-                    addr,
-                    json,
-                )
-                .await
+                send_operation(client, wallet, op, fee, addr, json).await
             }
 
             Command::send_transaction => {
-                if parameters.len() != 4 {
-                    bail!("wrong number of parameters");
-                }
-                let addr = parameters[0].parse::<Address>()?;
-                let recipient_address = parameters[1].parse::<Address>()?;
-                let amount = parameters[2].parse::<Amount>()?;
-                let fee = parameters[3].parse::<Amount>()?;
+                let (addr, op, fee) =
+                    parse_operation(wallet, Command::send_transaction, parameters).await?;
 
                 if !json {
                     if let Ok(addresses_info) = client.public.get_addresses(vec![addr]).await {
@@ -783,18 +1061,7 @@ impl Command {
                     }
                 }
 
-                send_operation(
-                    client,
-                    wallet,
-                    OperationType::Transaction {
-                        recipient_address,
-                        amount,
-                    },
-                    fee,
-                    addr,
-                    json,
-                )
-                .await
+                send_operation(client, wallet, op, fee, addr, json).await
             }
             Command::when_episode_ends => {
                 let end = match client.public.get_status().await {
@@ -824,19 +1091,21 @@ impl Command {
                 Ok(Box::new(()))
             }
             Command::send_smart_contract => {
-                if parameters.len() != 6 {
-                    bail!("wrong number of parameters");
-                }
-                let addr = parameters[0].parse::<Address>()?;
-                let path = parameters[1].parse::<PathBuf>()?;
-                let max_gas = parameters[2].parse::<u64>()?;
-                let gas_price = parameters[3].parse::<Amount>()?;
-                let coins = parameters[4].parse::<Amount>()?;
-                let fee = parameters[5].parse::<Amount>()?;
+                let (addr, op, fee) =
+                    parse_operation(wallet, Command::send_smart_contract, parameters).await?;
 
-                if !json {
+                if let (
+                    false,
+                    OperationType::ExecuteSC {
+                        data,
+                        max_gas,
+                        gas_price,
+                        ..
+                    },
+                ) = (json, &op)
+                {
                     match gas_price
-                        .checked_mul_u64(max_gas)
+                        .checked_mul_u64(*max_gas)
                         .and_then(|x| x.checked_add(fee))
                     {
                         Some(total) => {
@@ -859,9 +1128,6 @@ impl Command {
                             client_warning!("the total amount hit the limit overflow, operation will certainly be rejected");
                         }
                     }
-                };
-                let data = get_file_as_byte_vec(&path).await?;
-                if !json {
                     let max_block_size = match client.public.get_status().await {
                         Ok(node_status) => node_status.config.max_block_size,
                         Err(e) => bail!("RpcError: {}", e),
@@ -871,41 +1137,28 @@ impl Command {
                     }
                 }
 
-                send_operation(
-                    client,
-                    wallet,
-                    OperationType::ExecuteSC {
-                        data,
+                send_operation(client, wallet, op, fee, addr, json).await
+            }
+            Command::call_smart_contract => {
+                let (addr, op, fee) =
+                    parse_operation(wallet, Command::call_smart_contract, parameters).await?;
+                if let (
+                    false,
+                    OperationType::CallSC {
+                        target_addr,
                         max_gas,
-                        coins,
                         gas_price,
+                        ..
                     },
-                    fee,
-                    addr,
-                    json,
-                )
-                .await
-            }
-            Command::call_smart_contract => {
-                if parameters.len() != 8 {
-                    bail!("wrong number of parameters");
-                }
-                let addr = parameters[0].parse::<Address>()?;
-                let target_addr = parameters[1].parse::<Address>()?;
-                let target_func = parameters[2].clone();
-                let param = parameters[3].clone();
-                let max_gas = parameters[4].parse::<u64>()?;
-                let gas_price = parameters[5].parse::<Amount>()?;
-                let coins = parameters[6].parse::<Amount>()?;
-                let fee = parameters[7].parse::<Amount>()?;
-                if !json {
+                ) = (json, &op)
+                {
                     match gas_price
-                        .checked_mul_u64(max_gas)
+                        .checked_mul_u64(*max_gas)
                         .and_then(|x| x.checked_add(fee))
                     {
                         Some(total) => {
                             if let Ok(addresses_info) =
-                                client.public.get_addresses(vec![target_addr]).await
+                                client.public.get_addresses(vec![*target_addr]).await
                             {
                                 match addresses_info.get(0) {
                                     Some(info) => {
@@ -927,29 +1180,202 @@ impl Command {
                         }
                     }
                 };
-                send_operation(
-                    client,
-                    wallet,
-                    OperationType::CallSC {
-                        target_addr,
-                        target_func,
-                        param,
-                        max_gas,
-                        sequential_coins: Amount::zero(),
-                        parallel_coins: coins,
-                        gas_price,
+                send_operation(client, wallet, op, fee, addr, json).await
+            }
+            Command::prepare_operation => {
+                if parameters.len() < 3 {
+                    bail!("wrong number of parameters");
+                }
+                let path = parameters[0].parse::<PathBuf>()?;
+                let expire_period = parameters[1].parse::<u64>()?;
+                let command = parameters[2].parse::<Command>()?;
+                let (sender_address, op, fee) =
+                    parse_operation(wallet, command, &parameters[3..]).await?;
+                let unsigned_operation = UnsignedOperation {
+                    sender_address,
+                    operation: Operation {
+                        fee,
+                        expire_period,
+                        op,
                     },
-                    fee,
-                    addr,
-                    json,
+                    multisig: None,
+                };
+                tokio::fs::write(&path, serde_json::to_vec_pretty(&unsigned_operation)?).await?;
+                if !json {
+                    println!(
+                        "Unsigned operation written to {}, sign it with `sign_operation`",
+                        path.display()
+                    );
+                }
+                Ok(Box::new(()))
+            }
+            Command::sign_operation => {
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let unsigned_path = parameters[0].parse::<PathBuf>()?;
+                let signed_path = parameters[1].parse::<PathBuf>()?;
+                let mut unsigned_operation: UnsignedOperation =
+                    serde_json::from_slice(&get_file_as_byte_vec(&unsigned_path).await?)?;
+                if !json {
+                    println!("Signing operation:\n{}", unsigned_operation.operation);
+                }
+                let op = if let Some(mut multisig) = unsigned_operation.multisig.take() {
+                    let signers = wallet
+                        .sign_multisig_operation(&unsigned_operation.operation, &mut multisig)?;
+                    let signature_count = multisig.signatures.len();
+                    let threshold = multisig.policy.threshold as usize;
+                    unsigned_operation.multisig = Some(multisig.clone());
+                    tokio::fs::write(
+                        &unsigned_path,
+                        serde_json::to_vec_pretty(&unsigned_operation)?,
+                    )
+                    .await?;
+                    if !json {
+                        println!(
+                            "Signed by {}, {} of the {} required signatures collected",
+                            signers
+                                .iter()
+                                .map(|address| address.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                            signature_count,
+                            threshold
+                        );
+                    }
+                    if signature_count < threshold {
+                        if !json {
+                            println!(
+                                "Signatures saved to {}, pass it to the other signers",
+                                unsigned_path.display()
+                            );
+                        }
+                        return Ok(Box::new(()));
+                    }
+                    Operation::new_multisig_wrapped(unsigned_operation.operation, multisig)?
+                } else {
+                    wallet.create_operation(
+                        unsigned_operation.operation,
+                        unsigned_operation.sender_address,
+                    )?
+                };
+                let operation_id = op.id;
+                tokio::fs::write(
+                    &signed_path,
+                    serde_json::to_vec_pretty(&to_operation_input(op))?,
                 )
-                .await
+                .await?;
+                if !json {
+                    println!(
+                        "Signed operation {} written to {}, send it with `broadcast_operation`",
+                        operation_id,
+                        signed_path.display()
+                    );
+                }
+                Ok(Box::new(vec![operation_id]))
+            }
+            Command::multisig_address => {
+                if parameters.len() < 2 {
+                    bail!("wrong number of parameters");
+                }
+                let threshold = parameters[0].parse::<u32>()?;
+                let public_keys = parse_vec::<PublicKey>(&parameters[1..])?;
+                let address = MultisigPolicy::new(threshold, public_keys)?.address();
+                if !json {
+                    println!("Multisig address:");
+                }
+                Ok(Box::new(vec![address]))
+            }
+            Command::set_operation_multisig => {
+                if parameters.len() < 3 {
+                    bail!("wrong number of parameters");
+                }
+                let path = parameters[0].parse::<PathBuf>()?;
+                let threshold = parameters[1].parse::<u32>()?;
+                let public_keys = parse_vec::<PublicKey>(&parameters[2..])?;
+                let policy = MultisigPolicy::new(threshold, public_keys)?;
+                let mut unsigned_operation: UnsignedOperation =
+                    serde_json::from_slice(&get_file_as_byte_vec(&path).await?)?;
+                if policy.address() != unsigned_operation.sender_address {
+                    bail!(
+                        "the public keys and threshold give the address {}, not the sender address {}",
+                        policy.address(),
+                        unsigned_operation.sender_address
+                    );
+                }
+                unsigned_operation.multisig = Some(MultisigSignatures::new(policy));
+                tokio::fs::write(&path, serde_json::to_vec_pretty(&unsigned_operation)?).await?;
+                if !json {
+                    println!(
+                        "Multisig sender set in {}, each signer can now sign it with `sign_operation`",
+                        path.display()
+                    );
+                }
+                Ok(Box::new(()))
+            }
+            Command::broadcast_operation => {
+                if parameters.is_empty() {
+                    bail!("wrong number of parameters");
+                }
+                let mut operations = Vec::with_capacity(parameters.len());
+                for path in parse_vec::<PathBuf>(parameters)? {
+                    operations.push(serde_json::from_slice::<OperationInput>(
+                        &get_file_as_byte_vec(&path).await?,
+                    )?);
+                }
+                match client.public.send_operations(operations).await {
+                    Ok(operation_ids) => {
+                        if !json {
+                            println!("Sent operation IDs:");
+                        }
+                        Ok(Box::new(operation_ids))
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+            }
+            Command::wallet_add_watch_only_addresses => {
+                if parameters.is_empty() {
+                    bail!("wrong number of parameters");
+                }
+                let addresses = parse_vec::<Address>(parameters)?;
+                wallet.add_watch_only(&addresses)?;
+                if !json {
+                    println!("Watch-only addresses added to the wallet");
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::wallet_set_label => {
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let address = wallet.resolve_address(&parameters[0])?;
+                wallet.set_label(address, parameters[1].clone())?;
+                if !json {
+                    println!("Label {} given to {}", parameters[1], address);
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::wallet_remove_label => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let address = wallet
+                    .find_labeled_address(&parameters[0])
+                    .ok_or_else(|| anyhow!("unknown label {}", parameters[0]))?;
+                wallet.remove_label(&address)?;
+                if !json {
+                    println!("Label {} removed from the wallet", parameters[0]);
+                }
+                Ok(Box::new(()))
             }
+
             Command::wallet_sign => {
                 if parameters.len() != 2 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = wallet.resolve_address(&parameters[0])?;
                 let msg = parameters[1].clone();
                 if let Some(signed) = wallet.sign_message(&addr, msg.into_bytes()) {
                     Ok(Box::new(signed))
@@ -966,7 +1392,7 @@ impl Command {
                 let max_gas = parameters[1].parse::<u64>()?;
                 let simulated_gas_price = parameters[2].parse::<Amount>()?;
                 let address = if let Some(adr) = parameters.get(3) {
-                    Some(adr.parse::<Address>()?)
+                    Some(wallet.resolve_address(adr)?)
                 } else {
                     None
                 };
@@ -990,13 +1416,13 @@ impl Command {
                     bail!("wrong number of parameters");
                 }
 
-                let target_address = parameters[0].parse::<Address>()?;
+                let target_address = wallet.resolve_address(&parameters[0])?;
                 let target_function = parameters[1].parse::<String>()?;
                 let parameter = parameters[2].parse::<String>()?;
                 let max_gas = parameters[3].parse::<u64>()?;
                 let simulated_gas_price = parameters[4].parse::<Amount>()?;
                 let caller_address = if let Some(addr) = parameters.get(5) {
-                    Some(addr.parse::<Address>()?)
+                    Some(wallet.resolve_address(addr)?)
                 } else {
                     None
                 };
@@ -1016,6 +1442,74 @@ impl Command {
                     Err(e) => rpc_error!(e),
                 }
             }
+            Command::estimate_smart_contract => {
+                if parameters.len() != 4 && parameters.len() != 5 {
+                    bail!("wrong number of parameters");
+                }
+
+                let path = parameters[0].parse::<PathBuf>()?;
+                let max_gas = parameters[1].parse::<u64>()?;
+                let gas_price = parameters[2].parse::<Amount>()?;
+                let coins = parameters[3].parse::<Amount>()?;
+                let caller_address = if let Some(addr) = parameters.get(4) {
+                    Some(wallet.resolve_address(addr)?)
+                } else {
+                    None
+                };
+                let data = get_file_as_byte_vec(&path).await?;
+                match client
+                    .public
+                    .estimate_operation(EstimateOperation {
+                        op: OperationType::ExecuteSC {
+                            data,
+                            max_gas,
+                            coins,
+                            gas_price,
+                        },
+                        caller_address,
+                    })
+                    .await
+                {
+                    Ok(res) => Ok(Box::new(res)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+            Command::estimate_call => {
+                if parameters.len() != 6 && parameters.len() != 7 {
+                    bail!("wrong number of parameters");
+                }
+
+                let target_addr = wallet.resolve_address(&parameters[0])?;
+                let target_func = parameters[1].clone();
+                let param = parameters[2].clone();
+                let max_gas = parameters[3].parse::<u64>()?;
+                let gas_price = parameters[4].parse::<Amount>()?;
+                let coins = parameters[5].parse::<Amount>()?;
+                let caller_address = if let Some(addr) = parameters.get(6) {
+                    Some(wallet.resolve_address(addr)?)
+                } else {
+                    None
+                };
+                match client
+                    .public
+                    .estimate_operation(EstimateOperation {
+                        op: OperationType::CallSC {
+                            target_addr,
+                            target_func,
+                            param,
+                            max_gas,
+                            sequential_coins: Amount::zero(),
+                            parallel_coins: coins,
+                            gas_price,
+                        },
+                        caller_address,
+                    })
+                    .await
+                {
+                    Ok(res) => Ok(Box::new(res)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
             Command::node_whitelist => {
                 let ips = parse_vec::<IpAddr>(parameters)?;
                 match client.private.node_whitelist(ips).await {
@@ -1040,6 +1534,11 @@ impl Command {
                 }
                 Ok(Box::new(()))
             }
+
+            Command::node_peers => match client.private.get_peers_stats().await {
+                Ok(peers_stats) => Ok(Box::new(peers_stats)),
+                Err(e) => rpc_error!(e),
+            },
         }
     }
 }
@@ -1077,11 +1576,7 @@ async fn send_operation(
 
     match client
         .public
-        .send_operations(vec![OperationInput {
-            creator_public_key: op.creator_public_key,
-            serialized_content: op.serialized_data,
-            signature: op.signature,
-        }])
+        .send_operations(vec![to_operation_input(op)])
         .await
     {
         Ok(operation_ids) => {
@@ -1094,12 +1589,118 @@ async fn send_operation(
     }
 }
 
+/// Parses the parameters of an operation sending command, in the same order as the command itself.
+/// Used by the operation sending commands and by `prepare_operation`.
+/// Returns the sender address, the operation and its fee.
+async fn parse_operation(
+    wallet: &Wallet,
+    command: Command,
+    parameters: &[String],
+) -> Result<(Address, OperationType, Amount)> {
+    let expected_parameter_count = match command {
+        Command::buy_rolls | Command::sell_rolls => 3,
+        Command::send_transaction => 4,
+        Command::send_smart_contract => 6,
+        Command::call_smart_contract => 8,
+        _ => bail!("{} does not send an operation", command),
+    };
+    if parameters.len() != expected_parameter_count {
+        bail!("wrong number of parameters for {}", command);
+    }
+    let addr = wallet.resolve_address(&parameters[0])?;
+    let fee = parameters[expected_parameter_count - 1].parse::<Amount>()?;
+    let op = match command {
+        Command::buy_rolls => OperationType::RollBuy {
+            roll_count: parameters[1].parse::<u64>()?,
+        },
+        Command::sell_rolls => OperationType::RollSell {
+            roll_count: parameters[1].parse::<u64>()?,
+        },
+        Command::send_transaction => OperationType::Transaction {
+            recipient_address: wallet.resolve_address(&parameters[1])?,
+            amount: parameters[2].parse::<Amount>()?,
+        },
+        Command::send_smart_contract => OperationType::ExecuteSC {
+            data: get_file_as_byte_vec(&parameters[1].parse::<PathBuf>()?).await?,
+            max_gas: parameters[2].parse::<u64>()?,
+            gas_price: parameters[3].parse::<Amount>()?,
+            coins: parameters[4].parse::<Amount>()?,
+        },
+        _ => OperationType::CallSC {
+            target_addr: wallet.resolve_address(&parameters[1])?,
+            target_func: parameters[2].clone(),
+            param: parameters[3].clone(),
+            max_gas: parameters[4].parse::<u64>()?,
+            gas_price: parameters[5].parse::<Amount>()?,
+            sequential_coins: Amount::zero(),
+            parallel_coins: parameters[6].parse::<Amount>()?,
+        },
+    };
+    Ok((addr, op, fee))
+}
+
+/// Signed operation in the format expected by the `send_operations` API
+fn to_operation_input(op: WrappedOperation) -> OperationInput {
+    OperationInput {
+        creator_public_key: op.creator_public_key,
+        serialized_content: op.serialized_data,
+        signature: op.signature,
+        multisig: op.multisig,
+    }
+}
+
 /// TODO: ugly utilities functions
 /// takes a slice of string and makes it into a `Vec<T>`
 pub fn parse_vec<T: std::str::FromStr>(args: &[String]) -> anyhow::Result<Vec<T>, T::Err> {
     args.iter().map(|x| x.parse::<T>()).collect()
 }
 
+/// takes a slice of addresses or labels of the wallet and makes it into a `Vec<Address>`
+fn parse_addresses(wallet: &Wallet, args: &[String]) -> Result<Vec<Address>> {
+    args.iter()
+        .map(|x| Ok(wallet.resolve_address(x)?))
+        .collect()
+}
+
+/// Number of consecutive unused accounts after which the lookup of the accounts of a seed phrase stops
+const ACCOUNT_GAP_LIMIT: u32 = 20;
+
+/// Looks for the accounts derived from a seed phrase that were used on the network,
+/// returns the number of accounts to restore: up to the last used one, at least one.
+/// Accounts are looked up by batches until a whole batch of `ACCOUNT_GAP_LIMIT` accounts is unused.
+async fn find_used_account_count(client: &Client, phrase: &str) -> Result<u32> {
+    let seed_phrase = SeedPhrase::from_mnemonic(phrase)?;
+    let mut account_count = 1;
+    let mut batch_start = 0;
+    loop {
+        let addresses = (batch_start..batch_start.saturating_add(ACCOUNT_GAP_LIMIT))
+            .map(|account| {
+                seed_phrase
+                    .derive_account(account)
+                    .map(|keypair| Address::from_public_key(&keypair.get_public_key()))
+            })
+            .collect::<Result<Vec<Address>, _>>()?;
+        let addresses_info = match client.public.get_addresses(addresses).await {
+            Ok(addresses_info) => addresses_info,
+            Err(e) => rpc_error!(e),
+        };
+        let last_used = addresses_info.iter().rposition(|info| {
+            info.candidate_sequential_balance != Amount::zero()
+                || info.candidate_parallel_balance != Amount::zero()
+                || info.candidate_roll_count > 0
+                || !info.deferred_credits.is_empty()
+                || !info.created_operations.is_empty()
+        });
+        match last_used {
+            Some(index) => {
+                account_count = batch_start.saturating_add(index as u32).saturating_add(1);
+                batch_start = batch_start.saturating_add(ACCOUNT_GAP_LIMIT);
+            }
+            None => return Ok(account_count),
+        }
+    }
+}
+
 /// reads a file
 async fn get_file_as_byte_vec(filename: &std::path::Path) -> Result<Vec<u8>> {
     Ok(tokio::fs::read(filename).await?)
@@ -1118,3 +1719,111 @@ pub fn parse_key_value<T: std::str::FromStr>(p: &HashMap<&str, &str>, key: &str)
             .ok()
     })
 }
+
+/// Same as `parse_key_value` for an address, which can also be given by its label in the wallet
+fn parse_key_address(wallet: &Wallet, p: &HashMap<&str, &str>, key: &str) -> Option<Address> {
+    p.get(key).and_then(|name| {
+        wallet
+            .resolve_address(name)
+            .map_err(|_| {
+                client_warning!(format!(
+                    "'{}' parameter was ignored because of wrong corresponding value",
+                    key
+                ))
+            })
+            .ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::{
+        config::{MAX_DATASTORE_VALUE_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_PARAMETERS_SIZE},
+        operation::OperationDeserializer,
+        wrapped::{WrappedContent, WrappedDeserializer},
+    };
+    use massa_serialization::{DeserializeError, Deserializer};
+    use std::str::FromStr;
+
+    /// Prepare an operation, sign it and read it back the way the node does when it is broadcast
+    #[tokio::test]
+    async fn test_prepare_sign_broadcast_operation() {
+        let wallet_dir = tempfile::TempDir::new().unwrap();
+        let mut wallet =
+            Wallet::new(wallet_dir.path().join("wallet.dat"), "password".to_string()).unwrap();
+        let sender_address = wallet.add_keypairs(vec![KeyPair::generate()]).unwrap()[0];
+        let recipient_address = Address::from_public_key(&KeyPair::generate().get_public_key());
+
+        // prepare_operation
+        let (sender_address, op, fee) = parse_operation(
+            &wallet,
+            Command::send_transaction,
+            &[
+                sender_address.to_string(),
+                recipient_address.to_string(),
+                "12.5".to_string(),
+                "0.01".to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+        let unsigned_file = serde_json::to_vec_pretty(&UnsignedOperation {
+            sender_address,
+            operation: Operation {
+                fee,
+                expire_period: 42,
+                op,
+            },
+            multisig: None,
+        })
+        .unwrap();
+
+        // sign_operation
+        let unsigned_operation: UnsignedOperation = serde_json::from_slice(&unsigned_file).unwrap();
+        let signed_op = wallet
+            .create_operation(
+                unsigned_operation.operation,
+                unsigned_operation.sender_address,
+            )
+            .unwrap();
+        let signed_file =
+            serde_json::to_vec_pretty(&to_operation_input(signed_op.clone())).unwrap();
+
+        // broadcast_operation, deserialized and verified like the send_operations API does
+        let operation_input: OperationInput = serde_json::from_slice(&signed_file).unwrap();
+        let mut buffer = Vec::new();
+        Operation::serialize(
+            &operation_input.signature,
+            &operation_input.creator_public_key,
+            &operation_input.multisig,
+            &operation_input.serialized_content,
+            &mut buffer,
+        )
+        .unwrap();
+        let (rest, received): (&[u8], WrappedOperation) =
+            WrappedDeserializer::new(OperationDeserializer::new(
+                MAX_DATASTORE_VALUE_LENGTH,
+                MAX_FUNCTION_NAME_LENGTH,
+                MAX_PARAMETERS_SIZE,
+            ))
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        received.verify_signature().unwrap();
+        assert_eq!(received.id, signed_op.id);
+        assert_eq!(received.creator_address, sender_address);
+        assert_eq!(received.content.fee, Amount::from_str("0.01").unwrap());
+        assert_eq!(received.content.expire_period, 42);
+        match received.content.op {
+            OperationType::Transaction {
+                recipient_address: received_recipient,
+                amount,
+            } => {
+                assert_eq!(received_recipient, recipient_address);
+                assert_eq!(amount, Amount::from_str("12.5").unwrap());
+            }
+            _ => panic!("unexpected operation type"),
+        }
+    }
+}
@@ -8,12 +8,14 @@ use dialoguer::{theme::ColorfulTheme, Completion, History, Input};
 use erased_serde::{Serialize, Serializer};
 use glob::glob;
 use massa_models::api::{
-    AddressInfo, BlockInfo, DatastoreEntryOutput, EndorsementInfo, NodeStatus, OperationInfo,
+    AddressHistoryPage, AddressInfo, BlockInfo, DatastoreEntryOutput, EndorsementInfo,
+    LedgerEntryAtSlotOutput, LedgerProofOutput, NodeStatus, OperationInfo,
 };
 use massa_models::composite::PubkeySig;
-use massa_models::execution::ExecuteReadOnlyResponse;
+use massa_models::execution::{ExecuteReadOnlyResponse, OperationEstimation, OperationTrace};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
+use massa_models::stats::PeerStats;
 use massa_models::{address::Address, operation::OperationId};
 use massa_sdk::Client;
 use massa_wallet::Wallet;
@@ -248,6 +250,12 @@ impl Output for BlockInfo {
     }
 }
 
+impl Output for AddressHistoryPage {
+    fn pretty_print(&self) {
+        print!("{}", self);
+    }
+}
+
 impl Output for PreHashSet<Address> {
     fn pretty_print(&self) {
         println!(
@@ -274,6 +282,22 @@ impl Output for Vec<DatastoreEntryOutput> {
     }
 }
 
+impl Output for Vec<LedgerEntryAtSlotOutput> {
+    fn pretty_print(&self) {
+        for entry in self {
+            println!("{}", entry);
+        }
+    }
+}
+
+impl Output for Vec<LedgerProofOutput> {
+    fn pretty_print(&self) {
+        for proof in self {
+            println!("{}", proof);
+        }
+    }
+}
+
 impl Output for Vec<EndorsementInfo> {
     fn pretty_print(&self) {
         for endorsement_info in self {
@@ -290,6 +314,14 @@ impl Output for Vec<OperationInfo> {
     }
 }
 
+impl Output for Vec<OperationTrace> {
+    fn pretty_print(&self) {
+        for operation_trace in self {
+            println!("{}", operation_trace);
+        }
+    }
+}
+
 impl Output for Vec<OperationId> {
     fn pretty_print(&self) {
         for operation_id in self {
@@ -306,6 +338,14 @@ impl Output for Vec<Address> {
     }
 }
 
+impl Output for Vec<PeerStats> {
+    fn pretty_print(&self) {
+        for peer_stats in self {
+            println!("{}", peer_stats);
+        }
+    }
+}
+
 impl Output for Vec<SCOutputEvent> {
     fn pretty_print(&self) {
         for addr in self {
@@ -325,3 +365,9 @@ impl Output for ExecuteReadOnlyResponse {
         println!("{}", self);
     }
 }
+
+impl Output for OperationEstimation {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
@@ -44,14 +44,16 @@
 //! In unit test your allowed to use the `testing` feature flag that will
 //! use the default values from `/node_configuration/default_testing.rs` in the
 //! `massa-models` crate sources.
+use massa_archive::BlockArchive;
 use massa_execution_exports::ExecutionController;
 use massa_graph::settings::GraphConfig;
+use massa_models::api::BlockNotification;
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::{ProtocolCommandSender, ProtocolEventReceiver};
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 
 use crate::{
     commands::{ConsensusCommand, ConsensusManagementCommand},
@@ -143,6 +145,10 @@ pub struct ConsensusWorkerChannels {
     pub controller_event_tx: mpsc::Sender<ConsensusEvent>,
     /// Channel receiving consensus management commands.
     pub controller_manager_rx: mpsc::Receiver<ConsensusManagementCommand>,
+    /// Broadcast channels notifying subscribers of block graph changes.
+    pub broadcasts: ConsensusBroadcasts,
+    /// Archive of final blocks, if the node runs in archive mode.
+    pub archive: Option<BlockArchive>,
 }
 
 /// Public channels associated to the consensus module.
@@ -158,6 +164,36 @@ pub struct ConsensusChannels {
     pub pool_command_sender: Box<dyn PoolController>,
     /// selector controller
     pub selector_controller: Box<dyn SelectorController>,
+    /// broadcast channels notifying subscribers of block graph changes
+    pub broadcasts: ConsensusBroadcasts,
+    /// archive of final blocks, if the node runs in archive mode
+    pub archive: Option<BlockArchive>,
+}
+
+/// Broadcast channels used by consensus to notify any number of subscribers
+/// (for example the API) of block graph changes.
+///
+/// Sending never blocks consensus: if nobody listens the notification is dropped,
+/// and slow receivers lag behind and lose the oldest notifications.
+#[derive(Clone)]
+pub struct ConsensusBroadcasts {
+    /// blocks that became the head of their thread in the blockclique
+    pub blockclique_head_sender: broadcast::Sender<BlockNotification>,
+    /// blocks that entered the blockclique
+    pub blockclique_block_sender: broadcast::Sender<BlockNotification>,
+    /// blocks that became final
+    pub final_block_sender: broadcast::Sender<BlockNotification>,
+}
+
+impl ConsensusBroadcasts {
+    /// Create the broadcast channels, each one buffering at most `capacity` notifications
+    pub fn new(capacity: usize) -> Self {
+        ConsensusBroadcasts {
+            blockclique_head_sender: broadcast::channel(capacity).0,
+            blockclique_block_sender: broadcast::channel(capacity).0,
+            final_block_sender: broadcast::channel(capacity).0,
+        }
+    }
 }
 
 #[cfg(feature = "testing")]
@@ -7,8 +7,8 @@ use massa_consensus_exports::{
 };
 use massa_graph::{BlockGraph, BlockGraphExport};
 use massa_models::timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp};
-use massa_models::{address::Address, block::BlockId, slot::Slot};
-use massa_models::{block::WrappedHeader, prehash::PreHashMap};
+use massa_models::{address::Address, api::BlockNotification, block::BlockId, slot::Slot};
+use massa_models::{block::WrappedHeader, operation::WrappedOperation, prehash::PreHashMap};
 use massa_models::{prehash::PreHashSet, stats::ConsensusStats};
 use massa_protocol_exports::{ProtocolEvent, ProtocolEventReceiver};
 use massa_time::MassaTime;
@@ -54,6 +54,10 @@ pub struct ConsensusWorker {
     stats_desync_detection_timespan: MassaTime,
     /// time at which the node was launched (used for desynchronization detection)
     launch_time: MassaTime,
+    /// blockclique at the last block graph change, used to notify subscribers of new blockclique blocks
+    blockclique: PreHashSet<BlockId>,
+    /// best parents at the last block graph change, used to notify subscribers of new blockclique heads
+    best_parents: Vec<BlockId>,
 }
 
 impl ConsensusWorker {
@@ -132,14 +136,19 @@ impl ConsensusWorker {
 
         let final_blocks = block_db.get_all_final_blocks();
 
-        let blockclique = block_db
-            .get_blockclique()
-            .into_iter()
+        let blockclique_set = block_db.get_blockclique();
+        let best_parents = block_db
+            .get_best_parents()
+            .iter()
+            .map(|(block_id, _period)| *block_id)
+            .collect();
+        let blockclique = blockclique_set
+            .iter()
             .map(|block_id| {
                 let (a_block, storage) = block_db
-                    .get_active_block(&block_id)
+                    .get_active_block(block_id)
                     .expect("could not get active block for execution notification");
-                (a_block.slot, (block_id, storage.clone()))
+                (a_block.slot, (*block_id, storage.clone()))
             })
             .collect();
         channels
@@ -161,6 +170,8 @@ impl ConsensusWorker {
             stats_history_timespan: max(stats_desync_detection_timespan, cfg.stats_timespan),
             cfg,
             launch_time: MassaTime::now(clock_compensation)?,
+            blockclique: blockclique_set,
+            best_parents,
         })
     }
 
@@ -472,6 +483,14 @@ impl ConsensusWorker {
             final_block_count,
             stale_block_count,
             clique_count,
+            active_block_count: self.block_db.get_active_block_count() as u64,
+            incoming_block_count: self.block_db.get_incoming_block_count() as u64,
+            waiting_for_slot_block_count: self.block_db.get_waiting_for_slot_block_count() as u64,
+            waiting_for_dependencies_block_count: self
+                .block_db
+                .get_waiting_for_dependencies_block_count()
+                as u64,
+            discarded_block_count: self.block_db.get_discarded_block_count() as u64,
             start_timespan: timespan_start,
             end_timespan: timespan_end,
         })
@@ -544,6 +563,82 @@ impl ConsensusWorker {
         Ok(())
     }
 
+    /// Build the notification sent to subscribers about an active block
+    fn block_notification(&self, block_id: &BlockId) -> Option<BlockNotification> {
+        let (a_block, storage) = self.block_db.get_active_block(block_id)?;
+        let operations = storage
+            .read_blocks()
+            .get(block_id)
+            .map(|block| block.content.operations.clone())
+            .unwrap_or_default();
+        Some(BlockNotification {
+            id: *block_id,
+            slot: a_block.slot,
+            creator: a_block.creator_address,
+            parents: a_block.parents.iter().map(|(id, _period)| *id).collect(),
+            operations,
+            is_final: a_block.is_final,
+        })
+    }
+
+    /// Build the notifications of a set of blocks, sorted by slot
+    fn block_notifications<'a>(
+        &self,
+        block_ids: impl Iterator<Item = &'a BlockId>,
+    ) -> Vec<BlockNotification> {
+        let mut notifications: Vec<BlockNotification> = block_ids
+            .filter_map(|block_id| self.block_notification(block_id))
+            .collect();
+        notifications.sort_unstable_by_key(|notification| notification.slot);
+        notifications
+    }
+
+    /// Notify subscribers of new blockclique heads, new blockclique blocks and new final blocks.
+    /// Notifications are only built for the channels that have at least one receiver.
+    ///
+    /// # Arguments
+    /// * `new_final_block_ids`: blocks that became final since the last call
+    /// * `blockclique`: the current blockclique
+    fn notify_subscribers(
+        &mut self,
+        new_final_block_ids: &PreHashSet<BlockId>,
+        blockclique: PreHashSet<BlockId>,
+    ) {
+        let best_parents: Vec<BlockId> = self
+            .block_db
+            .get_best_parents()
+            .iter()
+            .map(|(block_id, _period)| *block_id)
+            .collect();
+        let broadcasts = &self.channels.broadcasts;
+
+        // sending only fails if all receivers were dropped in the meantime: ignore it
+        if broadcasts.blockclique_head_sender.receiver_count() > 0 {
+            let new_heads = best_parents
+                .iter()
+                .filter(|block_id| !self.best_parents.contains(*block_id));
+            for notification in self.block_notifications(new_heads) {
+                let _ = broadcasts.blockclique_head_sender.send(notification);
+            }
+        }
+        if broadcasts.blockclique_block_sender.receiver_count() > 0 {
+            let new_blocks = blockclique
+                .iter()
+                .filter(|block_id| !self.blockclique.contains(*block_id));
+            for notification in self.block_notifications(new_blocks) {
+                let _ = broadcasts.blockclique_block_sender.send(notification);
+            }
+        }
+        if broadcasts.final_block_sender.receiver_count() > 0 {
+            for notification in self.block_notifications(new_final_block_ids.iter()) {
+                let _ = broadcasts.final_block_sender.send(notification);
+            }
+        }
+
+        self.best_parents = best_parents;
+        self.blockclique = blockclique;
+    }
+
     /// call me if the block database changed
     /// Processing of final blocks, pruning and producing endorsement.
     /// Please refactor me
@@ -553,13 +648,14 @@ impl ConsensusWorker {
     /// 3. get new final blocks
     /// 4. get blockclique
     /// 5. notify Execution
-    /// 6. Process new final blocks
-    /// 7. Notify pool of new final ops
-    /// 8. Notify PoS of final blocks
-    /// 9. notify protocol of block wish list
-    /// 10. note new latest final periods (prune graph if changed)
-    /// 11. Produce endorsements
-    /// 12. add stale blocks to stats
+    /// 6. notify subscribers of the new blockclique and final blocks
+    /// 7. Process new final blocks (archive them if enabled)
+    /// 8. Notify pool of new final ops
+    /// 9. Notify PoS of final blocks
+    /// 10. notify protocol of block wish list
+    /// 11. note new latest final periods (prune graph if changed)
+    /// 12. Produce endorsements
+    /// 13. add stale blocks to stats
     async fn block_db_changed(&mut self) -> Result<()> {
         massa_trace!("consensus.consensus_worker.block_db_changed", {});
 
@@ -602,8 +698,8 @@ impl ConsensusWorker {
             })
             .collect();
         let blockclique = blockclique_set
-            .into_iter()
-            .filter_map(|b_id| match self.block_db.get_active_block(&b_id) {
+            .iter()
+            .filter_map(|b_id| match self.block_db.get_active_block(b_id) {
                 Some((a_b, storage)) => Some((a_b.slot, (a_b.block_id, storage.clone()))),
                 _ => None,
             })
@@ -612,10 +708,27 @@ impl ConsensusWorker {
             .execution_controller
             .update_blockclique_status(final_blocks, blockclique);
 
+        // notify subscribers
+        self.notify_subscribers(&new_final_block_ids, blockclique_set);
+
         // Process new final blocks
         let timestamp = MassaTime::now(self.clock_compensation)?;
         for b_id in new_final_block_ids.into_iter() {
-            if let Some((a_block, _block_store)) = self.block_db.get_active_block(&b_id) {
+            if let Some((a_block, block_store)) = self.block_db.get_active_block(&b_id) {
+                // archive the block along with its operations
+                if let Some(archive) = &self.channels.archive {
+                    if let Some(block) = block_store.read_blocks().get(&b_id) {
+                        let read_ops = block_store.read_operations();
+                        let operations: Vec<WrappedOperation> = block
+                            .content
+                            .operations
+                            .iter()
+                            .filter_map(|op_id| read_ops.get(op_id).cloned())
+                            .collect();
+                        archive.store_final_block(block, &operations);
+                    }
+                }
+
                 // add to stats
                 let block_is_from_protocol = self
                     .protocol_blocks
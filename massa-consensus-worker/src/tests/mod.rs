@@ -20,6 +20,7 @@ mod scenarios_pool_commands;
 mod scenarios_pruning;
 mod scenarios_reward_split;
 mod scenarios_send_block;
+mod scenarios_subscriptions;
 mod scenarios_wishlist;
 mod test_block_graph;
 pub mod tools;
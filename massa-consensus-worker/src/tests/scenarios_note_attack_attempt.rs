@@ -4,7 +4,7 @@ use super::tools::*;
 use crate::start_consensus_controller;
 use massa_pool_exports::test_exports::MockPoolController;
 
-use massa_consensus_exports::settings::ConsensusChannels;
+use massa_consensus_exports::settings::{ConsensusBroadcasts, ConsensusChannels};
 use massa_consensus_exports::ConsensusConfig;
 use massa_execution_exports::test_exports::MockExecutionController;
 use massa_hash::Hash;
@@ -53,6 +53,8 @@ async fn test_invalid_block_notified_as_attack_attempt() {
                 protocol_event_receiver,
                 pool_command_sender: pool_controller,
                 selector_controller,
+                broadcasts: ConsensusBroadcasts::new(cfg.channel_size),
+                archive: None,
             },
             None,
             storage.clone(),
@@ -123,6 +125,8 @@ async fn test_invalid_header_notified_as_attack_attempt() {
                 protocol_event_receiver,
                 pool_command_sender: pool_controller,
                 selector_controller,
+                broadcasts: ConsensusBroadcasts::new(cfg.channel_size),
+                archive: None,
             },
             None,
             storage,
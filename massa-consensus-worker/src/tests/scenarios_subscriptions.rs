@@ -0,0 +1,73 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::tools;
+use crate::tests::block_factory::BlockFactory;
+use massa_consensus_exports::{settings::ConsensusBroadcasts, ConsensusConfig};
+use massa_models::{block::BlockId, slot::Slot};
+use serial_test::serial;
+use std::time::Duration;
+
+#[tokio::test]
+#[serial]
+async fn test_blockclique_head_notifications() {
+    let cfg = ConsensusConfig {
+        t0: 1000.into(),
+        future_block_processing_max_periods: 50,
+        ..ConsensusConfig::default()
+    };
+    let broadcasts = ConsensusBroadcasts::new(cfg.channel_size);
+    let mut head_receiver = broadcasts.blockclique_head_sender.subscribe();
+
+    tools::consensus_without_pool_with_broadcasts_test(
+        cfg.clone(),
+        broadcasts.clone(),
+        async move |protocol_controller,
+                    consensus_command_sender,
+                    consensus_event_receiver,
+                    selector_controller| {
+            let parents: Vec<BlockId> = consensus_command_sender
+                .get_block_graph_status(None, None)
+                .await
+                .expect("could not get block graph status")
+                .best_parents
+                .iter()
+                .map(|(b, _p)| *b)
+                .collect();
+
+            let mut block_factory =
+                BlockFactory::start_block_factory(parents.clone(), protocol_controller);
+            block_factory.creator_keypair = cfg.genesis_key.clone();
+            block_factory.slot = Slot::new(1, 0);
+            let block_1 = block_factory.create_and_receive_block(true).await;
+
+            // the subscriber is notified of the new head of thread 0
+            let notification = tokio::time::timeout(Duration::from_secs(1), head_receiver.recv())
+                .await
+                .expect("no blockclique head notification received")
+                .expect("blockclique head channel closed");
+            assert_eq!(notification.id, block_1.id);
+            assert_eq!(notification.slot, Slot::new(1, 0));
+            assert_eq!(notification.parents, parents);
+            assert!(!notification.is_final);
+
+            // once the subscriber is gone, blocks are still processed
+            drop(head_receiver);
+            assert_eq!(broadcasts.blockclique_head_sender.receiver_count(), 0);
+            block_factory.slot = Slot::new(1, 1);
+            let block_2 = block_factory.create_and_receive_block(true).await;
+            let status = consensus_command_sender
+                .get_block_graph_status(None, None)
+                .await
+                .expect("could not get block graph status");
+            assert_eq!(status.best_parents[1].0, block_2.id);
+
+            (
+                block_factory.take_protocol_controller(),
+                consensus_command_sender,
+                consensus_event_receiver,
+                selector_controller,
+            )
+        },
+    )
+    .await;
+}
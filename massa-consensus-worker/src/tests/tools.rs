@@ -5,7 +5,8 @@ use crate::start_consensus_controller;
 use massa_cipher::decrypt;
 use massa_consensus_exports::error::ConsensusResult;
 use massa_consensus_exports::{
-    settings::ConsensusChannels, ConsensusCommandSender, ConsensusConfig, ConsensusEventReceiver,
+    settings::{ConsensusBroadcasts, ConsensusChannels},
+    ConsensusCommandSender, ConsensusConfig, ConsensusEventReceiver,
 };
 use massa_execution_exports::test_exports::MockExecutionController;
 use massa_graph::{export_active_block::ExportActiveBlock, BlockGraphExport, BootstrapableGraph};
@@ -696,6 +697,8 @@ pub async fn _consensus_pool_test<F, V>(
                 protocol_event_receiver,
                 pool_command_sender: pool_controller.clone(),
                 selector_controller,
+                broadcasts: ConsensusBroadcasts::new(cfg.channel_size),
+                archive: None,
             },
             boot_graph,
             storage.clone(),
@@ -785,6 +788,8 @@ pub async fn consensus_pool_test_with_storage<F, V>(
                 protocol_event_receiver,
                 pool_command_sender: pool_controller.clone(),
                 selector_controller: selector_controller,
+                broadcasts: ConsensusBroadcasts::new(cfg.channel_size),
+                archive: None,
             },
             boot_graph,
             storage.clone(),
@@ -840,6 +845,32 @@ where
             Box<dyn SelectorController>,
         ),
     >,
+{
+    let broadcasts = ConsensusBroadcasts::new(cfg.channel_size);
+    consensus_without_pool_with_broadcasts_test(cfg, broadcasts, test).await
+}
+
+/// Runs a consensus test, without passing a mock pool controller to it,
+/// and notifying block graph changes on the given broadcast channels.
+pub async fn consensus_without_pool_with_broadcasts_test<F, V>(
+    cfg: ConsensusConfig,
+    broadcasts: ConsensusBroadcasts,
+    test: F,
+) where
+    F: FnOnce(
+        MockProtocolController,
+        ConsensusCommandSender,
+        ConsensusEventReceiver,
+        Box<dyn SelectorController>,
+    ) -> V,
+    V: Future<
+        Output = (
+            MockProtocolController,
+            ConsensusCommandSender,
+            ConsensusEventReceiver,
+            Box<dyn SelectorController>,
+        ),
+    >,
 {
     let storage: Storage = Storage::create_root();
     // mock protocol & pool
@@ -878,6 +909,8 @@ where
                 protocol_event_receiver,
                 pool_command_sender: pool_controller,
                 selector_controller: selector_controller.clone(),
+                broadcasts,
+                archive: None,
             },
             None,
             storage.clone(),
@@ -970,6 +1003,8 @@ where
                 protocol_event_receiver,
                 pool_command_sender: pool_controller,
                 selector_controller: selector_controller.clone(),
+                broadcasts: ConsensusBroadcasts::new(cfg.channel_size),
+                archive: None,
             },
             None,
             storage.clone(),
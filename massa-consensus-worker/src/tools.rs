@@ -78,6 +78,8 @@ pub async fn start_consensus_controller(
                 controller_command_rx: command_rx,
                 controller_event_tx: event_tx,
                 controller_manager_rx: manager_rx,
+                broadcasts: channels.broadcasts,
+                archive: channels.archive,
             },
             block_db,
             clock_compensation,
@@ -0,0 +1,24 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module provides the channels through which the execution worker notifies other modules
+
+use massa_models::output_event::SCOutputEvent;
+use tokio::sync::broadcast;
+
+/// Channels used by the execution worker to notify any number of subscribers (for example the API)
+#[derive(Clone)]
+pub struct ExecutionChannels {
+    /// Smart contract output events, sent once when generated by an active slot execution (candidate)
+    /// and once again when they become final.
+    /// Candidate events can be sent several times if their slot is re-executed after a blockclique change.
+    pub sc_output_event_sender: broadcast::Sender<SCOutputEvent>,
+}
+
+impl ExecutionChannels {
+    /// Create the execution channels, each one buffering at most `capacity` notifications
+    pub fn new(capacity: usize) -> Self {
+        ExecutionChannels {
+            sc_output_event_sender: broadcast::channel(capacity).0,
+        }
+    }
+}
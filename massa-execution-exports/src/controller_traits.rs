@@ -2,14 +2,18 @@
 
 //! This module exports generic traits representing interfaces for interacting with the Execution worker
 
-use crate::types::ExecutionOutput;
+use crate::types::ReadOnlyExecutionOutput;
 use crate::types::ReadOnlyExecutionRequest;
 use crate::ExecutionAddressInfo;
 use crate::ExecutionError;
 use massa_models::address::Address;
 use massa_models::amount::Amount;
-use massa_models::api::EventFilter;
+use massa_models::api::{
+    AddressHistoryPage, AddressHistoryRequest, EventFilter, EventPage, EventPageRequest,
+    LedgerEntryAtSlotInput, LedgerEntryAtSlotOutput, LedgerProofInput, LedgerProofOutput,
+};
 use massa_models::block::BlockId;
+use massa_models::execution::{OperationExecutionStatus, OperationTrace};
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
@@ -40,6 +44,57 @@ pub trait ExecutionController: Send + Sync {
     /// * operation id
     fn get_filtered_sc_output_event(&self, filter: EventFilter) -> Vec<SCOutputEvent>;
 
+    /// Get a page of the final execution events stored in the on-disk archive,
+    /// filtered in the same way as `get_filtered_sc_output_event`.
+    ///
+    /// Returns `ExecutionError::EventArchiveDisabled` if the archive is disabled.
+    fn get_archived_sc_output_events(
+        &self,
+        request: EventPageRequest,
+    ) -> Result<EventPage, ExecutionError>;
+
+    /// Get a page of the final history of an address stored in the on-disk index:
+    /// executed operations sent or received, roll buys and sells, and incoming async messages.
+    ///
+    /// Returns `ExecutionError::AddressHistoryDisabled` if the index is disabled.
+    fn get_address_history(
+        &self,
+        request: AddressHistoryRequest,
+    ) -> Result<AddressHistoryPage, ExecutionError>;
+
+    /// Get the execution traces of operations executed in final or candidate slots.
+    /// Operations that were not executed, or whose trace is no longer cached, are left out.
+    ///
+    /// Returns `ExecutionError::ExecutionTracingDisabled` if tracing is disabled.
+    fn get_operation_traces(
+        &self,
+        operation_ids: &[OperationId],
+    ) -> Result<Vec<OperationTrace>, ExecutionError>;
+
+    /// Get the execution status of operations executed in final or candidate slots.
+    /// The status is `None` for operations that were not executed, or whose execution is no longer remembered.
+    fn get_operation_execution_statuses(
+        &self,
+        operation_ids: &[OperationId],
+    ) -> Vec<Option<OperationExecutionStatus>>;
+
+    /// Get final ledger entries as they were after the execution of past final slots.
+    ///
+    /// Returns `ExecutionError::LedgerHistoryError` if the ledger history is disabled
+    /// or does not retain one of the requested slots.
+    fn get_final_ledger_entries_at_slot(
+        &self,
+        inputs: Vec<LedgerEntryAtSlotInput>,
+    ) -> Result<Vec<LedgerEntryAtSlotOutput>, ExecutionError>;
+
+    /// Get final ledger values along with their Merkle proof against the root of the final ledger.
+    /// Inputs without slot are proven at the current final slot, and inputs asking for a past final slot
+    /// are proven against the root at that slot if the ledger history retains it.
+    fn get_final_ledger_proofs(
+        &self,
+        inputs: Vec<LedgerProofInput>,
+    ) -> Result<Vec<LedgerProofOutput>, ExecutionError>;
+
     /// Get the final and active values of sequential balances.
     ///
     /// # Return value
@@ -71,12 +126,12 @@ pub trait ExecutionController: Send + Sync {
     /// * `req`: an instance of `ReadOnlyCallRequest` describing the parameters of the execution
     ///
     /// # returns
-    /// An instance of `ExecutionOutput` containing a summary of the effects of the execution,
-    /// or an error if the execution failed.
+    /// An instance of `ReadOnlyExecutionOutput` containing a summary of the effects of the execution
+    /// and the gas it consumed, or an error if the execution failed.
     fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
-    ) -> Result<ExecutionOutput, ExecutionError>;
+    ) -> Result<ReadOnlyExecutionOutput, ExecutionError>;
 
     /// List which operations inside the provided list were not executed
     fn unexecuted_ops_among(
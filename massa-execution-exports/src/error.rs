@@ -3,6 +3,8 @@
 //! this file defines all possible execution error categories
 
 use displaydoc::Display;
+use massa_models::execution::OperationExecutionStatus;
+use massa_models::slot::Slot;
 use thiserror::Error;
 
 /// Errors of the execution component.
@@ -30,6 +32,15 @@ pub enum ExecutionError {
     /// Transaction error: {0}
     TransactionError(String),
 
+    /// Insufficient balance: {0}
+    InsufficientBalance(String),
+
+    /// Out of gas: {0}
+    OutOfGas(String),
+
+    /// VM error: {0}
+    VMError(String),
+
     /// Block gas error: {0}
     BlockGasError(String),
 
@@ -41,4 +52,31 @@ pub enum ExecutionError {
 
     /// Inlcude operation error: {0}
     InlcudeOperationError(String),
+
+    /// The final event archive is disabled on this node
+    EventArchiveDisabled,
+
+    /// The address history index is disabled on this node
+    AddressHistoryDisabled,
+
+    /// Execution tracing is disabled on this node
+    ExecutionTracingDisabled,
+
+    /// Ledger history error: {0}
+    LedgerHistoryError(String),
+
+    /// Ledger proofs are not available at slot {0}, which is not retained in the ledger history
+    LedgerProofSlotUnavailable(Slot),
+}
+
+impl ExecutionError {
+    /// Get the status to record for an operation whose execution failed with this error
+    pub fn to_operation_status(&self) -> OperationExecutionStatus {
+        match self {
+            ExecutionError::InsufficientBalance(_) => OperationExecutionStatus::InsufficientBalance,
+            ExecutionError::OutOfGas(_) => OperationExecutionStatus::OutOfGas,
+            ExecutionError::VMError(message) => OperationExecutionStatus::vm_error(message.clone()),
+            err => OperationExecutionStatus::failed(err.to_string()),
+        }
+    }
 }
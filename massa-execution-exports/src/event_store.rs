@@ -22,6 +22,11 @@ impl EventStore {
         std::mem::take(&mut self.0)
     }
 
+    /// Iterate over the events of the store, from the oldest to the most recent
+    pub fn iter(&self) -> impl Iterator<Item = &SCOutputEvent> {
+        self.0.iter()
+    }
+
     /// Clear the event store
     pub fn clear(&mut self) {
         self.0.clear()
@@ -56,39 +61,7 @@ impl EventStore {
     pub fn get_filtered_sc_output_events(&self, filter: &EventFilter) -> VecDeque<SCOutputEvent> {
         self.0
             .iter()
-            .filter(|x| {
-                if let Some(start) = filter.start {
-                    if x.context.slot < start {
-                        return false;
-                    }
-                }
-                if let Some(end) = filter.end {
-                    if x.context.slot >= end {
-                        return false;
-                    }
-                }
-                if let Some(is_final) = filter.is_final {
-                    if x.context.is_final != is_final {
-                        return false;
-                    }
-                }
-                match (filter.emitter_address, x.context.call_stack.front()) {
-                    (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
-                    (Some(_), None) => return false,
-                    _ => (),
-                }
-                match (filter.original_caller_address, x.context.call_stack.back()) {
-                    (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
-                    (Some(_), None) => return false,
-                    _ => (),
-                }
-                match (filter.original_operation_id, x.context.origin_operation_id) {
-                    (Some(addr1), Some(addr2)) if addr1 != addr2 => return false,
-                    (Some(_), None) => return false,
-                    _ => (),
-                }
-                true
-            })
+            .filter(|x| filter.matches(x))
             .cloned()
             .collect()
     }
@@ -22,6 +22,9 @@
 //!
 //! # Architecture
 //!
+//! ## `channels.rs`
+//! Defines the broadcast channels through which the execution worker notifies subscribers.
+//!
 //! ## `config.rs`
 //! Contains configuration parameters for the execution system.
 //!
@@ -44,19 +47,21 @@
 
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
+mod channels;
 mod controller_traits;
 mod error;
 mod event_store;
 mod settings;
 mod types;
 
+pub use channels::ExecutionChannels;
 pub use controller_traits::{ExecutionController, ExecutionManager};
 pub use error::ExecutionError;
 pub use event_store::EventStore;
 pub use settings::ExecutionConfig;
 pub use types::{
     ExecutionAddressInfo, ExecutionOutput, ExecutionStackElement, ReadOnlyCallRequest,
-    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
 };
 
 #[cfg(feature = "testing")]
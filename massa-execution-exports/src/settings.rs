@@ -5,6 +5,7 @@
 use massa_models::amount::Amount;
 use massa_time::MassaTime;
 use num::rational::Ratio;
+use std::path::PathBuf;
 
 /// Execution module configuration
 #[derive(Debug, Clone)]
@@ -13,6 +14,16 @@ pub struct ExecutionConfig {
     pub readonly_queue_length: usize,
     /// maximum number of SC output events kept in cache
     pub max_final_events: usize,
+    /// path to the on-disk archive of final SC output events, disabled if `None`
+    pub event_archive_path: Option<PathBuf>,
+    /// path to the on-disk index of the final history of each address, disabled if `None`
+    pub address_history_path: Option<PathBuf>,
+    /// record the interface calls made by the smart contracts executed by each operation
+    pub trace_execution: bool,
+    /// maximum number of final operation traces kept in cache
+    pub max_final_traces: usize,
+    /// maximum number of interface calls recorded in the trace of an operation
+    pub max_trace_entries: usize,
     /// maximum available gas for asynchronous messages execution
     pub max_async_gas: u64,
     /// maximum gas per block
@@ -12,6 +12,11 @@ impl Default for ExecutionConfig {
         Self {
             readonly_queue_length: 100,
             max_final_events: 1000,
+            event_archive_path: None,
+            address_history_path: None,
+            trace_execution: false,
+            max_final_traces: 1000,
+            max_trace_entries: 1000,
             max_async_gas: MAX_ASYNC_GAS,
             thread_count: THREAD_COUNT,
             roll_price: ROLL_PRICE,
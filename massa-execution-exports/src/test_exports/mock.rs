@@ -3,13 +3,25 @@
 //! This file defines utilities to mock the crate for testing purposes
 
 use crate::{
-    ExecutionAddressInfo, ExecutionController, ExecutionError, ExecutionOutput,
+    ExecutionAddressInfo, ExecutionController, ExecutionError, ReadOnlyExecutionOutput,
     ReadOnlyExecutionRequest,
 };
 use massa_ledger_exports::LedgerEntry;
 use massa_models::{
-    address::Address, amount::Amount, api::EventFilter, block::BlockId, operation::OperationId,
-    output_event::SCOutputEvent, prehash::PreHashSet, slot::Slot, stats::ExecutionStats,
+    address::Address,
+    amount::Amount,
+    api::{
+        AddressHistoryPage, AddressHistoryRequest, EventFilter, EventPage, EventPageRequest,
+        LedgerEntryAtSlotInput, LedgerEntryAtSlotOutput, LedgerProofInput, LedgerProofOutput,
+    },
+    block::BlockId,
+    execution::{OperationExecutionStatus, OperationTrace},
+    merkle::{merkle_empty_root, MerkleProof},
+    operation::OperationId,
+    output_event::SCOutputEvent,
+    prehash::PreHashSet,
+    slot::Slot,
+    stats::ExecutionStats,
 };
 use massa_storage::Storage;
 use massa_time::MassaTime;
@@ -56,7 +68,7 @@ pub enum MockExecutionControllerMessage {
         /// read only execution request
         req: ReadOnlyExecutionRequest,
         /// response channel
-        response_tx: mpsc::Sender<Result<ExecutionOutput, ExecutionError>>,
+        response_tx: mpsc::Sender<Result<ReadOnlyExecutionOutput, ExecutionError>>,
     },
     /// Unexecuted operation among call
     UnexecutedOpsAmong {
@@ -141,6 +153,75 @@ impl ExecutionController for MockExecutionController {
         response_rx.recv().unwrap()
     }
 
+    fn get_archived_sc_output_events(
+        &self,
+        _request: EventPageRequest,
+    ) -> Result<EventPage, ExecutionError> {
+        Ok(EventPage {
+            events: Vec::new(),
+            next_cursor: None,
+        })
+    }
+
+    fn get_final_ledger_entries_at_slot(
+        &self,
+        inputs: Vec<LedgerEntryAtSlotInput>,
+    ) -> Result<Vec<LedgerEntryAtSlotOutput>, ExecutionError> {
+        Ok(inputs
+            .into_iter()
+            .map(|input| LedgerEntryAtSlotOutput {
+                address: input.address,
+                slot: input.slot,
+                sequential_balance: None,
+                parallel_balance: None,
+                bytecode: None,
+                datastore_values: vec![None; input.datastore_keys.len()],
+            })
+            .collect())
+    }
+
+    fn get_final_ledger_proofs(
+        &self,
+        inputs: Vec<LedgerProofInput>,
+    ) -> Result<Vec<LedgerProofOutput>, ExecutionError> {
+        Ok(inputs
+            .into_iter()
+            .map(|input| LedgerProofOutput {
+                address: input.address,
+                key: input.key,
+                slot: Slot::new(0, 0),
+                root: merkle_empty_root(),
+                ledger_key: Vec::new(),
+                value: None,
+                proof: MerkleProof::default(),
+            })
+            .collect())
+    }
+
+    fn get_address_history(
+        &self,
+        _request: AddressHistoryRequest,
+    ) -> Result<AddressHistoryPage, ExecutionError> {
+        Ok(AddressHistoryPage {
+            entries: Vec::new(),
+            next_cursor: None,
+        })
+    }
+
+    fn get_operation_traces(
+        &self,
+        _operation_ids: &[OperationId],
+    ) -> Result<Vec<OperationTrace>, ExecutionError> {
+        Ok(Vec::new())
+    }
+
+    fn get_operation_execution_statuses(
+        &self,
+        operation_ids: &[OperationId],
+    ) -> Vec<Option<OperationExecutionStatus>> {
+        vec![None; operation_ids.len()]
+    }
+
     fn get_final_and_candidate_sequential_balances(
         &self,
         addresses: &[Address],
@@ -177,7 +258,7 @@ impl ExecutionController for MockExecutionController {
     fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
-    ) -> Result<ExecutionOutput, ExecutionError> {
+    ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
             .lock()
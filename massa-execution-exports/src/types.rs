@@ -5,8 +5,8 @@
 use crate::event_store::EventStore;
 use massa_final_state::StateChanges;
 use massa_models::{
-    address::Address, address::ExecutionAddressCycleInfo, amount::Amount, block::BlockId,
-    slot::Slot,
+    address::Address, address::ExecutionAddressCycleInfo, amount::Amount, api::AddressHistoryItem,
+    block::BlockId, execution::OperationTrace, operation::WrappedOperation, slot::Slot,
 };
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -49,6 +49,19 @@ pub struct ExecutionOutput {
     pub state_changes: StateChanges,
     /// events emitted by the execution step
     pub events: EventStore,
+    /// entries of the history of the involved addresses, in execution order
+    pub address_history: Vec<(Address, AddressHistoryItem)>,
+    /// traces of the operations executed by the execution step, if tracing is enabled
+    pub traces: Vec<OperationTrace>,
+}
+
+/// structure describing the output of a read-only execution
+#[derive(Debug, Clone)]
+pub struct ReadOnlyExecutionOutput {
+    /// output of the execution
+    pub out: ExecutionOutput,
+    /// gas consumed by the execution
+    pub gas_cost: u64,
 }
 
 /// structure describing different types of read-only execution request
@@ -79,6 +92,11 @@ pub enum ReadOnlyExecutionTarget {
         /// Parameter to pass to the target function
         parameter: String,
     },
+
+    /// Execute signed operations in order, as if they were included in a block.
+    /// The operations must belong to the same thread.
+    /// `max_gas` is the gas available to the whole block.
+    Operations(Vec<WrappedOperation>),
 }
 
 /// structure describing a read-only call
@@ -0,0 +1,198 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module provides an on-disk index of the final history of each address:
+//! executed operations sent or received, roll buys and sells, and received asynchronous messages.
+//!
+//! Entries are stored in the `history` column family under a key made of the address
+//! followed by the position of the entry (slot, index in slot),
+//! so that the history of an address is a contiguous range sorted by position.
+
+use massa_models::address::Address;
+use massa_models::api::{
+    AddressHistoryCursor, AddressHistoryEntry, AddressHistoryItem, AddressHistoryPage,
+    AddressHistoryRequest,
+};
+use massa_models::slot::{Slot, SLOT_KEY_SIZE};
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use std::path::PathBuf;
+use tracing::warn;
+
+const HISTORY_CF: &str = "history";
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+
+/// Size of the key identifying the position of an entry: slot key followed by the index in slot
+const CURSOR_KEY_SIZE: usize = SLOT_KEY_SIZE + 8;
+
+/// Build the key of the entry of an address at the given position
+fn entry_key(address: &Address, cursor: &AddressHistoryCursor) -> Vec<u8> {
+    let mut key = address.to_bytes().to_vec();
+    key.extend(cursor.slot.to_bytes_key());
+    key.extend(cursor.index_in_slot.to_be_bytes());
+    key
+}
+
+/// Read the position of an entry from the end of a key
+fn cursor_from_key(key: &[u8]) -> AddressHistoryCursor {
+    let cursor_key = &key[key.len() - CURSOR_KEY_SIZE..];
+    AddressHistoryCursor {
+        slot: Slot::from_bytes_key(cursor_key[..SLOT_KEY_SIZE].try_into().unwrap()),
+        index_in_slot: u64::from_be_bytes(cursor_key[SLOT_KEY_SIZE..].try_into().unwrap()),
+    }
+}
+
+/// On-disk index of the final history of each address
+pub(crate) struct AddressHistory {
+    db: DB,
+}
+
+impl AddressHistory {
+    /// Open the index, creating it if missing
+    ///
+    /// # Arguments
+    /// * `path`: path to the index directory
+    pub fn new(path: PathBuf) -> Self {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+
+        let db = DB::open_cf_descriptors(
+            &db_opts,
+            path,
+            vec![ColumnFamilyDescriptor::new(HISTORY_CF, Options::default())],
+        )
+        .expect(OPEN_ERROR);
+
+        AddressHistory { db }
+    }
+
+    /// Index the entries recorded during the final execution of a slot
+    ///
+    /// # Arguments
+    /// * `slot`: the executed slot
+    /// * `entries`: the entries of the involved addresses, in execution order
+    pub fn store(&self, slot: Slot, entries: &[(Address, AddressHistoryItem)]) {
+        let handle = self.db.cf_handle(HISTORY_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        for (index_in_slot, (address, item)) in entries.iter().enumerate() {
+            let cursor = AddressHistoryCursor {
+                slot,
+                index_in_slot: index_in_slot as u64,
+            };
+            let value = match serde_json::to_vec(item) {
+                Ok(value) => value,
+                Err(err) => {
+                    warn!("could not serialize address history entry: {}", err);
+                    continue;
+                }
+            };
+            batch.put_cf(handle, entry_key(address, &cursor), value);
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get a page of the history of an address, sorted by position
+    pub fn get_page(&self, request: &AddressHistoryRequest) -> AddressHistoryPage {
+        let handle = self.db.cf_handle(HISTORY_CF).expect(CF_ERROR);
+        let prefix = request.address.to_bytes();
+        let from = match &request.after {
+            Some(after) => entry_key(&request.address, after),
+            None => prefix.to_vec(),
+        };
+
+        let limit = request.limit as usize;
+        let mut entries: Vec<AddressHistoryEntry> = Vec::new();
+        let mut next_cursor = None;
+        for (key, value) in self
+            .db
+            .iterator_cf(handle, IteratorMode::From(&from, Direction::Forward))
+            .flatten()
+        {
+            if !key.starts_with(prefix) {
+                break;
+            }
+            let cursor = cursor_from_key(&key);
+            if request.after == Some(cursor) {
+                continue;
+            }
+            if entries.len() >= limit {
+                // there is at least one more entry to read
+                next_cursor = entries.last().map(|entry| entry.cursor);
+                break;
+            }
+            match serde_json::from_slice(&value) {
+                Ok(item) => entries.push(AddressHistoryEntry { cursor, item }),
+                Err(err) => warn!(
+                    "could not read address history entry at {:?}: {}",
+                    cursor, err
+                ),
+            }
+        }
+        AddressHistoryPage {
+            entries,
+            next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::amount::Amount;
+    use massa_models::operation::OperationId;
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_address_history_pages() {
+        let sender = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let recipient = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let temp_dir = TempDir::new().unwrap();
+        let history = AddressHistory::new(temp_dir.path().to_path_buf());
+        for period in 1..4 {
+            let operation_id = OperationId::from_bytes(&[period as u8; 32]);
+            history.store(
+                Slot::new(period, 0),
+                &[
+                    (
+                        sender,
+                        AddressHistoryItem::OperationSent {
+                            operation_id,
+                            success: true,
+                        },
+                    ),
+                    (
+                        recipient,
+                        AddressHistoryItem::TransactionReceived {
+                            operation_id,
+                            sender,
+                            amount: Amount::from_str("1").unwrap(),
+                        },
+                    ),
+                ],
+            );
+        }
+
+        // paginate over the history of the recipient
+        let mut request = AddressHistoryRequest {
+            address: recipient,
+            after: None,
+            limit: 2,
+        };
+        let page = history.get_page(&request);
+        assert_eq!(page.entries.len(), 2);
+        assert!(page
+            .entries
+            .iter()
+            .all(|entry| matches!(entry.item, AddressHistoryItem::TransactionReceived { .. })));
+        assert_eq!(page.entries[0].cursor.slot, Slot::new(1, 0));
+        assert_eq!(page.entries[0].cursor.index_in_slot, 1);
+        request.after = page.next_cursor;
+        let page = history.get_page(&request);
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].cursor.slot, Slot::new(3, 0));
+        assert!(page.next_cursor.is_none());
+    }
+}
@@ -21,7 +21,9 @@ use massa_models::address::ExecutionAddressCycleInfo;
 use massa_models::{
     address::Address,
     amount::Amount,
+    api::AddressHistoryItem,
     block::BlockId,
+    execution::{InterfaceCall, OperationExecutionStatus, OperationTrace, TraceEntry},
     operation::OperationId,
     output_event::{EventExecutionContext, SCOutputEvent},
     slot::Slot,
@@ -122,6 +124,18 @@ pub(crate) struct ExecutionContext {
 
     /// operation id that originally caused this execution (if any)
     pub origin_operation_id: Option<OperationId>,
+
+    /// entries of the history of the involved addresses, in execution order
+    address_history: Vec<(Address, AddressHistoryItem)>,
+
+    /// interface calls made on behalf of the operation being executed, if it is traced
+    trace: Option<Vec<TraceEntry>>,
+
+    /// true if calls were dropped from the trace of the operation being executed
+    trace_truncated: bool,
+
+    /// traces of the operations executed so far in the slot
+    traces: Vec<OperationTrace>,
 }
 
 impl ExecutionContext {
@@ -167,6 +181,10 @@ impl ExecutionContext {
             events: Default::default(),
             unsafe_rng: Xoshiro256PlusPlus::from_seed([0u8; 32]),
             origin_operation_id: Default::default(),
+            address_history: Default::default(),
+            trace: Default::default(),
+            trace_truncated: false,
+            traces: Default::default(),
             config,
         }
     }
@@ -733,6 +751,65 @@ impl ExecutionContext {
             block_id: std::mem::take(&mut self.opt_block_id),
             state_changes,
             events: std::mem::take(&mut self.events),
+            address_history: std::mem::take(&mut self.address_history),
+            traces: std::mem::take(&mut self.traces),
+        }
+    }
+
+    /// Records an entry in the history of an address.
+    /// Entries are only kept if the address history index is enabled.
+    pub fn record_address_history(&mut self, address: Address, item: AddressHistoryItem) {
+        if self.config.address_history_path.is_some() && !self.read_only {
+            self.address_history.push((address, item));
+        }
+    }
+
+    /// Starts recording the interface calls made on behalf of an operation.
+    /// Calls are only recorded if execution tracing is enabled.
+    pub fn start_operation_trace(&mut self) {
+        if self.config.trace_execution && !self.read_only {
+            self.trace = Some(Vec::new());
+            self.trace_truncated = false;
+        }
+    }
+
+    /// Records an interface call in the trace of the operation being executed, if it is traced.
+    /// The call is built lazily so that nothing is cloned when tracing is disabled.
+    /// Calls are dropped once the trace holds `max_trace_entries` entries.
+    pub fn trace_call(&mut self, call: impl FnOnce() -> InterfaceCall) {
+        if let (Some(trace), Some(element)) = (&mut self.trace, self.stack.last()) {
+            if trace.len() >= self.config.max_trace_entries {
+                self.trace_truncated = true;
+                return;
+            }
+            trace.push(TraceEntry {
+                depth: self.stack.len(),
+                address: element.address,
+                call: call(),
+            });
+        }
+    }
+
+    /// Stops recording the trace of an operation and adds it to the traces of the slot
+    ///
+    /// # Arguments
+    /// * `operation_id`: the traced operation
+    /// * `result`: the result of the operation execution, with the gas it used
+    pub fn finish_operation_trace(
+        &mut self,
+        operation_id: OperationId,
+        result: &Result<Option<u64>, ExecutionError>,
+    ) {
+        if let Some(calls) = self.trace.take() {
+            self.traces.push(OperationTrace {
+                operation_id,
+                slot: self.slot,
+                is_final: false,
+                gas_used: result.as_ref().ok().copied().flatten(),
+                error: result.as_ref().err().map(|err| err.to_string()),
+                calls,
+                truncated: self.trace_truncated,
+            });
         }
     }
 
@@ -809,6 +886,20 @@ impl ExecutionContext {
             .insert_executed_op(op_id, op_valid_until_slot)
     }
 
+    /// Set the execution status of an operation inserted with `insert_executed_op`
+    ///
+    /// # Arguments
+    /// * `op_id`: operation ID
+    /// * `status`: outcome of the execution of the operation
+    pub fn set_executed_op_status(
+        &mut self,
+        op_id: &OperationId,
+        status: OperationExecutionStatus,
+    ) {
+        self.speculative_executed_ops
+            .set_executed_op_status(op_id, status)
+    }
+
     /// gets the cycle infos for an address
     pub fn get_address_cycle_infos(
         &self,
@@ -7,9 +7,13 @@ use crate::execution::ExecutionState;
 use crate::request_queue::{RequestQueue, RequestWithResponseSender};
 use massa_execution_exports::{
     ExecutionAddressInfo, ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
-    ExecutionOutput, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
 };
-use massa_models::api::EventFilter;
+use massa_models::api::{
+    AddressHistoryPage, AddressHistoryRequest, EventFilter, EventPage, EventPageRequest,
+    LedgerEntryAtSlotInput, LedgerEntryAtSlotOutput, LedgerProofInput, LedgerProofOutput,
+};
+use massa_models::execution::{OperationExecutionStatus, OperationTrace};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::stats::ExecutionStats;
@@ -31,7 +35,7 @@ pub(crate) struct ExecutionInputData {
     /// new blockclique (if there is a new one), blocks indexed by slot
     pub new_blockclique: Option<HashMap<Slot, (BlockId, Storage)>>,
     /// queue for read-only execution requests and response MPSCs to send back their outputs
-    pub readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ExecutionOutput>,
+    pub readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
 }
 
 impl Display for ExecutionInputData {
@@ -116,6 +120,65 @@ impl ExecutionController for ExecutionControllerImpl {
             .get_filtered_sc_output_event(filter)
     }
 
+    /// Get a page of the final execution events stored in the on-disk archive
+    fn get_archived_sc_output_events(
+        &self,
+        request: EventPageRequest,
+    ) -> Result<EventPage, ExecutionError> {
+        // release the execution state lock before iterating over the archive
+        let final_event_archive = self.execution_state.read().get_final_event_archive()?;
+        Ok(final_event_archive.get_page(&request))
+    }
+
+    /// Get a page of the final history of an address stored in the on-disk index
+    fn get_address_history(
+        &self,
+        request: AddressHistoryRequest,
+    ) -> Result<AddressHistoryPage, ExecutionError> {
+        let final_address_history = self.execution_state.read().get_final_address_history()?;
+        Ok(final_address_history.get_page(&request))
+    }
+
+    /// Get the execution traces of operations executed in final or candidate slots
+    fn get_operation_traces(
+        &self,
+        operation_ids: &[OperationId],
+    ) -> Result<Vec<OperationTrace>, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_operation_traces(operation_ids)
+    }
+
+    /// Get the execution status of operations executed in final or candidate slots
+    fn get_operation_execution_statuses(
+        &self,
+        operation_ids: &[OperationId],
+    ) -> Vec<Option<OperationExecutionStatus>> {
+        self.execution_state
+            .read()
+            .get_operation_execution_statuses(operation_ids)
+    }
+
+    /// Get final ledger entries as they were after the execution of past final slots
+    fn get_final_ledger_entries_at_slot(
+        &self,
+        inputs: Vec<LedgerEntryAtSlotInput>,
+    ) -> Result<Vec<LedgerEntryAtSlotOutput>, ExecutionError> {
+        let lock = self.execution_state.read();
+        inputs
+            .iter()
+            .map(|input| lock.get_final_ledger_entry_at_slot(input))
+            .collect()
+    }
+
+    /// Get final ledger values along with their Merkle proof against the root of the final ledger
+    fn get_final_ledger_proofs(
+        &self,
+        inputs: Vec<LedgerProofInput>,
+    ) -> Result<Vec<LedgerProofOutput>, ExecutionError> {
+        self.execution_state.read().get_final_ledger_proofs(&inputs)
+    }
+
     /// Get a copy of a single datastore entry with its final and active values
     ///
     /// # Return value
@@ -158,7 +221,7 @@ impl ExecutionController for ExecutionControllerImpl {
     fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
-    ) -> Result<ExecutionOutput, ExecutionError> {
+    ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
         let resp_rx = {
             let mut input_data = self.input_data.1.lock();
 
@@ -171,7 +234,7 @@ impl ExecutionController for ExecutionControllerImpl {
 
             // prepare the channel to send back the result of the read-only execution
             let (resp_tx, resp_rx) =
-                std::sync::mpsc::channel::<Result<ExecutionOutput, ExecutionError>>();
+                std::sync::mpsc::channel::<Result<ReadOnlyExecutionOutput, ExecutionError>>();
 
             // append the request to the queue of input read-only requests
             input_data
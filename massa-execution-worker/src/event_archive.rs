@@ -0,0 +1,360 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module provides an on-disk archive of final SC output events,
+//! indexed by slot, emitter address, original caller address and origin operation id.
+//!
+//! Events are stored in the `events` column family under their position (slot, index in slot).
+//! Each index is a column family whose keys are the indexed value followed by the position of the event.
+
+use massa_execution_exports::EventStore;
+use massa_models::api::{EventCursor, EventPage, EventPageRequest};
+use massa_models::output_event::SCOutputEvent;
+use massa_models::slot::{Slot, SLOT_KEY_SIZE};
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use std::path::PathBuf;
+use tracing::warn;
+
+const EVENTS_CF: &str = "events";
+const EMITTER_CF: &str = "emitter";
+const CALLER_CF: &str = "caller";
+const OPERATION_CF: &str = "operation";
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+
+/// Size of the key identifying an event: slot key followed by the index in slot
+const EVENT_KEY_SIZE: usize = SLOT_KEY_SIZE + 8;
+
+/// Build the key identifying the event at the given position
+fn event_key(cursor: &EventCursor) -> [u8; EVENT_KEY_SIZE] {
+    let mut key = [0u8; EVENT_KEY_SIZE];
+    key[..SLOT_KEY_SIZE].copy_from_slice(&cursor.slot.to_bytes_key());
+    key[SLOT_KEY_SIZE..].copy_from_slice(&cursor.index_in_slot.to_be_bytes());
+    key
+}
+
+/// Read the position of an event from the end of a key
+fn cursor_from_key(key: &[u8]) -> EventCursor {
+    let event_key = &key[key.len() - EVENT_KEY_SIZE..];
+    EventCursor {
+        slot: Slot::from_bytes_key(event_key[..SLOT_KEY_SIZE].try_into().unwrap()),
+        index_in_slot: u64::from_be_bytes(event_key[SLOT_KEY_SIZE..].try_into().unwrap()),
+    }
+}
+
+/// Position of an event
+fn event_cursor(event: &SCOutputEvent) -> EventCursor {
+    EventCursor {
+        slot: event.context.slot,
+        index_in_slot: event.context.index_in_slot,
+    }
+}
+
+/// On-disk archive of final SC output events
+pub(crate) struct EventArchive {
+    db: DB,
+    /// maximum number of keys read from the scanned index to answer a request
+    max_scanned_keys: u64,
+}
+
+impl EventArchive {
+    /// Open the archive, creating it if missing
+    ///
+    /// # Arguments
+    /// * `path`: path to the archive directory
+    /// * `max_scanned_keys`: maximum number of keys read from the scanned index to answer a request
+    pub fn new(path: PathBuf, max_scanned_keys: u64) -> Self {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+
+        let db = DB::open_cf_descriptors(
+            &db_opts,
+            path,
+            vec![
+                ColumnFamilyDescriptor::new(EVENTS_CF, Options::default()),
+                ColumnFamilyDescriptor::new(EMITTER_CF, Options::default()),
+                ColumnFamilyDescriptor::new(CALLER_CF, Options::default()),
+                ColumnFamilyDescriptor::new(OPERATION_CF, Options::default()),
+            ],
+        )
+        .expect(OPEN_ERROR);
+
+        EventArchive {
+            db,
+            max_scanned_keys,
+        }
+    }
+
+    /// Archive final events along with their indexes
+    pub fn store(&self, events: &EventStore) {
+        let events_handle = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+        let emitter_handle = self.db.cf_handle(EMITTER_CF).expect(CF_ERROR);
+        let caller_handle = self.db.cf_handle(CALLER_CF).expect(CF_ERROR);
+        let operation_handle = self.db.cf_handle(OPERATION_CF).expect(CF_ERROR);
+
+        let mut batch = WriteBatch::default();
+        for event in events.iter() {
+            let key = event_key(&event_cursor(event));
+            let value = match serde_json::to_vec(event) {
+                Ok(value) => value,
+                Err(err) => {
+                    warn!("could not serialize event for archiving: {}", err);
+                    continue;
+                }
+            };
+            batch.put_cf(events_handle, key, value);
+            if let Some(emitter) = event.context.call_stack.front() {
+                batch.put_cf(
+                    emitter_handle,
+                    [&emitter.to_bytes()[..], &key[..]].concat(),
+                    b"",
+                );
+            }
+            if let Some(caller) = event.context.call_stack.back() {
+                batch.put_cf(
+                    caller_handle,
+                    [&caller.to_bytes()[..], &key[..]].concat(),
+                    b"",
+                );
+            }
+            if let Some(op_id) = event.context.origin_operation_id {
+                batch.put_cf(
+                    operation_handle,
+                    [&op_id.to_bytes()[..], &key[..]].concat(),
+                    b"",
+                );
+            }
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get a page of archived events matching a filter, sorted by position.
+    ///
+    /// The most selective index available in the filter is scanned
+    /// (operation id, then emitter, then original caller, then slot),
+    /// and the remaining criteria are checked on each event.
+    /// At most `max_scanned_keys` keys are read: if the scan stops there,
+    /// the page may hold less events than requested and the cursor points to the last key read.
+    pub fn get_page(&self, request: &EventPageRequest) -> EventPage {
+        let filter = &request.filter;
+        let (cf, prefix): (&str, Vec<u8>) = if let Some(op_id) = filter.original_operation_id {
+            (OPERATION_CF, op_id.to_bytes().to_vec())
+        } else if let Some(emitter) = filter.emitter_address {
+            (EMITTER_CF, emitter.to_bytes().to_vec())
+        } else if let Some(caller) = filter.original_caller_address {
+            (CALLER_CF, caller.to_bytes().to_vec())
+        } else {
+            (EVENTS_CF, Vec::new())
+        };
+
+        // start after the cursor (excluded), or at the start slot (included)
+        let start = match (request.after, filter.start) {
+            (Some(after), _) => Some(event_key(&after).to_vec()),
+            (None, Some(start)) => Some(
+                event_key(&EventCursor {
+                    slot: start,
+                    index_in_slot: 0,
+                })
+                .to_vec(),
+            ),
+            (None, None) => None,
+        };
+        let from = [&prefix[..], start.as_deref().unwrap_or_default()].concat();
+
+        let events_handle = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+        let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+        let limit = request.limit as usize;
+        let mut events = Vec::new();
+        let mut next_cursor = None;
+        let mut last_scanned = None;
+        let mut scanned_keys: u64 = 0;
+        for (key, value) in self
+            .db
+            .iterator_cf(handle, IteratorMode::From(&from, Direction::Forward))
+            .flatten()
+        {
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let cursor = cursor_from_key(&key);
+            if request.after == Some(cursor) {
+                continue;
+            }
+            if let Some(end) = filter.end {
+                if cursor.slot >= end {
+                    break;
+                }
+            }
+            if scanned_keys >= self.max_scanned_keys {
+                // resume after the last key read, even if it did not match the filter
+                next_cursor = last_scanned;
+                break;
+            }
+            scanned_keys += 1;
+            last_scanned = Some(cursor);
+            let event_bytes = if cf == EVENTS_CF {
+                Some(value.to_vec())
+            } else {
+                self.db
+                    .get_cf(events_handle, event_key(&cursor))
+                    .expect(CRUD_ERROR)
+            };
+            let event: SCOutputEvent = match event_bytes.map(|bytes| serde_json::from_slice(&bytes))
+            {
+                Some(Ok(event)) => event,
+                _ => {
+                    warn!("could not read archived event at {:?}", cursor);
+                    continue;
+                }
+            };
+            if !filter.matches(&event) {
+                continue;
+            }
+            if events.len() >= limit {
+                // there is at least one more matching event to read
+                next_cursor = events.last().map(event_cursor);
+                break;
+            }
+            events.push(event);
+        }
+        EventPage {
+            events,
+            next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::address::Address;
+    use massa_models::api::EventFilter;
+    use massa_models::output_event::EventExecutionContext;
+    use massa_signature::KeyPair;
+    use std::collections::VecDeque;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_event_archive_pages() {
+        let emitter = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let other = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let mut store = EventStore::default();
+        for i in 0..10 {
+            store.push(SCOutputEvent {
+                context: EventExecutionContext {
+                    slot: Slot::new(i / 2, 0),
+                    block: None,
+                    read_only: false,
+                    index_in_slot: i % 2,
+                    call_stack: VecDeque::from(vec![if i % 2 == 0 { emitter } else { other }]),
+                    origin_operation_id: None,
+                    is_final: true,
+                },
+                data: i.to_string(),
+            });
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let archive = EventArchive::new(temp_dir.path().to_path_buf(), 100);
+        archive.store(&store);
+
+        // paginate over the events of an emitter, starting at a given slot
+        let mut request = EventPageRequest {
+            filter: EventFilter {
+                start: Some(Slot::new(1, 0)),
+                emitter_address: Some(emitter),
+                ..Default::default()
+            },
+            after: None,
+            limit: 3,
+        };
+        let page = archive.get_page(&request);
+        let data: Vec<&str> = page.events.iter().map(|e| e.data.as_str()).collect();
+        assert_eq!(data, vec!["2", "4", "6"]);
+        request.after = page.next_cursor;
+        let page = archive.get_page(&request);
+        let data: Vec<&str> = page.events.iter().map(|e| e.data.as_str()).collect();
+        assert_eq!(data, vec!["8"]);
+        assert!(page.next_cursor.is_none());
+
+        // no cursor is returned when the page ends with the last matching event,
+        // even if non-matching events follow it in the scanned index
+        let mut called_store = EventStore::default();
+        called_store.push(SCOutputEvent {
+            context: EventExecutionContext {
+                slot: Slot::new(5, 0),
+                block: None,
+                read_only: false,
+                index_in_slot: 0,
+                call_stack: VecDeque::from(vec![emitter, other]),
+                origin_operation_id: None,
+                is_final: true,
+            },
+            data: "called".to_string(),
+        });
+        archive.store(&called_store);
+        request.filter.original_caller_address = Some(emitter);
+        request.after = None;
+        request.limit = 4;
+        let page = archive.get_page(&request);
+        let data: Vec<&str> = page.events.iter().map(|e| e.data.as_str()).collect();
+        assert_eq!(data, vec!["2", "4", "6", "8"]);
+        assert!(page.next_cursor.is_none());
+
+        // candidate events are never archived
+        request.filter.is_final = Some(false);
+        request.after = None;
+        assert!(archive.get_page(&request).events.is_empty());
+    }
+
+    #[test]
+    fn test_event_archive_scan_cap() {
+        let emitter = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let caller = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let other = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let mut store = EventStore::default();
+        for i in 0..10 {
+            let original_caller = if i == 1 || i == 8 { caller } else { other };
+            store.push(SCOutputEvent {
+                context: EventExecutionContext {
+                    slot: Slot::new(i, 0),
+                    block: None,
+                    read_only: false,
+                    index_in_slot: 0,
+                    call_stack: VecDeque::from(vec![emitter, original_caller]),
+                    origin_operation_id: None,
+                    is_final: true,
+                },
+                data: i.to_string(),
+            });
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let archive = EventArchive::new(temp_dir.path().to_path_buf(), 3);
+        archive.store(&store);
+
+        // the emitter index is scanned and the original caller is checked on each event:
+        // each request stops after 3 keys read, even if the page is not full,
+        // and returns a cursor to resume the scan from
+        let mut request = EventPageRequest {
+            filter: EventFilter {
+                emitter_address: Some(emitter),
+                original_caller_address: Some(caller),
+                ..Default::default()
+            },
+            after: None,
+            limit: 10,
+        };
+        let mut pages = Vec::new();
+        loop {
+            let page = archive.get_page(&request);
+            let data: Vec<String> = page.events.iter().map(|e| e.data.clone()).collect();
+            pages.push(data);
+            match page.next_cursor {
+                Some(cursor) => request.after = Some(cursor),
+                None => break,
+            }
+        }
+        let expected: Vec<Vec<String>> = vec![vec!["1".into()], vec![], vec!["8".into()], vec![]];
+        assert_eq!(pages, expected);
+    }
+}
@@ -9,18 +9,30 @@
 //! * the output of the execution is extracted from the context
 
 use crate::active_history::{ActiveHistory, HistorySearchResult};
+use crate::address_history::AddressHistory;
 use crate::context::ExecutionContext;
+use crate::event_archive::EventArchive;
 use crate::interface_impl::InterfaceImpl;
 use crate::stats::ExecutionStatsCounter;
+use crate::trace_store::TraceStore;
 use massa_async_pool::AsyncMessage;
 use massa_execution_exports::{
-    EventStore, ExecutionConfig, ExecutionError, ExecutionOutput, ExecutionStackElement,
-    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    EventStore, ExecutionChannels, ExecutionConfig, ExecutionError, ExecutionOutput,
+    ExecutionStackElement, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget,
 };
 use massa_final_state::FinalState;
-use massa_ledger_exports::{SetOrDelete, SetUpdateOrDelete};
+use massa_ledger_exports::{
+    bytecode_key, data_key, par_balance_key, seq_balance_key, LedgerError, SetOrDelete,
+    SetUpdateOrDelete, BYTECODE_IDENT, DATASTORE_IDENT, PAR_BALANCE_IDENT, SEQ_BALANCE_IDENT,
+};
 use massa_models::address::ExecutionAddressCycleInfo;
-use massa_models::api::EventFilter;
+use massa_models::api::{
+    AddressHistoryItem, EventFilter, LedgerEntryAtSlotInput, LedgerEntryAtSlotOutput,
+    LedgerProofInput, LedgerProofKey, LedgerProofOutput,
+};
+use massa_models::config::MAX_EVENT_ARCHIVE_SCANNED_KEYS;
+use massa_models::execution::{OperationExecutionStatus, OperationTrace};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::stats::ExecutionStats;
@@ -62,6 +74,12 @@ pub(crate) struct ExecutionState {
     pub final_cursor: Slot,
     // store containing execution events that became final
     final_events: EventStore,
+    // optional on-disk archive of all the execution events that became final
+    final_event_archive: Option<Arc<EventArchive>>,
+    // optional on-disk index of the final history of each address
+    final_address_history: Option<Arc<AddressHistory>>,
+    // cache of the execution traces of final operations, filled only if tracing is enabled
+    final_traces: TraceStore,
     // final state with atomic R/W access
     final_state: Arc<RwLock<FinalState>>,
     // execution context (see documentation in context.rs)
@@ -70,6 +88,8 @@ pub(crate) struct ExecutionState {
     execution_interface: Box<dyn Interface>,
     // execution statistics
     stats_counter: ExecutionStatsCounter,
+    // channels used to notify subscribers of execution outputs
+    channels: ExecutionChannels,
 }
 
 impl ExecutionState {
@@ -78,10 +98,15 @@ impl ExecutionState {
     /// # Arguments
     /// * `config`: execution configuration
     /// * `final_state`: atomic access to the final state
+    /// * `channels`: channels used to notify subscribers of execution outputs
     ///
     /// # returns
     /// A new `ExecutionState`
-    pub fn new(config: ExecutionConfig, final_state: Arc<RwLock<FinalState>>) -> ExecutionState {
+    pub fn new(
+        config: ExecutionConfig,
+        final_state: Arc<RwLock<FinalState>>,
+        channels: ExecutionChannels,
+    ) -> ExecutionState {
         // Get the slot at the output of which the final state is attached.
         // This should be among the latest final slots.
         let last_final_slot = final_state.read().slot;
@@ -111,6 +136,16 @@ impl ExecutionState {
             active_history,
             // empty final event store: it is not recovered through bootstrap
             final_events: Default::default(),
+            final_event_archive: config
+                .event_archive_path
+                .clone()
+                .map(|path| Arc::new(EventArchive::new(path, MAX_EVENT_ARCHIVE_SCANNED_KEYS))),
+            final_address_history: config
+                .address_history_path
+                .clone()
+                .map(|path| Arc::new(AddressHistory::new(path))),
+            // empty final trace cache: it is not recovered through bootstrap
+            final_traces: Default::default(),
             // no active slots executed yet: set active_cursor to the last final block
             active_cursor: last_final_slot,
             final_cursor: last_final_slot,
@@ -118,6 +153,7 @@ impl ExecutionState {
                 config.stats_time_window_duration,
                 config.clock_compensation,
             ),
+            channels,
             config,
         }
     }
@@ -168,8 +204,21 @@ impl ExecutionState {
 
         // append generated events to the final event store
         exec_out.events.finalize();
+        self.broadcast_events(&exec_out.events);
+        if let Some(final_event_archive) = &self.final_event_archive {
+            final_event_archive.store(&exec_out.events);
+        }
         self.final_events.extend(exec_out.events);
         self.final_events.prune(self.config.max_final_events);
+
+        // index the history of the involved addresses
+        if let Some(final_address_history) = &self.final_address_history {
+            final_address_history.store(exec_out.slot, &exec_out.address_history);
+        }
+
+        // cache the traces of the executed operations
+        self.final_traces.extend(exec_out.traces);
+        self.final_traces.prune(self.config.max_final_traces);
     }
 
     /// Applies an execution output to the active (non-final) state
@@ -188,10 +237,24 @@ impl ExecutionState {
         // update active cursor to reflect the new latest active slot
         self.active_cursor = exec_out.slot;
 
+        // notify subscribers of the generated candidate events
+        self.broadcast_events(&exec_out.events);
+
         // add the execution output at the end of the output history
         self.active_history.write().0.push_back(exec_out);
     }
 
+    /// Sends events to the subscribers, if any
+    fn broadcast_events(&self, events: &EventStore) {
+        if self.channels.sc_output_event_sender.receiver_count() == 0 {
+            return;
+        }
+        for event in events.iter() {
+            // sending only fails if all receivers were dropped in the meantime: ignore it
+            let _ = self.channels.sc_output_event_sender.send(event.clone());
+        }
+    }
+
     /// Clear the whole execution history,
     /// deleting caches on executed non-final slots.
     pub fn clear_history(&mut self) {
@@ -350,6 +413,14 @@ impl ExecutionState {
             // set the context origin operation ID
             context.origin_operation_id = Some(operation_id);
 
+            // trace the interface calls of the smart contracts executed by the operation
+            if matches!(
+                operation.content.op,
+                OperationType::ExecuteSC { .. } | OperationType::CallSC { .. }
+            ) {
+                context.start_operation_trace();
+            }
+
             // execution context lock dropped here because the op-specific execution functions below acquire it again
         }
 
@@ -360,39 +431,87 @@ impl ExecutionState {
         *block_credits = new_block_credits;
 
         // Call the execution process specific to the operation type.
+        // Smart contract executions also return the gas they used.
         let execution_result = match &operation.content.op {
-            OperationType::ExecuteSC { .. } => {
-                self.execute_executesc_op(&operation.content.op, sender_addr)
-            }
-            OperationType::CallSC { .. } => {
-                self.execute_callsc_op(&operation.content.op, sender_addr)
-            }
-            OperationType::RollBuy { .. } => {
-                self.execute_roll_buy_op(&operation.content.op, sender_addr)
-            }
-            OperationType::RollSell { .. } => {
-                self.execute_roll_sell_op(&operation.content.op, sender_addr)
-            }
-            OperationType::Transaction { .. } => {
-                self.execute_transaction_op(&operation.content.op, sender_addr)
-            }
+            OperationType::ExecuteSC { .. } => self
+                .execute_executesc_op(&operation.content.op, sender_addr)
+                .map(Some),
+            OperationType::CallSC { .. } => self
+                .execute_callsc_op(&operation.content.op, sender_addr)
+                .map(Some),
+            OperationType::RollBuy { .. } => self
+                .execute_roll_buy_op(&operation.content.op, sender_addr)
+                .map(|_| None),
+            OperationType::RollSell { .. } => self
+                .execute_roll_sell_op(&operation.content.op, sender_addr)
+                .map(|_| None),
+            OperationType::Transaction { .. } => self
+                .execute_transaction_op(&operation.content.op, sender_addr)
+                .map(|_| None),
         };
 
         {
             // lock execution context
             let mut context = context_guard!(self);
 
+            // store the trace of the operation, if it was traced
+            context.finish_operation_trace(operation_id, &execution_result);
+
+            // record the operation in the history of the involved addresses
+            let success = execution_result.is_ok();
+            context.record_address_history(
+                sender_addr,
+                AddressHistoryItem::OperationSent {
+                    operation_id,
+                    success,
+                },
+            );
+            if success {
+                match &operation.content.op {
+                    OperationType::Transaction {
+                        recipient_address,
+                        amount,
+                    } => context.record_address_history(
+                        *recipient_address,
+                        AddressHistoryItem::TransactionReceived {
+                            operation_id,
+                            sender: sender_addr,
+                            amount: *amount,
+                        },
+                    ),
+                    OperationType::RollBuy { roll_count } => context.record_address_history(
+                        sender_addr,
+                        AddressHistoryItem::RollBuy {
+                            operation_id,
+                            roll_count: *roll_count,
+                        },
+                    ),
+                    OperationType::RollSell { roll_count } => context.record_address_history(
+                        sender_addr,
+                        AddressHistoryItem::RollSell {
+                            operation_id,
+                            roll_count: *roll_count,
+                        },
+                    ),
+                    _ => {}
+                }
+            }
+
             // check execution results
             match execution_result {
                 Ok(_) => {}
                 Err(err) => {
                     // an error occurred: emit error event and reset context to snapshot
+                    let status = err.to_operation_status();
                     let err = ExecutionError::RuntimeError(format!(
                         "runtime error when executing operation {}: {}",
                         operation_id, &err
                     ));
                     debug!("{}", &err);
                     context.reset_to_snapshot(context_snapshot, Some(err));
+
+                    // the operation stays executed: record why it failed
+                    context.set_executed_op_status(&operation_id, status);
                 }
             }
         }
@@ -481,7 +600,7 @@ impl ExecutionState {
         if let Err(err) =
             context.transfer_sequential_coins(Some(buyer_addr), None, spend_coins, false)
         {
-            return Err(ExecutionError::RollBuyError(format!(
+            return Err(ExecutionError::InsufficientBalance(format!(
                 "{} failed to buy {} rolls: {}",
                 buyer_addr, roll_count, err
             )));
@@ -532,10 +651,16 @@ impl ExecutionState {
             *amount,
             false,
         ) {
-            return Err(ExecutionError::TransactionError(format!(
+            let message = format!(
                 "transfer of {} coins from {} to {} failed: {}",
                 amount, sender_addr, recipient_address, err
-            )));
+            );
+            return Err(match err {
+                ExecutionError::InsufficientBalance(_) => {
+                    ExecutionError::InsufficientBalance(message)
+                }
+                _ => ExecutionError::TransactionError(message),
+            });
         }
 
         Ok(())
@@ -547,11 +672,14 @@ impl ExecutionState {
     /// # Arguments
     /// * `operation`: the `WrappedOperation` to process, must be an `ExecuteSC`
     /// * `sender_addr`: address of the sender
+    ///
+    /// # Returns
+    /// The gas used by the execution
     pub fn execute_executesc_op(
         &self,
         operation: &OperationType,
         sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<u64, ExecutionError> {
         // process ExecuteSC operations only
         let (bytecode, max_gas, coins) = match &operation {
             OperationType::ExecuteSC {
@@ -582,7 +710,7 @@ impl ExecutionState {
             if let Err(err) =
                 context.transfer_sequential_coins(Some(sender_addr), None, *coins, false)
             {
-                return Err(ExecutionError::RuntimeError(format!(
+                return Err(ExecutionError::InsufficientBalance(format!(
                     "failed to debit operation sender {} with {} operation sequential coins: {}",
                     sender_addr, *coins, err
                 )));
@@ -601,17 +729,12 @@ impl ExecutionState {
 
         // run the VM on the bytecode contained in the operation
         match massa_sc_runtime::run_main(bytecode, *max_gas, &*self.execution_interface) {
-            Ok(_reamining_gas) => {}
+            Ok(remaining_gas) => Ok(max_gas.saturating_sub(remaining_gas)),
             Err(err) => {
                 // there was an error during bytecode execution
-                return Err(ExecutionError::RuntimeError(format!(
-                    "bytecode execution error: {}",
-                    err
-                )));
+                Err(bytecode_execution_error(err))
             }
         }
-
-        Ok(())
     }
 
     /// Execute an operation of type `CallSC`
@@ -622,11 +745,14 @@ impl ExecutionState {
     /// * `block_creator_addr`: address of the block creator
     /// * `operation_id`: ID of the operation
     /// * `sender_addr`: address of the sender
+    ///
+    /// # Returns
+    /// The gas used by the execution
     pub fn execute_callsc_op(
         &self,
         operation: &OperationType,
         sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<u64, ExecutionError> {
         // process CallSC operations only
         let (max_gas, target_addr, target_func, param, parallel_coins, sequential_coins) =
             match &operation {
@@ -685,7 +811,7 @@ impl ExecutionState {
             if let Err(err) =
                 context.transfer_sequential_coins(Some(sender_addr), None, sequential_coins, false)
             {
-                return Err(ExecutionError::RuntimeError(format!(
+                return Err(ExecutionError::InsufficientBalance(format!(
                     "failed to debit operation sender {} with {} operation sequential coins: {}",
                     sender_addr, sequential_coins, err
                 )));
@@ -695,7 +821,7 @@ impl ExecutionState {
             if let Err(err) =
                 context.transfer_parallel_coins(Some(sender_addr), None, parallel_coins, false)
             {
-                return Err(ExecutionError::RuntimeError(format!(
+                return Err(ExecutionError::InsufficientBalance(format!(
                     "failed to debit operation sender {} with {} operation parallel coins: {}",
                     sender_addr, parallel_coins, err
                 )));
@@ -716,7 +842,7 @@ impl ExecutionState {
 
             // quit if there is no function to be called
             if target_func.is_empty() {
-                return Ok(());
+                return Ok(0);
             }
 
             // Load bytecode. Assume empty bytecode if not found.
@@ -731,17 +857,12 @@ impl ExecutionState {
             param,
             &*self.execution_interface,
         ) {
-            Ok(_reamining_gas) => {}
+            Ok(remaining_gas) => Ok(max_gas.saturating_sub(remaining_gas)),
             Err(err) => {
                 // there was an error during bytecode execution
-                return Err(ExecutionError::RuntimeError(format!(
-                    "bytecode execution error: {}",
-                    err
-                )));
+                Err(bytecode_execution_error(err))
             }
         }
-
-        Ok(())
     }
 
     /// Tries to execute an asynchronous message
@@ -870,9 +991,28 @@ impl ExecutionState {
         // Try executing asynchronous messages.
         // Effects are cancelled on failure and the sender is reimbursed.
         for (opt_bytecode, message) in messages {
-            if let Err(err) = self.execute_async_message(message, opt_bytecode) {
-                debug!("failed executing async message: {}", err);
-            }
+            let (destination, sender, handler, coins) = (
+                message.destination,
+                message.sender,
+                message.handler.clone(),
+                message.coins,
+            );
+            let success = match self.execute_async_message(message, opt_bytecode) {
+                Ok(()) => true,
+                Err(err) => {
+                    debug!("failed executing async message: {}", err);
+                    false
+                }
+            };
+            context_guard!(self).record_address_history(
+                destination,
+                AddressHistoryItem::AsyncMessageReceived {
+                    sender,
+                    handler,
+                    coins,
+                    success,
+                },
+            );
         }
 
         // Check if there is a block at this slot
@@ -1030,27 +1170,50 @@ impl ExecutionState {
 
     /// Runs a read-only execution request.
     /// The executed bytecode appears to be able to read and write the consensus state,
-    /// but all accumulated changes are simply returned as an `ExecutionOutput` object,
+    /// but all accumulated changes are simply returned as a `ReadOnlyExecutionOutput` object,
     /// and not actually applied to the consensus state.
     ///
     /// # Arguments
     /// * `req`: a read-only execution request
     ///
     /// # Returns
-    ///  `ExecutionOutput` describing the output of the execution, or an error
+    ///  `ReadOnlyExecutionOutput` describing the output of the execution and the gas it consumed, or an error
     pub(crate) fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
-    ) -> Result<ExecutionOutput, ExecutionError> {
+    ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
         // TODO ensure that speculative things are reset after every execution ends (incl. on error and readonly)
         // otherwise, on prod stats accumulation etc... from the API we might be counting the remainder of this speculative execution
 
         // set the execution slot to be the one after the latest executed active slot
-        let slot = self
+        let mut slot = self
             .active_cursor
             .get_next_slot(self.config.thread_count)
             .expect("slot overflow in readonly execution");
 
+        // operations can only be included in blocks of their own thread
+        if let ReadOnlyExecutionTarget::Operations(operations) = &req.target {
+            if let Some(operation) = operations.first() {
+                let op_thread = operation
+                    .creator_address
+                    .get_thread(self.config.thread_count);
+                // all the operations of a block belong to the same thread
+                if let Some(other) = operations.iter().find(|other| {
+                    other.creator_address.get_thread(self.config.thread_count) != op_thread
+                }) {
+                    return Err(ExecutionError::InlcudeOperationError(format!(
+                        "operation {} is not in thread {} like operation {}: simulated operations must all be emitted from the same thread",
+                        other.id, op_thread, operation.id
+                    )));
+                }
+                while slot.thread != op_thread {
+                    slot = slot
+                        .get_next_slot(self.config.thread_count)
+                        .expect("slot overflow in readonly execution");
+                }
+            }
+        }
+
         // create a readonly execution context
         let execution_context = ExecutionContext::readonly(
             self.config.clone(),
@@ -1063,14 +1226,14 @@ impl ExecutionState {
         );
 
         // run the intepreter according to the target type
-        match req.target {
+        let remaining_gas = match req.target {
             ReadOnlyExecutionTarget::BytecodeExecution(bytecode) => {
                 // set the execution context for execution
                 *context_guard!(self) = execution_context;
 
                 // run the bytecode's main function
                 massa_sc_runtime::run_main(&bytecode, req.max_gas, &*self.execution_interface)
-                    .map_err(|err| ExecutionError::RuntimeError(err.to_string()))?;
+                    .map_err(|err| ExecutionError::RuntimeError(err.to_string()))?
             }
             ReadOnlyExecutionTarget::FunctionCall {
                 target_addr,
@@ -1093,12 +1256,38 @@ impl ExecutionState {
                     &parameter,
                     &*self.execution_interface,
                 )
-                .map_err(|err| ExecutionError::RuntimeError(err.to_string()))?;
+                .map_err(|err| ExecutionError::RuntimeError(err.to_string()))?
             }
-        }
+            ReadOnlyExecutionTarget::Operations(operations) => {
+                // set the execution context for execution
+                *context_guard!(self) = execution_context;
+
+                // apply the operations as if they were included in a block at this slot
+                let mut remaining_block_gas = req.max_gas;
+                let mut block_credits = Amount::zero();
+                for operation in operations.iter() {
+                    self.execute_operation(
+                        operation,
+                        slot,
+                        &mut remaining_block_gas,
+                        &mut block_credits,
+                    )
+                    .map_err(|err| {
+                        ExecutionError::InlcudeOperationError(format!(
+                            "operation {} could not be included: {}",
+                            operation.id, err
+                        ))
+                    })?;
+                }
+                remaining_block_gas
+            }
+        };
 
         // return the execution output
-        Ok(context_guard!(self).settle_slot())
+        Ok(ReadOnlyExecutionOutput {
+            out: context_guard!(self).settle_slot(),
+            gas_cost: req.max_gas.saturating_sub(remaining_gas),
+        })
     }
 
     /// Gets a parallel balance both at the latest final and candidate executed slots
@@ -1267,6 +1456,155 @@ impl ExecutionState {
         }
     }
 
+    /// Get a handle to the on-disk archive of final events, if enabled.
+    /// The archive can be read without holding the execution state lock.
+    pub(crate) fn get_final_event_archive(&self) -> Result<Arc<EventArchive>, ExecutionError> {
+        self.final_event_archive
+            .clone()
+            .ok_or(ExecutionError::EventArchiveDisabled)
+    }
+
+    /// Get a handle to the on-disk index of the final history of addresses, if enabled.
+    /// The index can be read without holding the execution state lock.
+    pub(crate) fn get_final_address_history(&self) -> Result<Arc<AddressHistory>, ExecutionError> {
+        self.final_address_history
+            .clone()
+            .ok_or(ExecutionError::AddressHistoryDisabled)
+    }
+
+    /// Get the execution traces of operations, searching candidate slots first, then final slots
+    pub fn get_operation_traces(
+        &self,
+        operation_ids: &[OperationId],
+    ) -> Result<Vec<OperationTrace>, ExecutionError> {
+        if !self.config.trace_execution {
+            return Err(ExecutionError::ExecutionTracingDisabled);
+        }
+        let active_history = self.active_history.read();
+        Ok(operation_ids
+            .iter()
+            .filter_map(|operation_id| {
+                active_history
+                    .0
+                    .iter()
+                    .rev()
+                    .flat_map(|output| output.traces.iter())
+                    .find(|trace| &trace.operation_id == operation_id)
+                    .or_else(|| self.final_traces.get(operation_id))
+                    .cloned()
+            })
+            .collect())
+    }
+
+    /// Get the execution status of operations, searching candidate slots first, then final slots
+    pub fn get_operation_execution_statuses(
+        &self,
+        operation_ids: &[OperationId],
+    ) -> Vec<Option<OperationExecutionStatus>> {
+        // check active history
+        let mut statuses: Vec<Option<OperationExecutionStatus>> = {
+            let active_history = self.active_history.read();
+            operation_ids
+                .iter()
+                .map(|operation_id| {
+                    active_history
+                        .0
+                        .iter()
+                        .rev()
+                        .find_map(|output| {
+                            output.state_changes.executed_ops.get_status(operation_id)
+                        })
+                        .cloned()
+                })
+                .collect()
+        };
+
+        // check final state for the operations not found in the active history
+        let final_state = self.final_state.read();
+        for (operation_id, status) in operation_ids.iter().zip(statuses.iter_mut()) {
+            if status.is_none() {
+                *status = final_state.executed_ops.get_status(operation_id).cloned();
+            }
+        }
+        statuses
+    }
+
+    /// Get a final ledger entry as it was after the execution of a past final slot
+    pub fn get_final_ledger_entry_at_slot(
+        &self,
+        input: &LedgerEntryAtSlotInput,
+    ) -> Result<LedgerEntryAtSlotOutput, ExecutionError> {
+        let final_state = self.final_state.read();
+        let ledger = &final_state.ledger;
+        let to_execution_error =
+            |err: LedgerError| ExecutionError::LedgerHistoryError(err.to_string());
+        Ok(LedgerEntryAtSlotOutput {
+            address: input.address,
+            slot: input.slot,
+            sequential_balance: ledger
+                .get_sequential_balance_at_slot(&input.address, &input.slot)
+                .map_err(to_execution_error)?,
+            parallel_balance: ledger
+                .get_parallel_balance_at_slot(&input.address, &input.slot)
+                .map_err(to_execution_error)?,
+            bytecode: ledger
+                .get_bytecode_at_slot(&input.address, &input.slot)
+                .map_err(to_execution_error)?,
+            datastore_values: input
+                .datastore_keys
+                .iter()
+                .map(|key| ledger.get_data_entry_at_slot(&input.address, key, &input.slot))
+                .collect::<Result<_, _>>()
+                .map_err(to_execution_error)?,
+        })
+    }
+
+    /// Get final ledger values along with their Merkle proof against the root of the final ledger.
+    /// The final state is locked once so that all the proofs at the current final slot are against the same root.
+    ///
+    /// Inputs asking for a past final slot are proven against the root at that slot,
+    /// which is only available if the ledger history retains the slot.
+    pub fn get_final_ledger_proofs(
+        &self,
+        inputs: &[LedgerProofInput],
+    ) -> Result<Vec<LedgerProofOutput>, ExecutionError> {
+        let final_state = self.final_state.read();
+        let final_root = final_state.ledger.get_merkle_root();
+        inputs
+            .iter()
+            .map(|input| {
+                let ledger_key = match &input.key {
+                    LedgerProofKey::SequentialBalance => seq_balance_key!(input.address),
+                    LedgerProofKey::ParallelBalance => par_balance_key!(input.address),
+                    LedgerProofKey::Bytecode => bytecode_key!(input.address),
+                    LedgerProofKey::Datastore(key) => data_key!(input.address, key),
+                };
+                let (slot, root, value, proof) = match input.slot {
+                    Some(slot) if slot != final_state.slot => {
+                        let (root, value, proof) = final_state
+                            .ledger
+                            .get_merkle_proof_at_slot(&ledger_key, &slot)
+                            .map_err(|_| ExecutionError::LedgerProofSlotUnavailable(slot))?;
+                        (slot, root, value, proof)
+                    }
+                    _ => {
+                        let (value, proof) = final_state.ledger.get_merkle_proof(&ledger_key);
+                        (final_state.slot, final_root, value, proof)
+                    }
+                };
+                Ok(LedgerProofOutput {
+                    address: input.address,
+                    key: input.key.clone(),
+                    slot,
+                    root,
+                    ledger_key,
+                    value,
+                    proof,
+                })
+            })
+            .collect()
+    }
+
     /// List which operations inside the provided list were not executed
     pub fn unexecuted_ops_among(
         &self,
@@ -1312,3 +1650,23 @@ impl ExecutionState {
         context_guard!(self).get_address_future_deferred_credits(address, self.config.thread_count)
     }
 }
+
+/// Message with which the runtime reports that the gas of the execution was exhausted
+const OUT_OF_GAS_MESSAGE: &str = "Not enough gas";
+
+/// Converts an error returned by the VM into an `ExecutionError`.
+///
+/// The runtime only reports errors as text. It checks the remaining gas itself when the execution fails,
+/// and reports gas exhaustion with a message of its own, pinned by the `sc_out_of_gas` test.
+/// The other errors (aborts, interface errors) are reported with a prefix of the VM,
+/// so that the text of a smart contract is never at the start of the message:
+/// only the start of the message is compared.
+pub(crate) fn bytecode_execution_error(err: anyhow::Error) -> ExecutionError {
+    let out_of_gas = err.to_string().starts_with(OUT_OF_GAS_MESSAGE);
+    let message = format!("bytecode execution error: {}", err);
+    if out_of_gas {
+        ExecutionError::OutOfGas(message)
+    } else {
+        ExecutionError::VMError(message)
+    }
+}
@@ -11,7 +11,11 @@ use massa_async_pool::AsyncMessage;
 use massa_execution_exports::ExecutionConfig;
 use massa_execution_exports::ExecutionStackElement;
 use massa_models::{
-    address::Address, amount::Amount, slot::Slot, timeslots::get_block_slot_timestamp,
+    address::Address,
+    amount::Amount,
+    execution::{InterfaceCall, TracedValue},
+    slot::Slot,
+    timeslots::get_block_slot_timestamp,
 };
 use massa_sc_runtime::{Interface, InterfaceClone};
 use parking_lot::Mutex;
@@ -109,6 +113,12 @@ impl Interface for InterfaceImpl {
             );
         }
 
+        // trace the call from the caller's point of view
+        context.trace_call(|| InterfaceCall::Call {
+            target: to_address,
+            coins,
+        });
+
         // push a new call stack element on top of the current call stack
         context.stack.push(ExecutionStackElement {
             address: to_address,
@@ -125,6 +135,7 @@ impl Interface for InterfaceImpl {
     fn finish_call(&self) -> Result<()> {
         let mut context = context_guard!(self);
 
+        context.trace_call(|| InterfaceCall::FinishCall);
         if context.stack.pop().is_none() {
             bail!("call stack out of bounds")
         }
@@ -171,8 +182,12 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The string representation of the newly created address
     fn create_module(&self, bytecode: &[u8]) -> Result<String> {
-        match context_guard!(self).create_new_sc_address(bytecode.to_vec()) {
-            Ok(addr) => Ok(addr.to_string()),
+        let mut context = context_guard!(self);
+        match context.create_new_sc_address(bytecode.to_vec()) {
+            Ok(address) => {
+                context.trace_call(|| InterfaceCall::CreateModule { address });
+                Ok(address.to_string())
+            }
             Err(err) => bail!("couldn't create new SC address: {}", err),
         }
     }
@@ -187,8 +202,14 @@ impl Interface for InterfaceImpl {
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn raw_get_data_for(&self, address: &str, key: &str) -> Result<Vec<u8>> {
         let addr = &massa_models::address::Address::from_str(address)?;
-        let context = context_guard!(self);
-        match context.get_data_entry(addr, key.as_bytes()) {
+        let mut context = context_guard!(self);
+        let value = context.get_data_entry(addr, key.as_bytes());
+        context.trace_call(|| InterfaceCall::DatastoreRead {
+            address: *addr,
+            key: key.as_bytes().to_vec(),
+            value: value.as_deref().map(TracedValue::new),
+        });
+        match value {
             Some(value) => Ok(value),
             _ => bail!("data entry not found"),
         }
@@ -206,6 +227,11 @@ impl Interface for InterfaceImpl {
         let addr = massa_models::address::Address::from_str(address)?;
         let mut context = context_guard!(self);
         context.set_data_entry(&addr, key.as_bytes().to_vec(), value.to_vec())?;
+        context.trace_call(|| InterfaceCall::DatastoreSet {
+            address: addr,
+            key: key.as_bytes().to_vec(),
+            value: TracedValue::new(value),
+        });
         Ok(())
     }
 
@@ -218,7 +244,13 @@ impl Interface for InterfaceImpl {
     /// * value: value to append
     fn raw_append_data_for(&self, address: &str, key: &str, value: &[u8]) -> Result<()> {
         let addr = massa_models::address::Address::from_str(address)?;
-        context_guard!(self).append_data_entry(&addr, key.as_bytes().to_vec(), value.to_vec())?;
+        let mut context = context_guard!(self);
+        context.append_data_entry(&addr, key.as_bytes().to_vec(), value.to_vec())?;
+        context.trace_call(|| InterfaceCall::DatastoreAppend {
+            address: addr,
+            key: key.as_bytes().to_vec(),
+            value: TracedValue::new(value),
+        });
         Ok(())
     }
 
@@ -230,7 +262,12 @@ impl Interface for InterfaceImpl {
     /// * key: string key of the datastore entry to delete
     fn raw_delete_data_for(&self, address: &str, key: &str) -> Result<()> {
         let addr = &massa_models::address::Address::from_str(address)?;
-        context_guard!(self).delete_data_entry(addr, key.as_bytes())?;
+        let mut context = context_guard!(self);
+        context.delete_data_entry(addr, key.as_bytes())?;
+        context.trace_call(|| InterfaceCall::DatastoreDelete {
+            address: *addr,
+            key: key.as_bytes().to_vec(),
+        });
         Ok(())
     }
 
@@ -244,8 +281,14 @@ impl Interface for InterfaceImpl {
     /// true if the address exists and has the entry matching the provided key in its datastore, otherwise false
     fn has_data_for(&self, address: &str, key: &str) -> Result<bool> {
         let addr = massa_models::address::Address::from_str(address)?;
-        let context = context_guard!(self);
-        Ok(context.has_data_entry(&addr, key.as_bytes()))
+        let mut context = context_guard!(self);
+        let found = context.has_data_entry(&addr, key.as_bytes());
+        context.trace_call(|| InterfaceCall::DatastoreHas {
+            address: addr,
+            key: key.as_bytes().to_vec(),
+            found,
+        });
+        Ok(found)
     }
 
     /// Gets a datastore value by key for the current address (top of the call stack).
@@ -256,9 +299,15 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn raw_get_data(&self, key: &str) -> Result<Vec<u8>> {
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
-        match context.get_data_entry(&addr, key.as_bytes()) {
+        let value = context.get_data_entry(&addr, key.as_bytes());
+        context.trace_call(|| InterfaceCall::DatastoreRead {
+            address: addr,
+            key: key.as_bytes().to_vec(),
+            value: value.as_deref().map(TracedValue::new),
+        });
+        match value {
             Some(data) => Ok(data),
             _ => bail!("data entry not found"),
         }
@@ -276,6 +325,11 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.set_data_entry(&addr, key.as_bytes().to_vec(), value.to_vec())?;
+        context.trace_call(|| InterfaceCall::DatastoreSet {
+            address: addr,
+            key: key.as_bytes().to_vec(),
+            value: TracedValue::new(value),
+        });
         Ok(())
     }
 
@@ -290,6 +344,11 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.append_data_entry(&addr, key.as_bytes().to_vec(), value.to_vec())?;
+        context.trace_call(|| InterfaceCall::DatastoreAppend {
+            address: addr,
+            key: key.as_bytes().to_vec(),
+            value: TracedValue::new(value),
+        });
         Ok(())
     }
 
@@ -302,6 +361,10 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.delete_data_entry(&addr, key.as_bytes())?;
+        context.trace_call(|| InterfaceCall::DatastoreDelete {
+            address: addr,
+            key: key.as_bytes().to_vec(),
+        });
         Ok(())
     }
 
@@ -313,9 +376,15 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// true if the address exists and has the entry matching the provided key in its datastore, otherwise false
     fn has_data(&self, key: &str) -> Result<bool> {
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
-        Ok(context.has_data_entry(&addr, key.as_bytes()))
+        let found = context.has_data_entry(&addr, key.as_bytes());
+        context.trace_call(|| InterfaceCall::DatastoreHas {
+            address: addr,
+            key: key.as_bytes().to_vec(),
+            found,
+        });
+        Ok(found)
     }
 
     /// Hashes arbitrary data
@@ -375,6 +444,11 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let from_address = context.get_current_address()?;
         context.transfer_parallel_coins(Some(from_address), Some(to_address), amount, true)?;
+        context.trace_call(|| InterfaceCall::Transfer {
+            from: from_address,
+            to: to_address,
+            amount,
+        });
         Ok(())
     }
 
@@ -395,6 +469,11 @@ impl Interface for InterfaceImpl {
         let amount = massa_models::amount::Amount::from_raw(raw_amount);
         let mut context = context_guard!(self);
         context.transfer_parallel_coins(Some(from_address), Some(to_address), amount, true)?;
+        context.trace_call(|| InterfaceCall::Transfer {
+            from: from_address,
+            to: to_address,
+            amount,
+        });
         Ok(())
     }
 
@@ -441,6 +520,7 @@ impl Interface for InterfaceImpl {
     /// data: the string data that is the payload of the event
     fn generate_event(&self, data: String) -> Result<()> {
         let mut context = context_guard!(self);
+        context.trace_call(|| InterfaceCall::GenerateEvent { data: data.clone() });
         let event = context.event_create(data);
         context.event_emit(event);
         Ok(())
@@ -501,7 +581,7 @@ impl Interface for InterfaceImpl {
         let emission_slot = execution_context.slot;
         let emission_index = execution_context.created_message_index;
         let sender = execution_context.get_current_address()?;
-        execution_context.push_new_message(AsyncMessage {
+        let message = AsyncMessage {
             emission_slot,
             emission_index,
             sender,
@@ -513,7 +593,17 @@ impl Interface for InterfaceImpl {
             gas_price: Amount::from_raw(gas_price),
             coins: Amount::from_raw(raw_coins),
             data: data.to_vec(),
+        };
+        execution_context.trace_call(|| InterfaceCall::SendMessage {
+            target_address: message.destination,
+            target_handler: message.handler.clone(),
+            validity_start: message.validity_start,
+            validity_end: message.validity_end,
+            max_gas: message.max_gas,
+            gas_price: message.gas_price,
+            coins: message.coins,
         });
+        execution_context.push_new_message(message);
         execution_context.created_message_index += 1;
         Ok(())
     }
@@ -535,7 +625,10 @@ impl Interface for InterfaceImpl {
         let mut execution_context = context_guard!(self);
         let address = execution_context.get_current_address()?;
         match execution_context.set_bytecode(&address, bytecode.to_vec()) {
-            Ok(()) => Ok(()),
+            Ok(()) => {
+                execution_context.trace_call(|| InterfaceCall::SetBytecode { address });
+                Ok(())
+            }
             Err(err) => bail!("couldn't set address {} bytecode: {}", address, err),
         }
     }
@@ -546,7 +639,10 @@ impl Interface for InterfaceImpl {
         let address = massa_models::address::Address::from_str(address)?;
         let mut execution_context = context_guard!(self);
         match execution_context.set_bytecode(&address, bytecode.to_vec()) {
-            Ok(()) => Ok(()),
+            Ok(()) => {
+                execution_context.trace_call(|| InterfaceCall::SetBytecode { address });
+                Ok(())
+            }
             Err(err) => bail!("couldn't set address {} bytecode: {}", address, err),
         }
     }
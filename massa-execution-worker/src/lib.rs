@@ -57,6 +57,16 @@
 //! It also serves as an access point to the current execution state and speculative ledger
 //! as defined in `speculative_ledger.rs`.
 //!
+//! ## `event_archive.rs`
+//! An optional on-disk archive of final execution events, indexed to be queried by `EventFilter` page by page.
+//!
+//! ## `address_history.rs`
+//! An optional on-disk index of the final history of each address
+//! (executed operations, roll buys and sells, received async messages), queried page by page.
+//!
+//! ## `trace_store.rs`
+//! An in-memory cache of the execution traces of final operations, queried by operation ID.
+//!
 //! ## `speculative_ledger.rs`
 //! A speculative (non-final) ledger that supports canceling already-executed operations
 //! in the case of some blockclique changes.
@@ -77,8 +87,10 @@
 #![feature(map_try_insert)]
 
 mod active_history;
+mod address_history;
 mod context;
 mod controller;
+mod event_archive;
 mod execution;
 mod interface_impl;
 mod request_queue;
@@ -87,6 +99,7 @@ mod speculative_executed_ops;
 mod speculative_ledger;
 mod speculative_roll_state;
 mod stats;
+mod trace_store;
 mod worker;
 
 pub use worker::start_execution_worker;
@@ -4,7 +4,7 @@
 
 use crate::active_history::{ActiveHistory, HistorySearchResult};
 use massa_final_state::{ExecutedOps, FinalState};
-use massa_models::{operation::OperationId, slot::Slot};
+use massa_models::{execution::OperationExecutionStatus, operation::OperationId, slot::Slot};
 use parking_lot::RwLock;
 use std::sync::Arc;
 
@@ -85,4 +85,17 @@ impl SpeculativeExecutedOps {
     pub fn insert_executed_op(&mut self, op_id: OperationId, op_valid_until_slot: Slot) {
         self.executed_ops.insert(op_id, op_valid_until_slot);
     }
+
+    /// Set the execution status of an operation inserted with `insert_executed_op`
+    ///
+    /// # Arguments
+    /// * `op_id`: operation ID
+    /// * `status`: outcome of the execution of the operation
+    pub fn set_executed_op_status(
+        &mut self,
+        op_id: &OperationId,
+        status: OperationExecutionStatus,
+    ) {
+        self.executed_ops.set_status(op_id, status);
+    }
 }
@@ -152,7 +152,10 @@ impl SpeculativeLedger {
                 .unwrap_or_default()
                 .checked_sub(amount)
                 .ok_or_else(|| {
-                    ExecutionError::RuntimeError("insufficient from_addr balance".into())
+                    ExecutionError::InsufficientBalance(format!(
+                        "{} has less than {}",
+                        from_addr, amount
+                    ))
                 })?;
             changes.set_sequential_balance(from_addr, new_balance);
         }
@@ -200,7 +203,10 @@ impl SpeculativeLedger {
                 .unwrap_or_default()
                 .checked_sub(amount)
                 .ok_or_else(|| {
-                    ExecutionError::RuntimeError("insufficient from_addr balance".into())
+                    ExecutionError::InsufficientBalance(format!(
+                        "{} has less than {}",
+                        from_addr, amount
+                    ))
                 })?;
             changes.set_parallel_balance(from_addr, new_balance);
         }
@@ -1,9 +1,11 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::execution::bytecode_execution_error;
 use crate::start_execution_worker;
 use massa_async_pool::AsyncPoolConfig;
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionError, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ExecutionChannels, ExecutionConfig, ExecutionError, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget,
 };
 use massa_final_state::{FinalState, FinalStateConfig};
 use massa_hash::Hash;
@@ -15,9 +17,10 @@ use massa_models::config::{
 use massa_models::prehash::PreHashMap;
 use massa_models::{address::Address, amount::Amount, slot::Slot};
 use massa_models::{
-    api::EventFilter,
+    api::{EventFilter, LedgerProofInput, LedgerProofKey},
     block::{Block, BlockHeader, BlockHeaderSerializer, BlockId, BlockSerializer, WrappedBlock},
     config::THREAD_COUNT,
+    execution::{InterfaceCall, OperationExecutionStatus},
     operation::{Operation, OperationSerializer, OperationType, WrappedOperation},
     wrapped::WrappedContent,
 };
@@ -76,6 +79,7 @@ fn test_execution_shutdown() {
         ExecutionConfig::default(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
     manager.stop();
 }
@@ -88,6 +92,7 @@ fn test_sending_command() {
         ExecutionConfig::default(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
     controller.update_blockclique_status(Default::default(), Default::default());
     manager.stop();
@@ -101,8 +106,9 @@ fn test_sending_read_only_execution_command() {
         ExecutionConfig::default(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
-    controller
+    let output = controller
         .execute_readonly_request(ReadOnlyExecutionRequest {
             max_gas: 1_000_000,
             simulated_gas_price: Amount::from_mantissa_scale(1_000_000, 0),
@@ -112,6 +118,154 @@ fn test_sending_read_only_execution_command() {
             ),
         })
         .unwrap();
+    assert!(output.gas_cost > 0 && output.gas_cost <= 1_000_000);
+    manager.stop();
+}
+
+/// Ledger proofs are produced at the current final slot, and rejected at slots that the ledger history does not retain
+#[test]
+#[serial]
+fn test_ledger_proofs_slot() {
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (mut manager, controller) = start_execution_worker(
+        ExecutionConfig::default(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
+    );
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let address = Address::from_public_key(&keypair.get_public_key());
+    let final_slot = sample_state.read().slot;
+    let input = |slot| LedgerProofInput {
+        address,
+        key: LedgerProofKey::SequentialBalance,
+        slot,
+    };
+
+    for slot in [None, Some(final_slot)] {
+        let outputs = controller
+            .get_final_ledger_proofs(vec![input(slot)])
+            .unwrap();
+        assert_eq!(outputs[0].slot, final_slot);
+        assert!(outputs[0].value.is_some());
+        assert!(outputs[0].proof.verify(
+            &outputs[0].root,
+            &outputs[0].ledger_key,
+            outputs[0].value.as_deref()
+        ));
+    }
+
+    let other_slot = final_slot.get_next_slot(THREAD_COUNT).unwrap();
+    match controller.get_final_ledger_proofs(vec![input(None), input(Some(other_slot))]) {
+        Err(ExecutionError::LedgerProofSlotUnavailable(slot)) => assert_eq!(slot, other_slot),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    manager.stop();
+}
+
+/// Simulate a signed transaction in read-only mode and check that
+/// its effects are returned without being applied to the final state.
+#[test]
+#[serial]
+fn test_simulate_operations_read_only() {
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (mut manager, controller) = start_execution_worker(
+        ExecutionConfig::default(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
+    );
+    let sender_keypair =
+        KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let (recipient_address, _keypair) = get_random_address_full();
+    let operation = Operation::new_wrapped(
+        Operation {
+            fee: Amount::zero(),
+            expire_period: 10,
+            op: OperationType::Transaction {
+                recipient_address,
+                amount: Amount::from_str("100").unwrap(),
+            },
+        },
+        OperationSerializer::new(),
+        &sender_keypair,
+    )
+    .unwrap();
+    let output = controller
+        .execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: 1_000_000,
+            simulated_gas_price: Amount::zero(),
+            call_stack: vec![],
+            target: ReadOnlyExecutionTarget::Operations(vec![operation.clone()]),
+        })
+        .unwrap();
+    assert_eq!(
+        output.out.slot.thread,
+        operation.creator_address.get_thread(THREAD_COUNT)
+    );
+    let recipient_changes = output
+        .out
+        .state_changes
+        .ledger_changes
+        .get_address_changes()
+        .into_iter()
+        .find(|changes| changes.address == recipient_address)
+        .expect("missing recipient ledger changes");
+    assert_eq!(
+        recipient_changes.sequential_balance,
+        Some(Amount::from_str("100").unwrap())
+    );
+    // nothing was applied
+    assert!(sample_state
+        .read()
+        .ledger
+        .get_sequential_balance(&recipient_address)
+        .is_none());
+
+    // the same operation cannot be included twice
+    let result = controller.execute_readonly_request(ReadOnlyExecutionRequest {
+        max_gas: 1_000_000,
+        simulated_gas_price: Amount::zero(),
+        call_stack: vec![],
+        target: ReadOnlyExecutionTarget::Operations(vec![operation.clone(), operation.clone()]),
+    });
+    assert!(matches!(
+        result,
+        Err(ExecutionError::InlcudeOperationError(_))
+    ));
+
+    // operations emitted from different threads cannot be simulated together
+    let other_keypair = loop {
+        let keypair = KeyPair::generate();
+        if Address::from_public_key(&keypair.get_public_key()).get_thread(THREAD_COUNT)
+            != operation.creator_address.get_thread(THREAD_COUNT)
+        {
+            break keypair;
+        }
+    };
+    let other_operation = Operation::new_wrapped(
+        Operation {
+            fee: Amount::zero(),
+            expire_period: 10,
+            op: OperationType::Transaction {
+                recipient_address,
+                amount: Amount::from_str("100").unwrap(),
+            },
+        },
+        OperationSerializer::new(),
+        &other_keypair,
+    )
+    .unwrap();
+    let result = controller.execute_readonly_request(ReadOnlyExecutionRequest {
+        max_gas: 1_000_000,
+        simulated_gas_price: Amount::zero(),
+        call_stack: vec![],
+        target: ReadOnlyExecutionTarget::Operations(vec![operation, other_operation]),
+    });
+    assert!(matches!(
+        result,
+        Err(ExecutionError::InlcudeOperationError(_))
+    ));
     manager.stop();
 }
 
@@ -140,6 +294,7 @@ fn test_nested_call_gas_usage() {
         exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
     // get random keypair
     let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
@@ -244,6 +399,7 @@ fn send_and_receive_async_message() {
         exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
     // keypair associated to thread 0
     let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
@@ -298,6 +454,7 @@ pub fn send_and_receive_transaction() {
         exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
     // generate the sender_keypair and recipient_address
     let sender_keypair =
@@ -343,6 +500,88 @@ pub fn send_and_receive_transaction() {
     manager.stop();
 }
 
+#[test]
+#[serial]
+pub fn transaction_insufficient_balance_status() {
+    // setup the period duration
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        ..ExecutionConfig::default()
+    };
+    // get a sample final state
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+    // init the storage
+    let mut storage = Storage::create_root();
+    // start the execution worker
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg,
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
+    );
+    // generate the sender_keypair and recipient_address
+    let sender_keypair =
+        KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let (recipient_address, _keypair) = get_random_address_full();
+    // create a transaction of more coins than the sender owns, and a valid one
+    let create_transaction = |amount: &str| {
+        Operation::new_wrapped(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str(amount).unwrap(),
+                },
+            },
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap()
+    };
+    let failing_operation = create_transaction("1_000_000");
+    let operation = create_transaction("100");
+    let operation_ids = [failing_operation.id, operation.id];
+    // create the block contaning the transaction operations
+    storage.store_operations(vec![failing_operation.clone(), operation.clone()]);
+    let block = create_block(
+        KeyPair::generate(),
+        vec![failing_operation, operation],
+        Slot::new(1, 0),
+    )
+    .unwrap();
+    // store the block in storage
+    storage.store_block(block.clone());
+    // set our block as a final block so the transactions are processed
+    let mut finalized_blocks: HashMap<Slot, (BlockId, Storage)> = Default::default();
+    finalized_blocks.insert(
+        block.content.header.content.slot,
+        (block.id, storage.clone()),
+    );
+    controller.update_blockclique_status(finalized_blocks, Default::default());
+    std::thread::sleep(Duration::from_millis(10));
+    // check the execution statuses of the operations
+    assert_eq!(
+        controller.get_operation_execution_statuses(&operation_ids),
+        vec![
+            Some(OperationExecutionStatus::InsufficientBalance),
+            Some(OperationExecutionStatus::Success)
+        ]
+    );
+    // check that only the valid transaction was applied
+    assert_eq!(
+        sample_state
+            .read()
+            .ledger
+            .get_sequential_balance(&recipient_address)
+            .unwrap(),
+        Amount::from_str("100").unwrap()
+    );
+    // stop the execution controller
+    manager.stop();
+}
+
 #[test]
 #[serial]
 pub fn roll_buy() {
@@ -361,6 +600,7 @@ pub fn roll_buy() {
         exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
     // generate the keypair and its corresponding address
     let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
@@ -420,6 +660,7 @@ pub fn roll_sell() {
         exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
     // generate the keypair and its corresponding address
     let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
@@ -488,6 +729,7 @@ pub fn missed_blocks_roll_slash() {
         exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
     // sleep to get slashed on missed blocks and reach the reimbursment
     std::thread::sleep(Duration::from_millis(100));
@@ -525,6 +767,7 @@ fn sc_execution_error() {
         exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
     // keypair associated to thread 0
     let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
@@ -533,6 +776,7 @@ fn sc_execution_error() {
     let bytecode = include_bytes!("./wasm/execution_error.wasm");
     // create the block contaning the erroneous smart contract execution operation
     let operation = create_execute_sc_operation(&keypair, bytecode).unwrap();
+    let operation_id = operation.id;
     storage.store_operations(vec![operation.clone()]);
     let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
     // store the block in storage
@@ -555,10 +799,142 @@ fn sc_execution_error() {
         .data
         .contains("runtime error when executing operation"));
     assert!(events[0].data.contains("address parsing error"));
+    // check that the failure was recorded in the execution status of the operation
+    match controller
+        .get_operation_execution_statuses(&[operation_id])
+        .as_slice()
+    {
+        [Some(OperationExecutionStatus::VmError(message))] => {
+            assert!(message.contains("address parsing error"))
+        }
+        statuses => panic!("unexpected execution statuses: {:?}", statuses),
+    }
     // stop the execution controller
     manager.stop();
 }
 
+/// Execute a smart contract with too little gas and check that the failure is recognized as gas exhaustion.
+/// The runtime only reports errors as text: this pins the message matched by the execution worker.
+#[test]
+#[serial]
+fn sc_out_of_gas() {
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        ..ExecutionConfig::default()
+    };
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let mut storage = Storage::create_root();
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg,
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
+    );
+    // keypair associated to thread 0
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    // create an operation executing the bytecode with a single unit of gas
+    let operation = Operation::new_wrapped(
+        Operation {
+            fee: Amount::zero(),
+            expire_period: 10,
+            op: OperationType::ExecuteSC {
+                data: include_bytes!("./wasm/event_test.wasm").to_vec(),
+                max_gas: 1,
+                coins: Amount::zero(),
+                gas_price: Amount::from_mantissa_scale(1, 0),
+            },
+        },
+        OperationSerializer::new(),
+        &keypair,
+    )
+    .unwrap();
+    let operation_id = operation.id;
+    storage.store_operations(vec![operation.clone()]);
+    let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
+    storage.store_block(block.clone());
+    let mut finalized_blocks: HashMap<Slot, (BlockId, Storage)> = Default::default();
+    finalized_blocks.insert(
+        block.content.header.content.slot,
+        (block.id, storage.clone()),
+    );
+    controller.update_blockclique_status(finalized_blocks, Default::default());
+    std::thread::sleep(Duration::from_millis(10));
+
+    // the error event carries the message of the runtime
+    let events = controller.get_filtered_sc_output_event(EventFilter::default());
+    assert!(!events.is_empty(), "One event was expected");
+    assert!(events[0].data.contains("Not enough gas"));
+    assert_eq!(
+        controller.get_operation_execution_statuses(&[operation_id]),
+        vec![Some(OperationExecutionStatus::OutOfGas)]
+    );
+    manager.stop();
+}
+
+/// Only the gas exhaustion message of the runtime is recognized as such,
+/// not a VM error carrying a smart contract text that mentions it.
+#[test]
+fn bytecode_execution_error_out_of_gas() {
+    assert!(matches!(
+        bytecode_execution_error(anyhow::anyhow!(
+            "Not enough gas, limit reached at: {}",
+            "main"
+        )),
+        ExecutionError::OutOfGas(_)
+    ));
+    assert!(matches!(
+        bytecode_execution_error(anyhow::anyhow!(
+            "RuntimeError: error: {} at main.ts:1 col: 1",
+            "Not enough gas"
+        )),
+        ExecutionError::VMError(_)
+    ));
+}
+
+#[test]
+#[serial]
+fn broadcast_sc_output_events() {
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        max_async_gas: 100_000,
+        ..ExecutionConfig::default()
+    };
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let mut storage = Storage::create_root();
+    // subscribe to the events before starting the worker
+    let channels = ExecutionChannels::new(10);
+    let mut event_receiver = channels.sc_output_event_sender.subscribe();
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg,
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        channels,
+    );
+    // execute a smart contract generating an error event in a final block
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let bytecode = include_bytes!("./wasm/execution_error.wasm");
+    let operation = create_execute_sc_operation(&keypair, bytecode).unwrap();
+    storage.store_operations(vec![operation.clone()]);
+    let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
+    storage.store_block(block.clone());
+    let mut finalized_blocks: HashMap<Slot, (BlockId, Storage)> = Default::default();
+    finalized_blocks.insert(
+        block.content.header.content.slot,
+        (block.id, storage.clone()),
+    );
+    controller.update_blockclique_status(finalized_blocks, Default::default());
+    std::thread::sleep(Duration::from_millis(10));
+
+    // the final event must have been broadcast
+    let event = event_receiver
+        .try_recv()
+        .expect("an event should have been broadcast");
+    assert!(event.context.is_final);
+    assert_eq!(event.context.block, Some(block.id));
+    assert!(event.data.contains("massa_execution_error"));
+    manager.stop();
+}
+
 #[test]
 #[serial]
 fn generate_events() {
@@ -574,6 +950,7 @@ fn generate_events() {
         exec_cfg,
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
     );
 
     let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
@@ -603,6 +980,95 @@ fn generate_events() {
     manager.stop();
 }
 
+/// Execute `event_test.wasm` with execution tracing enabled
+/// and check that the emitted event is recorded in the trace of the operation.
+#[test]
+#[serial]
+fn trace_operation_execution() {
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        trace_execution: true,
+        ..ExecutionConfig::default()
+    };
+    let mut storage: Storage = Storage::create_root();
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg,
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
+    );
+
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let sender_address = Address::from_public_key(&keypair.get_public_key());
+    let event_test_data = include_bytes!("./wasm/event_test.wasm");
+    let operation = create_execute_sc_operation(&keypair, event_test_data).unwrap();
+    storage.store_operations(vec![operation.clone()]);
+    let block = create_block(keypair, vec![operation.clone()], Slot::new(1, 0)).unwrap();
+    let slot = block.content.header.content.slot;
+    storage.store_block(block.clone());
+    let mut blockclique: HashMap<Slot, (BlockId, Storage)> = HashMap::new();
+    blockclique.insert(slot, (block.id, storage.clone()));
+    controller.update_blockclique_status(Default::default(), blockclique);
+    std::thread::sleep(Duration::from_millis(1000));
+
+    let traces = controller.get_operation_traces(&[operation.id]).unwrap();
+    assert_eq!(traces.len(), 1);
+    let trace = &traces[0];
+    assert_eq!(trace.slot, slot);
+    assert!(!trace.is_final);
+    assert!(trace.error.is_none());
+    assert!(trace.gas_used.unwrap() > 0);
+    assert!(!trace.truncated);
+    assert!(trace.calls.iter().any(|entry| entry.depth == 1
+        && entry.address == sender_address
+        && matches!(entry.call, InterfaceCall::GenerateEvent { .. })));
+    manager.stop();
+}
+
+/// Execute `event_test.wasm` with execution tracing enabled but no room for trace entries
+/// and check that the trace of the operation is marked as truncated.
+#[test]
+#[serial]
+fn trace_operation_execution_truncated() {
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        trace_execution: true,
+        max_trace_entries: 0,
+        ..ExecutionConfig::default()
+    };
+    let mut storage: Storage = Storage::create_root();
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg,
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::new(10),
+    );
+
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let event_test_data = include_bytes!("./wasm/event_test.wasm");
+    let operation = create_execute_sc_operation(&keypair, event_test_data).unwrap();
+    storage.store_operations(vec![operation.clone()]);
+    let block = create_block(keypair, vec![operation.clone()], Slot::new(1, 0)).unwrap();
+    let slot = block.content.header.content.slot;
+    storage.store_block(block.clone());
+    let mut blockclique: HashMap<Slot, (BlockId, Storage)> = HashMap::new();
+    blockclique.insert(slot, (block.id, storage.clone()));
+    controller.update_blockclique_status(Default::default(), blockclique);
+    std::thread::sleep(Duration::from_millis(1000));
+
+    let traces = controller.get_operation_traces(&[operation.id]).unwrap();
+    assert_eq!(traces.len(), 1);
+    let trace = &traces[0];
+    assert_eq!(trace.slot, slot);
+    assert!(!trace.is_final);
+    assert!(trace.error.is_none());
+    assert!(trace.calls.is_empty());
+    assert!(trace.truncated);
+    manager.stop();
+}
+
 /// Create an operation for the given sender with `data` as bytecode.
 /// Return a result that should be unwrapped in the root `#[test]` routine.
 fn create_execute_sc_operation(
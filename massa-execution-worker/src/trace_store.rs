@@ -0,0 +1,71 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module provides an in-memory cache of the execution traces of final operations,
+//! indexed by operation ID. The oldest traces are dropped first when the cache is full.
+
+use massa_models::{execution::OperationTrace, operation::OperationId, prehash::PreHashMap};
+use std::collections::VecDeque;
+
+/// Cache of the execution traces of final operations
+#[derive(Default)]
+pub(crate) struct TraceStore {
+    /// traces by operation ID
+    traces: PreHashMap<OperationId, OperationTrace>,
+    /// operation IDs, oldest first
+    order: VecDeque<OperationId>,
+}
+
+impl TraceStore {
+    /// Add the traces of a slot that became final
+    pub fn extend(&mut self, traces: Vec<OperationTrace>) {
+        for mut trace in traces {
+            trace.is_final = true;
+            self.order.push_back(trace.operation_id);
+            self.traces.insert(trace.operation_id, trace);
+        }
+    }
+
+    /// Prune the store if its size is over the given limit
+    pub fn prune(&mut self, max_traces: usize) {
+        while self.order.len() > max_traces {
+            if let Some(operation_id) = self.order.pop_front() {
+                self.traces.remove(&operation_id);
+            }
+        }
+    }
+
+    /// Get the trace of an operation
+    pub fn get(&self, operation_id: &OperationId) -> Option<&OperationTrace> {
+        self.traces.get(operation_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::slot::Slot;
+
+    #[test]
+    fn test_trace_store_prune() {
+        let traces: Vec<OperationTrace> = (0..3u8)
+            .map(|index| OperationTrace {
+                operation_id: OperationId::from_bytes(&[index; 32]),
+                slot: Slot::new(1, 0),
+                is_final: false,
+                gas_used: Some(index as u64),
+                error: None,
+                calls: Vec::new(),
+                truncated: false,
+            })
+            .collect();
+        let mut store = TraceStore::default();
+        store.extend(traces);
+        store.prune(2);
+        assert!(store.get(&OperationId::from_bytes(&[0; 32])).is_none());
+        let trace = store
+            .get(&OperationId::from_bytes(&[2; 32]))
+            .expect("missing trace");
+        assert!(trace.is_final);
+        assert_eq!(trace.gas_used, Some(2));
+    }
+}
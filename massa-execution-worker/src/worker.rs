@@ -9,8 +9,8 @@ use crate::controller::{ExecutionControllerImpl, ExecutionInputData, ExecutionMa
 use crate::execution::ExecutionState;
 use crate::request_queue::RequestQueue;
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager, ExecutionOutput,
-    ReadOnlyExecutionRequest,
+    ExecutionChannels, ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
+    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
 };
 use massa_final_state::FinalState;
 use massa_models::block::BlockId;
@@ -48,7 +48,7 @@ pub(crate) struct ExecutionThread {
     // Execution state (see execution.rs) to which execution requests are sent
     execution_state: Arc<RwLock<ExecutionState>>,
     /// queue for read-only requests and response MPSCs to send back their outputs
-    readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ExecutionOutput>,
+    readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
     /// Selector controller
     selector: Box<dyn SelectorController>,
 }
@@ -416,7 +416,7 @@ impl ExecutionThread {
     /// Cancel those that are in excess if there are too many.
     fn update_readonly_requests(
         &mut self,
-        new_requests: RequestQueue<ReadOnlyExecutionRequest, ExecutionOutput>,
+        new_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
     ) {
         // Append incoming readonly requests to our readonly request queue
         // Excess requests are cancelled
@@ -616,6 +616,8 @@ impl ExecutionThread {
 /// # parameters
 /// * `config`: execution configuration
 /// * `final_state`: a thread-safe shared access to the final state for reading and writing
+/// * `selector`: access to the PoS selector
+/// * `channels`: channels used to notify subscribers of execution outputs
 ///
 /// # Returns
 /// A pair `(execution_manager, execution_controller)` where:
@@ -625,11 +627,13 @@ pub fn start_execution_worker(
     config: ExecutionConfig,
     final_state: Arc<RwLock<FinalState>>,
     selector: Box<dyn SelectorController>,
+    channels: ExecutionChannels,
 ) -> (Box<dyn ExecutionManager>, Box<dyn ExecutionController>) {
     // create an execution state
     let execution_state = Arc::new(RwLock::new(ExecutionState::new(
         config.clone(),
         final_state,
+        channels,
     )));
 
     // define the input data interface
@@ -13,4 +13,6 @@ pub enum FinalStateError {
     LedgerError(String),
     /// PoS error: {0}
     PosError(String),
+    /// snapshot error: {0}
+    SnapshotError(String),
 }
@@ -3,8 +3,14 @@
 //! This file defines a structure to list and prune previously executed operations.
 //! Used to detect operation reuse.
 
+use massa_hash::Hash;
 use massa_models::{
+    config::MAX_OPERATION_STATUS_MESSAGE_LENGTH,
     error::ModelsError,
+    execution::{
+        OperationExecutionStatus, OperationExecutionStatusDeserializer,
+        OperationExecutionStatusSerializer,
+    },
     operation::{OperationId, OperationIdDeserializer},
     prehash::PreHashMap,
     slot::{Slot, SlotDeserializer, SlotSerializer},
@@ -18,11 +24,13 @@ use nom::{
     sequence::tuple,
     IResult, Parser,
 };
+use std::collections::BTreeMap;
 use std::ops::Bound::{Excluded, Included};
 
-/// A structure to list and prune previously executed operations
+/// A structure to list and prune previously executed operations,
+/// along with their last valid slot and the outcome of their execution
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
-pub struct ExecutedOps(PreHashMap<OperationId, Slot>);
+pub struct ExecutedOps(PreHashMap<OperationId, (Slot, OperationExecutionStatus)>);
 
 impl ExecutedOps {
     /// returns the number of executed operations
@@ -45,16 +53,48 @@ impl ExecutedOps {
         self.0.contains_key(op_id)
     }
 
-    /// marks an op as executed
+    /// marks an op as executed, successfully until told otherwise by `set_status`
     pub fn insert(&mut self, op_id: OperationId, last_valid_slot: Slot) {
-        self.0.insert(op_id, last_valid_slot);
+        self.0
+            .insert(op_id, (last_valid_slot, OperationExecutionStatus::Success));
+    }
+
+    /// sets the execution status of an executed op.
+    /// Does nothing if the op is not marked as executed.
+    pub fn set_status(&mut self, op_id: &OperationId, status: OperationExecutionStatus) {
+        if let Some((_, op_status)) = self.0.get_mut(op_id) {
+            *op_status = status;
+        }
+    }
+
+    /// get the execution status of an executed op
+    pub fn get_status(&self, op_id: &OperationId) -> Option<&OperationExecutionStatus> {
+        self.0.get(op_id).map(|(_, status)| status)
     }
 
     /// Prune all operations that expire strictly before max_slot
     pub fn prune(&mut self, max_slot: Slot) {
         // TODO use slot-sorted structure for more efficient pruning (this has a linear complexity currently)
         self.0
-            .retain(|_id, last_valid_slot| *last_valid_slot >= max_slot);
+            .retain(|_id, (last_valid_slot, _status)| *last_valid_slot >= max_slot);
+    }
+
+    /// Compute a hash committing to the executed operations, their last valid slot
+    /// and the kind of their execution status, in the order of their IDs.
+    /// Used to check the integrity of bootstrapped executed operations.
+    ///
+    /// The failure messages are left out: they are free-form text from the runtime
+    /// that nodes running different versions may word differently.
+    pub fn compute_hash(&self) -> Hash {
+        let sorted_ops: BTreeMap<&OperationId, &(Slot, OperationExecutionStatus)> =
+            self.0.iter().collect();
+        let mut bytes = Vec::new();
+        for (op_id, (last_valid_slot, status)) in sorted_ops {
+            bytes.extend(op_id.to_bytes());
+            bytes.extend(last_valid_slot.to_bytes_key());
+            bytes.extend(status.kind_id().to_be_bytes());
+        }
+        Hash::compute_from(&bytes)
     }
 
     /// Get a part of the executed operations.
@@ -203,6 +243,7 @@ impl Deserializer<ExecutedOpsStreamingStep> for ExecutedOpsStreamingStepDeserial
 #[derive(Default)]
 pub struct ExecutedOpsSerializer {
     slot_serializer: SlotSerializer,
+    status_serializer: OperationExecutionStatusSerializer,
     u64_serializer: U64VarIntSerializer,
 }
 
@@ -211,6 +252,7 @@ impl ExecutedOpsSerializer {
     pub fn new() -> ExecutedOpsSerializer {
         ExecutedOpsSerializer {
             slot_serializer: SlotSerializer::new(),
+            status_serializer: OperationExecutionStatusSerializer::new(),
             u64_serializer: U64VarIntSerializer::new(),
         }
     }
@@ -225,9 +267,10 @@ impl Serializer<ExecutedOps> for ExecutedOpsSerializer {
         self.u64_serializer.serialize(&entry_count, buffer)?;
 
         // encode entries
-        for (op_id, slot) in &value.0 {
+        for (op_id, (slot, status)) in &value.0 {
             buffer.extend(op_id.to_bytes());
             self.slot_serializer.serialize(slot, buffer)?;
+            self.status_serializer.serialize(status, buffer)?;
         }
 
         Ok(())
@@ -238,6 +281,7 @@ impl Serializer<ExecutedOps> for ExecutedOpsSerializer {
 pub struct ExecutedOpsDeserializer {
     operation_id_deserializer: OperationIdDeserializer,
     slot_deserializer: SlotDeserializer,
+    status_deserializer: OperationExecutionStatusDeserializer,
     u64_deserializer: U64VarIntDeserializer,
 }
 
@@ -250,6 +294,9 @@ impl ExecutedOpsDeserializer {
                 (Included(u64::MIN), Included(u64::MAX)),
                 (Included(0), Excluded(thread_count)),
             ),
+            status_deserializer: OperationExecutionStatusDeserializer::new(
+                MAX_OPERATION_STATUS_MESSAGE_LENGTH,
+            ),
             u64_deserializer: U64VarIntDeserializer::new(Included(u64::MIN), Included(u64::MAX)),
         }
     }
@@ -269,10 +316,18 @@ impl Deserializer<ExecutedOps> for ExecutedOpsDeserializer {
                 tuple((
                     |input| self.operation_id_deserializer.deserialize(input),
                     |input| self.slot_deserializer.deserialize(input),
+                    |input| self.status_deserializer.deserialize(input),
                 )),
             ),
         )
-        .map(|elements| ExecutedOps(elements.into_iter().collect()))
+        .map(|elements| {
+            ExecutedOps(
+                elements
+                    .into_iter()
+                    .map(|(op_id, slot, status)| (op_id, (slot, status)))
+                    .collect(),
+            )
+        })
         .parse(buffer)
     }
 }
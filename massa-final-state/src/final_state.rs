@@ -10,10 +10,12 @@ use crate::{
     ExecutedOpsStreamingStep,
 };
 use massa_async_pool::{AsyncMessageId, AsyncPool, AsyncPoolChanges, Change};
+use massa_hash::Hash;
 use massa_ledger_exports::{LedgerChanges, LedgerController};
 use massa_models::{address::Address, slot::Slot};
 use massa_pos_exports::{PoSCycleStreamingStep, PoSFinalState, SelectorController};
 use std::collections::VecDeque;
+use std::ops::{Bound, RangeBounds};
 
 /// Represents a final state `(ledger, async pool, executed_ops and the state of the PoS)`
 pub struct FinalState {
@@ -82,6 +84,32 @@ impl FinalState {
             .map_err(|err| FinalStateError::PosError(err.to_string()))
     }
 
+    /// Compute a hash committing to the whole final state at its slot:
+    /// the slot, the Merkle root of the ledger, and the hashes of the async pool,
+    /// of the PoS state and of the executed operations.
+    ///
+    /// Used to check the integrity of a bootstrapped final state.
+    pub fn compute_state_hash(&self) -> Hash {
+        let mut bytes = self.slot.to_bytes_key().to_vec();
+        bytes.extend(self.ledger.get_merkle_root().to_bytes());
+        bytes.extend(self.async_pool.compute_hash().to_bytes());
+        bytes.extend(self.pos_state.compute_hash().to_bytes());
+        bytes.extend(self.executed_ops.compute_hash().to_bytes());
+        Hash::compute_from(&bytes)
+    }
+
+    /// Drop the whole final state and attach it back at the output of the last genesis slot.
+    ///
+    /// Used when a partially bootstrapped state cannot be completed and must be received again.
+    pub fn reset(&mut self) {
+        self.slot = Slot::new(0, self.config.thread_count.saturating_sub(1));
+        self.ledger.reset();
+        self.async_pool = AsyncPool::new(self.config.async_pool_config.clone());
+        self.pos_state.reset();
+        self.executed_ops = ExecutedOps::default();
+        self.changes_history.clear();
+    }
+
     /// Applies changes to the execution state at a given slot, and settles that slot forever.
     /// Once this is called, the state is attached at the output of the provided slot.
     ///
@@ -99,7 +127,7 @@ impl FinalState {
         // update current slot
         self.slot = slot;
 
-        // apply changes
+        // apply changes, the ledger updating its Merkle commitment in the same write
         self.ledger
             .apply_changes(changes.ledger_changes.clone(), self.slot);
         self.async_pool
@@ -208,6 +236,52 @@ impl FinalState {
         }
         Ok(res_changes)
     }
+
+    /// Used for bootstrap
+    /// Take the ledger changes of the addresses within `addresses` that are after `last_slot`,
+    /// and before or at `until_slot` if defined.
+    /// Used to bring a range of the ledger downloaded at `last_slot` to a later final slot.
+    ///
+    /// Error case: When the last_slot is too old for `self.changes_history` or `until_slot` is in the future
+    pub fn get_ledger_changes_part(
+        &self,
+        last_slot: Slot,
+        until_slot: Option<Slot>,
+        addresses: (Bound<Address>, Bound<Address>),
+    ) -> Result<Vec<(Slot, LedgerChanges)>, FinalStateError> {
+        let until_slot = until_slot.unwrap_or(self.slot);
+        if until_slot > self.slot {
+            return Err(FinalStateError::LedgerError(
+                "Until slot is not final yet.".to_string(),
+            ));
+        }
+        if last_slot >= until_slot {
+            return Ok(Vec::new());
+        }
+        match self.changes_history.front() {
+            Some((first_slot, _)) if *first_slot <= last_slot => {}
+            _ => {
+                return Err(FinalStateError::LedgerError(
+                    "Last slot is overflowing history.".to_string(),
+                ))
+            }
+        }
+        Ok(self
+            .changes_history
+            .iter()
+            .filter(|(slot, _)| *slot > last_slot && *slot <= until_slot)
+            .map(|(slot, changes)| {
+                let ledger_changes = changes
+                    .ledger_changes
+                    .0
+                    .iter()
+                    .filter(|(address, _)| addresses.contains(*address))
+                    .map(|(address, change)| (*address, change.clone()))
+                    .collect();
+                (*slot, LedgerChanges(ledger_changes))
+            })
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -25,6 +25,10 @@
 //! Defines a structure to list and prune previously executed operations.
 //! Used to detect operation reuse.
 //!
+//! ## `snapshot.rs`
+//! Exports the whole final state to a portable, hash-checked snapshot file,
+//! and loads it back to start a node without bootstrapping from the network.
+//!
 //! ## `bootstrap.rs`
 //! Provides serializable structures and tools for bootstrapping the final state.
 //!
@@ -43,6 +47,7 @@ mod config;
 mod error;
 mod executed_ops;
 mod final_state;
+mod snapshot;
 mod state_changes;
 
 pub use config::FinalStateConfig;
@@ -0,0 +1,342 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines the export and import of the final state to and from a portable snapshot file.
+//!
+//! A snapshot is made of:
+//! * a header: the `MASSA_SNAPSHOT` magic bytes, the format version (u32), the thread count (u8)
+//!   and the slot of the final state (slot bytes key)
+//! * a sequence of parts, each made of a kind (u8), a length (u64) and the data of the part.
+//!   The data of a part is produced by the same streaming serializers as the ones used by the bootstrap
+//!   (ledger, async pool, PoS cycles, PoS deferred credits and executed operations).
+//!   It may be followed by a graph part: the final blocks of the consensus graph at the same slot,
+//!   serialized by the caller and written and read back as is.
+//! * an end marker (u8)
+//! * the 32 bytes of a hash chained over the header and every part:
+//!   each chunk of the file is hashed along with the hash of the previous chunks.
+//!
+//! All integers are big-endian.
+//! The whole file is checked against its hash before anything is written to the final state.
+
+use crate::{ExecutedOpsStreamingStep, FinalState, FinalStateError};
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_models::slot::{Slot, SLOT_KEY_SIZE};
+use massa_pos_exports::PoSCycleStreamingStep;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Magic bytes at the start of every snapshot
+const SNAPSHOT_MAGIC: &[u8; 14] = b"MASSA_SNAPSHOT";
+/// Version of the snapshot format
+const SNAPSHOT_VERSION: u32 = 1;
+/// Maximum size of a single part, checked before allocating it
+const MAX_SNAPSHOT_PART_SIZE: u64 = 1 << 32;
+
+/// Kind of a snapshot part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum SnapshotPartKind {
+    Ledger = 0,
+    AsyncPool = 1,
+    PosCycle = 2,
+    PosCredits = 3,
+    ExecutedOps = 4,
+    Graph = 5,
+    End = 255,
+}
+
+impl TryFrom<u8> for SnapshotPartKind {
+    type Error = FinalStateError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(SnapshotPartKind::Ledger),
+            1 => Ok(SnapshotPartKind::AsyncPool),
+            2 => Ok(SnapshotPartKind::PosCycle),
+            3 => Ok(SnapshotPartKind::PosCredits),
+            4 => Ok(SnapshotPartKind::ExecutedOps),
+            5 => Ok(SnapshotPartKind::Graph),
+            255 => Ok(SnapshotPartKind::End),
+            _ => Err(FinalStateError::SnapshotError(format!(
+                "unknown snapshot part kind {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Convert an IO error into a snapshot error
+fn io_error(err: std::io::Error) -> FinalStateError {
+    FinalStateError::SnapshotError(format!("snapshot IO error: {}", err))
+}
+
+/// Writes the chunks of a snapshot while chaining their hash
+struct SnapshotWriter {
+    writer: BufWriter<File>,
+    hash: Option<Hash>,
+}
+
+impl SnapshotWriter {
+    fn write_chunk(&mut self, chunk: &[u8]) -> Result<(), FinalStateError> {
+        self.hash = Some(chain_hash(self.hash, chunk));
+        self.writer.write_all(chunk).map_err(io_error)
+    }
+
+    fn write_part(&mut self, kind: SnapshotPartKind, data: &[u8]) -> Result<(), FinalStateError> {
+        let mut chunk = Vec::with_capacity(data.len() + 9);
+        chunk.push(kind as u8);
+        chunk.extend((data.len() as u64).to_be_bytes());
+        chunk.extend(data);
+        self.write_chunk(&chunk)
+    }
+}
+
+/// Reads the chunks of a snapshot while chaining their hash
+struct SnapshotReader {
+    reader: BufReader<File>,
+    hash: Option<Hash>,
+}
+
+impl SnapshotReader {
+    fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, FinalStateError> {
+        let mut bytes = vec![0u8; len];
+        self.reader.read_exact(&mut bytes).map_err(io_error)?;
+        Ok(bytes)
+    }
+
+    /// Read the header, returning the slot of the snapshot
+    fn read_header(&mut self, thread_count: u8) -> Result<Slot, FinalStateError> {
+        let header = self.read_bytes(SNAPSHOT_MAGIC.len() + 4 + 1 + SLOT_KEY_SIZE)?;
+        self.hash = Some(chain_hash(self.hash, &header));
+        let (magic, rest) = header.split_at(SNAPSHOT_MAGIC.len());
+        if magic != SNAPSHOT_MAGIC {
+            return Err(FinalStateError::SnapshotError(
+                "not a final state snapshot".to_string(),
+            ));
+        }
+        let (version, rest) = rest.split_at(4);
+        let version = u32::from_be_bytes(version.try_into().unwrap());
+        if version != SNAPSHOT_VERSION {
+            return Err(FinalStateError::SnapshotError(format!(
+                "unsupported snapshot version {}",
+                version
+            )));
+        }
+        if rest[0] != thread_count {
+            return Err(FinalStateError::SnapshotError(format!(
+                "snapshot thread count {} does not match the node thread count {}",
+                rest[0], thread_count
+            )));
+        }
+        Ok(Slot::from_bytes_key(rest[1..].try_into().unwrap()))
+    }
+
+    /// Read the next part
+    fn read_part(&mut self) -> Result<(SnapshotPartKind, Vec<u8>), FinalStateError> {
+        let prefix = self.read_bytes(9)?;
+        let kind = SnapshotPartKind::try_from(prefix[0])?;
+        let len = u64::from_be_bytes(prefix[1..].try_into().unwrap());
+        if len > MAX_SNAPSHOT_PART_SIZE {
+            return Err(FinalStateError::SnapshotError(format!(
+                "snapshot part of {} bytes is too large",
+                len
+            )));
+        }
+        let data = self.read_bytes(len as usize)?;
+        self.hash = Some(chain_hash(self.hash, &[&prefix[..], &data[..]].concat()));
+        Ok((kind, data))
+    }
+
+    /// Read the hash at the end of the snapshot and check it against the chained hash
+    fn check_hash(&mut self) -> Result<Hash, FinalStateError> {
+        let bytes = self.read_bytes(HASH_SIZE_BYTES)?;
+        let expected = Hash::from_bytes(bytes.as_slice().try_into().unwrap());
+        match self.hash {
+            Some(hash) if hash == expected => Ok(hash),
+            _ => Err(FinalStateError::SnapshotError(
+                "snapshot hash mismatch, the file is corrupted".to_string(),
+            )),
+        }
+    }
+}
+
+/// Hash a chunk along with the hash of the previous chunks
+fn chain_hash(previous: Option<Hash>, chunk: &[u8]) -> Hash {
+    match previous {
+        Some(previous) => Hash::compute_from(&[&previous.to_bytes()[..], chunk].concat()),
+        None => Hash::compute_from(chunk),
+    }
+}
+
+impl FinalState {
+    /// Write the whole final state to a snapshot file, at the current final slot.
+    ///
+    /// # Arguments
+    /// * `path`: path of the snapshot file
+    /// * `graph`: serialized final blocks of the consensus graph at the same slot, if any
+    ///
+    /// # Returns
+    /// The hash of the snapshot
+    pub fn export_snapshot(
+        &self,
+        path: &Path,
+        graph: Option<&[u8]>,
+    ) -> Result<Hash, FinalStateError> {
+        let mut writer = SnapshotWriter {
+            writer: BufWriter::new(File::create(path).map_err(io_error)?),
+            hash: None,
+        };
+        let mut header = SNAPSHOT_MAGIC.to_vec();
+        header.extend(SNAPSHOT_VERSION.to_be_bytes());
+        header.push(self.config.thread_count);
+        header.extend(self.slot.to_bytes_key());
+        writer.write_chunk(&header)?;
+
+        // ledger
+        let mut last_key = None;
+        loop {
+            let (data, new_last_key) = self
+                .ledger
+                .get_ledger_part(&last_key)
+                .map_err(|err| FinalStateError::LedgerError(err.to_string()))?;
+            if data.is_empty() {
+                break;
+            }
+            writer.write_part(SnapshotPartKind::Ledger, &data)?;
+            last_key = new_last_key;
+        }
+
+        // async pool
+        let mut last_id = None;
+        loop {
+            let (data, new_last_id) = self
+                .async_pool
+                .get_pool_part(last_id)
+                .map_err(|err| FinalStateError::SnapshotError(err.to_string()))?;
+            if data.is_empty() {
+                break;
+            }
+            writer.write_part(SnapshotPartKind::AsyncPool, &data)?;
+            last_id = new_last_id;
+        }
+
+        // PoS cycle history
+        let mut cycle_step = PoSCycleStreamingStep::Started;
+        loop {
+            let (data, new_cycle_step) = self
+                .pos_state
+                .get_cycle_history_part(cycle_step)
+                .map_err(|err| FinalStateError::PosError(err.to_string()))?;
+            if !data.is_empty() {
+                writer.write_part(SnapshotPartKind::PosCycle, &data)?;
+            }
+            if new_cycle_step == PoSCycleStreamingStep::Finished {
+                break;
+            }
+            cycle_step = new_cycle_step;
+        }
+
+        // PoS deferred credits
+        let mut last_credits_slot = None;
+        loop {
+            let (data, new_last_credits_slot) = self
+                .pos_state
+                .get_deferred_credits_part(last_credits_slot)
+                .map_err(|err| FinalStateError::PosError(err.to_string()))?;
+            if data.is_empty() {
+                break;
+            }
+            writer.write_part(SnapshotPartKind::PosCredits, &data)?;
+            last_credits_slot = new_last_credits_slot;
+        }
+
+        // executed operations
+        let mut exec_ops_step = ExecutedOpsStreamingStep::Started;
+        while exec_ops_step != ExecutedOpsStreamingStep::Finished {
+            let (data, new_exec_ops_step) = self
+                .executed_ops
+                .get_executed_ops_part(exec_ops_step)
+                .map_err(|err| FinalStateError::SnapshotError(err.to_string()))?;
+            if !data.is_empty() {
+                writer.write_part(SnapshotPartKind::ExecutedOps, &data)?;
+            }
+            exec_ops_step = new_exec_ops_step;
+        }
+
+        // consensus graph
+        if let Some(graph) = graph {
+            writer.write_part(SnapshotPartKind::Graph, graph)?;
+        }
+
+        writer.write_part(SnapshotPartKind::End, &[])?;
+        let hash = writer.hash.expect("snapshot header was written");
+        writer.writer.write_all(hash.to_bytes()).map_err(io_error)?;
+        writer.writer.flush().map_err(io_error)?;
+        Ok(hash)
+    }
+
+    /// Load the whole final state from a snapshot file.
+    /// The file is fully checked against its hash before the final state is modified,
+    /// which is expected to be empty.
+    ///
+    /// # Returns
+    /// The hash of the snapshot and the serialized consensus graph, if the snapshot has one
+    pub fn import_snapshot(
+        &mut self,
+        path: &Path,
+    ) -> Result<(Hash, Option<Vec<u8>>), FinalStateError> {
+        let open = || -> Result<SnapshotReader, FinalStateError> {
+            Ok(SnapshotReader {
+                reader: BufReader::new(File::open(path).map_err(io_error)?),
+                hash: None,
+            })
+        };
+        let thread_count = self.config.thread_count;
+
+        // first pass: check the file
+        let mut reader = open()?;
+        reader.read_header(thread_count)?;
+        while reader.read_part()?.0 != SnapshotPartKind::End {}
+        let hash = reader.check_hash()?;
+
+        // second pass: load the final state
+        let mut reader = open()?;
+        let slot = reader.read_header(thread_count)?;
+        let mut graph = None;
+        loop {
+            let (kind, data) = reader.read_part()?;
+            match kind {
+                SnapshotPartKind::Ledger => {
+                    self.ledger
+                        .set_ledger_part(data)
+                        .map_err(|err| FinalStateError::LedgerError(err.to_string()))?;
+                }
+                SnapshotPartKind::AsyncPool => {
+                    self.async_pool
+                        .set_pool_part(&data)
+                        .map_err(|err| FinalStateError::SnapshotError(err.to_string()))?;
+                }
+                SnapshotPartKind::PosCycle => {
+                    self.pos_state
+                        .set_cycle_history_part(&data)
+                        .map_err(|err| FinalStateError::PosError(err.to_string()))?;
+                }
+                SnapshotPartKind::PosCredits => {
+                    self.pos_state
+                        .set_deferred_credits_part(&data)
+                        .map_err(|err| FinalStateError::PosError(err.to_string()))?;
+                }
+                SnapshotPartKind::ExecutedOps => {
+                    self.executed_ops
+                        .set_executed_ops_part(&data, thread_count)
+                        .map_err(|err| FinalStateError::SnapshotError(err.to_string()))?;
+                }
+                SnapshotPartKind::Graph => graph = Some(data),
+                SnapshotPartKind::End => break,
+            }
+        }
+        self.slot = slot;
+        self.changes_history.clear();
+        Ok((hash, graph))
+    }
+}
@@ -2632,6 +2632,31 @@ impl BlockGraph {
         self.max_cliques.len()
     }
 
+    /// get active block count
+    pub fn get_active_block_count(&self) -> usize {
+        self.active_index.len()
+    }
+
+    /// get incoming block count
+    pub fn get_incoming_block_count(&self) -> usize {
+        self.incoming_index.len()
+    }
+
+    /// get count of blocks waiting for their slot
+    pub fn get_waiting_for_slot_block_count(&self) -> usize {
+        self.waiting_for_slot_index.len()
+    }
+
+    /// get count of blocks waiting for their dependencies
+    pub fn get_waiting_for_dependencies_block_count(&self) -> usize {
+        self.waiting_for_dependencies_index.len()
+    }
+
+    /// get discarded block count
+    pub fn get_discarded_block_count(&self) -> usize {
+        self.discarded_index.len()
+    }
+
     /// get the clique of higher fitness
     pub fn get_blockclique(&self) -> PreHashSet<BlockId> {
         self.max_cliques
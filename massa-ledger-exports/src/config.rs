@@ -17,4 +17,6 @@ pub struct LedgerConfig {
     pub max_key_length: u8,
     /// max ledger part size
     pub max_ledger_part_size: u64,
+    /// number of final slots for which the ledger history is retained, `None` to disable it
+    pub history_length: Option<u64>,
 }
@@ -1,4 +1,7 @@
-use massa_models::{address::Address, amount::Amount, error::ModelsError, slot::Slot};
+use massa_hash::Hash;
+use massa_models::{
+    address::Address, amount::Amount, error::ModelsError, merkle::MerkleProof, slot::Slot,
+};
 use std::collections::BTreeSet;
 use std::fmt::Debug;
 
@@ -61,6 +64,79 @@ pub trait LedgerController: Send + Sync + Debug {
     /// A BTreeSet of the datastore keys
     fn get_datastore_keys(&self, addr: &Address) -> BTreeSet<Vec<u8>>;
 
+    /// Gets the sequential balance of a ledger entry as it was after the execution of a final slot
+    ///
+    /// # Returns
+    /// The sequential balance, or None if the ledger entry was not found,
+    /// or an error if the ledger history is disabled or does not retain the slot
+    fn get_sequential_balance_at_slot(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<Amount>, LedgerError>;
+
+    /// Gets the parallel balance of a ledger entry as it was after the execution of a final slot
+    ///
+    /// # Returns
+    /// The parallel balance, or None if the ledger entry was not found,
+    /// or an error if the ledger history is disabled or does not retain the slot
+    fn get_parallel_balance_at_slot(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<Amount>, LedgerError>;
+
+    /// Gets a copy of the bytecode of a ledger entry as it was after the execution of a final slot
+    ///
+    /// # Returns
+    /// A copy of the bytecode, or None if the ledger entry was not found,
+    /// or an error if the ledger history is disabled or does not retain the slot
+    fn get_bytecode_at_slot(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError>;
+
+    /// Gets a copy of the value of a datastore entry as it was after the execution of a final slot
+    ///
+    /// # Returns
+    /// A copy of the datastore value, or None if the ledger entry or datastore entry was not found,
+    /// or an error if the ledger history is disabled or does not retain the slot
+    fn get_data_entry_at_slot(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError>;
+
+    /// Gets the root of the Merkle tree committing to every sub-entry of the ledger
+    fn get_merkle_root(&self) -> Hash;
+
+    /// Gets the value of a ledger key along with its proof against the Merkle root
+    ///
+    /// # Arguments
+    /// * `key`: ledger key of a balance, bytecode or datastore entry
+    ///
+    /// # Returns
+    /// The value if it exists, and its inclusion or non-inclusion proof
+    fn get_merkle_proof(&self, key: &[u8]) -> (Option<Vec<u8>>, MerkleProof);
+
+    /// Gets the value of a ledger key as it was after the execution of a final slot,
+    /// along with its proof against the Merkle root at that slot
+    ///
+    /// # Arguments
+    /// * `key`: ledger key of a balance, bytecode or datastore entry
+    /// * `slot`: slot of the queried state
+    ///
+    /// # Returns
+    /// The Merkle root at that slot, the value if it existed and its inclusion or non-inclusion proof,
+    /// or an error if the ledger history is disabled or does not retain the slot
+    fn get_merkle_proof_at_slot(
+        &self,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<(Hash, Option<Vec<u8>>, MerkleProof), LedgerError>;
+
     /// Get a part of the ledger
     /// Used for bootstrap
     /// Return: Tuple with data and last key
@@ -69,11 +145,24 @@ pub trait LedgerController: Send + Sync + Debug {
         last_key: &Option<Vec<u8>>,
     ) -> Result<(Vec<u8>, Option<Vec<u8>>), ModelsError>;
 
+    /// Get a part of the ledger that stops before `end_key` (excluded)
+    /// Used for bootstrap, to download disjoint ranges of the ledger
+    /// Return: Tuple with data and last key
+    fn get_ledger_range_part(
+        &self,
+        last_key: &Option<Vec<u8>>,
+        end_key: Option<&[u8]>,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>), ModelsError>;
+
     /// Set a part of the ledger
     /// Used for bootstrap
     /// Return: Last key inserted
     fn set_ledger_part(&self, data: Vec<u8>) -> Result<Option<Vec<u8>>, ModelsError>;
 
+    /// Remove everything from the ledger
+    /// Used for bootstrap, when a partially received ledger must be dropped
+    fn reset(&mut self);
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug and test purposes.
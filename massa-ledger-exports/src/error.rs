@@ -15,4 +15,8 @@ pub enum LedgerError {
     MissingEntry(String),
     /// file error: `{0}`
     FileError(String),
+    /// ledger history is disabled
+    HistoryDisabled,
+    /// slot not retained in the ledger history: `{0}`
+    SlotNotRetained(String),
 }
@@ -10,6 +10,7 @@ use crate::types::{
 };
 use massa_models::address::{Address, AddressDeserializer};
 use massa_models::amount::{Amount, AmountDeserializer, AmountSerializer};
+use massa_models::execution::AddressLedgerChanges;
 use massa_models::prehash::PreHashMap;
 use massa_models::serialization::{VecU8Deserializer, VecU8Serializer};
 use massa_serialization::{
@@ -531,6 +532,59 @@ impl Applicable<LedgerChanges> for LedgerChanges {
 }
 
 impl LedgerChanges {
+    /// Summarizes the changes of each address, sorted by address
+    pub fn get_address_changes(&self) -> Vec<AddressLedgerChanges> {
+        let mut res: Vec<AddressLedgerChanges> = self
+            .0
+            .iter()
+            .map(|(address, change)| match change {
+                SetUpdateOrDelete::Set(entry) => AddressLedgerChanges {
+                    address: *address,
+                    deleted: false,
+                    sequential_balance: Some(entry.sequential_balance),
+                    parallel_balance: Some(entry.parallel_balance),
+                    bytecode_changed: true,
+                    datastore: entry
+                        .datastore
+                        .iter()
+                        .map(|(key, value)| (key.clone(), Some(value.clone())))
+                        .collect(),
+                },
+                SetUpdateOrDelete::Update(update) => AddressLedgerChanges {
+                    address: *address,
+                    deleted: false,
+                    sequential_balance: match update.sequential_balance {
+                        SetOrKeep::Set(balance) => Some(balance),
+                        SetOrKeep::Keep => None,
+                    },
+                    parallel_balance: match update.parallel_balance {
+                        SetOrKeep::Set(balance) => Some(balance),
+                        SetOrKeep::Keep => None,
+                    },
+                    bytecode_changed: matches!(update.bytecode, SetOrKeep::Set(_)),
+                    datastore: update
+                        .datastore
+                        .iter()
+                        .map(|(key, value)| match value {
+                            SetOrDelete::Set(value) => (key.clone(), Some(value.clone())),
+                            SetOrDelete::Delete => (key.clone(), None),
+                        })
+                        .collect(),
+                },
+                SetUpdateOrDelete::Delete => AddressLedgerChanges {
+                    address: *address,
+                    deleted: true,
+                    sequential_balance: None,
+                    parallel_balance: None,
+                    bytecode_changed: false,
+                    datastore: Vec::new(),
+                },
+            })
+            .collect();
+        res.sort_unstable_by_key(|changes| changes.address);
+        res
+    }
+
     /// Get an item from the `LedgerChanges`
     pub fn get(
         &self,
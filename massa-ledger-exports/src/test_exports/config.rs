@@ -21,6 +21,7 @@ impl Default for LedgerConfig {
             thread_count: THREAD_COUNT,
             max_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_ledger_part_size: LEDGER_PART_SIZE_MESSAGE_BYTES,
+            history_length: None,
         }
     }
 }
@@ -43,6 +44,7 @@ impl LedgerConfig {
                 max_key_length: MAX_DATASTORE_KEY_LENGTH,
                 max_ledger_part_size: LEDGER_PART_SIZE_MESSAGE_BYTES,
                 thread_count: THREAD_COUNT,
+                history_length: None,
             },
             initial_ledger,
             disk_ledger,
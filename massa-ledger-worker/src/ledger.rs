@@ -3,6 +3,7 @@
 //! This file defines the final ledger associating addresses to their balances, bytecode and data.
 
 use crate::ledger_db::{LedgerDB, LedgerSubEntry};
+use massa_hash::Hash;
 use massa_ledger_exports::{
     LedgerChanges, LedgerConfig, LedgerController, LedgerEntry, LedgerError,
 };
@@ -10,6 +11,7 @@ use massa_models::{
     address::Address,
     amount::{Amount, AmountDeserializer},
     error::ModelsError,
+    merkle::MerkleProof,
     slot::Slot,
 };
 use massa_serialization::{DeserializeError, Deserializer};
@@ -38,6 +40,7 @@ impl FinalLedger {
             config.thread_count,
             config.max_key_length,
             config.max_ledger_part_size,
+            config.history_length,
         );
 
         // generate the final ledger
@@ -171,6 +174,111 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.get_datastore_keys(addr)
     }
 
+    /// Gets the sequential balance of a ledger entry as it was after the execution of a final slot
+    ///
+    /// # Returns
+    /// The sequential balance, or None if the ledger entry was not found,
+    /// or an error if the ledger history is disabled or does not retain the slot
+    fn get_sequential_balance_at_slot(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<Amount>, LedgerError> {
+        let amount_deserializer =
+            AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+        Ok(self
+            .sorted_ledger
+            .get_sub_entry_at_slot(addr, LedgerSubEntry::SeqBalance, slot)?
+            .map(|bytes| {
+                amount_deserializer
+                    .deserialize::<DeserializeError>(&bytes)
+                    .expect("critical: invalid sequential balance format")
+                    .1
+            }))
+    }
+
+    /// Gets the parallel balance of a ledger entry as it was after the execution of a final slot
+    ///
+    /// # Returns
+    /// The parallel balance, or None if the ledger entry was not found,
+    /// or an error if the ledger history is disabled or does not retain the slot
+    fn get_parallel_balance_at_slot(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<Amount>, LedgerError> {
+        let amount_deserializer =
+            AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+        Ok(self
+            .sorted_ledger
+            .get_sub_entry_at_slot(addr, LedgerSubEntry::ParBalance, slot)?
+            .map(|bytes| {
+                amount_deserializer
+                    .deserialize::<DeserializeError>(&bytes)
+                    .expect("critical: invalid balance format")
+                    .1
+            }))
+    }
+
+    /// Gets a copy of the bytecode of a ledger entry as it was after the execution of a final slot
+    ///
+    /// # Returns
+    /// A copy of the bytecode, or None if the ledger entry was not found,
+    /// or an error if the ledger history is disabled or does not retain the slot
+    fn get_bytecode_at_slot(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError> {
+        self.sorted_ledger
+            .get_sub_entry_at_slot(addr, LedgerSubEntry::Bytecode, slot)
+    }
+
+    /// Gets a copy of the value of a datastore entry as it was after the execution of a final slot
+    ///
+    /// # Returns
+    /// A copy of the datastore value, or None if the ledger entry or datastore entry was not found,
+    /// or an error if the ledger history is disabled or does not retain the slot
+    fn get_data_entry_at_slot(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError> {
+        self.sorted_ledger.get_sub_entry_at_slot(
+            addr,
+            LedgerSubEntry::Datastore(key.to_owned()),
+            slot,
+        )
+    }
+
+    /// Gets the root of the Merkle tree committing to every sub-entry of the ledger
+    fn get_merkle_root(&self) -> Hash {
+        self.sorted_ledger.get_merkle_root()
+    }
+
+    /// Gets the value of a ledger key along with its proof against the Merkle root
+    ///
+    /// # Returns
+    /// The value if it exists, and its inclusion or non-inclusion proof
+    fn get_merkle_proof(&self, key: &[u8]) -> (Option<Vec<u8>>, MerkleProof) {
+        self.sorted_ledger.get_merkle_proof(key)
+    }
+
+    /// Gets the value of a ledger key as it was after the execution of a final slot,
+    /// along with its proof against the Merkle root at that slot
+    ///
+    /// # Returns
+    /// The Merkle root at that slot, the value if it existed and its inclusion or non-inclusion proof,
+    /// or an error if the ledger history is disabled or does not retain the slot
+    fn get_merkle_proof_at_slot(
+        &self,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<(Hash, Option<Vec<u8>>, MerkleProof), LedgerError> {
+        self.sorted_ledger.get_merkle_proof_at_slot(key, slot)
+    }
+
     /// Get a part of the disk ledger.
     ///
     /// Solely used by the bootstrap.
@@ -184,6 +292,20 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.get_ledger_part(last_key)
     }
 
+    /// Get a part of the disk ledger that stops before a given key.
+    ///
+    /// Solely used by the bootstrap, to download disjoint ranges of the ledger.
+    ///
+    /// # Returns
+    /// A tuple containing the data and the last returned key
+    fn get_ledger_range_part(
+        &self,
+        last_key: &Option<Vec<u8>>,
+        end_key: Option<&[u8]>,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>), ModelsError> {
+        self.sorted_ledger.get_ledger_range_part(last_key, end_key)
+    }
+
     /// Set a part of the disk ledger.
     ///
     /// Solely used by the bootstrap.
@@ -194,6 +316,12 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.set_ledger_part(data.as_bytes())
     }
 
+    /// Remove everything from the ledger
+    /// Used for bootstrap, when a partially received ledger must be dropped
+    fn reset(&mut self) {
+        self.sorted_ledger.reset();
+    }
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug and test purposes.
@@ -2,35 +2,48 @@
 
 //! Module to interact with the disk ledger
 
+use massa_hash::{Hash, HASH_SIZE_BYTES};
 use massa_ledger_exports::*;
 use massa_models::{
     address::{Address, ADDRESS_SIZE_BYTES},
-    amount::AmountSerializer,
+    amount::{Amount, AmountSerializer},
     error::ModelsError,
+    merkle::{
+        merkle_bit, merkle_empty_root, merkle_leaf_hash, merkle_node_hash, merkle_path,
+        MerkleProof, MERKLE_DEPTH,
+    },
     serialization::{VecU8Deserializer, VecU8Serializer},
-    slot::{Slot, SlotSerializer},
+    slot::{Slot, SlotDeserializer, SlotSerializer, SLOT_KEY_SIZE},
 };
-use massa_serialization::{Deserializer, Serializer};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use nom::multi::many0;
 use nom::sequence::tuple;
 use rocksdb::{
     ColumnFamilyDescriptor, Direction, IteratorMode, Options, ReadOptions, WriteBatch, DB,
 };
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 use std::ops::Bound;
 use std::path::PathBuf;
 use std::rc::Rc;
 
 #[cfg(feature = "testing")]
-use massa_models::amount::{Amount, AmountDeserializer};
+use massa_models::amount::AmountDeserializer;
 
 const LEDGER_CF: &str = "ledger";
 const METADATA_CF: &str = "metadata";
+const HISTORY_CF: &str = "history";
+const HISTORY_SLOTS_CF: &str = "history_slots";
+const MERKLE_CF: &str = "merkle";
+const MERKLE_HISTORY_CF: &str = "merkle_history";
 const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
 const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
 const CF_ERROR: &str = "critical: rocksdb column family operation failed";
 const SLOT_KEY: &[u8; 1] = b"s";
+const HISTORY_START_KEY: &[u8; 1] = b"h";
+/// Tags of the records indexed in the `history_slots` column family
+const LEDGER_HISTORY_TAG: u8 = 0;
+const MERKLE_HISTORY_TAG: u8 = 1;
 
 /// Ledger sub entry enum
 pub enum LedgerSubEntry {
@@ -47,9 +60,24 @@ pub enum LedgerSubEntry {
 /// Disk ledger DB module
 ///
 /// Contains a RocksDB DB instance
+///
+/// When the ledger history is enabled, applying changes also records the previous value
+/// of every written sub-entry in the `history` column family, under the sub-entry key
+/// (prefixed by its length) followed by the slot of the changes.
+/// The `history_slots` column family indexes those records by slot so that old ones can be pruned.
+/// The value of a sub-entry at a retained slot is then the previous value recorded
+/// by the first change after that slot, or the current value if there is none.
+///
+/// Every write to the ledger also updates, in the same batch, the compressed sparse Merkle tree
+/// committing to all the sub-entries (see `massa_models::merkle`).
+/// Its non-empty nodes are stored in the `merkle` column family, a subtree holding a single value
+/// being stored as a leaf at its root, and their history is recorded in the `merkle_history`
+/// column family in the same way as the one of the sub-entries, so that proofs can be produced
+/// at every retained slot.
 pub(crate) struct LedgerDB {
     db: DB,
     thread_count: u8,
+    history_length: Option<u64>,
     amount_serializer: AmountSerializer,
     slot_serializer: SlotSerializer,
     max_datastore_key_length: u8,
@@ -81,6 +109,117 @@ fn end_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
     }
 }
 
+/// Build the key of the history record of a sub-entry at the given slot.
+/// The sub-entry key is prefixed by its length so that the records of a sub-entry
+/// are contiguous and cannot be mixed up with the ones of a longer key.
+fn history_key(key: &[u8], slot: &Slot) -> Vec<u8> {
+    let mut history_key = (key.len() as u32).to_be_bytes().to_vec();
+    history_key.extend(key);
+    history_key.extend(slot.to_bytes_key());
+    history_key
+}
+
+/// Build the key of the Merkle tree node at the given depth on the given path:
+/// the depth followed by the first `depth` bits of the path, the other bits being zeroed.
+fn merkle_node_key(path: &Hash, depth: u16) -> Vec<u8> {
+    let mut node_key = depth.to_be_bytes().to_vec();
+    node_key.extend(path.to_bytes().iter().enumerate().map(|(index, byte)| {
+        match (depth as usize).saturating_sub(index * 8).min(8) {
+            0 => 0,
+            bits => byte & (0xff << (8 - bits)),
+        }
+    }));
+    node_key
+}
+
+/// Get the path going through the sibling of the node at the given depth on the given path
+fn merkle_sibling_path(path: &Hash, depth: u16) -> Hash {
+    let mut bytes = path.into_bytes();
+    let bit = (depth - 1) as usize;
+    bytes[bit / 8] ^= 0x80 >> (bit % 8);
+    Hash::from_bytes(&bytes)
+}
+
+/// Node of the Merkle tree, as stored in the `merkle` column family
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MerkleNode {
+    /// Root of a subtree holding a single value: its path and the hash of the value
+    Leaf { path: Hash, value_hash: Hash },
+    /// Root of a subtree holding several values: its hash
+    Inner(Hash),
+}
+
+impl MerkleNode {
+    /// Hash of the subtree of the node
+    fn hash(&self) -> Hash {
+        match self {
+            MerkleNode::Leaf { path, value_hash } => merkle_leaf_hash(path, value_hash),
+            MerkleNode::Inner(hash) => *hash,
+        }
+    }
+
+    /// Serialize the node: 0 followed by the path and the value hash for a leaf, 1 followed by the hash otherwise
+    fn to_bytes(self) -> Vec<u8> {
+        match self {
+            MerkleNode::Leaf { path, value_hash } => {
+                [&[0u8][..], path.to_bytes(), value_hash.to_bytes()].concat()
+            }
+            MerkleNode::Inner(hash) => [&[1u8][..], hash.to_bytes()].concat(),
+        }
+    }
+
+    /// Deserialize a node written by `to_bytes`
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes.split_first() {
+            Some((0, rest)) if rest.len() == 2 * HASH_SIZE_BYTES => {
+                let (path, value_hash) = rest.split_at(HASH_SIZE_BYTES);
+                Some(MerkleNode::Leaf {
+                    path: Hash::from_bytes(path.try_into().ok()?),
+                    value_hash: Hash::from_bytes(value_hash.try_into().ok()?),
+                })
+            }
+            Some((1, hash)) => Some(MerkleNode::Inner(Hash::from_bytes(hash.try_into().ok()?))),
+            _ => None,
+        }
+    }
+}
+
+/// Nodes of the Merkle tree read or updated while preparing a batch:
+/// each node is read from the DB at most once,
+/// and the nodes updated by the batch must be read from here instead of the DB
+struct MerkleNodes<'a> {
+    ledger: &'a LedgerDB,
+    nodes: HashMap<Vec<u8>, Option<MerkleNode>>,
+    updated_node_keys: HashSet<Vec<u8>>,
+}
+
+impl MerkleNodes<'_> {
+    fn get(&mut self, node_key: &[u8]) -> Option<MerkleNode> {
+        if let Some(node) = self.nodes.get(node_key) {
+            return *node;
+        }
+        let node = self.ledger.get_merkle_node(node_key);
+        self.nodes.insert(node_key.to_vec(), node);
+        node
+    }
+
+    /// Read all the unknown nodes among the given ones at once
+    fn prefetch(&mut self, node_keys: Vec<Vec<u8>>) {
+        let unknown_node_keys: Vec<Vec<u8>> = node_keys
+            .into_iter()
+            .filter(|node_key| !self.nodes.contains_key(node_key))
+            .collect();
+        let unknown_nodes = self.ledger.get_merkle_nodes(&unknown_node_keys);
+        self.nodes
+            .extend(unknown_node_keys.into_iter().zip(unknown_nodes));
+    }
+
+    fn set(&mut self, node_key: Vec<u8>, node: Option<MerkleNode>) {
+        self.nodes.insert(node_key.clone(), node);
+        self.updated_node_keys.insert(node_key);
+    }
+}
+
 #[test]
 fn test_end_prefix() {
     assert_eq!(end_prefix(&[5, 6, 7]), Some(vec![5, 6, 8]));
@@ -92,11 +231,13 @@ impl LedgerDB {
     ///
     /// # Arguments
     /// * path: path to the desired disk ledger db directory
+    /// * history_length: number of final slots for which the ledger history is retained, `None` to disable it
     pub fn new(
         path: PathBuf,
         thread_count: u8,
         max_datastore_key_length: u8,
         ledger_part_size_message_bytes: u64,
+        history_length: Option<u64>,
     ) -> Self {
         let mut db_opts = Options::default();
         db_opts.create_if_missing(true);
@@ -108,6 +249,10 @@ impl LedgerDB {
             vec![
                 ColumnFamilyDescriptor::new(LEDGER_CF, Options::default()),
                 ColumnFamilyDescriptor::new(METADATA_CF, Options::default()),
+                ColumnFamilyDescriptor::new(HISTORY_CF, Options::default()),
+                ColumnFamilyDescriptor::new(HISTORY_SLOTS_CF, Options::default()),
+                ColumnFamilyDescriptor::new(MERKLE_CF, Options::default()),
+                ColumnFamilyDescriptor::new(MERKLE_HISTORY_CF, Options::default()),
             ],
         )
         .expect(OPEN_ERROR);
@@ -115,6 +260,7 @@ impl LedgerDB {
         LedgerDB {
             db,
             thread_count,
+            history_length,
             amount_serializer: AmountSerializer::new(),
             slot_serializer: SlotSerializer::new(),
             max_datastore_key_length,
@@ -132,9 +278,12 @@ impl LedgerDB {
     /// # Arguments
     pub fn load_initial_ledger(&mut self, initial_ledger: HashMap<Address, LedgerEntry>) {
         let mut batch = WriteBatch::default();
+        let mut values = Vec::new();
         for (address, entry) in initial_ledger {
+            values.extend(self.get_entry_values(&address, &entry));
             self.put_entry(&address, entry, &mut batch);
         }
+        self.update_merkle_tree(&values, None, &mut batch);
         self.set_metadata(
             Slot::new(0, self.thread_count.saturating_sub(1)),
             &mut batch,
@@ -150,6 +299,13 @@ impl LedgerDB {
     pub fn apply_changes(&mut self, changes: LedgerChanges, slot: Slot) {
         // create the batch
         let mut batch = WriteBatch::default();
+        let values = self.get_changed_values(&changes);
+        // record the previous values of the changed sub-entries
+        if self.history_length.is_some() {
+            self.record_history(&values, slot, &mut batch);
+        }
+        // commit to the new values, recording the previous Merkle tree nodes along with the previous values
+        self.update_merkle_tree(&values, self.history_length.map(|_| slot), &mut batch);
         // for all incoming changes
         for (addr, change) in changes.0 {
             match change {
@@ -177,6 +333,562 @@ impl LedgerDB {
         self.write_batch(batch);
     }
 
+    /// Get the value written by the given changes for every touched sub-entry, `None` for a deletion.
+    ///
+    /// # Arguments
+    /// * changes: ledger changes about to be applied
+    fn get_changed_values(&self, changes: &LedgerChanges) -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+        let mut values = Vec::new();
+        for (addr, change) in changes.0.iter() {
+            match change {
+                SetUpdateOrDelete::Set(new_entry) => {
+                    values.extend(self.get_entry_values(addr, new_entry));
+                }
+                SetUpdateOrDelete::Update(entry_update) => {
+                    if let SetOrKeep::Set(balance) = &entry_update.sequential_balance {
+                        values.push((seq_balance_key!(addr), Some(self.amount_bytes(balance))));
+                    }
+                    if let SetOrKeep::Set(balance) = &entry_update.parallel_balance {
+                        values.push((par_balance_key!(addr), Some(self.amount_bytes(balance))));
+                    }
+                    if let SetOrKeep::Set(bytecode) = &entry_update.bytecode {
+                        values.push((bytecode_key!(addr), Some(bytecode.clone())));
+                    }
+                    for (key, update) in entry_update.datastore.iter() {
+                        let value = match update {
+                            SetOrDelete::Set(value) => Some(value.clone()),
+                            SetOrDelete::Delete => None,
+                        };
+                        values.push((data_key!(addr, key), value));
+                    }
+                }
+                SetUpdateOrDelete::Delete => {
+                    values.push((seq_balance_key!(addr), None));
+                    values.push((par_balance_key!(addr), None));
+                    values.push((bytecode_key!(addr), None));
+                    values.extend(
+                        self.get_datastore_keys(addr)
+                            .into_iter()
+                            .map(|key| (data_key!(addr, key), None)),
+                    );
+                }
+            }
+        }
+        values
+    }
+
+    /// Get the value of every sub-entry of a complete entry
+    fn get_entry_values(
+        &self,
+        addr: &Address,
+        ledger_entry: &LedgerEntry,
+    ) -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+        let mut values = vec![
+            (
+                seq_balance_key!(addr),
+                Some(self.amount_bytes(&ledger_entry.sequential_balance)),
+            ),
+            (
+                par_balance_key!(addr),
+                Some(self.amount_bytes(&ledger_entry.parallel_balance)),
+            ),
+            (bytecode_key!(addr), Some(ledger_entry.bytecode.clone())),
+        ];
+        values.extend(
+            ledger_entry
+                .datastore
+                .iter()
+                .map(|(key, value)| (data_key!(addr, key), Some(value.clone()))),
+        );
+        values
+    }
+
+    /// Serialize an amount as it is stored in the ledger
+    fn amount_bytes(&self, amount: &Amount) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // Amount serialization never fails
+        self.amount_serializer
+            .serialize(amount, &mut bytes)
+            .unwrap();
+        bytes
+    }
+
+    /// Record the previous value of every written sub-entry,
+    /// and prune the records that are out of the retained history.
+    ///
+    /// # Arguments
+    /// * values: sub-entries about to be written
+    /// * slot: slot of the changes
+    /// * batch: the given operation batch to update
+    fn record_history(
+        &self,
+        values: &[(Vec<u8>, Option<Vec<u8>>)],
+        slot: Slot,
+        batch: &mut WriteBatch,
+    ) {
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let history_handle = self.db.cf_handle(HISTORY_CF).expect(CF_ERROR);
+        let merkle_history_handle = self.db.cf_handle(MERKLE_HISTORY_CF).expect(CF_ERROR);
+        let slots_handle = self.db.cf_handle(HISTORY_SLOTS_CF).expect(CF_ERROR);
+        let metadata_handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+
+        // the history starts at the final slot preceding the first recorded changes
+        let history_start = match self.get_history_start() {
+            Some(start) => start,
+            None => {
+                let start = self.get_slot().unwrap_or(slot);
+                batch.put_cf(metadata_handle, HISTORY_START_KEY, start.to_bytes_key());
+                start
+            }
+        };
+
+        for (key, _) in values {
+            let previous = self.db.get_cf(ledger_handle, key).expect(CRUD_ERROR);
+            self.put_history_record(LEDGER_HISTORY_TAG, key, slot, previous.as_deref(), batch);
+        }
+
+        // prune the records of the slots that are not retained anymore
+        let history_length = self.history_length.unwrap_or(u64::MAX);
+        let slot_index = slot
+            .period
+            .saturating_mul(self.thread_count as u64)
+            .saturating_add(slot.thread as u64);
+        if let Some(cutoff_index) = slot_index.checked_sub(history_length) {
+            let cutoff = Slot::new(
+                cutoff_index / self.thread_count as u64,
+                (cutoff_index % self.thread_count as u64) as u8,
+            );
+            if cutoff > history_start {
+                // the records of the slots up to the previous history start are already pruned:
+                // seek past them instead of iterating over their deletion markers
+                let start_key = history_start.to_bytes_key();
+                let end_key = cutoff
+                    .get_next_slot(self.thread_count)
+                    .expect("slot overflow in ledger history pruning")
+                    .to_bytes_key();
+                for (key, _) in self
+                    .db
+                    .iterator_cf(
+                        slots_handle,
+                        IteratorMode::From(&start_key, Direction::Forward),
+                    )
+                    .flatten()
+                {
+                    if key[..SLOT_KEY_SIZE] >= end_key[..] {
+                        break;
+                    }
+                    let handle = match key[SLOT_KEY_SIZE] {
+                        MERKLE_HISTORY_TAG => merkle_history_handle,
+                        _ => history_handle,
+                    };
+                    batch.delete_cf(handle, &key[SLOT_KEY_SIZE + 1..]);
+                }
+                batch.delete_range_cf(slots_handle, start_key, end_key);
+                batch.put_cf(metadata_handle, HISTORY_START_KEY, cutoff.to_bytes_key());
+            }
+        }
+    }
+
+    /// Record the previous value of a key of the ledger or of the Merkle tree
+    /// in its history column family, and index the record by slot.
+    ///
+    /// # Arguments
+    /// * tag: `LEDGER_HISTORY_TAG` or `MERKLE_HISTORY_TAG`
+    /// * key: key of the sub-entry or of the Merkle tree node
+    /// * slot: slot of the changes
+    /// * previous: value before the changes, `None` if absent
+    /// * batch: the given operation batch to update
+    fn put_history_record(
+        &self,
+        tag: u8,
+        key: &[u8],
+        slot: Slot,
+        previous: Option<&[u8]>,
+        batch: &mut WriteBatch,
+    ) {
+        let cf = match tag {
+            MERKLE_HISTORY_TAG => MERKLE_HISTORY_CF,
+            _ => HISTORY_CF,
+        };
+        let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+        let slots_handle = self.db.cf_handle(HISTORY_SLOTS_CF).expect(CF_ERROR);
+        // previous value, prefixed by 1 if present, or 0 if absent
+        let previous = match previous {
+            Some(value) => [&[1u8][..], value].concat(),
+            None => vec![0u8],
+        };
+        let history_key = history_key(key, &slot);
+        batch.put_cf(
+            slots_handle,
+            [&slot.to_bytes_key()[..], &[tag], &history_key[..]].concat(),
+            b"",
+        );
+        batch.put_cf(handle, history_key, previous);
+    }
+
+    /// Update the Merkle tree with the given sub-entry values, `None` for a deletion.
+    ///
+    /// # Arguments
+    /// * values: sub-entries about to be written
+    /// * history_slot: slot of the changes if the previous nodes must be recorded in the history
+    /// * batch: the given operation batch to update
+    fn update_merkle_tree(
+        &self,
+        values: &[(Vec<u8>, Option<Vec<u8>>)],
+        history_slot: Option<Slot>,
+        batch: &mut WriteBatch,
+    ) {
+        let handle = self.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        let mut nodes = MerkleNodes {
+            ledger: self,
+            nodes: HashMap::new(),
+            updated_node_keys: HashSet::new(),
+        };
+        for (key, value) in values {
+            let path = merkle_path(key);
+            // walk down the path to its first empty node or leaf
+            let mut depth = 0;
+            let end_node = loop {
+                match nodes.get(&merkle_node_key(&path, depth)) {
+                    Some(MerkleNode::Inner(_)) => depth += 1,
+                    node => break node,
+                }
+            };
+            let new_leaf = value.as_ref().map(|value| MerkleNode::Leaf {
+                path,
+                value_hash: Hash::compute_from(value),
+            });
+
+            // update the end of the path, and get the depth of the deepest node to hash again
+            let bottom = match (end_node, new_leaf) {
+                // the leaf of another key is pushed down along with the new one,
+                // until the first bit where their paths differ
+                (
+                    Some(MerkleNode::Leaf {
+                        path: other_path, ..
+                    }),
+                    Some(leaf),
+                ) if other_path != path => {
+                    let split = (depth..MERKLE_DEPTH)
+                        .find(|bit| merkle_bit(&path, *bit) != merkle_bit(&other_path, *bit))
+                        .expect("distinct paths have a differing bit");
+                    nodes.set(merkle_node_key(&other_path, split + 1), end_node);
+                    nodes.set(merkle_node_key(&path, split + 1), Some(leaf));
+                    split
+                }
+                // the value to delete is absent
+                (
+                    Some(MerkleNode::Leaf {
+                        path: other_path, ..
+                    }),
+                    None,
+                ) if other_path != path => continue,
+                (None, None) => continue,
+                // the path ends in an empty subtree or at the leaf of the key
+                (_, new_leaf) => {
+                    nodes.set(merkle_node_key(&path, depth), new_leaf);
+                    match depth.checked_sub(1) {
+                        Some(bottom) => bottom,
+                        None => continue,
+                    }
+                }
+            };
+
+            // hash again the nodes on the path, from the bottom up to the root
+            nodes.prefetch(
+                (1..=bottom + 1)
+                    .map(|depth| merkle_node_key(&merkle_sibling_path(&path, depth), depth))
+                    .collect(),
+            );
+            for depth in (0..=bottom).rev() {
+                let child_key = merkle_node_key(&path, depth + 1);
+                let sibling_key =
+                    merkle_node_key(&merkle_sibling_path(&path, depth + 1), depth + 1);
+                let node = match (nodes.get(&child_key), nodes.get(&sibling_key)) {
+                    // a leaf without sibling is lifted up
+                    (Some(leaf @ MerkleNode::Leaf { .. }), None) => {
+                        nodes.set(child_key, None);
+                        Some(leaf)
+                    }
+                    (None, Some(leaf @ MerkleNode::Leaf { .. })) => {
+                        nodes.set(sibling_key, None);
+                        Some(leaf)
+                    }
+                    (child, sibling) => {
+                        let (child, sibling) = (
+                            child.map(|node| node.hash()),
+                            sibling.map(|node| node.hash()),
+                        );
+                        let hash = if merkle_bit(&path, depth) {
+                            merkle_node_hash(sibling, child)
+                        } else {
+                            merkle_node_hash(child, sibling)
+                        };
+                        hash.map(MerkleNode::Inner)
+                    }
+                };
+                nodes.set(merkle_node_key(&path, depth), node);
+            }
+        }
+
+        let updated_node_keys: Vec<Vec<u8>> = nodes.updated_node_keys.into_iter().collect();
+        if let Some(slot) = history_slot {
+            for (node_key, previous) in updated_node_keys.iter().zip(
+                self.db
+                    .multi_get_cf(updated_node_keys.iter().map(|node_key| (handle, node_key))),
+            ) {
+                let previous = previous.expect(CRUD_ERROR);
+                self.put_history_record(
+                    MERKLE_HISTORY_TAG,
+                    node_key,
+                    slot,
+                    previous.as_deref(),
+                    batch,
+                );
+            }
+        }
+        for node_key in updated_node_keys {
+            match nodes.nodes.get(&node_key).copied().flatten() {
+                Some(node) => batch.put_cf(handle, node_key, node.to_bytes()),
+                None => batch.delete_cf(handle, node_key),
+            }
+        }
+    }
+
+    /// Get a node of the Merkle tree, `None` if its subtree is empty
+    fn get_merkle_node(&self, node_key: &[u8]) -> Option<MerkleNode> {
+        let handle = self.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle, node_key)
+            .expect(CRUD_ERROR)
+            .map(|bytes| MerkleNode::from_bytes(&bytes).expect(CRUD_ERROR))
+    }
+
+    /// Get several nodes of the Merkle tree in a single read, `None` for the empty subtrees
+    fn get_merkle_nodes<K: AsRef<[u8]>>(&self, node_keys: &[K]) -> Vec<Option<MerkleNode>> {
+        let handle = self.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        self.db
+            .multi_get_cf(node_keys.iter().map(|node_key| (handle, node_key)))
+            .into_iter()
+            .map(|node| {
+                node.expect(CRUD_ERROR)
+                    .map(|bytes| MerkleNode::from_bytes(&bytes).expect(CRUD_ERROR))
+            })
+            .collect()
+    }
+
+    /// Get the root of the Merkle tree committing to every sub-entry of the ledger
+    pub fn get_merkle_root(&self) -> Hash {
+        self.get_merkle_node(&merkle_node_key(&merkle_empty_root(), 0))
+            .map_or_else(merkle_empty_root, |node| node.hash())
+    }
+
+    /// Get the value of a sub-entry along with its proof against the current Merkle root.
+    ///
+    /// # Arguments
+    /// * key: ledger key of the sub-entry
+    ///
+    /// # Returns
+    /// The value of the sub-entry if it exists, and its inclusion or non-inclusion proof
+    pub fn get_merkle_proof(&self, key: &[u8]) -> (Option<Vec<u8>>, MerkleProof) {
+        let (_, proof) = self
+            .build_merkle_proof(key, |node_key| Ok(self.get_merkle_node(node_key)))
+            .expect("reading the current Merkle tree cannot fail");
+        let value = self
+            .db
+            .get_cf(self.db.cf_handle(LEDGER_CF).expect(CF_ERROR), key)
+            .expect(CRUD_ERROR);
+        (value, proof)
+    }
+
+    /// Get the value of a sub-entry as it was after the execution of a given final slot,
+    /// along with its proof against the Merkle root at that slot.
+    ///
+    /// # Arguments
+    /// * key: ledger key of the sub-entry
+    /// * slot: slot of the queried state
+    ///
+    /// # Returns
+    /// The Merkle root at that slot, the value of the sub-entry if it existed and its proof,
+    /// or an error if the history is disabled or does not retain the given slot
+    pub fn get_merkle_proof_at_slot(
+        &self,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<(Hash, Option<Vec<u8>>, MerkleProof), LedgerError> {
+        self.check_history_slot(slot)?;
+        let (root, proof) = Self::build_merkle_proof(key, |node_key| {
+            Ok(self
+                .get_value_at_slot(MERKLE_CF, MERKLE_HISTORY_CF, node_key, slot)?
+                .map(|bytes| MerkleNode::from_bytes(&bytes).expect(CRUD_ERROR)))
+        })?;
+        let value = self.get_value_at_slot(LEDGER_CF, HISTORY_CF, key, slot)?;
+        Ok((root, value, proof))
+    }
+
+    /// Walk down the path of a key in the Merkle tree to build its proof.
+    ///
+    /// # Arguments
+    /// * key: ledger key of the sub-entry
+    /// * get_node: reads a node of the tree in the queried state
+    ///
+    /// # Returns
+    /// The root of the tree and the proof
+    fn build_merkle_proof<F>(key: &[u8], get_node: F) -> Result<(Hash, MerkleProof), LedgerError>
+    where
+        F: Fn(&[u8]) -> Result<Option<MerkleNode>, LedgerError>,
+    {
+        let path = merkle_path(key);
+        let root = get_node(&merkle_node_key(&path, 0))?;
+        let mut siblings = Vec::new();
+        let mut node = root;
+        let mut depth = 0;
+        while let Some(MerkleNode::Inner(_)) = node {
+            depth += 1;
+            if let Some(sibling) =
+                get_node(&merkle_node_key(&merkle_sibling_path(&path, depth), depth))?
+            {
+                siblings.push((depth, sibling.hash()));
+            }
+            node = get_node(&merkle_node_key(&path, depth))?;
+        }
+        siblings.reverse();
+        let leaf = match node {
+            Some(MerkleNode::Leaf {
+                path: leaf_path,
+                value_hash,
+            }) if leaf_path != path => Some((leaf_path, value_hash)),
+            _ => None,
+        };
+        Ok((
+            root.map_or_else(merkle_empty_root, |node| node.hash()),
+            MerkleProof { siblings, leaf },
+        ))
+    }
+
+    /// Get the slot of the final ledger, if any
+    fn get_slot(&self) -> Option<Slot> {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let slot_deserializer = SlotDeserializer::new(
+            (Bound::Included(0), Bound::Included(u64::MAX)),
+            (Bound::Included(0), Bound::Excluded(self.thread_count)),
+        );
+        self.db
+            .get_cf(handle, SLOT_KEY)
+            .expect(CRUD_ERROR)
+            .and_then(|bytes| {
+                slot_deserializer
+                    .deserialize::<DeserializeError>(&bytes)
+                    .ok()
+                    .map(|(_, slot)| slot)
+            })
+    }
+
+    /// Get the oldest slot of the ledger history, if any
+    fn get_history_start(&self) -> Option<Slot> {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle, HISTORY_START_KEY)
+            .expect(CRUD_ERROR)
+            .and_then(|bytes| bytes.as_slice().try_into().ok().map(Slot::from_bytes_key))
+    }
+
+    /// Check that the ledger history retains the given slot
+    fn check_history_slot(&self, slot: &Slot) -> Result<(), LedgerError> {
+        if self.history_length.is_none() {
+            return Err(LedgerError::HistoryDisabled);
+        }
+        let (start, end) = match (self.get_history_start(), self.get_slot()) {
+            (Some(start), Some(end)) => (start, end),
+            (None, Some(end)) if end == *slot => (end, end),
+            _ => {
+                return Err(LedgerError::SlotNotRetained(format!(
+                    "no ledger history at slot {}",
+                    slot
+                )))
+            }
+        };
+        if *slot < start || *slot > end {
+            return Err(LedgerError::SlotNotRetained(format!(
+                "slot {} is not between slots {} and {}",
+                slot, start, end
+            )));
+        }
+        Ok(())
+    }
+
+    /// Get the value of a key of the ledger or of the Merkle tree as it was after the execution
+    /// of a retained slot: the previous value recorded by the first change after that slot,
+    /// or the current value if there is none.
+    ///
+    /// # Arguments
+    /// * cf: column family of the current values
+    /// * history_cf: column family of the previous values
+    /// * key: key of the sub-entry or of the Merkle tree node
+    /// * slot: slot of the queried state, checked by `check_history_slot`
+    fn get_value_at_slot(
+        &self,
+        cf: &str,
+        history_cf: &str,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError> {
+        let prefix = history_key(key, &Slot::new(0, 0));
+        let prefix = &prefix[..prefix.len() - SLOT_KEY_SIZE];
+
+        // the first change after the queried slot holds the value at that slot
+        let handle = self.db.cf_handle(history_cf).expect(CF_ERROR);
+        let next_slot = slot
+            .get_next_slot(self.thread_count)
+            .map_err(|err| LedgerError::SlotNotRetained(err.to_string()))?;
+        let from = history_key(key, &next_slot);
+        if let Some((record_key, previous)) = self
+            .db
+            .iterator_cf(handle, IteratorMode::From(&from, Direction::Forward))
+            .flatten()
+            .next()
+        {
+            if record_key.starts_with(prefix) {
+                return Ok(match previous.split_first() {
+                    Some((&1, value)) => Some(value.to_vec()),
+                    _ => None,
+                });
+            }
+        }
+
+        // unchanged since the queried slot
+        Ok(self
+            .db
+            .get_cf(self.db.cf_handle(cf).expect(CF_ERROR), key)
+            .expect(CRUD_ERROR))
+    }
+
+    /// Get the given sub-entry of a given address, as it was after the execution of a given final slot.
+    ///
+    /// # Arguments
+    /// * addr: associated address
+    /// * ty: type of the queried sub-entry
+    /// * slot: slot of the queried state
+    ///
+    /// # Returns
+    /// An Option of the sub-entry value as bytes,
+    /// or an error if the history is disabled or does not retain the given slot
+    pub fn get_sub_entry_at_slot(
+        &self,
+        addr: &Address,
+        ty: LedgerSubEntry,
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError> {
+        self.check_history_slot(slot)?;
+        let key = match ty {
+            LedgerSubEntry::SeqBalance => seq_balance_key!(addr),
+            LedgerSubEntry::ParBalance => par_balance_key!(addr),
+            LedgerSubEntry::Bytecode => bytecode_key!(addr),
+            LedgerSubEntry::Datastore(hash) => data_key!(addr, hash),
+        };
+        self.get_value_at_slot(LEDGER_CF, HISTORY_CF, &key, slot)
+    }
+
     /// Apply the given operation batch to the disk ledger.
     ///
     /// NOTE: the batch is not saved within the object because it cannot be shared between threads safely
@@ -379,6 +1091,25 @@ impl LedgerDB {
     pub fn get_ledger_part(
         &self,
         last_key: &Option<Vec<u8>>,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>), ModelsError> {
+        self.get_ledger_range_part(last_key, None)
+    }
+
+    /// Get a part of the disk Ledger that stops before a given key.
+    /// Used in the bootstrap process to download disjoint ranges of the ledger.
+    ///
+    /// # Arguments
+    /// * last_key: key after which the part retrieving must start
+    /// * end_key: key before which the part retrieving must stop, `None` to go until the end of the ledger
+    ///
+    /// # Returns
+    /// A tuple containing:
+    /// * The ledger part as bytes
+    /// * The last taken key (this is an optimization to easily keep a reference to the last key)
+    pub fn get_ledger_range_part(
+        &self,
+        last_key: &Option<Vec<u8>>,
+        end_key: Option<&[u8]>,
     ) -> Result<(Vec<u8>, Option<Vec<u8>>), ModelsError> {
         let ser = VecU8Serializer::new();
         let key_serializer = KeySerializer::new();
@@ -386,29 +1117,33 @@ impl LedgerDB {
         let mut part = Vec::new();
         let opt = ReadOptions::default();
 
-        // Creates an iterator from the next element after the last if defined, otherwise initialize it at the first key of the ledger.
+        // Creates an iterator from the last key if defined, otherwise initialize it at the first key of the ledger.
         let db_iterator = if let Some(key) = last_key {
-            let mut iter =
-                self.db
-                    .iterator_cf_opt(handle, opt, IteratorMode::From(key, Direction::Forward));
-            iter.next();
-            iter
+            self.db
+                .iterator_cf_opt(handle, opt, IteratorMode::From(key, Direction::Forward))
         } else {
             self.db.iterator_cf_opt(handle, opt, IteratorMode::Start)
         };
-        let mut last_key = None;
+        let mut new_last_key = None;
 
         // Iterates over the whole database
         for (key, entry) in db_iterator.flatten() {
+            // The last key itself was already taken, but it may have been deleted since
+            if Some(&key[..]) == last_key.as_deref() {
+                continue;
+            }
+            if end_key.map_or(false, |end_key| &key[..] >= end_key) {
+                break;
+            }
             if (part.len() as u64) < (self.ledger_part_size_message_bytes) {
                 key_serializer.serialize(&key.to_vec(), &mut part)?;
                 ser.serialize(&entry.to_vec(), &mut part)?;
-                last_key = Some(key.to_vec());
+                new_last_key = Some(key.to_vec());
             } else {
                 break;
             }
         }
-        Ok((part, last_key))
+        Ok((part, new_last_key))
     }
 
     /// Set a part of the ledger in the database.
@@ -427,6 +1162,7 @@ impl LedgerDB {
         let key_deserializer = KeyDeserializer::new(self.max_datastore_key_length);
         let mut last_key = Rc::new(None);
         let mut batch = WriteBatch::default();
+        let mut values = Vec::new();
 
         // Since this data is coming from the network, deser to address and ser back to bytes for a security check.
         let (rest, _) = many0(|input: &'a [u8]| {
@@ -437,13 +1173,15 @@ impl LedgerDB {
             *Rc::get_mut(&mut last_key).ok_or_else(|| {
                 nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Fail))
             })? = Some(key.clone());
-            batch.put_cf(handle, key, value);
+            batch.put_cf(handle, key.clone(), value.clone());
+            values.push((key, Some(value)));
             Ok((rest, ()))
         })(data)
         .map_err(|_| ModelsError::SerializeError("Error in deserialization".to_string()))?;
 
         // Every byte should have been read
         if rest.is_empty() {
+            self.update_merkle_tree(&values, None, &mut batch);
             self.db.write(batch).expect(CRUD_ERROR);
             Ok((*last_key).clone())
         } else {
@@ -453,6 +1191,40 @@ impl LedgerDB {
         }
     }
 
+    /// Remove every entry, history record and Merkle tree node along with the metadata.
+    /// Used for bootstrap, to drop a partially received ledger that cannot be completed.
+    pub fn reset(&mut self) {
+        for cf in [
+            LEDGER_CF,
+            METADATA_CF,
+            HISTORY_CF,
+            HISTORY_SLOTS_CF,
+            MERKLE_CF,
+            MERKLE_HISTORY_CF,
+        ] {
+            let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+            let first_key = self
+                .db
+                .iterator_cf(handle, IteratorMode::Start)
+                .flatten()
+                .next()
+                .map(|(key, _)| key);
+            let last_key = self
+                .db
+                .iterator_cf(handle, IteratorMode::End)
+                .flatten()
+                .next()
+                .map(|(key, _)| key);
+            if let (Some(first_key), Some(last_key)) = (first_key, last_key) {
+                // the end of the range is excluded from the deletion
+                self.db
+                    .delete_range_cf(handle, &first_key, &last_key)
+                    .expect(CRUD_ERROR);
+                self.db.delete_cf(handle, &last_key).expect(CRUD_ERROR);
+            }
+        }
+    }
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug purposes.
@@ -464,7 +1236,6 @@ impl LedgerDB {
         &self,
     ) -> std::collections::BTreeMap<Address, massa_models::amount::Amount> {
         use massa_models::address::AddressDeserializer;
-        use massa_serialization::DeserializeError;
 
         let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
 
@@ -525,17 +1296,23 @@ impl LedgerDB {
 
 #[cfg(test)]
 mod tests {
-    use super::LedgerDB;
+    use super::{LedgerDB, LEDGER_CF, MERKLE_CF};
     use crate::ledger_db::LedgerSubEntry;
-    use massa_ledger_exports::{LedgerEntry, LedgerEntryUpdate, SetOrKeep};
+    use massa_ledger_exports::{
+        bytecode_key, data_key, par_balance_key, seq_balance_key, LedgerChanges, LedgerEntry,
+        LedgerEntryUpdate, SetOrDelete, SetOrKeep, SetUpdateOrDelete, BYTECODE_IDENT,
+        DATASTORE_IDENT, PAR_BALANCE_IDENT, SEQ_BALANCE_IDENT,
+    };
     use massa_models::{
         address::Address,
         amount::{Amount, AmountDeserializer},
+        merkle::merkle_empty_root,
+        slot::Slot,
     };
     use massa_serialization::{DeserializeError, Deserializer};
     use massa_signature::KeyPair;
-    use rocksdb::WriteBatch;
-    use std::collections::BTreeMap;
+    use rocksdb::{IteratorMode, WriteBatch};
+    use std::collections::{BTreeMap, HashMap};
     use std::ops::Bound::Included;
     use tempfile::TempDir;
 
@@ -559,7 +1336,7 @@ mod tests {
 
         // write data
         let temp_dir = TempDir::new().unwrap();
-        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000);
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000, None);
         let mut batch = WriteBatch::default();
         db.put_entry(&addr, entry, &mut batch);
         db.update_entry(&addr, entry_update, &mut batch);
@@ -612,4 +1389,291 @@ mod tests {
         let res = db.get_ledger_part(&None).unwrap();
         db.set_ledger_part(&res.0[..]).unwrap();
     }
+
+    #[test]
+    fn test_ledger_range_parts() {
+        let (a, b) = {
+            let a = Address::from_public_key(&KeyPair::generate().get_public_key());
+            let b = Address::from_public_key(&KeyPair::generate().get_public_key());
+            (a.min(b), a.max(b))
+        };
+        let entry = LedgerEntry {
+            parallel_balance: Amount::from_mantissa_scale(42, 0),
+            ..Default::default()
+        };
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000, None);
+        db.load_initial_ledger(HashMap::from([(a, entry.clone()), (b, entry)]));
+
+        // the ranges split at the address of b hold disjoint parts of the ledger
+        let (first_part, first_last_key) = db
+            .get_ledger_range_part(&None, Some(&b.to_bytes()[..]))
+            .unwrap();
+        let (second_part, _) = db
+            .get_ledger_range_part(&Some(b.to_bytes().to_vec()), None)
+            .unwrap();
+        let (whole_part, _) = db.get_ledger_part(&None).unwrap();
+        assert_eq!(first_last_key, Some(bytecode_key!(a)));
+        assert!(!second_part.is_empty());
+        assert_eq!([first_part, second_part].concat(), whole_part);
+    }
+
+    #[test]
+    fn test_ledger_reset() {
+        let a = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000, None);
+        db.load_initial_ledger(HashMap::from([(
+            a,
+            LedgerEntry {
+                parallel_balance: Amount::from_mantissa_scale(42, 0),
+                datastore: BTreeMap::from([(b"1".to_vec(), b"a".to_vec())]),
+                ..Default::default()
+            },
+        )]));
+        let (part, _) = db.get_ledger_part(&None).unwrap();
+        let root = db.get_merkle_root();
+
+        // a reset ledger is empty and can be filled again with bootstrap parts
+        db.reset();
+        assert_eq!(db.get_merkle_root(), merkle_empty_root());
+        assert!(db.get_ledger_part(&None).unwrap().0.is_empty());
+        db.set_ledger_part(&part[..]).unwrap();
+        assert_eq!(db.get_merkle_root(), root);
+    }
+
+    #[test]
+    fn test_ledger_history() {
+        let a = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let amount_deserializer =
+            AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000, Some(3));
+        let balance_at = |db: &LedgerDB, slot: Slot| {
+            db.get_sub_entry_at_slot(&a, LedgerSubEntry::ParBalance, &slot)
+                .map(|bytes| {
+                    bytes.map(|bytes| {
+                        amount_deserializer
+                            .deserialize::<DeserializeError>(&bytes)
+                            .unwrap()
+                            .1
+                    })
+                })
+        };
+
+        // initial ledger at slot (0, 31), balance update at slot (1, 0), deletion at slot (1, 1)
+        db.load_initial_ledger(HashMap::from([(
+            a,
+            LedgerEntry {
+                parallel_balance: Amount::from_mantissa_scale(42, 0),
+                datastore: BTreeMap::from([(b"1".to_vec(), b"a".to_vec())]),
+                ..Default::default()
+            },
+        )]));
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(
+            a,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                parallel_balance: SetOrKeep::Set(Amount::from_mantissa_scale(21, 0)),
+                ..Default::default()
+            }),
+        );
+        db.apply_changes(changes, Slot::new(1, 0));
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(a, SetUpdateOrDelete::Delete);
+        db.apply_changes(changes, Slot::new(1, 1));
+
+        assert_eq!(
+            balance_at(&db, Slot::new(0, 31)).unwrap(),
+            Some(Amount::from_mantissa_scale(42, 0))
+        );
+        assert_eq!(
+            balance_at(&db, Slot::new(1, 0)).unwrap(),
+            Some(Amount::from_mantissa_scale(21, 0))
+        );
+        assert_eq!(balance_at(&db, Slot::new(1, 1)).unwrap(), None);
+        assert!(balance_at(&db, Slot::new(1, 2)).is_err());
+        assert_eq!(
+            db.get_sub_entry_at_slot(
+                &a,
+                LedgerSubEntry::Datastore(b"1".to_vec()),
+                &Slot::new(1, 0)
+            )
+            .unwrap(),
+            Some(b"a".to_vec())
+        );
+
+        // only the last 3 slots are retained
+        db.apply_changes(LedgerChanges::default(), Slot::new(1, 3));
+        assert!(balance_at(&db, Slot::new(0, 31)).is_err());
+        assert_eq!(
+            balance_at(&db, Slot::new(1, 0)).unwrap(),
+            Some(Amount::from_mantissa_scale(21, 0))
+        );
+        assert_eq!(balance_at(&db, Slot::new(1, 3)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_ledger_merkle() {
+        let a = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let b = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let entry = |balance: u64, data: &[(&str, &str)]| LedgerEntry {
+            parallel_balance: Amount::from_mantissa_scale(balance, 0),
+            datastore: data
+                .iter()
+                .map(|(key, value)| (key.as_bytes().to_vec(), value.as_bytes().to_vec()))
+                .collect(),
+            ..Default::default()
+        };
+
+        // build the same ledger from changes and from an initial ledger
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000, None);
+        assert_eq!(db.get_merkle_root(), merkle_empty_root());
+        db.load_initial_ledger(HashMap::from([
+            (a, entry(42, &[("1", "a"), ("2", "b")])),
+            (b, entry(7, &[])),
+        ]));
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(
+            a,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                parallel_balance: SetOrKeep::Set(Amount::from_mantissa_scale(21, 0)),
+                datastore: BTreeMap::from([(b"2".to_vec(), SetOrDelete::Delete)]),
+                ..Default::default()
+            }),
+        );
+        db.apply_changes(changes, Slot::new(1, 0));
+        let other_temp_dir = TempDir::new().unwrap();
+        let mut other_db = LedgerDB::new(
+            other_temp_dir.path().to_path_buf(),
+            32,
+            255,
+            1_000_000,
+            None,
+        );
+        other_db.load_initial_ledger(HashMap::from([
+            (a, entry(21, &[("1", "a")])),
+            (b, entry(7, &[])),
+        ]));
+        let root = db.get_merkle_root();
+        assert_ne!(root, merkle_empty_root());
+        assert_eq!(root, other_db.get_merkle_root());
+
+        // proofs of present and absent values
+        for key in [
+            par_balance_key!(a),
+            data_key!(a, b"1".to_vec()),
+            data_key!(a, b"2".to_vec()),
+            seq_balance_key!(b),
+        ] {
+            let (value, proof) = db.get_merkle_proof(&key);
+            assert_eq!(
+                value,
+                db.db
+                    .get_cf(db.db.cf_handle(LEDGER_CF).unwrap(), &key)
+                    .unwrap()
+            );
+            assert!(proof.verify(&root, &key, value.as_deref()));
+            assert!(!proof.verify(&root, &key, Some(&b"forged"[..])));
+        }
+
+        // a ledger received through bootstrap parts has the same root
+        let bootstrap_temp_dir = TempDir::new().unwrap();
+        let bootstrap_db = LedgerDB::new(
+            bootstrap_temp_dir.path().to_path_buf(),
+            32,
+            255,
+            1_000_000,
+            None,
+        );
+        bootstrap_db
+            .set_ledger_part(&db.get_ledger_part(&None).unwrap().0)
+            .unwrap();
+        assert_eq!(root, bootstrap_db.get_merkle_root());
+
+        // the tree is compressed: each value is stored as a single leaf, below a few inner nodes
+        let merkle_handle = db.db.cf_handle(MERKLE_CF).unwrap();
+        let node_count = db
+            .db
+            .iterator_cf(merkle_handle, IteratorMode::Start)
+            .count();
+        assert!(node_count < 4 * 7, "{} nodes for 7 values", node_count);
+
+        // removing everything empties the tree
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(a, SetUpdateOrDelete::Delete);
+        changes.0.insert(b, SetUpdateOrDelete::Delete);
+        db.apply_changes(changes, Slot::new(1, 1));
+        assert_eq!(db.get_merkle_root(), merkle_empty_root());
+        assert_eq!(
+            db.db
+                .iterator_cf(merkle_handle, IteratorMode::Start)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_ledger_merkle_history() {
+        let a = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000, Some(3));
+        db.load_initial_ledger(HashMap::from([(
+            a,
+            LedgerEntry {
+                parallel_balance: Amount::from_mantissa_scale(42, 0),
+                datastore: BTreeMap::from([(b"1".to_vec(), b"a".to_vec())]),
+                ..Default::default()
+            },
+        )]));
+        let initial_root = db.get_merkle_root();
+
+        // a datastore value is updated at slot (1, 0) and the entry is deleted at slot (1, 1)
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(
+            a,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                datastore: BTreeMap::from([
+                    (b"1".to_vec(), SetOrDelete::Set(b"b".to_vec())),
+                    (b"2".to_vec(), SetOrDelete::Set(b"c".to_vec())),
+                ]),
+                ..Default::default()
+            }),
+        );
+        db.apply_changes(changes, Slot::new(1, 0));
+        let updated_root = db.get_merkle_root();
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(a, SetUpdateOrDelete::Delete);
+        db.apply_changes(changes, Slot::new(1, 1));
+        assert_eq!(db.get_merkle_root(), merkle_empty_root());
+
+        // values are proven against the root of each retained slot
+        for (slot, root, value) in [
+            (Slot::new(0, 31), initial_root, Some(b"a".to_vec())),
+            (Slot::new(1, 0), updated_root, Some(b"b".to_vec())),
+            (Slot::new(1, 1), merkle_empty_root(), None),
+        ] {
+            let key = data_key!(a, b"1".to_vec());
+            let (slot_root, slot_value, proof) = db.get_merkle_proof_at_slot(&key, &slot).unwrap();
+            assert_eq!(slot_root, root);
+            assert_eq!(slot_value, value);
+            assert!(proof.verify(&root, &key, value.as_deref()));
+        }
+        let key = data_key!(a, b"2".to_vec());
+        let (_, value, proof) = db
+            .get_merkle_proof_at_slot(&key, &Slot::new(0, 31))
+            .unwrap();
+        assert_eq!(value, None);
+        assert!(proof.verify(&initial_root, &key, None));
+        assert!(db.get_merkle_proof_at_slot(&key, &Slot::new(1, 2)).is_err());
+
+        // the Merkle history is pruned along with the ledger history
+        db.apply_changes(LedgerChanges::default(), Slot::new(1, 3));
+        assert!(db
+            .get_merkle_proof_at_slot(&key, &Slot::new(0, 31))
+            .is_err());
+        let (root, _, _) = db.get_merkle_proof_at_slot(&key, &Slot::new(1, 0)).unwrap();
+        assert_eq!(root, updated_root);
+    }
 }
@@ -22,6 +22,7 @@ pub fn create_final_ledger(
         THREAD_COUNT,
         MAX_DATASTORE_KEY_LENGTH,
         LEDGER_PART_SIZE_MESSAGE_BYTES,
+        config.history_length,
     );
     db.load_initial_ledger(initial_ledger.unwrap_or_default());
     FinalLedger {
@@ -4,7 +4,8 @@ use crate::error::ModelsError;
 use crate::prehash::PreHashed;
 use massa_hash::{Hash, HashDeserializer};
 use massa_serialization::{
-    DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+    DeserializeError, Deserializer, SerializeError, Serializer, U64VarIntDeserializer,
+    U64VarIntSerializer,
 };
 use massa_signature::PublicKey;
 use nom::error::{context, ContextError, ParseError};
@@ -245,6 +246,35 @@ impl Address {
     }
 }
 
+/// Serializer for `Address`
+#[derive(Default, Clone)]
+pub struct AddressSerializer;
+
+impl AddressSerializer {
+    /// Creates a new serializer for `Address`
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Serializer<Address> for AddressSerializer {
+    /// ## Example
+    /// ```rust
+    /// use massa_models::address::{Address, AddressSerializer};
+    /// use massa_serialization::Serializer;
+    /// use std::str::FromStr;
+    ///
+    /// let address = Address::from_str("A12hgh5ULW9o8fJE9muLNXhQENaUUswQbxPyDSq8ridnDGu5gRiJ").unwrap();
+    /// let mut bytes = Vec::new();
+    /// AddressSerializer::new().serialize(&address, &mut bytes).unwrap();
+    /// assert_eq!(bytes, address.into_bytes());
+    /// ```
+    fn serialize(&self, value: &Address, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        buffer.extend(value.to_bytes());
+        Ok(())
+    }
+}
+
 /// Deserializer for `Address`
 #[derive(Default)]
 pub struct AddressDeserializer {
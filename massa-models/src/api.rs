@@ -2,14 +2,19 @@
 
 use crate::address::ExecutionAddressCycleInfo;
 use crate::endorsement::{EndorsementId, WrappedEndorsement};
+use crate::execution::OperationExecutionStatus;
 use crate::ledger_models::LedgerData;
+use crate::merkle::MerkleProof;
+use crate::multisig::MultisigSignatures;
 use crate::node::NodeId;
-use crate::operation::{OperationId, WrappedOperation};
+use crate::operation::{OperationId, OperationType, WrappedOperation};
+use crate::output_event::SCOutputEvent;
 use crate::stats::{ConsensusStats, ExecutionStats, NetworkStats};
 use crate::{
     address::Address, amount::Amount, block::Block, block::BlockId, config::CompactConfig,
     slot::Slot, version::Version,
 };
+use massa_hash::Hash;
 use massa_signature::{PublicKey, Signature};
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
@@ -25,6 +30,9 @@ pub struct OperationInput {
     pub signature: Signature,
     /// The serialized version of the content base58 encoded
     pub serialized_content: Vec<u8>,
+    /// The signatures of the other signers, if the creator is a multisig address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigSignatures>,
 }
 
 /// node status
@@ -101,7 +109,7 @@ impl std::fmt::Display for NodeStatus {
 }
 
 /// Operation and contextual info about it
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OperationInfo {
     /// id
     pub id: OperationId,
@@ -114,6 +122,9 @@ pub struct OperationInfo {
     pub is_final: bool,
     /// the operation itself
     pub operation: WrappedOperation,
+    /// outcome of the execution of the operation,
+    /// `None` if it was not executed or if its execution is no longer remembered
+    pub execution_status: Option<OperationExecutionStatus>,
 }
 
 impl std::fmt::Display for OperationInfo {
@@ -129,6 +140,9 @@ impl std::fmt::Display for OperationInfo {
         for block_id in &self.in_blocks {
             writeln!(f, "\t- {}", block_id)?;
         }
+        if let Some(status) = &self.execution_status {
+            writeln!(f, "Execution status: {}", status)?;
+        }
         writeln!(f, "{}", self.operation)?;
         Ok(())
     }
@@ -542,6 +556,123 @@ impl std::fmt::Display for DatastoreEntryOutput {
     }
 }
 
+/// Query input for a final ledger entry as it was at a given slot
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct LedgerEntryAtSlotInput {
+    /// address of the entry
+    pub address: Address,
+    /// final slot after the execution of which the entry is read
+    pub slot: Slot,
+    /// datastore keys whose values are read
+    pub datastore_keys: Vec<Vec<u8>>,
+}
+
+/// Final ledger entry as it was at a given slot
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct LedgerEntryAtSlotOutput {
+    /// address of the entry
+    pub address: Address,
+    /// final slot after the execution of which the entry was read
+    pub slot: Slot,
+    /// sequential balance, `None` if the entry did not exist
+    pub sequential_balance: Option<Amount>,
+    /// parallel balance, `None` if the entry did not exist
+    pub parallel_balance: Option<Amount>,
+    /// bytecode, `None` if the entry did not exist
+    pub bytecode: Option<Vec<u8>>,
+    /// values of the requested datastore keys, in the same order
+    pub datastore_values: Vec<Option<Vec<u8>>>,
+}
+
+impl std::fmt::Display for LedgerEntryAtSlotOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address {} at slot {}:", self.address, self.slot)?;
+        match (self.sequential_balance, self.parallel_balance) {
+            (Some(sequential_balance), Some(parallel_balance)) => {
+                writeln!(f, "\tSequential balance: {}", sequential_balance)?;
+                writeln!(f, "\tParallel balance: {}", parallel_balance)?;
+            }
+            _ => writeln!(f, "\tNo ledger entry")?,
+        }
+        if let Some(bytecode) = &self.bytecode {
+            writeln!(f, "\tBytecode size: {} bytes", bytecode.len())?;
+        }
+        for value in self.datastore_values.iter() {
+            writeln!(f, "\tDatastore value: {:?}", value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Part of a final ledger entry whose value is proven
+#[derive(Debug, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub enum LedgerProofKey {
+    /// sequential balance
+    SequentialBalance,
+    /// parallel balance
+    ParallelBalance,
+    /// bytecode
+    Bytecode,
+    /// datastore entry of the given key
+    Datastore(Vec<u8>),
+}
+
+/// Query input for a final ledger value along with its Merkle proof.
+///
+/// Only the final ledger is committed by the Merkle root (not the async pool nor the PoS state),
+/// and proofs can be produced at the current final slot or at the past final slots retained in the ledger history.
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct LedgerProofInput {
+    /// address of the entry
+    pub address: Address,
+    /// proven part of the entry
+    pub key: LedgerProofKey,
+    /// final slot of the proof, the current final slot if `None`.
+    /// The request is rejected if a past slot is not retained in the ledger history.
+    #[serde(default)]
+    pub slot: Option<Slot>,
+}
+
+/// Final ledger value along with its Merkle proof against the ledger root at a final slot
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct LedgerProofOutput {
+    /// address of the entry
+    pub address: Address,
+    /// proven part of the entry
+    pub key: LedgerProofKey,
+    /// final slot of the ledger
+    pub slot: Slot,
+    /// Merkle root of the final ledger at that slot
+    pub root: Hash,
+    /// raw key of the value in the ledger, from which the proof path is derived
+    pub ledger_key: Vec<u8>,
+    /// raw value, `None` if absent (the proof is then a proof of non-inclusion)
+    pub value: Option<Vec<u8>>,
+    /// proof of the value against the root
+    pub proof: MerkleProof,
+}
+
+impl std::fmt::Display for LedgerProofOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address {} {:?}:", self.address, self.key)?;
+        writeln!(f, "	Final slot: {}", self.slot)?;
+        writeln!(f, "	Ledger root: {}", self.root)?;
+        match &self.value {
+            Some(value) => writeln!(f, "	Value: {:?}", value)?,
+            None => writeln!(f, "	No value")?,
+        }
+        writeln!(
+            f,
+            "	Proof: {} non-empty siblings",
+            self.proof.siblings.len()
+        )?;
+        let verified = self
+            .proof
+            .verify(&self.root, &self.ledger_key, self.value.as_deref());
+        writeln!(f, "	Proof verified: {}", verified)
+    }
+}
+
 /// filter used when retrieving SC output events
 #[derive(Default, Debug, Deserialize, Clone, Serialize)]
 pub struct EventFilter {
@@ -563,6 +694,266 @@ pub struct EventFilter {
     pub is_final: Option<bool>,
 }
 
+impl EventFilter {
+    /// Returns true if the given event passes every criterion of the filter
+    pub fn matches(&self, event: &SCOutputEvent) -> bool {
+        if let Some(start) = self.start {
+            if event.context.slot < start {
+                return false;
+            }
+        }
+        if let Some(end) = self.end {
+            if event.context.slot >= end {
+                return false;
+            }
+        }
+        if let Some(is_final) = self.is_final {
+            if event.context.is_final != is_final {
+                return false;
+            }
+        }
+        match (self.emitter_address, event.context.call_stack.front()) {
+            (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
+            (Some(_), None) => return false,
+            _ => (),
+        }
+        match (
+            self.original_caller_address,
+            event.context.call_stack.back(),
+        ) {
+            (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
+            (Some(_), None) => return false,
+            _ => (),
+        }
+        match (
+            self.original_operation_id,
+            event.context.origin_operation_id,
+        ) {
+            (Some(addr1), Some(addr2)) if addr1 != addr2 => return false,
+            (Some(_), None) => return false,
+            _ => (),
+        }
+        true
+    }
+}
+
+/// Position of an SC output event, used as a pagination cursor
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventCursor {
+    /// slot at which the event was emitted
+    pub slot: Slot,
+    /// index of the event in the slot
+    pub index_in_slot: u64,
+}
+
+/// Request for a page of archived final SC output events
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct EventPageRequest {
+    /// filter the events must match
+    pub filter: EventFilter,
+    /// optional cursor: only the events after it (excluded) are returned
+    pub after: Option<EventCursor>,
+    /// max number of events returned
+    pub limit: u64,
+}
+
+/// A page of archived final SC output events
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct EventPage {
+    /// events, sorted by slot and index in slot
+    pub events: Vec<SCOutputEvent>,
+    /// cursor to request the next page with, `None` if there are no more events.
+    /// The number of events read per request is capped,
+    /// so a page may hold less events than requested and still come with a cursor.
+    pub next_cursor: Option<EventCursor>,
+}
+
+/// Notification sent to subscribers about a block
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct BlockNotification {
+    /// block id
+    pub id: BlockId,
+    /// the slot the block is in
+    pub slot: Slot,
+    /// the block creator
+    pub creator: Address,
+    /// the block parents
+    pub parents: Vec<BlockId>,
+    /// operations included in the block
+    pub operations: Vec<OperationId>,
+    /// true if the block is final
+    pub is_final: bool,
+}
+
+/// Notification sent to subscribers about the inclusion of an operation in a block
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct OperationNotification {
+    /// operation id
+    pub id: OperationId,
+    /// the block including the operation
+    pub block_id: BlockId,
+    /// the slot of that block
+    pub slot: Slot,
+    /// true if the including block is final
+    pub is_final: bool,
+}
+
+/// Something that happened to an address, recorded in its history
+#[derive(Debug, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub enum AddressHistoryItem {
+    /// operation sent by the address
+    OperationSent {
+        /// operation id
+        operation_id: OperationId,
+        /// false if the execution of the operation failed after paying the fees
+        success: bool,
+    },
+    /// coins received through a transaction
+    TransactionReceived {
+        /// operation id
+        operation_id: OperationId,
+        /// sender of the transaction
+        sender: Address,
+        /// amount of coins received
+        amount: Amount,
+    },
+    /// rolls bought by the address
+    RollBuy {
+        /// operation id
+        operation_id: OperationId,
+        /// number of rolls bought
+        roll_count: u64,
+    },
+    /// rolls sold by the address
+    RollSell {
+        /// operation id
+        operation_id: OperationId,
+        /// number of rolls sold
+        roll_count: u64,
+    },
+    /// asynchronous message received by the address
+    AsyncMessageReceived {
+        /// sender of the message
+        sender: Address,
+        /// function called by the message
+        handler: String,
+        /// coins sent with the message
+        coins: Amount,
+        /// false if the message execution failed and the sender was reimbursed
+        success: bool,
+    },
+}
+
+impl std::fmt::Display for AddressHistoryItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressHistoryItem::OperationSent {
+                operation_id,
+                success,
+            } => write!(
+                f,
+                "Sent operation {}{}",
+                operation_id,
+                if *success { "" } else { " (failed)" }
+            ),
+            AddressHistoryItem::TransactionReceived {
+                operation_id,
+                sender,
+                amount,
+            } => write!(
+                f,
+                "Received {} coins from {} in operation {}",
+                amount, sender, operation_id
+            ),
+            AddressHistoryItem::RollBuy {
+                operation_id,
+                roll_count,
+            } => write!(
+                f,
+                "Bought {} rolls in operation {}",
+                roll_count, operation_id
+            ),
+            AddressHistoryItem::RollSell {
+                operation_id,
+                roll_count,
+            } => write!(f, "Sold {} rolls in operation {}", roll_count, operation_id),
+            AddressHistoryItem::AsyncMessageReceived {
+                sender,
+                handler,
+                coins,
+                success,
+            } => write!(
+                f,
+                "Received async message from {} calling {} with {} coins{}",
+                sender,
+                handler,
+                coins,
+                if *success { "" } else { " (failed)" }
+            ),
+        }
+    }
+}
+
+/// Position of an entry in the history of an address, used as a pagination cursor
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AddressHistoryCursor {
+    /// slot at which the entry was recorded
+    pub slot: Slot,
+    /// index of the entry among the ones recorded at that slot
+    pub index_in_slot: u64,
+}
+
+/// An entry of the history of an address
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressHistoryEntry {
+    /// position of the entry
+    pub cursor: AddressHistoryCursor,
+    /// what happened
+    pub item: AddressHistoryItem,
+}
+
+impl std::fmt::Display for AddressHistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.cursor.slot, self.item)
+    }
+}
+
+/// Request for a page of the final history of an address
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressHistoryRequest {
+    /// address
+    pub address: Address,
+    /// optional cursor: only the entries after it (excluded) are returned
+    pub after: Option<AddressHistoryCursor>,
+    /// max number of entries returned
+    pub limit: u64,
+}
+
+/// A page of the final history of an address
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressHistoryPage {
+    /// entries, sorted by slot and index in slot
+    pub entries: Vec<AddressHistoryEntry>,
+    /// cursor to request the next page with, `None` if there are no more entries
+    pub next_cursor: Option<AddressHistoryCursor>,
+}
+
+impl std::fmt::Display for AddressHistoryPage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in self.entries.iter() {
+            writeln!(f, "{}", entry)?;
+        }
+        if let Some(cursor) = self.next_cursor {
+            writeln!(
+                f,
+                "More entries after slot {} index {}",
+                cursor.slot, cursor.index_in_slot
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// read only bytecode execution request
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct ReadOnlyBytecodeExecution {
@@ -592,3 +983,13 @@ pub struct ReadOnlyCall {
     /// caller's address, optional
     pub caller_address: Option<Address>,
 }
+
+/// operation estimation request
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct EstimateOperation {
+    /// operation to estimate. Only `ExecuteSC` and `CallSC` operations are executed,
+    /// the gas limit of the execution is the `max_gas` of the operation
+    pub op: OperationType,
+    /// address of the operation creator, optional
+    pub caller_address: Option<Address>,
+}
@@ -5,6 +5,7 @@ use crate::wrapped::{Id, Wrapped, WrappedContent, WrappedDeserializer, WrappedSe
 use crate::{
     endorsement::{Endorsement, EndorsementDeserializer, WrappedEndorsement},
     error::ModelsError,
+    multisig::MultisigSignatures,
     operation::{OperationId, OperationIdsDeserializer, OperationIdsSerializer, WrappedOperation},
     slot::{Slot, SlotDeserializer, SlotSerializer},
 };
@@ -30,7 +31,7 @@ use std::ops::Bound::{Excluded, Included};
 use std::str::FromStr;
 
 /// Size in bytes of a serialized block ID
-const BLOCK_ID_SIZE_BYTES: usize = massa_hash::HASH_SIZE_BYTES;
+pub const BLOCK_ID_SIZE_BYTES: usize = massa_hash::HASH_SIZE_BYTES;
 
 /// block id
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
@@ -123,12 +124,14 @@ impl WrappedContent for Block {
             id: U::new(*content.header.id.get_hash()),
             content,
             serialized_data: content_serialized,
+            multisig: None,
         })
     }
 
     fn serialize(
         _signature: &Signature,
         _creator_public_key: &PublicKey,
+        _multisig: &Option<MultisigSignatures>,
         serialized_content: &[u8],
         buffer: &mut Vec<u8>,
     ) -> Result<(), SerializeError> {
@@ -157,6 +160,7 @@ impl WrappedContent for Block {
                 id: U::new(*content.header.id.get_hash()),
                 content,
                 serialized_data: buffer[..buffer.len() - rest.len()].to_vec(),
+                multisig: None,
             },
         ))
     }
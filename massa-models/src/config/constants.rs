@@ -64,7 +64,7 @@ lazy_static::lazy_static! {
         if cfg!(feature = "sandbox") {
             "SAND.0.0"
         } else {
-            "TEST.14.7"
+            "TEST.14.8"
         }
         .parse()
         .unwrap()
@@ -128,6 +128,12 @@ pub const MAX_DATASTORE_ENTRY_COUNT: u64 = u64::MAX;
 pub const MAX_FUNCTION_NAME_LENGTH: u16 = u16::MAX;
 /// Maximum size of parameters in call sc
 pub const MAX_PARAMETERS_SIZE: u32 = 10_000_000;
+/// Maximum length of the failure message stored in an operation execution status
+pub const MAX_OPERATION_STATUS_MESSAGE_LENGTH: u16 = 1024;
+/// Maximum number of archived events read to answer a request for a page of events
+pub const MAX_EVENT_ARCHIVE_SCANNED_KEYS: u64 = 10_000;
+/// Maximum number of public keys of a multisig address
+pub const MAX_MULTISIG_PUBLIC_KEYS: u32 = 16;
 /// Maximum length of rng_seed in thread cycle
 pub const MAX_RNG_SEED_LENGTH: u32 = PERIODS_PER_CYCLE.saturating_mul(THREAD_COUNT as u64) as u32;
 /// Maximum length of rolls_update in thread cycle
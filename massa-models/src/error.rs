@@ -34,6 +34,8 @@ pub enum ModelsError {
     AddressParseError,
     /// checked operation error
     CheckedOperationError(String),
+    /// multisig error: {0}
+    MultisigError(String),
     /// invalid version identifier: {0}
     InvalidVersionError(String),
     /// invalid ledger change: {0}
@@ -1,6 +1,23 @@
-use std::{collections::VecDeque, fmt::Display};
+use std::{collections::VecDeque, fmt::Display, ops::Bound::Included};
 
-use crate::{output_event::SCOutputEvent, slot::Slot};
+use crate::{
+    address::Address,
+    amount::Amount,
+    config::MAX_OPERATION_STATUS_MESSAGE_LENGTH,
+    operation::OperationId,
+    output_event::SCOutputEvent,
+    serialization::{StringDeserializer, StringSerializer},
+    slot::Slot,
+};
+use massa_hash::Hash;
+use massa_serialization::{
+    Deserializer, SerializeError, Serializer, U16VarIntDeserializer, U16VarIntSerializer,
+    U32VarIntDeserializer, U32VarIntSerializer,
+};
+use nom::{
+    error::{context, ContextError, ParseError},
+    IResult, Parser,
+};
 use serde::{Deserialize, Serialize};
 
 /// The result of the read-only execution.
@@ -24,6 +41,163 @@ pub struct ExecuteReadOnlyResponse {
     pub output_events: VecDeque<SCOutputEvent>,
 }
 
+/// Changes made by an execution to the ledger entry of an address
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AddressLedgerChanges {
+    /// address whose ledger entry changed
+    pub address: Address,
+    /// true if the ledger entry was deleted
+    pub deleted: bool,
+    /// new sequential balance, if it changed
+    pub sequential_balance: Option<Amount>,
+    /// new parallel balance, if it changed
+    pub parallel_balance: Option<Amount>,
+    /// true if the bytecode changed
+    pub bytecode_changed: bool,
+    /// changed datastore entries: new value, or None if the entry was deleted
+    pub datastore: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl Display for AddressLedgerChanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address {}:", self.address)?;
+        if self.deleted {
+            return writeln!(f, "\tLedger entry deleted");
+        }
+        if let Some(balance) = self.sequential_balance {
+            writeln!(f, "\tSequential balance: {}", balance)?;
+        }
+        if let Some(balance) = self.parallel_balance {
+            writeln!(f, "\tParallel balance: {}", balance)?;
+        }
+        if self.bytecode_changed {
+            writeln!(f, "\tBytecode changed")?;
+        }
+        for (key, value) in self.datastore.iter() {
+            match value {
+                Some(value) => writeln!(f, "\tDatastore set {:?}: {:?}", key, value)?,
+                None => writeln!(f, "\tDatastore deleted {:?}", key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The estimated cost and effects of an operation, obtained by executing it in read-only mode.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OperationEstimation {
+    /// The slot at which the read-only execution occurred.
+    pub executed_at: Slot,
+    /// The result of the read-only execution.
+    pub result: ReadOnlyResult,
+    /// The gas consumed by the execution.
+    /// If the execution failed, it is the max gas of the operation:
+    /// the runtime does not report the gas used before a failure,
+    /// and the max gas is entirely consumed when the execution runs out of gas.
+    pub gas_cost: u64,
+    /// The ledger changes caused by the execution.
+    pub ledger_changes: Vec<AddressLedgerChanges>,
+    /// The output events generated by the execution.
+    pub output_events: VecDeque<SCOutputEvent>,
+    /// A fee that should get the operation included in the next block of its thread,
+    /// given the operations currently in the pool.
+    pub suggested_fee: Amount,
+}
+
+impl Display for OperationEstimation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Executed at slot: {}", self.executed_at)?;
+        writeln!(
+            f,
+            "Result: {}",
+            match &self.result {
+                ReadOnlyResult::Error(e) =>
+                    format!("an error occurred during the execution: {}", e),
+                ReadOnlyResult::Ok => "ok".to_string(),
+            }
+        )?;
+        writeln!(f, "Gas cost: {}", self.gas_cost)?;
+        writeln!(f, "Suggested fee: {}", self.suggested_fee)?;
+        if !self.ledger_changes.is_empty() {
+            writeln!(f, "Ledger changes:")?;
+            for changes in self.ledger_changes.iter() {
+                write!(f, "{}", changes)?;
+            }
+        }
+        if !self.output_events.is_empty() {
+            writeln!(f, "Generated events:",)?;
+            for event in self.output_events.iter() {
+                writeln!(f, "{}", event)?; // id already displayed in event
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The effects of signed operations executed in read-only mode, as if they were included in a block.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OperationsSimulation {
+    /// The slot at which the operations were executed.
+    pub executed_at: Slot,
+    /// The result of the simulation.
+    /// It is an error if an operation could not be included in a block, runtime errors are reported as events.
+    pub result: ReadOnlyResult,
+    /// The simulated operations, in execution order.
+    pub operation_ids: Vec<OperationId>,
+    /// The ledger changes caused by the operations.
+    pub ledger_changes: Vec<AddressLedgerChanges>,
+    /// The new roll counts of the addresses whose rolls changed.
+    pub roll_changes: Vec<(Address, u64)>,
+    /// The deferred credits set by the operations, by target slot.
+    pub deferred_credits: Vec<(Slot, Address, Amount)>,
+    /// The output events generated by the operations.
+    pub output_events: VecDeque<SCOutputEvent>,
+}
+
+impl Display for OperationsSimulation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Executed at slot: {}", self.executed_at)?;
+        writeln!(
+            f,
+            "Result: {}",
+            match &self.result {
+                ReadOnlyResult::Error(e) =>
+                    format!("an error occurred during the execution: {}", e),
+                ReadOnlyResult::Ok => "ok".to_string(),
+            }
+        )?;
+        writeln!(f, "Operations:")?;
+        for id in self.operation_ids.iter() {
+            writeln!(f, "\t{}", id)?;
+        }
+        if !self.ledger_changes.is_empty() {
+            writeln!(f, "Ledger changes:")?;
+            for changes in self.ledger_changes.iter() {
+                write!(f, "{}", changes)?;
+            }
+        }
+        if !self.roll_changes.is_empty() {
+            writeln!(f, "Roll changes:")?;
+            for (address, roll_count) in self.roll_changes.iter() {
+                writeln!(f, "\t{}: {} rolls", address, roll_count)?;
+            }
+        }
+        if !self.deferred_credits.is_empty() {
+            writeln!(f, "Deferred credits:")?;
+            for (slot, address, amount) in self.deferred_credits.iter() {
+                writeln!(f, "\t{} at slot {}: {}", address, slot, amount)?;
+            }
+        }
+        if !self.output_events.is_empty() {
+            writeln!(f, "Generated events:",)?;
+            for event in self.output_events.iter() {
+                writeln!(f, "{}", event)?; // id already displayed in event
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Display for ExecuteReadOnlyResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Executed at slot: {}", self.executed_at)?;
@@ -45,3 +219,423 @@ impl Display for ExecuteReadOnlyResponse {
         Ok(())
     }
 }
+
+/// A datastore value recorded in a trace by its length and hash, to keep traces small
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TracedValue {
+    /// length of the value in bytes
+    pub length: usize,
+    /// hash of the value
+    pub hash: Hash,
+}
+
+impl TracedValue {
+    /// Record a value by its length and hash
+    pub fn new(value: &[u8]) -> Self {
+        TracedValue {
+            length: value.len(),
+            hash: Hash::compute_from(value),
+        }
+    }
+}
+
+impl Display for TracedValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} bytes with hash {}", self.length, self.hash)
+    }
+}
+
+/// A call made by a smart contract to the node through the execution interface
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum InterfaceCall {
+    /// call to a function of another smart contract.
+    /// The gas used by the call is not recorded: the runtime meters gas for the whole execution
+    /// and does not pass the remaining gas to the interface when a call starts or ends,
+    /// so a per-call breakdown needs the runtime interface to be extended first.
+    Call {
+        /// called address
+        target: Address,
+        /// parallel coins sent with the call
+        coins: Amount,
+    },
+    /// end of the innermost call
+    FinishCall,
+    /// smart contract creation
+    CreateModule {
+        /// address of the new smart contract
+        address: Address,
+    },
+    /// bytecode change
+    SetBytecode {
+        /// address whose bytecode is set
+        address: Address,
+    },
+    /// datastore entry read, `value` is `None` if the entry was not found
+    DatastoreRead {
+        /// address owning the datastore
+        address: Address,
+        /// key of the entry
+        key: Vec<u8>,
+        /// length and hash of the value read
+        value: Option<TracedValue>,
+    },
+    /// datastore entry existence check
+    DatastoreHas {
+        /// address owning the datastore
+        address: Address,
+        /// key of the entry
+        key: Vec<u8>,
+        /// true if the entry exists
+        found: bool,
+    },
+    /// datastore entry write
+    DatastoreSet {
+        /// address owning the datastore
+        address: Address,
+        /// key of the entry
+        key: Vec<u8>,
+        /// length and hash of the new value
+        value: TracedValue,
+    },
+    /// datastore entry append
+    DatastoreAppend {
+        /// address owning the datastore
+        address: Address,
+        /// key of the entry
+        key: Vec<u8>,
+        /// length and hash of the appended value
+        value: TracedValue,
+    },
+    /// datastore entry deletion
+    DatastoreDelete {
+        /// address owning the datastore
+        address: Address,
+        /// key of the entry
+        key: Vec<u8>,
+    },
+    /// parallel coins transfer
+    Transfer {
+        /// sender
+        from: Address,
+        /// recipient
+        to: Address,
+        /// transferred amount
+        amount: Amount,
+    },
+    /// asynchronous message emission
+    SendMessage {
+        /// destination address
+        target_address: Address,
+        /// handler function of the destination
+        target_handler: String,
+        /// first slot at which the message can be executed
+        validity_start: Slot,
+        /// last slot at which the message can be executed
+        validity_end: Slot,
+        /// gas available to the message execution
+        max_gas: u64,
+        /// gas price of the message execution
+        gas_price: Amount,
+        /// coins sent with the message
+        coins: Amount,
+    },
+    /// event emission
+    GenerateEvent {
+        /// event payload
+        data: String,
+    },
+}
+
+impl Display for InterfaceCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InterfaceCall::Call { target, coins } => {
+                write!(f, "call {} with {} coins", target, coins)
+            }
+            InterfaceCall::FinishCall => write!(f, "end of call"),
+            InterfaceCall::CreateModule { address } => write!(f, "create module {}", address),
+            InterfaceCall::SetBytecode { address } => write!(f, "set bytecode of {}", address),
+            InterfaceCall::DatastoreRead {
+                address,
+                key,
+                value,
+            } => match value {
+                Some(value) => write!(f, "read {} {:?}: {}", address, key, value),
+                None => write!(f, "read {} {:?}: not found", address, key),
+            },
+            InterfaceCall::DatastoreHas {
+                address,
+                key,
+                found,
+            } => write!(f, "check {} {:?}: {}", address, key, found),
+            InterfaceCall::DatastoreSet {
+                address,
+                key,
+                value,
+            } => write!(f, "set {} {:?}: {}", address, key, value),
+            InterfaceCall::DatastoreAppend {
+                address,
+                key,
+                value,
+            } => write!(f, "append {} {:?}: {}", address, key, value),
+            InterfaceCall::DatastoreDelete { address, key } => {
+                write!(f, "delete {} {:?}", address, key)
+            }
+            InterfaceCall::Transfer { from, to, amount } => {
+                write!(f, "transfer {} coins from {} to {}", amount, from, to)
+            }
+            InterfaceCall::SendMessage {
+                target_address,
+                target_handler,
+                validity_start,
+                validity_end,
+                max_gas,
+                gas_price,
+                coins,
+            } => write!(
+                f,
+                "send message to {}::{} valid from {} to {} with {} gas at {}, {} coins",
+                target_address,
+                target_handler,
+                validity_start,
+                validity_end,
+                max_gas,
+                gas_price,
+                coins
+            ),
+            InterfaceCall::GenerateEvent { data } => write!(f, "generate event {}", data),
+        }
+    }
+}
+
+/// An interface call recorded in an execution trace
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TraceEntry {
+    /// size of the call stack when the call was made, 1 for the operation's own bytecode
+    pub depth: usize,
+    /// address at the top of the call stack
+    pub address: Address,
+    /// the call
+    pub call: InterfaceCall,
+}
+
+/// Trace of the interface calls made by the smart contracts executed by an operation
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OperationTrace {
+    /// operation that caused the execution
+    pub operation_id: OperationId,
+    /// slot at which the operation was executed
+    pub slot: Slot,
+    /// true if the slot is final
+    pub is_final: bool,
+    /// gas used by the execution, nested calls included.
+    /// There is no per-call breakdown.
+    /// `None` if the execution failed
+    pub gas_used: Option<u64>,
+    /// execution error, if any
+    pub error: Option<String>,
+    /// interface calls, in execution order
+    pub calls: Vec<TraceEntry>,
+    /// true if the calls made after the maximum number of trace entries was reached were dropped
+    pub truncated: bool,
+}
+
+impl Display for OperationTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Operation {}:", self.operation_id)?;
+        writeln!(
+            f,
+            "\tExecuted at slot {} ({})",
+            self.slot,
+            if self.is_final { "final" } else { "candidate" }
+        )?;
+        if let Some(gas_used) = self.gas_used {
+            writeln!(f, "\tGas used: {}", gas_used)?;
+        }
+        if let Some(error) = &self.error {
+            writeln!(f, "\tError: {}", error)?;
+        }
+        for entry in self.calls.iter() {
+            writeln!(
+                f,
+                "\t{}{}: {}",
+                "  ".repeat(entry.depth.saturating_sub(1)),
+                entry.address,
+                entry.call
+            )?;
+        }
+        if self.truncated {
+            writeln!(f, "\tFurther calls were not recorded")?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of the execution of an operation included in a block.
+/// Fees are spent in every case, but the effects of a failed operation are reverted.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum OperationExecutionStatus {
+    /// the operation was executed successfully
+    Success,
+    /// the operation ran out of gas
+    OutOfGas,
+    /// the sender could not afford the coins or rolls involved in the operation
+    InsufficientBalance,
+    /// the smart contract execution failed
+    VmError(String),
+    /// the operation failed for another reason
+    Failed(String),
+}
+
+impl OperationExecutionStatus {
+    /// Creates a `VmError` status, truncating the message to `MAX_OPERATION_STATUS_MESSAGE_LENGTH` bytes
+    pub fn vm_error(message: String) -> Self {
+        OperationExecutionStatus::VmError(truncate_status_message(message))
+    }
+
+    /// Creates a `Failed` status, truncating the message to `MAX_OPERATION_STATUS_MESSAGE_LENGTH` bytes
+    pub fn failed(message: String) -> Self {
+        OperationExecutionStatus::Failed(truncate_status_message(message))
+    }
+
+    /// Identifier of the kind of status, without its message
+    pub fn kind_id(&self) -> u32 {
+        match self {
+            OperationExecutionStatus::Success => 0,
+            OperationExecutionStatus::OutOfGas => 1,
+            OperationExecutionStatus::InsufficientBalance => 2,
+            OperationExecutionStatus::VmError(_) => 3,
+            OperationExecutionStatus::Failed(_) => 4,
+        }
+    }
+}
+
+/// Truncates a message on a char boundary so that it fits in `MAX_OPERATION_STATUS_MESSAGE_LENGTH` bytes
+fn truncate_status_message(mut message: String) -> String {
+    let mut length = message
+        .len()
+        .min(MAX_OPERATION_STATUS_MESSAGE_LENGTH as usize);
+    while !message.is_char_boundary(length) {
+        length -= 1;
+    }
+    message.truncate(length);
+    message
+}
+
+impl Display for OperationExecutionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationExecutionStatus::Success => write!(f, "success"),
+            OperationExecutionStatus::OutOfGas => write!(f, "out of gas"),
+            OperationExecutionStatus::InsufficientBalance => write!(f, "insufficient balance"),
+            OperationExecutionStatus::VmError(message) => write!(f, "VM error: {}", message),
+            OperationExecutionStatus::Failed(message) => write!(f, "failed: {}", message),
+        }
+    }
+}
+
+/// Serializer for `OperationExecutionStatus`
+pub struct OperationExecutionStatusSerializer {
+    u32_serializer: U32VarIntSerializer,
+    message_serializer: StringSerializer<U16VarIntSerializer, u16>,
+}
+
+impl OperationExecutionStatusSerializer {
+    /// Creates a new `OperationExecutionStatusSerializer`
+    pub fn new() -> Self {
+        Self {
+            u32_serializer: U32VarIntSerializer::new(),
+            message_serializer: StringSerializer::new(U16VarIntSerializer::new()),
+        }
+    }
+}
+
+impl Default for OperationExecutionStatusSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<OperationExecutionStatus> for OperationExecutionStatusSerializer {
+    fn serialize(
+        &self,
+        value: &OperationExecutionStatus,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.u32_serializer.serialize(&value.kind_id(), buffer)?;
+        match value {
+            OperationExecutionStatus::VmError(message)
+            | OperationExecutionStatus::Failed(message) => {
+                self.message_serializer.serialize(message, buffer)?;
+            }
+            OperationExecutionStatus::Success
+            | OperationExecutionStatus::OutOfGas
+            | OperationExecutionStatus::InsufficientBalance => {}
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for `OperationExecutionStatus`
+pub struct OperationExecutionStatusDeserializer {
+    u32_deserializer: U32VarIntDeserializer,
+    message_deserializer: StringDeserializer<U16VarIntDeserializer, u16>,
+}
+
+impl OperationExecutionStatusDeserializer {
+    /// Creates a new `OperationExecutionStatusDeserializer`
+    ///
+    /// # Arguments
+    /// * `max_message_length`: maximum length in bytes of a failure message
+    pub fn new(max_message_length: u16) -> Self {
+        Self {
+            u32_deserializer: U32VarIntDeserializer::new(Included(0), Included(4)),
+            message_deserializer: StringDeserializer::new(U16VarIntDeserializer::new(
+                Included(0),
+                Included(max_message_length),
+            )),
+        }
+    }
+}
+
+impl Deserializer<OperationExecutionStatus> for OperationExecutionStatusDeserializer {
+    /// ## Example
+    /// ```rust
+    /// use massa_models::execution::{OperationExecutionStatus, OperationExecutionStatusSerializer, OperationExecutionStatusDeserializer};
+    /// use massa_serialization::{Deserializer, Serializer, DeserializeError};
+    ///
+    /// let status = OperationExecutionStatus::vm_error("abort called".to_string());
+    /// let mut buffer = Vec::new();
+    /// OperationExecutionStatusSerializer::new().serialize(&status, &mut buffer).unwrap();
+    /// let (rest, status_deserialized) = OperationExecutionStatusDeserializer::new(1024).deserialize::<DeserializeError>(&buffer).unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(status, status_deserialized);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], OperationExecutionStatus, E> {
+        context(
+            "Failed OperationExecutionStatus deserialization",
+            |buffer| {
+                let (input, id) = self.u32_deserializer.deserialize(buffer)?;
+                match id {
+                    0 => Ok((input, OperationExecutionStatus::Success)),
+                    1 => Ok((input, OperationExecutionStatus::OutOfGas)),
+                    2 => Ok((input, OperationExecutionStatus::InsufficientBalance)),
+                    3 => context("Failed message deserialization", |input| {
+                        self.message_deserializer.deserialize(input)
+                    })
+                    .map(OperationExecutionStatus::VmError)
+                    .parse(input),
+                    _ => context("Failed message deserialization", |input| {
+                        self.message_deserializer.deserialize(input)
+                    })
+                    .map(OperationExecutionStatus::Failed)
+                    .parse(input),
+                }
+            },
+        )
+        .parse(buffer)
+    }
+}
@@ -33,6 +33,10 @@ pub mod error;
 pub mod execution;
 /// ledger related structures
 pub mod ledger_models;
+/// sparse Merkle tree commitment over the ledger
+pub mod merkle;
+/// m-of-n multisig addresses and signatures
+pub mod multisig;
 /// node related structure
 pub mod node;
 /// operations
@@ -0,0 +1,194 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Compressed sparse Merkle tree commitment over the final ledger.
+//!
+//! Every ledger key is placed on the path of a binary tree of depth 256 designated by the bits
+//! of the hash of the key (most significant bit first, 0 going left).
+//! * a subtree holding a single value hashes to its leaf `H(0 ++ H(key) ++ H(value))`:
+//!   the leaf is lifted up to the first depth at which it has a non-empty sibling
+//! * a subtree holding several values hashes to `H(1 ++ left ++ right)`, an empty child being represented by 32 zero bytes
+//! * a subtree without any value is empty, and the root of an empty tree is the zero hash
+//!
+//! The tree only depends on the set of key-value pairs, not on the order of insertion.
+//! A proof for a key that is absent from the ledger is a proof of non-inclusion:
+//! the path of the key ends either in an empty subtree or at the leaf of another key.
+
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use serde::{Deserialize, Serialize};
+
+/// Depth of the sparse Merkle tree, which is the number of bits of a key hash
+pub const MERKLE_DEPTH: u16 = (HASH_SIZE_BYTES * 8) as u16;
+
+/// Hash of an empty tree
+pub fn merkle_empty_root() -> Hash {
+    Hash::from_bytes(&[0u8; HASH_SIZE_BYTES])
+}
+
+/// Path of a key in the tree
+pub fn merkle_path(key: &[u8]) -> Hash {
+    Hash::compute_from(key)
+}
+
+/// Direction taken by a path below a node at the given depth: `true` for right, `false` for left
+pub fn merkle_bit(path: &Hash, depth: u16) -> bool {
+    let byte = path.to_bytes()[(depth / 8) as usize];
+    (byte >> (7 - depth % 8)) & 1 == 1
+}
+
+/// Hash of the leaf holding a value, given by its hash, at the given path
+pub fn merkle_leaf_hash(path: &Hash, value_hash: &Hash) -> Hash {
+    Hash::compute_from(&[&[0u8][..], path.to_bytes(), value_hash.to_bytes()].concat())
+}
+
+/// Hash of an inner node from the hashes of its children, `None` standing for an empty subtree
+pub fn merkle_node_hash(left: Option<Hash>, right: Option<Hash>) -> Option<Hash> {
+    if left.is_none() && right.is_none() {
+        return None;
+    }
+    let empty = [0u8; HASH_SIZE_BYTES];
+    Some(Hash::compute_from(
+        &[
+            &[1u8][..],
+            left.as_ref().map_or(&empty, |hash| hash.to_bytes()),
+            right.as_ref().map_or(&empty, |hash| hash.to_bytes()),
+        ]
+        .concat(),
+    ))
+}
+
+/// Inclusion (or non-inclusion) proof of a ledger value against a Merkle root
+#[derive(Debug, Default, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// hashes of the non-empty siblings of the nodes on the path of the key,
+    /// along with their depth (1 for the children of the root), deepest first
+    pub siblings: Vec<(u16, Hash)>,
+    /// for a proof of non-inclusion, the path and value hash of the leaf of another key
+    /// at which the path of the key ends, `None` if it ends in an empty subtree
+    pub leaf: Option<(Hash, Hash)>,
+}
+
+impl MerkleProof {
+    /// Compute the root implied by the proof for a key and its value (`None` if absent)
+    pub fn compute_root(&self, key: &[u8], value: Option<&[u8]>) -> Hash {
+        let path = merkle_path(key);
+        let mut current = match (value, &self.leaf) {
+            (Some(value), _) => Some(merkle_leaf_hash(&path, &Hash::compute_from(value))),
+            (None, Some((leaf_path, value_hash))) => Some(merkle_leaf_hash(leaf_path, value_hash)),
+            (None, None) => None,
+        };
+        // a leaf is lifted up to its first non-empty sibling
+        let mut lifted_leaf = current.is_some();
+        let mut siblings = self.siblings.iter().peekable();
+        for depth in (1..=MERKLE_DEPTH).rev() {
+            let sibling = siblings
+                .next_if(|(sibling_depth, _)| *sibling_depth == depth)
+                .map(|(_, hash)| *hash);
+            if lifted_leaf && sibling.is_none() {
+                continue;
+            }
+            lifted_leaf = false;
+            current = if merkle_bit(&path, depth - 1) {
+                merkle_node_hash(sibling, current)
+            } else {
+                merkle_node_hash(current, sibling)
+            };
+        }
+        current.unwrap_or_else(merkle_empty_root)
+    }
+
+    /// Check that a key has the given value (`None` if absent) in the tree of the given root
+    pub fn verify(&self, root: &Hash, key: &[u8], value: Option<&[u8]>) -> bool {
+        let leaf_is_other_key = match (&self.leaf, value) {
+            (Some((leaf_path, _)), None) => *leaf_path != merkle_path(key),
+            (Some(_), Some(_)) => false,
+            (None, _) => true,
+        };
+        leaf_is_other_key
+            && self
+                .siblings
+                .iter()
+                .all(|(depth, _)| (1..=MERKLE_DEPTH).contains(depth))
+            && self.siblings.windows(2).all(|pair| pair[0].0 > pair[1].0)
+            && self.compute_root(key, value) == *root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hash of a tree holding two values whose paths first differ at the given depth
+    fn two_values_root(path_a: &Hash, leaf_a: Hash, leaf_b: Hash, split: u16) -> Hash {
+        let mut current = if merkle_bit(path_a, split) {
+            merkle_node_hash(Some(leaf_b), Some(leaf_a))
+        } else {
+            merkle_node_hash(Some(leaf_a), Some(leaf_b))
+        };
+        for depth in (0..split).rev() {
+            current = if merkle_bit(path_a, depth) {
+                merkle_node_hash(None, current)
+            } else {
+                merkle_node_hash(current, None)
+            };
+        }
+        current.unwrap()
+    }
+
+    #[test]
+    fn test_merkle_proof() {
+        let (key_a, value_a) = (b"key_a".to_vec(), b"value_a".to_vec());
+        let (key_b, value_b) = (b"key_b".to_vec(), b"value_b".to_vec());
+        let (path_a, path_b) = (merkle_path(&key_a), merkle_path(&key_b));
+        let (value_hash_a, value_hash_b) =
+            (Hash::compute_from(&value_a), Hash::compute_from(&value_b));
+        let (leaf_a, leaf_b) = (
+            merkle_leaf_hash(&path_a, &value_hash_a),
+            merkle_leaf_hash(&path_b, &value_hash_b),
+        );
+
+        // the two leaves are siblings below the first bit where their paths differ
+        let split = (0..MERKLE_DEPTH)
+            .find(|depth| merkle_bit(&path_a, *depth) != merkle_bit(&path_b, *depth))
+            .unwrap();
+        let root = two_values_root(&path_a, leaf_a, leaf_b, split);
+        let proof_a = MerkleProof {
+            siblings: vec![(split + 1, leaf_b)],
+            leaf: None,
+        };
+        let proof_b = MerkleProof {
+            siblings: vec![(split + 1, leaf_a)],
+            leaf: None,
+        };
+        assert!(proof_a.verify(&root, &key_a, Some(&value_a)));
+        assert!(proof_b.verify(&root, &key_b, Some(&value_b)));
+        assert!(!proof_a.verify(&root, &key_a, Some(&value_b)));
+        assert!(!proof_a.verify(&root, &key_a, None));
+
+        // a key whose path ends at the leaf of another key is proven absent with that leaf
+        let key_c = (0..)
+            .map(|index| format!("key_c{}", index).into_bytes())
+            .find(|key| {
+                let path = merkle_path(key);
+                (0..=split).all(|depth| merkle_bit(&path, depth) == merkle_bit(&path_a, depth))
+            })
+            .unwrap();
+        let proof_c = MerkleProof {
+            siblings: vec![(split + 1, leaf_b)],
+            leaf: Some((path_a, value_hash_a)),
+        };
+        assert!(proof_c.verify(&root, &key_c, None));
+        assert!(!proof_c.verify(&root, &key_c, Some(&value_a)));
+        assert!(!proof_c.verify(&root, &key_a, None));
+
+        // a tree holding a single value hashes to its leaf, removing a value gives back the tree of the other one
+        let single_proof = MerkleProof::default();
+        assert_eq!(single_proof.compute_root(&key_a, Some(&value_a)), leaf_a);
+        assert!(single_proof.verify(&leaf_a, &key_a, Some(&value_a)));
+        let absent_proof = MerkleProof {
+            siblings: Vec::new(),
+            leaf: Some((path_a, value_hash_a)),
+        };
+        assert!(absent_proof.verify(&leaf_a, &key_b, None));
+        assert!(MerkleProof::default().verify(&merkle_empty_root(), &key_a, None));
+    }
+}
@@ -0,0 +1,307 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{address::Address, config::MAX_MULTISIG_PUBLIC_KEYS, error::ModelsError};
+use massa_hash::Hash;
+use massa_serialization::{
+    Deserializer, SerializeError, Serializer, U32VarIntDeserializer, U32VarIntSerializer,
+};
+use massa_signature::{
+    KeyPair, PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer,
+};
+use nom::{
+    error::{context, ContextError, ParseError},
+    multi::length_count,
+    sequence::tuple,
+    IResult, Parser,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, ops::Bound::Included};
+
+/// Prefix of the hashed data of a multisig address,
+/// so that it can never collide with the address of a single public key
+const MULTISIG_ADDRESS_DOMAIN: &[u8] = b"massa_multisig_address";
+
+/// Set of public keys and number of them that must sign for a m-of-n multisig address
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigPolicy {
+    /// minimal number of distinct signers
+    pub threshold: u32,
+    /// public keys allowed to sign, sorted by bytes and without duplicates
+    pub public_keys: Vec<PublicKey>,
+}
+
+impl MultisigPolicy {
+    /// Creates a policy from public keys given in any order.
+    /// The threshold must be between 1 and the number of distinct public keys,
+    /// and there can be at most `MAX_MULTISIG_PUBLIC_KEYS` public keys.
+    pub fn new(threshold: u32, mut public_keys: Vec<PublicKey>) -> Result<Self, ModelsError> {
+        public_keys.sort_unstable();
+        public_keys.dedup();
+        if public_keys.len() > MAX_MULTISIG_PUBLIC_KEYS as usize {
+            return Err(ModelsError::MultisigError(format!(
+                "a multisig address has at most {} public keys",
+                MAX_MULTISIG_PUBLIC_KEYS
+            )));
+        }
+        if threshold == 0 || threshold as usize > public_keys.len() {
+            return Err(ModelsError::MultisigError(format!(
+                "threshold {} is out of range for {} distinct public keys",
+                threshold,
+                public_keys.len()
+            )));
+        }
+        Ok(MultisigPolicy {
+            threshold,
+            public_keys,
+        })
+    }
+
+    /// Address controlled by this policy
+    pub fn address(&self) -> Address {
+        let mut hash_data = MULTISIG_ADDRESS_DOMAIN.to_vec();
+        hash_data.extend(self.threshold.to_be_bytes());
+        for public_key in &self.public_keys {
+            hash_data.extend(public_key.to_bytes());
+        }
+        Address(Hash::compute_from(&hash_data))
+    }
+
+    /// Index of `public_key` in the policy, if it is one of its signers
+    pub fn get_signer_index(&self, public_key: &PublicKey) -> Option<u32> {
+        self.public_keys
+            .iter()
+            .position(|key| key == public_key)
+            .map(|index| index as u32)
+    }
+}
+
+/// Signatures of an operation emitted by a multisig address.
+/// Each signature is given with the index of its signer in the policy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigSignatures {
+    /// policy of the emitting address
+    pub policy: MultisigPolicy,
+    /// signatures of the operation hash, sorted by signer index
+    pub signatures: Vec<(u32, Signature)>,
+}
+
+impl MultisigSignatures {
+    /// Creates an empty set of signatures for `policy`
+    pub fn new(policy: MultisigPolicy) -> Self {
+        MultisigSignatures {
+            policy,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Hash signed by every signer of an operation emitted by `address`
+    pub fn compute_signed_hash(address: &Address, serialized_content: &[u8]) -> Hash {
+        let mut hash_data = address.to_bytes().to_vec();
+        hash_data.extend(serialized_content);
+        Hash::compute_from(&hash_data)
+    }
+
+    /// Adds (or replaces) the signature of `keypair` over `hash`.
+    /// Fails if `keypair` is not one of the signers of the policy.
+    pub fn sign(&mut self, hash: &Hash, keypair: &KeyPair) -> Result<(), ModelsError> {
+        let index = self
+            .policy
+            .get_signer_index(&keypair.get_public_key())
+            .ok_or_else(|| {
+                ModelsError::MultisigError(format!(
+                    "{} is not a signer of the multisig address",
+                    keypair.get_public_key()
+                ))
+            })?;
+        let signature = keypair.sign(hash)?;
+        match self.signatures.binary_search_by_key(&index, |(i, _)| *i) {
+            Ok(pos) => self.signatures[pos] = (index, signature),
+            Err(pos) => self.signatures.insert(pos, (index, signature)),
+        }
+        Ok(())
+    }
+
+    /// Checks that enough distinct signers of the policy signed `hash`.
+    /// `extra_signer` is a signature given outside of the list, by a key that must also belong to the policy.
+    pub fn verify(
+        &self,
+        hash: &Hash,
+        extra_signer: Option<(&PublicKey, &Signature)>,
+    ) -> Result<(), ModelsError> {
+        let mut signers = BTreeSet::new();
+        if let Some((public_key, signature)) = extra_signer {
+            let index = self.policy.get_signer_index(public_key).ok_or_else(|| {
+                ModelsError::MultisigError(format!(
+                    "{} is not a signer of the multisig address",
+                    public_key
+                ))
+            })?;
+            public_key.verify_signature(hash, signature)?;
+            signers.insert(index);
+        }
+        for (index, signature) in &self.signatures {
+            let public_key = self
+                .policy
+                .public_keys
+                .get(*index as usize)
+                .ok_or_else(|| {
+                    ModelsError::MultisigError(format!("signer index {} is out of range", index))
+                })?;
+            public_key.verify_signature(hash, signature)?;
+            signers.insert(*index);
+        }
+        if signers.len() < self.policy.threshold as usize {
+            return Err(ModelsError::MultisigError(format!(
+                "{} distinct signatures out of the {} required",
+                signers.len(),
+                self.policy.threshold
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Serializer for `MultisigSignatures`
+#[derive(Default)]
+pub struct MultisigSignaturesSerializer {
+    u32_serializer: U32VarIntSerializer,
+}
+
+impl MultisigSignaturesSerializer {
+    /// Creates a new `MultisigSignaturesSerializer`
+    pub fn new() -> Self {
+        Self {
+            u32_serializer: U32VarIntSerializer::new(),
+        }
+    }
+}
+
+impl Serializer<MultisigSignatures> for MultisigSignaturesSerializer {
+    fn serialize(
+        &self,
+        value: &MultisigSignatures,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.u32_serializer
+            .serialize(&value.policy.threshold, buffer)?;
+        let key_count: u32 = value.policy.public_keys.len().try_into().map_err(|err| {
+            SerializeError::GeneralError(format!("too many multisig public keys: {}", err))
+        })?;
+        self.u32_serializer.serialize(&key_count, buffer)?;
+        for public_key in &value.policy.public_keys {
+            buffer.extend(public_key.to_bytes());
+        }
+        let signature_count: u32 = value.signatures.len().try_into().map_err(|err| {
+            SerializeError::GeneralError(format!("too many multisig signatures: {}", err))
+        })?;
+        self.u32_serializer.serialize(&signature_count, buffer)?;
+        for (index, signature) in &value.signatures {
+            self.u32_serializer.serialize(index, buffer)?;
+            buffer.extend(signature.to_bytes());
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for `MultisigSignatures`
+pub struct MultisigSignaturesDeserializer {
+    threshold_deserializer: U32VarIntDeserializer,
+    count_deserializer: U32VarIntDeserializer,
+    index_deserializer: U32VarIntDeserializer,
+    public_key_deserializer: PublicKeyDeserializer,
+    signature_deserializer: SignatureDeserializer,
+}
+
+impl MultisigSignaturesDeserializer {
+    /// Creates a new `MultisigSignaturesDeserializer`
+    pub fn new() -> Self {
+        Self {
+            threshold_deserializer: U32VarIntDeserializer::new(
+                Included(1),
+                Included(MAX_MULTISIG_PUBLIC_KEYS),
+            ),
+            count_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_MULTISIG_PUBLIC_KEYS),
+            ),
+            index_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_MULTISIG_PUBLIC_KEYS - 1),
+            ),
+            public_key_deserializer: PublicKeyDeserializer::new(),
+            signature_deserializer: SignatureDeserializer::new(),
+        }
+    }
+}
+
+impl Default for MultisigSignaturesDeserializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deserializer<MultisigSignatures> for MultisigSignaturesDeserializer {
+    /// ## Example
+    /// ```
+    /// use massa_models::multisig::{MultisigPolicy, MultisigSignatures, MultisigSignaturesSerializer, MultisigSignaturesDeserializer};
+    /// use massa_serialization::{Serializer, Deserializer, DeserializeError};
+    /// use massa_signature::KeyPair;
+    ///
+    /// let keypairs: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate()).collect();
+    /// let policy = MultisigPolicy::new(2, keypairs.iter().map(|k| k.get_public_key()).collect()).unwrap();
+    /// let hash = MultisigSignatures::compute_signed_hash(&policy.address(), b"content");
+    /// let mut multisig = MultisigSignatures::new(policy);
+    /// multisig.sign(&hash, &keypairs[0]).unwrap();
+    /// assert!(multisig.verify(&hash, None).is_err());
+    /// multisig.sign(&hash, &keypairs[2]).unwrap();
+    /// multisig.verify(&hash, None).unwrap();
+    ///
+    /// let mut buffer = Vec::new();
+    /// MultisigSignaturesSerializer::new().serialize(&multisig, &mut buffer).unwrap();
+    /// let (rest, deserialized) = MultisigSignaturesDeserializer::new().deserialize::<DeserializeError>(&buffer).unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(multisig, deserialized);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], MultisigSignatures, E> {
+        let (rest, (threshold, public_keys, signatures)) = context(
+            "Failed MultisigSignatures deserialization",
+            tuple((
+                context("Failed threshold deserialization", |input| {
+                    self.threshold_deserializer.deserialize(input)
+                }),
+                context(
+                    "Failed public_keys deserialization",
+                    length_count(
+                        |input| self.count_deserializer.deserialize(input),
+                        |input| self.public_key_deserializer.deserialize(input),
+                    ),
+                ),
+                context(
+                    "Failed signatures deserialization",
+                    length_count(
+                        |input| self.count_deserializer.deserialize(input),
+                        tuple((
+                            |input| self.index_deserializer.deserialize(input),
+                            |input| self.signature_deserializer.deserialize(input),
+                        )),
+                    ),
+                ),
+            )),
+        )
+        .parse(buffer)?;
+        // only accept the canonical form of the policy, so that it maps to a single address
+        let policy = match MultisigPolicy::new(threshold, public_keys.clone()) {
+            Ok(policy) if policy.public_keys == public_keys => policy,
+            _ => {
+                return Err(nom::Err::Error(ParseError::from_error_kind(
+                    buffer,
+                    nom::error::ErrorKind::Verify,
+                )))
+            }
+        };
+        Ok((rest, MultisigSignatures { policy, signatures }))
+    }
+}
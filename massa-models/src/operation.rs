@@ -7,6 +7,7 @@ use crate::{
     address::{Address, AddressDeserializer},
     amount::{Amount, AmountDeserializer, AmountSerializer},
     error::ModelsError,
+    multisig::{MultisigSignatures, MultisigSignaturesDeserializer, MultisigSignaturesSerializer},
     serialization::{StringSerializer, VecU8Deserializer, VecU8Serializer},
 };
 use massa_hash::{Hash, HashDeserializer};
@@ -14,6 +15,9 @@ use massa_serialization::{
     Deserializer, SerializeError, Serializer, U16VarIntDeserializer, U16VarIntSerializer,
     U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
+use massa_signature::{
+    PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer, SIGNATURE_SIZE_BYTES,
+};
 use nom::error::context;
 use nom::multi::length_count;
 use nom::sequence::tuple;
@@ -204,7 +208,158 @@ impl std::fmt::Display for Operation {
 /// signed operation
 pub type WrappedOperation = Wrapped<Operation, OperationId>;
 
-impl WrappedContent for Operation {}
+/// Bit set in the last byte of the signature of a wrapped operation
+/// when the signatures of a multisig address follow the creator public key.
+///
+/// Canonical ed25519 signatures end with a scalar lower than 2^253, so this bit is always unset in them:
+/// operations emitted by a single public key keep their original encoding.
+const MULTISIG_SIGNATURE_FLAG: u8 = 0x80;
+
+impl WrappedContent for Operation {
+    /// Operations emitted by a multisig address have `MULTISIG_SIGNATURE_FLAG` set in their signature,
+    /// and carry the signatures of the other signers between the creator public key and the content.
+    fn serialize(
+        signature: &Signature,
+        creator_public_key: &PublicKey,
+        multisig: &Option<MultisigSignatures>,
+        serialized_content: &[u8],
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        let mut signature_bytes = signature.into_bytes();
+        if signature_bytes[SIGNATURE_SIZE_BYTES - 1] & MULTISIG_SIGNATURE_FLAG != 0 {
+            return Err(SerializeError::GeneralError(
+                "non-canonical operation signature".to_string(),
+            ));
+        }
+        if multisig.is_some() {
+            signature_bytes[SIGNATURE_SIZE_BYTES - 1] |= MULTISIG_SIGNATURE_FLAG;
+        }
+        buffer.extend(signature_bytes);
+        buffer.extend(creator_public_key.into_bytes());
+        if let Some(multisig) = multisig {
+            MultisigSignaturesSerializer::new().serialize(multisig, buffer)?;
+        }
+        buffer.extend(serialized_content);
+        Ok(())
+    }
+
+    /// The id of an operation emitted by a multisig address is the hash signed by all its signers:
+    /// it depends on the address instead of the creator public key.
+    fn deserialize<
+        'a,
+        E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+        DC: Deserializer<Self>,
+        U: Id,
+    >(
+        _signature_deserializer: &SignatureDeserializer,
+        creator_public_key_deserializer: &PublicKeyDeserializer,
+        content_deserializer: &DC,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Wrapped<Self, U>, E> {
+        let (rest, (signature, is_multisig)) =
+            context("Failed signature deserialization", |input: &'a [u8]| {
+                let mut signature_bytes: [u8; SIGNATURE_SIZE_BYTES] = input
+                    .get(..SIGNATURE_SIZE_BYTES)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| {
+                        nom::Err::Error(ParseError::from_error_kind(
+                            input,
+                            nom::error::ErrorKind::LengthValue,
+                        ))
+                    })?;
+                let is_multisig =
+                    signature_bytes[SIGNATURE_SIZE_BYTES - 1] & MULTISIG_SIGNATURE_FLAG != 0;
+                signature_bytes[SIGNATURE_SIZE_BYTES - 1] &= !MULTISIG_SIGNATURE_FLAG;
+                let signature = Signature::from_bytes(&signature_bytes).map_err(|_| {
+                    nom::Err::Error(ParseError::from_error_kind(
+                        input,
+                        nom::error::ErrorKind::Fail,
+                    ))
+                })?;
+                Ok((&input[SIGNATURE_SIZE_BYTES..], (signature, is_multisig)))
+            })(buffer)?;
+        let (rest, creator_public_key) = context("Failed public_key deserialization", |input| {
+            creator_public_key_deserializer.deserialize(input)
+        })(rest)?;
+        let (serialized_data, multisig) = if is_multisig {
+            let (rest, multisig) = context("Failed multisig deserialization", |input| {
+                MultisigSignaturesDeserializer::new().deserialize(input)
+            })(rest)?;
+            (rest, Some(multisig))
+        } else {
+            (rest, None)
+        };
+        let (rest, content) = content_deserializer.deserialize(serialized_data)?;
+        // Avoid getting the rest of the data in the serialized data
+        let content_serialized = &serialized_data[..serialized_data.len() - rest.len()];
+        let (creator_address, hash) = match &multisig {
+            Some(multisig) => {
+                let address = multisig.policy.address();
+                let hash = MultisigSignatures::compute_signed_hash(&address, content_serialized);
+                (address, hash)
+            }
+            None => {
+                let mut serialized_full_data = creator_public_key.to_bytes().to_vec();
+                serialized_full_data.extend(content_serialized);
+                (
+                    Address::from_public_key(&creator_public_key),
+                    Hash::compute_from(&serialized_full_data),
+                )
+            }
+        };
+        Ok((
+            rest,
+            Wrapped {
+                content,
+                signature,
+                creator_public_key,
+                creator_address,
+                serialized_data: content_serialized.to_vec(),
+                id: U::new(hash),
+                multisig,
+            },
+        ))
+    }
+}
+
+impl Operation {
+    /// Wraps an operation emitted by a multisig address from the signatures collected for it.
+    /// The first signature is used as the main signature of the wrapped operation.
+    pub fn new_multisig_wrapped(
+        content: Self,
+        mut multisig: MultisigSignatures,
+    ) -> Result<WrappedOperation, ModelsError> {
+        let mut serialized_content = Vec::new();
+        OperationSerializer::new().serialize(&content, &mut serialized_content)?;
+        let creator_address = multisig.policy.address();
+        let hash = MultisigSignatures::compute_signed_hash(&creator_address, &serialized_content);
+        if multisig.signatures.is_empty() {
+            return Err(ModelsError::MultisigError(
+                "the operation has no signature".to_string(),
+            ));
+        }
+        let (index, signature) = multisig.signatures.remove(0);
+        let creator_public_key =
+            *multisig
+                .policy
+                .public_keys
+                .get(index as usize)
+                .ok_or_else(|| {
+                    ModelsError::MultisigError(format!("signer index {} is out of range", index))
+                })?;
+        let wrapped = Wrapped {
+            content,
+            signature,
+            creator_public_key,
+            creator_address,
+            id: OperationId::new(hash),
+            multisig: Some(multisig),
+            serialized_data: serialized_content,
+        };
+        wrapped.verify_signature()?;
+        Ok(wrapped)
+    }
+}
 
 /// Serializer for `Operation`
 pub struct OperationSerializer {
@@ -805,8 +960,7 @@ impl WrappedOperation {
     /// get the addresses that are involved in this operation from a ledger point of view
     pub fn get_ledger_involved_addresses(&self) -> PreHashSet<Address> {
         let mut res = PreHashSet::<Address>::default();
-        let emitter_address = Address::from_public_key(&self.creator_public_key);
-        res.insert(emitter_address);
+        res.insert(self.creator_address);
         match &self.content.op {
             OperationType::Transaction {
                 recipient_address, ..
@@ -846,10 +1000,10 @@ impl WrappedOperation {
         match self.content.op {
             OperationType::Transaction { .. } => {}
             OperationType::RollBuy { .. } => {
-                res.insert(Address::from_public_key(&self.creator_public_key));
+                res.insert(self.creator_address);
             }
             OperationType::RollSell { .. } => {
-                res.insert(Address::from_public_key(&self.creator_public_key));
+                res.insert(self.creator_address);
             }
             OperationType::ExecuteSC { .. } => {}
             OperationType::CallSC { .. } => {}
@@ -1259,6 +1413,7 @@ mod tests {
     };
 
     use super::*;
+    use crate::multisig::MultisigPolicy;
     use massa_serialization::DeserializeError;
     use massa_signature::KeyPair;
     use serial_test::serial;
@@ -1456,4 +1611,128 @@ mod tests {
 
         assert_eq!(op.get_validity_range(10), 40..=50);
     }
+
+    #[test]
+    #[serial]
+    fn test_multisig_operation() {
+        let keypairs: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate()).collect();
+        let policy =
+            MultisigPolicy::new(2, keypairs.iter().map(|k| k.get_public_key()).collect()).unwrap();
+        let multisig_address = policy.address();
+        let content = Operation {
+            fee: Amount::from_str("20").unwrap(),
+            op: OperationType::RollSell { roll_count: 1 },
+            expire_period: 50,
+        };
+        let mut ser_content = Vec::new();
+        OperationSerializer::new()
+            .serialize(&content, &mut ser_content)
+            .unwrap();
+        let hash = MultisigSignatures::compute_signed_hash(&multisig_address, &ser_content);
+
+        // a single signature does not reach the threshold
+        let mut multisig = MultisigSignatures::new(policy);
+        multisig.sign(&hash, &keypairs[1]).unwrap();
+        assert!(Operation::new_multisig_wrapped(content.clone(), multisig.clone()).is_err());
+        assert!(multisig.sign(&hash, &KeyPair::generate()).is_err());
+
+        multisig.sign(&hash, &keypairs[0]).unwrap();
+        let op = Operation::new_multisig_wrapped(content, multisig).unwrap();
+        assert_eq!(op.creator_address, multisig_address);
+        assert!(op
+            .get_roll_involved_addresses()
+            .unwrap()
+            .contains(&multisig_address));
+
+        let mut ser_op = Vec::new();
+        WrappedSerializer::new()
+            .serialize(&op, &mut ser_op)
+            .unwrap();
+        let deserializer = WrappedDeserializer::new(OperationDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+        ));
+        let (rest, res_op): (&[u8], WrappedOperation) = deserializer
+            .deserialize::<DeserializeError>(&ser_op)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(ser_op.len(), op.serialized_size());
+        assert_eq!(res_op.id, op.id);
+        assert_eq!(res_op.creator_address, multisig_address);
+        assert_eq!(res_op.serialized_size(), op.serialized_size());
+        res_op.verify_signature().unwrap();
+
+        // dropping a signature makes the verification fail
+        let mut res_op = res_op;
+        res_op.multisig.as_mut().unwrap().signatures.clear();
+        assert!(res_op.verify_signature().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_wrapped_operation_serialized_size() {
+        let keypairs: Vec<KeyPair> = (0..2).map(|_| KeyPair::generate()).collect();
+        let content = Operation {
+            fee: Amount::from_str("20").unwrap(),
+            op: OperationType::RollBuy { roll_count: 3 },
+            expire_period: 50,
+        };
+        let mut ser_content = Vec::new();
+        OperationSerializer::new()
+            .serialize(&content, &mut ser_content)
+            .unwrap();
+        let deserializer = WrappedDeserializer::new(OperationDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+        ));
+
+        // operations emitted by a single public key are signature, public key and content
+        let op = Operation::new_wrapped(content.clone(), OperationSerializer::new(), &keypairs[0])
+            .unwrap();
+        let mut ser_op = Vec::new();
+        WrappedSerializer::new()
+            .serialize(&op, &mut ser_op)
+            .unwrap();
+        let mut expected = op.signature.to_bytes().to_vec();
+        expected.extend(op.creator_public_key.to_bytes());
+        expected.extend(&ser_content);
+        assert_eq!(ser_op, expected);
+        assert_eq!(ser_op.len(), op.serialized_size());
+        let (rest, res_op): (&[u8], WrappedOperation) = deserializer
+            .deserialize::<DeserializeError>(&ser_op)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert!(res_op.multisig.is_none());
+        assert_eq!(res_op.id, op.id);
+        assert_eq!(res_op.serialized_size(), ser_op.len());
+
+        // operations emitted by a multisig address also carry the other signatures
+        let policy =
+            MultisigPolicy::new(2, keypairs.iter().map(|k| k.get_public_key()).collect()).unwrap();
+        let hash = MultisigSignatures::compute_signed_hash(&policy.address(), &ser_content);
+        let mut multisig = MultisigSignatures::new(policy);
+        for keypair in &keypairs {
+            multisig.sign(&hash, keypair).unwrap();
+        }
+        let op = Operation::new_multisig_wrapped(content, multisig).unwrap();
+        let mut ser_op = Vec::new();
+        WrappedSerializer::new()
+            .serialize(&op, &mut ser_op)
+            .unwrap();
+        assert_eq!(ser_op.len(), op.serialized_size());
+        // both operations can follow each other in a buffer
+        let mut ser_ops = expected;
+        ser_ops.extend(&ser_op);
+        let (rest, _): (&[u8], WrappedOperation) = deserializer
+            .deserialize::<DeserializeError>(&ser_ops)
+            .unwrap();
+        let (rest, res_op): (&[u8], WrappedOperation) =
+            deserializer.deserialize::<DeserializeError>(rest).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(res_op.id, op.id);
+        assert_eq!(res_op.serialized_size(), ser_op.len());
+        res_op.verify_signature().unwrap();
+    }
 }
@@ -1,9 +1,12 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::node::NodeId;
 use crate::slot::Slot;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Formatter;
+use std::net::IpAddr;
 
 /// execution statistics
 #[derive(Serialize, Deserialize, Debug)]
@@ -61,6 +64,12 @@ pub struct NetworkStats {
     pub banned_peer_count: u64,
     /// active node count
     pub active_node_count: u64,
+    /// number of distinct subnets among in connections
+    pub in_subnet_count: u64,
+    /// number of distinct subnets among out connections
+    pub out_subnet_count: u64,
+    /// number of in connections refused because their subnet was full
+    pub subnet_refused_connection_count: u64,
 }
 
 impl std::fmt::Display for NetworkStats {
@@ -71,6 +80,70 @@ impl std::fmt::Display for NetworkStats {
         writeln!(f, "\tKnown peers: {}", self.known_peer_count)?;
         writeln!(f, "\tBanned peers: {}", self.banned_peer_count)?;
         writeln!(f, "\tActive nodes: {}", self.active_node_count)?;
+        writeln!(f, "\tIn connection subnets: {}", self.in_subnet_count)?;
+        writeln!(f, "\tOut connection subnets: {}", self.out_subnet_count)?;
+        writeln!(
+            f,
+            "\tConnections refused by subnet limits: {}",
+            self.subnet_refused_connection_count
+        )?;
+        Ok(())
+    }
+}
+
+/// traffic stats of a single active connection, produced by network module
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerStats {
+    /// node id of the peer
+    pub node_id: NodeId,
+    /// ip of the peer
+    pub ip: IpAddr,
+    /// true if we initiated the connection
+    pub is_outgoing: bool,
+    /// time elapsed since the connection was established
+    pub connection_age: MassaTime,
+    /// bytes sent to the peer
+    pub bytes_sent: u64,
+    /// bytes received from the peer
+    pub bytes_received: u64,
+    /// number of messages sent to the peer, by message type
+    pub messages_sent: BTreeMap<String, u64>,
+    /// number of messages received from the peer, by message type
+    pub messages_received: BTreeMap<String, u64>,
+    /// last measured round-trip latency, if any
+    pub round_trip_latency: Option<MassaTime>,
+    /// number of messages delayed by the read bandwidth limit
+    pub read_throttle_hits: u64,
+    /// number of messages delayed by the write bandwidth limit
+    pub write_throttle_hits: u64,
+}
+
+impl std::fmt::Display for PeerStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Node {} ({}, {}):",
+            self.node_id,
+            self.ip,
+            if self.is_outgoing { "out" } else { "in" }
+        )?;
+        writeln!(f, "\tConnection age: {} ms", self.connection_age)?;
+        writeln!(f, "\tBytes sent: {}", self.bytes_sent)?;
+        writeln!(f, "\tBytes received: {}", self.bytes_received)?;
+        writeln!(f, "\tMessages sent:")?;
+        for (message_type, count) in &self.messages_sent {
+            writeln!(f, "\t\t{}: {}", message_type, count)?;
+        }
+        writeln!(f, "\tMessages received:")?;
+        for (message_type, count) in &self.messages_received {
+            writeln!(f, "\t\t{}: {}", message_type, count)?;
+        }
+        match self.round_trip_latency {
+            Some(latency) => writeln!(f, "\tRound-trip latency: {} ms", latency)?,
+            None => writeln!(f, "\tRound-trip latency: unknown")?,
+        }
+        writeln!(f, "\tRead throttle hits: {}", self.read_throttle_hits)?;
+        writeln!(f, "\tWrite throttle hits: {}", self.write_throttle_hits)?;
         Ok(())
     }
 }
@@ -88,6 +161,16 @@ pub struct ConsensusStats {
     pub stale_block_count: u64,
     ///  number of actives cliques
     pub clique_count: u64,
+    /// number of active blocks in the block graph
+    pub active_block_count: u64,
+    /// number of incoming blocks in the block graph
+    pub incoming_block_count: u64,
+    /// number of blocks waiting for their slot in the block graph
+    pub waiting_for_slot_block_count: u64,
+    /// number of blocks waiting for their dependencies in the block graph
+    pub waiting_for_dependencies_block_count: u64,
+    /// number of discarded blocks in the block graph
+    pub discarded_block_count: u64,
 }
 
 impl std::fmt::Display for ConsensusStats {
@@ -106,6 +189,19 @@ impl std::fmt::Display for ConsensusStats {
         writeln!(f, "\tFinal block count: {}", self.final_block_count)?;
         writeln!(f, "\tStale block count: {}", self.stale_block_count)?;
         writeln!(f, "\tClique count: {}", self.clique_count)?;
+        writeln!(f, "\tActive blocks: {}", self.active_block_count)?;
+        writeln!(f, "\tIncoming blocks: {}", self.incoming_block_count)?;
+        writeln!(
+            f,
+            "\tBlocks waiting for slot: {}",
+            self.waiting_for_slot_block_count
+        )?;
+        writeln!(
+            f,
+            "\tBlocks waiting for dependencies: {}",
+            self.waiting_for_dependencies_block_count
+        )?;
+        writeln!(f, "\tDiscarded blocks: {}", self.discarded_block_count)?;
         Ok(())
     }
 }
@@ -16,6 +16,9 @@ use std::{convert::TryInto, fmt, str::FromStr};
 
 const INSTANCE_LEN: usize = 4;
 
+/// First (major, minor) version whose nodes encrypt their bootstrap streams and peer connections
+const ENCRYPTION_MIN_VERSION: (u32, u32) = (14, 8);
+
 /// Application version, checked during handshakes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Version {
@@ -187,6 +190,18 @@ impl Deserializer<Version> for VersionDeserializer {
 }
 
 impl Version {
+    /// true if a node at this version encrypts its bootstrap streams and peer connections
+    /// ```rust
+    /// # use massa_models::version::Version;
+    /// # use std::str::FromStr;
+    /// assert!(!Version::from_str("TEST.14.7").unwrap().supports_encryption());
+    /// assert!(Version::from_str("TEST.14.8").unwrap().supports_encryption());
+    /// assert!(Version::from_str("TEST.15.0").unwrap().supports_encryption());
+    /// ```
+    pub fn supports_encryption(&self) -> bool {
+        (self.major, self.minor) >= ENCRYPTION_MIN_VERSION
+    }
+
     /// true if instance and major are the same
     pub fn is_compatible(&self, other: &Version) -> bool {
         self.instance == other.instance
@@ -1,6 +1,10 @@
 use std::fmt::Display;
 
-use crate::{address::Address, error::ModelsError};
+use crate::{
+    address::Address,
+    error::ModelsError,
+    multisig::{MultisigSignatures, MultisigSignaturesSerializer},
+};
 use massa_hash::Hash;
 use massa_serialization::{Deserializer, SerializeError, Serializer};
 use massa_signature::{
@@ -31,6 +35,10 @@ where
     pub creator_address: Address,
     /// Id
     pub id: U,
+    /// signatures of the other signers, if the creator address is a multisig address.
+    /// `signature` and `creator_public_key` are then those of one of the signers.
+    #[serde(default)]
+    pub multisig: Option<MultisigSignatures>,
     #[serde(skip)]
     /// Content serialized
     pub serialized_data: Vec<u8>,
@@ -70,13 +78,16 @@ where
             content,
             serialized_data: content_serialized,
             id: U::new(hash),
+            multisig: None,
         })
     }
 
-    /// Serialize the wrapped structure
+    /// Serialize the wrapped structure.
+    /// Only operations can be emitted by multisig addresses: `multisig` is ignored by default.
     fn serialize(
         signature: &Signature,
         creator_public_key: &PublicKey,
+        _multisig: &Option<MultisigSignatures>,
         serialized_content: &[u8],
         buffer: &mut Vec<u8>,
     ) -> Result<(), SerializeError> {
@@ -124,6 +135,7 @@ where
                 creator_address,
                 serialized_data: content_serialized.to_vec(),
                 id: U::new(Hash::compute_from(&serialized_full_data)),
+                multisig: None,
             },
         ))
     }
@@ -149,19 +161,44 @@ where
     T: Display + WrappedContent,
     U: Id,
 {
-    /// check if self has been signed by public key
+    /// check if self has been signed by public key,
+    /// or by enough signers if the creator address is a multisig address
     pub fn verify_signature(&self) -> Result<(), ModelsError> {
-        Ok(self
-            .creator_public_key
-            .verify_signature(self.id.get_hash(), &self.signature)?)
+        match &self.multisig {
+            None => Ok(self
+                .creator_public_key
+                .verify_signature(self.id.get_hash(), &self.signature)?),
+            Some(multisig) => {
+                if multisig.policy.address() != self.creator_address {
+                    return Err(ModelsError::MultisigError(
+                        "creator address does not match the multisig policy".to_string(),
+                    ));
+                }
+                multisig.verify(
+                    self.id.get_hash(),
+                    Some((&self.creator_public_key, &self.signature)),
+                )
+            }
+        }
     }
 
-    /// get full serialized size
+    /// get full serialized size, including the signatures of the other signers of a multisig address
     pub fn serialized_size(&self) -> usize {
+        let multisig_size = match &self.multisig {
+            Some(multisig) => {
+                let mut buffer = Vec::new();
+                MultisigSignaturesSerializer::new()
+                    .serialize(multisig, &mut buffer)
+                    .map(|_| buffer.len())
+                    .unwrap_or_default()
+            }
+            None => 0,
+        };
         self.serialized_data
             .len()
             .saturating_add(SIGNATURE_SIZE_BYTES)
             .saturating_add(PUBLIC_KEY_SIZE_BYTES)
+            .saturating_add(multisig_size)
     }
 }
 
@@ -186,6 +223,7 @@ where
         T::serialize(
             &value.signature,
             &value.creator_public_key,
+            &value.multisig,
             &value.serialized_data,
             buffer,
         )
@@ -69,14 +69,14 @@
 //! Look at `massa-protocol-worker/src/node-info.rs` to look further how we
 //! remember which node know what.
 
-use crate::{BootstrapPeers, ConnectionClosureReason, Peers};
+use crate::{BootstrapPeers, ConnectionClosureReason, PeerScoreEvent, Peers};
 use massa_models::{
     block::{BlockId, WrappedHeader},
     composite::PubkeySig,
     endorsement::WrappedEndorsement,
     node::NodeId,
     operation::{OperationId, OperationPrefixIds, WrappedOperation},
-    stats::NetworkStats,
+    stats::{NetworkStats, PeerStats},
 };
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, net::IpAddr};
@@ -204,6 +204,11 @@ pub enum NetworkCommand {
         /// response channels
         response_tx: oneshot::Sender<NetworkStats>,
     },
+    /// gets the traffic stats of every active connection
+    GetPeersStats {
+        /// response channels
+        response_tx: oneshot::Sender<Vec<PeerStats>>,
+    },
     /// Send a batch of full operations
     SendOperations {
         /// to node id
@@ -229,6 +234,13 @@ pub enum NetworkCommand {
     Whitelist(Vec<IpAddr>),
     /// Remove from whitelist a list of `IpAddr`
     RemoveFromWhitelist(Vec<IpAddr>),
+    /// Update the score of the peer behind a node
+    NotePeerScoreEvent {
+        /// node id
+        node: NodeId,
+        /// event affecting the score
+        event: PeerScoreEvent,
+    },
 }
 
 /// A node replied with info about a block.
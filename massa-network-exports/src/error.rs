@@ -31,6 +31,8 @@ pub enum NetworkError {
     IOError(#[from] std::io::Error),
     /// Serde error : {0}
     SerdeError(#[from] serde_json::Error),
+    /// cipher error: {0}
+    CipherError(#[from] massa_cipher::CipherError),
     /// `MassaHash` error {0}
     MassaHashError(#[from] massa_hash::MassaHashError),
     /// massa_signature error {0}
@@ -74,6 +76,8 @@ pub enum HandshakeErrorType {
     HandshakeInvalidSignature,
     /// Incompatible version
     IncompatibleVersion,
+    /// The peer supports encryption but its handshake was altered to disable it
+    HandshakeDowngrade,
     /// Outgoing connection returned a bootstrapable peer list: {0:?}
     PeerListReceived(Vec<IpAddr>),
 }
@@ -107,6 +111,8 @@ pub enum NetworkConnectionErrorType {
     TooManyConnectionFailure(IpAddr),
     /// Max connected peers reached: {0}
     MaxPeersConnectionReached(IpAddr),
+    /// Max connections with the subnet of that peer reached: {0}
+    MaxSubnetConnectionReached(IpAddr),
     /// Attempt too connect from you own IP
     SelfConnection,
     /// A banned peer is trying to connect: {0}
@@ -15,7 +15,7 @@ pub use establisher::{Establisher, Listener, ReadHalf, WriteHalf};
 pub use network_controller::{NetworkCommandSender, NetworkEventReceiver, NetworkManager};
 pub use peers::{
     BootstrapPeers, BootstrapPeersDeserializer, BootstrapPeersSerializer, ConnectionCount, Peer,
-    PeerInfo, PeerType, Peers,
+    PeerInfo, PeerScoreEvent, PeerType, Peers, MAX_PEER_SCORE, MIN_PEER_SCORE,
 };
 pub use settings::NetworkConfig;
 
@@ -3,7 +3,7 @@
 use crate::{
     commands::{AskForBlocksInfo, NetworkManagementCommand},
     error::NetworkError,
-    BlockInfoReply, BootstrapPeers, NetworkCommand, NetworkEvent, Peers,
+    BlockInfoReply, BootstrapPeers, NetworkCommand, NetworkEvent, PeerScoreEvent, Peers,
 };
 use massa_models::{
     block::{BlockId, WrappedHeader},
@@ -11,7 +11,7 @@ use massa_models::{
     endorsement::WrappedEndorsement,
     node::NodeId,
     operation::{OperationPrefixIds, WrappedOperation},
-    stats::NetworkStats,
+    stats::{NetworkStats, PeerStats},
 };
 use std::{
     collections::{HashMap, VecDeque},
@@ -66,6 +66,21 @@ impl NetworkCommandSender {
         Ok(())
     }
 
+    /// note an event affecting the score of the peer behind a node
+    pub async fn note_peer_score_event(
+        &self,
+        node: NodeId,
+        event: PeerScoreEvent,
+    ) -> Result<(), NetworkError> {
+        self.0
+            .send(NetworkCommand::NotePeerScoreEvent { node, event })
+            .await
+            .map_err(|_| {
+                NetworkError::ChannelError("could not send NotePeerScoreEvent command".into())
+            })?;
+        Ok(())
+    }
+
     /// remove from banned node(s) by id(s)
     pub async fn node_unban_by_ids(&self, ids: Vec<NodeId>) -> Result<(), NetworkError> {
         self.0
@@ -157,6 +172,20 @@ impl NetworkCommandSender {
             .map_err(|_| NetworkError::ChannelError("could not send GetStats upstream".into()))
     }
 
+    /// get the traffic stats of every active connection
+    pub async fn get_peers_stats(&self) -> Result<Vec<PeerStats>, NetworkError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(NetworkCommand::GetPeersStats { response_tx })
+            .await
+            .map_err(|_| {
+                NetworkError::ChannelError("could not send GetPeersStats command".into())
+            })?;
+        response_rx
+            .await
+            .map_err(|_| NetworkError::ChannelError("could not send GetPeersStats upstream".into()))
+    }
+
     /// Send the order to get bootstrap peers.
     pub async fn get_bootstrap_peers(&self) -> Result<BootstrapPeers, NetworkError> {
         let (response_tx, response_rx) = oneshot::channel::<BootstrapPeers>();
@@ -202,6 +202,14 @@ pub struct PeerInfo {
     /// Isn't dump into peer file.
     #[serde(default = "usize::default")]
     pub active_in_connections: usize,
+    /// Reputation of the peer, fed by protocol events. See `PeerScoreEvent`.
+    #[serde(default = "i64::default")]
+    pub score: i64,
+    /// Whether one of the in connections of that peer is being closed
+    /// to make room for a better scoring peer.
+    /// Isn't dump into peer file.
+    #[serde(default = "bool::default")]
+    pub pending_eviction: bool,
 }
 
 impl PeerInfo {
@@ -215,6 +223,8 @@ impl PeerInfo {
         self.active_out_connection_attempts = 0;
         self.active_out_connections = 0;
         self.active_in_connections = 0;
+        self.pending_eviction = false;
+        self.score = self.score.clamp(MIN_PEER_SCORE, MAX_PEER_SCORE);
     }
 
     /// Returns true if there is at least one connection attempt /
@@ -244,9 +254,19 @@ impl PeerInfo {
             active_in_connections: 0,
             peer_type: Default::default(),
             banned: false,
+            score: 0,
+            pending_eviction: false,
         }
     }
 
+    /// Applies a protocol event to the score of the peer, within `[MIN_PEER_SCORE, MAX_PEER_SCORE]`
+    pub fn apply_score_event(&mut self, event: PeerScoreEvent) {
+        self.score = self
+            .score
+            .saturating_add(event.score_delta())
+            .clamp(MIN_PEER_SCORE, MAX_PEER_SCORE);
+    }
+
     /// peer is ready to be retried, enough time has elapsed since last failure
     pub fn is_peer_ready(&self, wakeup_interval: MassaTime, now: MassaTime) -> bool {
         if let Some(last_failure) = self.last_failure {
@@ -264,6 +284,54 @@ impl PeerInfo {
     }
 }
 
+/// Lowest score a peer can have
+pub const MIN_PEER_SCORE: i64 = -1000;
+
+/// Highest score a peer can have
+pub const MAX_PEER_SCORE: i64 = 1000;
+
+/// Replies to block requests faster than this are rewarded
+const FAST_REPLY_LATENCY: u64 = 1000;
+
+/// Replies to block requests slower than this are penalized
+const SLOW_REPLY_LATENCY: u64 = 5000;
+
+/// Protocol events affecting the score of a peer
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PeerScoreEvent {
+    /// The peer delivered a block we were looking for
+    UsefulBlock,
+    /// The peer sent an invalid header
+    InvalidHeader,
+    /// The peer did not answer a block request before the timeout
+    UnansweredAskForBlocks,
+    /// The peer sent operations we already had
+    DuplicateOperations,
+    /// The peer answered a block request after the given delay
+    Latency(MassaTime),
+}
+
+impl PeerScoreEvent {
+    /// Score change caused by the event
+    pub fn score_delta(&self) -> i64 {
+        match self {
+            PeerScoreEvent::UsefulBlock => 10,
+            PeerScoreEvent::InvalidHeader => -100,
+            PeerScoreEvent::UnansweredAskForBlocks => -10,
+            PeerScoreEvent::DuplicateOperations => -1,
+            PeerScoreEvent::Latency(latency) => {
+                if latency.to_millis() < FAST_REPLY_LATENCY {
+                    1
+                } else if latency.to_millis() > SLOW_REPLY_LATENCY {
+                    -1
+                } else {
+                    0
+                }
+            }
+        }
+    }
+}
+
 /// Connection count for a category
 #[derive(Default, Debug)]
 pub struct ConnectionCount {
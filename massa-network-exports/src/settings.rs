@@ -32,6 +32,10 @@ pub struct NetworkConfig {
     pub peer_types_config: EnumMap<PeerType, PeerTypeConnectionConfig>,
     /// Limit on the number of in connections per ip.
     pub max_in_connections_per_ip: usize,
+    /// Limit on the number of in connections with standard peers of the same subnet (/24 for IPv4, /48 for IPv6).
+    pub max_in_connections_per_subnet: usize,
+    /// Limit on the number of out connections and attempts with standard peers of the same subnet (/24 for IPv4, /48 for IPv6).
+    pub max_out_connections_per_subnet: usize,
     /// Limit on the number of idle peers we remember.
     pub max_idle_peers: usize,
     /// Limit on the number of banned peers we remember.
@@ -143,6 +147,8 @@ pub mod tests {
                 wakeup_interval: MassaTime::from_millis(10_000),
                 peers_file: std::path::PathBuf::new(),
                 max_in_connections_per_ip: 2,
+                max_in_connections_per_subnet: 100,
+                max_out_connections_per_subnet: 100,
                 max_idle_peers: 3,
                 max_banned_peers: 3,
                 peers_file_dump_interval: MassaTime::from_millis(10_000),
@@ -206,6 +212,8 @@ pub mod tests {
                 peers_file: peers_file.to_path_buf(),
                 wakeup_interval: MassaTime::from_millis(3000),
                 max_in_connections_per_ip: 100,
+                max_in_connections_per_subnet: 100,
+                max_out_connections_per_subnet: 100,
                 max_idle_peers: 100,
                 max_banned_peers: 100,
                 peers_file_dump_interval: MassaTime::from_millis(30000),
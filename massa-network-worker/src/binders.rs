@@ -1,10 +1,12 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 //! `Flexbuffer` layer between raw data and our objects.
+use crate::connection_stats::{ConnectionStats, ThrottleMeter};
 use crate::messages::{MessageDeserializer, MessageSerializer};
 
 use super::messages::Message;
 use async_speed_limit::{clock::StandardClock, Limiter, Resource};
+use massa_cipher::{SessionDecrypter, SessionEncrypter, TAG_SIZE};
 use massa_models::{
     error::ModelsError,
     serialization::{DeserializeMinBEInt, SerializeMinBEInt},
@@ -13,6 +15,7 @@ use massa_network_exports::{NetworkError, ReadHalf, WriteHalf};
 use massa_serialization::Serializer;
 use massa_serialization::{DeserializeError, Deserializer};
 use std::convert::TryInto;
+use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tracing::log::warn;
 
@@ -21,6 +24,9 @@ pub struct WriteBinder {
     write_half: Resource<WriteHalf, StandardClock>,
     message_index: u64,
     max_message_size: u32,
+    encrypter: Option<SessionEncrypter>,
+    stats: Arc<ConnectionStats>,
+    throttle_meter: ThrottleMeter,
 }
 
 impl WriteBinder {
@@ -29,14 +35,31 @@ impl WriteBinder {
     /// # Argument
     /// * `write_half`: writer half.
     /// * `limit`: limit max bytes per second write
-    pub fn new(write_half: WriteHalf, limit: f64, max_message_size: u32) -> Self {
+    /// * `stats`: traffic counters of the connection
+    pub fn new(
+        write_half: WriteHalf,
+        limit: f64,
+        max_message_size: u32,
+        stats: Arc<ConnectionStats>,
+    ) -> Self {
         WriteBinder {
             write_half: <Limiter>::new(limit).limit(write_half),
             message_index: 0,
             max_message_size,
+            encrypter: None,
+            stats,
+            throttle_meter: ThrottleMeter::new(limit),
         }
     }
 
+    /// Encrypts the messages sent from now on.
+    ///
+    /// # Argument
+    /// * `encrypter`: sending half of the session agreed on during the handshake.
+    pub fn enable_encryption(&mut self, encrypter: SessionEncrypter) {
+        self.encrypter = Some(encrypter);
+    }
+
     /// Sends a serialized message.
     ///
     /// # Argument
@@ -45,17 +68,25 @@ impl WriteBinder {
         //        massa_trace!("binder.send", { "msg": msg });
         let mut buf = Vec::new();
         MessageSerializer::new().serialize(msg, &mut buf)?;
+        let mut max_message_size = self.max_message_size;
+        if let Some(encrypter) = &mut self.encrypter {
+            buf = encrypter.encrypt(&buf)?;
+            max_message_size = max_message_size.saturating_add(TAG_SIZE as u32);
+        }
         let msg_size: u32 = buf
             .len()
             .try_into()
             .map_err(|_| NetworkError::GeneralProtocolError("message too long".into()))?;
-        self.write_half
-            .write_all(&msg_size.to_be_bytes_min(self.max_message_size)?[..])
-            .await?;
+        let size_field = msg_size.to_be_bytes_min(max_message_size)?;
+        self.write_half.write_all(&size_field[..]).await?;
 
         // send message
         self.write_half.write_all(&buf).await?;
 
+        let wire_size = (size_field.len() + buf.len()) as u64;
+        let throttled = self.throttle_meter.note_transfer(wire_size);
+        self.stats.note_sent(msg.type_id(), wire_size, throttled);
+
         let res_index = self.message_index;
         self.message_index += 1;
         //        massa_trace!("binder.send END", { "index": res_index });
@@ -72,6 +103,9 @@ pub struct ReadBinder {
     msg_size: Option<u32>,
     max_message_size: u32,
     message_deserializer: MessageDeserializer,
+    decrypter: Option<SessionDecrypter>,
+    stats: Arc<ConnectionStats>,
+    throttle_meter: ThrottleMeter,
 }
 
 impl ReadBinder {
@@ -80,11 +114,13 @@ impl ReadBinder {
     /// # Argument
     /// * `read_half`: reader half.
     /// * `limit`: limit max bytes per second read.
+    /// * `stats`: traffic counters of the connection
     pub fn new(
         read_half: ReadHalf,
         limit: f64,
         max_message_size: u32,
         message_deserializer: MessageDeserializer,
+        stats: Arc<ConnectionStats>,
     ) -> Self {
         ReadBinder {
             read_half: <Limiter>::new(limit).limit(read_half),
@@ -94,9 +130,25 @@ impl ReadBinder {
             msg_size: None,
             max_message_size,
             message_deserializer,
+            decrypter: None,
+            stats,
+            throttle_meter: ThrottleMeter::new(limit),
         }
     }
 
+    /// Returns the traffic counters of the connection.
+    pub fn stats(&self) -> Arc<ConnectionStats> {
+        self.stats.clone()
+    }
+
+    /// Decrypts the messages received from now on.
+    ///
+    /// # Argument
+    /// * `decrypter`: receiving half of the session agreed on during the handshake.
+    pub fn enable_encryption(&mut self, decrypter: SessionDecrypter) {
+        self.decrypter = Some(decrypter);
+    }
+
     /// Awaits the next incoming message and deserializes it. Asynchronous cancel-safe.
     /// Returns the message, as well as the serialized object in the case of a block.
     ///
@@ -109,10 +161,17 @@ impl ReadBinder {
     /// We can't use `read_exact` and similar because they are not cancel-safe:
     /// `https://docs.rs/tokio/latest/tokio/io/trait.AsyncReadExt.html#cancel-safety-2`
     pub async fn next(&mut self) -> Result<Option<(u64, Message)>, NetworkError> {
+        // encrypted messages are longer by their authentication tag
+        let max_message_size = if self.decrypter.is_some() {
+            self.max_message_size.saturating_add(TAG_SIZE as u32)
+        } else {
+            self.max_message_size
+        };
+
         // check if we are in the process of reading the message length
         if self.msg_size.is_none() {
             // pre-allocate the buffer to fit the encoded message size if the buffer is not already allocated
-            let size_field_len = u32::be_bytes_min_length(self.max_message_size);
+            let size_field_len = u32::be_bytes_min_length(max_message_size);
             if self.buf.len() != size_field_len {
                 self.buf = vec![0u8; size_field_len];
             }
@@ -140,7 +199,7 @@ impl ReadBinder {
             }
 
             // once we have all the message size bytes, deserialize it
-            let res_size = u32::from_be_bytes_min(&self.buf, self.max_message_size)?.0;
+            let res_size = u32::from_be_bytes_min(&self.buf, max_message_size)?.0;
             // set self.msg_size to indicate that we are now in the process of reading the message contents (and not the size anymore).
             self.msg_size = Some(res_size);
             // allocate the buffer to match the message length
@@ -170,6 +229,12 @@ impl ReadBinder {
                 }
             }
         }
+        let wire_size = (u32::be_bytes_min_length(max_message_size) + self.buf.len()) as u64;
+
+        // decrypting does not await, so the session counter cannot be advanced by a cancelled readout
+        if let Some(decrypter) = &mut self.decrypter {
+            self.buf = decrypter.decrypt(&self.buf)?;
+        }
         let (_, res_msg) = self
             .message_deserializer
             .deserialize::<DeserializeError>(&self.buf)
@@ -185,6 +250,10 @@ impl ReadBinder {
         // clear the buffer to not leave dangling data around (note that clear() doesn't deallocate)
         self.buf.clear();
 
+        let throttled = self.throttle_meter.note_transfer(wire_size);
+        self.stats
+            .note_received(res_msg.type_id(), wire_size, throttled);
+
         // update sequence numbers and return the deserialized message
         let res_index = self.message_index;
         self.message_index += 1;
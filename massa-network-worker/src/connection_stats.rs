@@ -0,0 +1,183 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Traffic counters of a connection, shared between its binders and the network worker.
+
+use crate::messages::{MessageTypeId, MESSAGE_TYPE_COUNT};
+use massa_models::{node::NodeId, stats::PeerStats};
+use massa_time::MassaTime;
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    net::IpAddr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Value of the round-trip latency counter while no round trip was measured
+const UNKNOWN_LATENCY: u64 = u64::MAX;
+
+/// Time window over which the transferred bytes are compared to the bandwidth limit
+const THROTTLE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Traffic counters of a connection.
+///
+/// The binders update them while sending and receiving,
+/// the network worker reads them to answer stats requests.
+pub struct ConnectionStats {
+    /// When the connection was established
+    established_at: Instant,
+    /// Bytes written to the socket
+    bytes_sent: AtomicU64,
+    /// Bytes read from the socket
+    bytes_received: AtomicU64,
+    /// Messages sent, indexed by `MessageTypeId`
+    messages_sent: [AtomicU64; MESSAGE_TYPE_COUNT],
+    /// Messages received, indexed by `MessageTypeId`
+    messages_received: [AtomicU64; MESSAGE_TYPE_COUNT],
+    /// Last measured round-trip latency in milliseconds
+    round_trip_latency: AtomicU64,
+    /// Messages delayed by the read bandwidth limit
+    read_throttle_hits: AtomicU64,
+    /// Messages delayed by the write bandwidth limit
+    write_throttle_hits: AtomicU64,
+}
+
+impl ConnectionStats {
+    /// Creates the counters of a connection established now.
+    pub fn new() -> Self {
+        ConnectionStats {
+            established_at: Instant::now(),
+            bytes_sent: Default::default(),
+            bytes_received: Default::default(),
+            messages_sent: Default::default(),
+            messages_received: Default::default(),
+            round_trip_latency: AtomicU64::new(UNKNOWN_LATENCY),
+            read_throttle_hits: Default::default(),
+            write_throttle_hits: Default::default(),
+        }
+    }
+
+    /// Notes a message written to the socket.
+    ///
+    /// # Arguments
+    /// * `type_id`: type of the sent message.
+    /// * `bytes`: size of the message on the wire.
+    /// * `throttled`: whether the write bandwidth limit delayed the message.
+    pub(crate) fn note_sent(&self, type_id: MessageTypeId, bytes: u64, throttled: bool) {
+        self.bytes_sent.fetch_add(bytes, Ordering::Relaxed);
+        self.messages_sent[type_id as usize].fetch_add(1, Ordering::Relaxed);
+        if throttled {
+            self.write_throttle_hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Notes a message read from the socket.
+    ///
+    /// # Arguments
+    /// * `type_id`: type of the received message.
+    /// * `bytes`: size of the message on the wire.
+    /// * `throttled`: whether the read bandwidth limit delayed the message.
+    pub(crate) fn note_received(&self, type_id: MessageTypeId, bytes: u64, throttled: bool) {
+        self.bytes_received.fetch_add(bytes, Ordering::Relaxed);
+        self.messages_received[type_id as usize].fetch_add(1, Ordering::Relaxed);
+        if throttled {
+            self.read_throttle_hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Notes the round-trip latency of the last request answered by the peer.
+    pub(crate) fn note_round_trip_latency(&self, latency: Duration) {
+        let millis = u64::try_from(latency.as_millis())
+            .unwrap_or(u64::MAX)
+            .min(UNKNOWN_LATENCY - 1);
+        self.round_trip_latency.store(millis, Ordering::Relaxed);
+    }
+
+    /// Takes a snapshot of the counters.
+    ///
+    /// # Arguments
+    /// * `node_id`: id of the peer.
+    /// * `ip`: ip of the peer.
+    /// * `is_outgoing`: whether we initiated the connection.
+    pub(crate) fn get_peer_stats(
+        &self,
+        node_id: NodeId,
+        ip: IpAddr,
+        is_outgoing: bool,
+    ) -> PeerStats {
+        let connection_age =
+            u64::try_from(self.established_at.elapsed().as_millis()).unwrap_or(u64::MAX);
+        let round_trip_latency = match self.round_trip_latency.load(Ordering::Relaxed) {
+            UNKNOWN_LATENCY => None,
+            millis => Some(MassaTime::from_millis(millis)),
+        };
+        PeerStats {
+            node_id,
+            ip,
+            is_outgoing,
+            connection_age: MassaTime::from_millis(connection_age),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            messages_sent: message_counts(&self.messages_sent),
+            messages_received: message_counts(&self.messages_received),
+            round_trip_latency,
+            read_throttle_hits: self.read_throttle_hits.load(Ordering::Relaxed),
+            write_throttle_hits: self.write_throttle_hits.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for ConnectionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Maps the non-zero message counters to the name of their message type.
+fn message_counts(counters: &[AtomicU64; MESSAGE_TYPE_COUNT]) -> BTreeMap<String, u64> {
+    counters
+        .iter()
+        .enumerate()
+        .filter_map(|(index, counter)| {
+            let count = counter.load(Ordering::Relaxed);
+            if count == 0 {
+                return None;
+            }
+            let type_id = MessageTypeId::try_from(index as u32).ok()?;
+            Some((format!("{:?}", type_id), count))
+        })
+        .collect()
+}
+
+/// Estimates whether the bandwidth limiter of a binder had to delay a transfer,
+/// by comparing the bytes transferred over a time window to the limit.
+pub(crate) struct ThrottleMeter {
+    /// Bandwidth limit in bytes per second
+    limit: f64,
+    /// Start of the current window
+    window_start: Instant,
+    /// Bytes transferred since the start of the current window
+    window_bytes: f64,
+}
+
+impl ThrottleMeter {
+    /// Creates a meter for a bandwidth `limit` in bytes per second.
+    pub(crate) fn new(limit: f64) -> Self {
+        ThrottleMeter {
+            limit,
+            window_start: Instant::now(),
+            window_bytes: 0.0,
+        }
+    }
+
+    /// Notes a transfer of `bytes` and returns true if it exceeded the limit of the current window.
+    pub(crate) fn note_transfer(&mut self, bytes: u64) -> bool {
+        let now = Instant::now();
+        if now.duration_since(self.window_start) >= THROTTLE_WINDOW {
+            self.window_start = now;
+            self.window_bytes = 0.0;
+        }
+        self.window_bytes += bytes as f64;
+        self.window_bytes > self.limit * THROTTLE_WINDOW.as_secs_f64()
+    }
+}
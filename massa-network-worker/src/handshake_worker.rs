@@ -2,13 +2,14 @@
 
 //! Here are happening handshakes.
 
-use crate::messages::MessageDeserializer;
+use crate::{connection_stats::ConnectionStats, messages::MessageDeserializer};
 
 use super::{
     binders::{ReadBinder, WriteBinder},
     messages::Message,
 };
 use futures::future::try_join;
+use massa_cipher::{EphemeralKey, EPHEMERAL_KEY_SIZE};
 use massa_hash::Hash;
 use massa_logging::massa_trace;
 use massa_models::{
@@ -17,7 +18,7 @@ use massa_models::{
         ENDORSEMENT_COUNT, MAX_ADVERTISE_LENGTH, MAX_ENDORSEMENTS_PER_MESSAGE, MAX_MESSAGE_SIZE,
         MAX_OPERATIONS_PER_BLOCK, THREAD_COUNT,
     },
-    version::Version,
+    version::{Version, VersionSerializer},
 };
 use massa_models::{
     config::{MAX_ASK_BLOCKS_PER_MESSAGE, MAX_OPERATIONS_PER_MESSAGE},
@@ -27,12 +28,20 @@ use massa_network_exports::{
     throw_handshake_error as throw, ConnectionId, HandshakeErrorType, NetworkError, ReadHalf,
     WriteHalf,
 };
+use massa_serialization::Serializer;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::sync::Arc;
 use tokio::{task::JoinHandle, time::timeout};
 use tracing::debug;
 
+/// Marker ending the random bytes sent by the nodes that support encryption.
+/// The random bytes of a node are covered by the signature of its peer, so they cannot be altered,
+/// and a node receiving marked random bytes along with a version or a missing ephemeral key
+/// that disable encryption knows that the handshake initiation of its peer was altered.
+const ENCRYPTION_MARKER: [u8; 8] = *b"MASSAENC";
+
 /// Type alias for more readability
 pub type HandshakeReturnType = Result<(NodeId, ReadBinder, WriteBinder), NetworkError>;
 
@@ -87,6 +96,7 @@ impl HandshakeWorker {
 
         let connection_id_copy = connection_id;
         tokio::spawn(async move {
+            let stats = Arc::new(ConnectionStats::new());
             (
                 connection_id_copy,
                 HandshakeWorker {
@@ -106,8 +116,14 @@ impl HandshakeWorker {
                             MAX_FUNCTION_NAME_LENGTH,
                             MAX_PARAMETERS_SIZE,
                         ),
+                        stats.clone(),
+                    ),
+                    writer: WriteBinder::new(
+                        socket_writer,
+                        max_bytes_write,
+                        MAX_MESSAGE_SIZE,
+                        stats,
                     ),
-                    writer: WriteBinder::new(socket_writer, max_bytes_write, MAX_MESSAGE_SIZE),
                     self_node_id,
                     keypair,
                     timeout_duration,
@@ -119,6 +135,35 @@ impl HandshakeWorker {
         })
     }
 
+    /// Computes the challenge that `signer_id` has to sign to prove its identity to `receiver_id`.
+    ///
+    /// When the connection is encrypted, the challenge commits to the random bytes of the receiver
+    /// and to the versions and ephemeral keys advertised by both sides, given as `(receiver, signer)`,
+    /// so that the encryption cannot be negotiated by anyone else than the owners of the two node ids.
+    /// Otherwise it is the hash of the random bytes of the receiver, as signed by older nodes.
+    fn handshake_challenge(
+        receiver_id: &NodeId,
+        receiver_random_bytes: &[u8; 32],
+        signer_id: &NodeId,
+        versions: (&Version, &Version),
+        ephemeral_keys: Option<(&[u8; EPHEMERAL_KEY_SIZE], &[u8; EPHEMERAL_KEY_SIZE])>,
+    ) -> Result<Hash, NetworkError> {
+        match ephemeral_keys {
+            Some((receiver_ephemeral_key, signer_ephemeral_key)) => {
+                let version_serializer = VersionSerializer::new();
+                let mut challenge_data = receiver_id.0.to_bytes().to_vec();
+                challenge_data.extend(receiver_random_bytes);
+                version_serializer.serialize(versions.0, &mut challenge_data)?;
+                challenge_data.extend(receiver_ephemeral_key);
+                challenge_data.extend(signer_id.0.to_bytes());
+                version_serializer.serialize(versions.1, &mut challenge_data)?;
+                challenge_data.extend(signer_ephemeral_key);
+                Ok(Hash::compute_from(&challenge_data))
+            }
+            None => Ok(Hash::compute_from(receiver_random_bytes)),
+        }
+    }
+
     /// Manages one on going handshake.
     /// Consumes self.
     /// Returns a tuple `(ConnectionId, Result)`.
@@ -127,12 +172,22 @@ impl HandshakeWorker {
         // generate random bytes
         let mut self_random_bytes = [0u8; 32];
         StdRng::from_entropy().fill_bytes(&mut self_random_bytes);
-        let self_random_hash = Hash::compute_from(&self_random_bytes);
+        if self.version.supports_encryption() {
+            self_random_bytes[32 - ENCRYPTION_MARKER.len()..].copy_from_slice(&ENCRYPTION_MARKER);
+        }
+        // generate the ephemeral key used to agree on the session keys, if our version supports encryption
+        let self_ephemeral_key = if self.version.supports_encryption() {
+            Some(EphemeralKey::generate())
+        } else {
+            None
+        };
+        let self_ephemeral_public = self_ephemeral_key.as_ref().map(EphemeralKey::public_bytes);
         // send handshake init future
         let msg = Message::HandshakeInitiation {
             public_key: self.self_node_id.0,
             random_bytes: self_random_bytes,
             version: self.version,
+            ephemeral_key: self_ephemeral_public,
         };
         let send_init_fut = self.writer.send(&msg);
 
@@ -140,25 +195,27 @@ impl HandshakeWorker {
         let recv_init_fut = self.reader.next();
 
         // join send_init_fut and recv_init_fut with a timeout, and match result
-        let (other_node_id, other_random_bytes, other_version) = match timeout(
-            self.timeout_duration.to_duration(),
-            try_join(send_init_fut, recv_init_fut),
-        )
-        .await
-        {
-            Err(_) => throw!(HandshakeTimeout),
-            Ok(Err(e)) => return Err(e),
-            Ok(Ok((_, None))) => throw!(HandshakeInterruption, "init".into()),
-            Ok(Ok((_, Some((_, msg))))) => match msg {
-                Message::HandshakeInitiation {
-                    public_key: pk,
-                    random_bytes: rb,
-                    version,
-                } => (NodeId(pk), rb, version),
-                Message::PeerList(list) => throw!(PeerListReceived, list),
-                _ => throw!(HandshakeWrongMessage),
-            },
-        };
+        let (other_node_id, other_random_bytes, other_version, other_ephemeral_public) =
+            match timeout(
+                self.timeout_duration.to_duration(),
+                try_join(send_init_fut, recv_init_fut),
+            )
+            .await
+            {
+                Err(_) => throw!(HandshakeTimeout),
+                Ok(Err(e)) => return Err(e),
+                Ok(Ok((_, None))) => throw!(HandshakeInterruption, "init".into()),
+                Ok(Ok((_, Some((_, msg))))) => match msg {
+                    Message::HandshakeInitiation {
+                        public_key: pk,
+                        random_bytes: rb,
+                        version,
+                        ephemeral_key,
+                    } => (NodeId(pk), rb, version, ephemeral_key),
+                    Message::PeerList(list) => throw!(PeerListReceived, list),
+                    _ => throw!(HandshakeWrongMessage),
+                },
+            };
 
         // check if remote node ID is the same as ours
         if other_node_id == self.self_node_id {
@@ -170,9 +227,45 @@ impl HandshakeWorker {
             throw!(IncompatibleVersion)
         }
 
-        // sign their random bytes
-        let other_random_hash = Hash::compute_from(&other_random_bytes);
-        let self_signature = self.keypair.sign(&other_random_hash)?;
+        // a peer that supports encryption marks its random bytes, advertises a version supporting it
+        // and sends its ephemeral key: anything else means that its initiation was altered
+        if self.version.supports_encryption()
+            && other_random_bytes.ends_with(&ENCRYPTION_MARKER)
+                != (other_version.supports_encryption() && other_ephemeral_public.is_some())
+        {
+            throw!(HandshakeDowngrade)
+        }
+
+        // the connection is encrypted if both sides support it,
+        // a peer whose version supports encryption must have sent its ephemeral key
+        let session_keys = match self_ephemeral_key {
+            Some(self_ephemeral_key) if other_version.supports_encryption() => {
+                match other_ephemeral_public {
+                    Some(other_ephemeral_public) => {
+                        let self_ephemeral_public = self_ephemeral_key.public_bytes();
+                        Some((
+                            self_ephemeral_key,
+                            self_ephemeral_public,
+                            other_ephemeral_public,
+                        ))
+                    }
+                    None => throw!(HandshakeWrongMessage),
+                }
+            }
+            _ => None,
+        };
+
+        // sign their random bytes, along with both versions and ephemeral keys if the connection is encrypted
+        let other_challenge = Self::handshake_challenge(
+            &other_node_id,
+            &other_random_bytes,
+            &self.self_node_id,
+            (&other_version, &self.version),
+            session_keys
+                .as_ref()
+                .map(|(_, self_public, other_public)| (other_public, self_public)),
+        )?;
+        let self_signature = self.keypair.sign(&other_challenge)?;
 
         // send handshake reply future
         let msg = Message::HandshakeReply {
@@ -200,13 +293,49 @@ impl HandshakeWorker {
         };
 
         // check their signature
+        let self_challenge = Self::handshake_challenge(
+            &self.self_node_id,
+            &self_random_bytes,
+            &other_node_id,
+            (&self.version, &other_version),
+            session_keys
+                .as_ref()
+                .map(|(_, self_public, other_public)| (self_public, other_public)),
+        )?;
         other_node_id
             .0
-            .verify_signature(&self_random_hash, &other_signature)
+            .verify_signature(&self_challenge, &other_signature)
             .map_err(|_err| {
                 NetworkError::HandshakeError(HandshakeErrorType::HandshakeInvalidSignature)
             })?;
 
+        if let Some((self_ephemeral_key, _, other_ephemeral_public)) = session_keys {
+            // derive the session keys, both sides order the challenges by node id to get the same handshake hash
+            let initiator = self.self_node_id < other_node_id;
+            let handshake_hash = if initiator {
+                Hash::compute_from(
+                    &[
+                        &self_challenge.to_bytes()[..],
+                        &other_challenge.to_bytes()[..],
+                    ]
+                    .concat(),
+                )
+            } else {
+                Hash::compute_from(
+                    &[
+                        &other_challenge.to_bytes()[..],
+                        &self_challenge.to_bytes()[..],
+                    ]
+                    .concat(),
+                )
+            };
+            let (encrypter, decrypter) = self_ephemeral_key
+                .into_session(other_ephemeral_public, &handshake_hash, initiator)?
+                .split();
+            self.writer.enable_encryption(encrypter);
+            self.reader.enable_encryption(decrypter);
+        }
+
         Ok((other_node_id, self.reader, self.writer))
     }
 }
@@ -23,6 +23,7 @@ use tracing::{debug, error, info, warn};
 
 //pub use establisher::Establisher;
 mod binders;
+mod connection_stats;
 mod handshake_worker;
 mod messages;
 mod network_cmd_impl;
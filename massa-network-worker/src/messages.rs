@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_cipher::EPHEMERAL_KEY_SIZE;
 use massa_hash::HashDeserializer;
 use massa_models::{
     block::{BlockHeader, BlockHeaderDeserializer, BlockId, WrappedHeader},
@@ -22,6 +23,7 @@ use massa_serialization::{
 use massa_signature::{PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer};
 use nom::{
     bytes::complete::take,
+    combinator::opt,
     error::{context, ContextError, ParseError},
     multi::length_count,
     sequence::tuple,
@@ -45,10 +47,14 @@ pub enum Message {
         /// let us know their public key.
         random_bytes: [u8; HANDSHAKE_RANDOMNESS_SIZE_BYTES],
         version: Version,
+        /// Our ephemeral key, to agree on the keys encrypting the connection.
+        /// Only sent by nodes supporting encryption, older nodes ignore it.
+        ephemeral_key: Option<[u8; EPHEMERAL_KEY_SIZE]>,
     },
     /// Reply to a handshake initiation message.
     HandshakeReply {
-        /// Signature of the received random bytes with our `keypair`.
+        /// Signature with our `keypair` of the received random bytes,
+        /// along with the ephemeral keys and the node ids of both sides.
         signature: Signature,
     },
     /// Block header
@@ -74,7 +80,7 @@ pub enum Message {
     Endorsements(Vec<WrappedEndorsement>),
 }
 
-#[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
+#[derive(IntoPrimitive, Debug, Clone, Copy, Eq, PartialEq, TryFromPrimitive)]
 #[repr(u32)]
 pub(crate) enum MessageTypeId {
     HandshakeInitiation = 0u32,
@@ -90,6 +96,28 @@ pub(crate) enum MessageTypeId {
     ReplyForBlocks,
 }
 
+/// Number of variants of `MessageTypeId`
+pub(crate) const MESSAGE_TYPE_COUNT: usize = MessageTypeId::ReplyForBlocks as usize + 1;
+
+impl Message {
+    /// Returns the type id under which the message is serialized.
+    pub(crate) fn type_id(&self) -> MessageTypeId {
+        match self {
+            Message::HandshakeInitiation { .. } => MessageTypeId::HandshakeInitiation,
+            Message::HandshakeReply { .. } => MessageTypeId::HandshakeReply,
+            Message::BlockHeader(_) => MessageTypeId::BlockHeader,
+            Message::AskForBlocks(_) => MessageTypeId::AskForBlocks,
+            Message::ReplyForBlocks(_) => MessageTypeId::ReplyForBlocks,
+            Message::AskPeerList => MessageTypeId::AskPeerList,
+            Message::PeerList(_) => MessageTypeId::PeerList,
+            Message::OperationsAnnouncement(_) => MessageTypeId::OperationsAnnouncement,
+            Message::AskForOperations(_) => MessageTypeId::AskForOperations,
+            Message::Operations(_) => MessageTypeId::Operations,
+            Message::Endorsements(_) => MessageTypeId::Endorsements,
+        }
+    }
+}
+
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
 #[repr(u32)]
 pub(crate) enum BlockInfoType {
@@ -140,12 +168,16 @@ impl Serializer<Message> for MessageSerializer {
                 public_key,
                 random_bytes,
                 version,
+                ephemeral_key,
             } => {
                 self.u32_serializer
                     .serialize(&(MessageTypeId::HandshakeInitiation as u32), buffer)?;
                 buffer.extend(public_key.to_bytes());
                 buffer.extend(random_bytes);
                 self.version_serializer.serialize(version, buffer)?;
+                if let Some(ephemeral_key) = ephemeral_key {
+                    buffer.extend(ephemeral_key);
+                }
             }
             Message::HandshakeReply { signature } => {
                 self.u32_serializer
@@ -347,15 +379,23 @@ impl Deserializer<Message> for MessageDeserializer {
                         context("Failed version deserialization", |input| {
                             self.version_deserializer.deserialize(input)
                         }),
+                        context(
+                            "Failed ephemeral_key deserialization",
+                            opt(take(EPHEMERAL_KEY_SIZE)),
+                        ),
                     ))
-                    .map(|(public_key, random_bytes, version)| {
-                        // Unwrap safety: we checked above that we took enough bytes
-                        Message::HandshakeInitiation {
-                            public_key,
-                            random_bytes: array_from_slice(random_bytes).unwrap(),
-                            version,
-                        }
-                    }),
+                    .map(
+                        |(public_key, random_bytes, version, ephemeral_key)| {
+                            // Unwrap safety: we checked above that we took enough bytes
+                            Message::HandshakeInitiation {
+                                public_key,
+                                random_bytes: array_from_slice(random_bytes).unwrap(),
+                                version,
+                                ephemeral_key: ephemeral_key
+                                    .map(|key| array_from_slice(key).unwrap()),
+                            }
+                        },
+                    ),
                 )
                 .parse(input),
                 MessageTypeId::HandshakeReply => {
@@ -560,10 +600,13 @@ mod tests {
         let mut random_bytes = [0u8; 32];
         StdRng::from_entropy().fill_bytes(&mut random_bytes);
         let keypair = KeyPair::generate();
+        let mut ephemeral_key = [0u8; 32];
+        StdRng::from_entropy().fill_bytes(&mut ephemeral_key);
         let msg = Message::HandshakeInitiation {
             public_key: keypair.get_public_key(),
             random_bytes,
             version: Version::from_str("TEST.1.10").unwrap(),
+            ephemeral_key: Some(ephemeral_key),
         };
         let mut ser = Vec::new();
         message_serializer.serialize(&msg, &mut ser).unwrap();
@@ -576,18 +619,55 @@ mod tests {
                     public_key: pk1,
                     random_bytes: rb1,
                     version: v1,
+                    ephemeral_key: ek1,
                 },
                 Message::HandshakeInitiation {
                     public_key,
                     random_bytes,
                     version,
+                    ephemeral_key,
                 },
             ) => {
                 assert_eq!(pk1, public_key);
                 assert_eq!(rb1, random_bytes);
                 assert_eq!(v1, version);
+                assert_eq!(ek1, ephemeral_key);
             }
             _ => panic!("unexpected message"),
         }
     }
+
+    #[test]
+    #[serial]
+    fn test_ser_deser_handshake_without_ephemeral_key() {
+        let message_deserializer = MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+        );
+        // handshake initiation of a node that does not support encryption
+        let msg = Message::HandshakeInitiation {
+            public_key: KeyPair::generate().get_public_key(),
+            random_bytes: [0u8; 32],
+            version: Version::from_str("TEST.1.7").unwrap(),
+            ephemeral_key: None,
+        };
+        let mut ser = Vec::new();
+        MessageSerializer::new().serialize(&msg, &mut ser).unwrap();
+        let (rest, deser) = message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        assert!(rest.is_empty());
+        match deser {
+            Message::HandshakeInitiation { ephemeral_key, .. } => assert!(ephemeral_key.is_none()),
+            _ => panic!("unexpected message"),
+        }
+    }
 }
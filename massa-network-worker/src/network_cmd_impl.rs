@@ -29,11 +29,11 @@ use massa_models::{
     endorsement::WrappedEndorsement,
     node::NodeId,
     operation::{OperationPrefixIds, WrappedOperation},
-    stats::NetworkStats,
+    stats::{NetworkStats, PeerStats},
 };
 use massa_network_exports::{
     AskForBlocksInfo, BlockInfoReply, BootstrapPeers, ConnectionClosureReason, ConnectionId,
-    NetworkError, NodeCommand, Peer, Peers,
+    NetworkError, NodeCommand, Peer, PeerScoreEvent, Peers,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -324,6 +324,22 @@ pub async fn on_remove_from_whitelist_cmd(
     worker.peer_info_db.remove_from_whitelist(ips).await
 }
 
+pub async fn on_note_peer_score_event_cmd(
+    worker: &mut NetworkWorker,
+    node: NodeId,
+    event: PeerScoreEvent,
+) -> Result<(), NetworkError> {
+    massa_trace!(
+        "network_worker.manage_network_command receive NetworkCommand::NotePeerScoreEvent",
+        { "node": node, "event": event }
+    );
+    // the node may have been disconnected in the meantime
+    if let Some(ip) = get_ip(worker, &node) {
+        worker.peer_info_db.peer_score_event(&ip, event)?;
+    }
+    Ok(())
+}
+
 pub async fn on_get_stats_cmd(
     worker: &mut NetworkWorker,
     response_tx: oneshot::Sender<NetworkStats>,
@@ -339,12 +355,33 @@ pub async fn on_get_stats_cmd(
             .filter(|(_, p)| p.banned)
             .fold(0, |acc, _| acc + 1),
         active_node_count: worker.active_nodes.len() as u64,
+        in_subnet_count: worker.peer_info_db.get_in_subnet_count(),
+        out_subnet_count: worker.peer_info_db.get_out_subnet_count(),
+        subnet_refused_connection_count: worker.subnet_refused_connection_count,
     };
     if response_tx.send(res).is_err() {
         warn!("network: could not send NodeSignMessage response upstream");
     }
 }
 
+pub async fn on_get_peers_stats_cmd(
+    worker: &mut NetworkWorker,
+    response_tx: oneshot::Sender<Vec<PeerStats>>,
+) {
+    let res: Vec<PeerStats> = worker
+        .connection_stats
+        .iter()
+        .filter_map(|(node_id, stats)| {
+            let (connection_id, _) = worker.active_nodes.get(node_id)?;
+            let (ip, is_outgoing) = worker.active_connections.get(connection_id)?;
+            Some(stats.get_peer_stats(*node_id, *ip, *is_outgoing))
+        })
+        .collect();
+    if response_tx.send(res).is_err() {
+        warn!("network: could not send GetPeersStats response upstream");
+    }
+}
+
 /// Network worker received the command `NetworkCommand::SendOperations` from
 /// the controller. Happen when the program has received a new set of operation
 /// or run a kind of "send operations" loop.
@@ -6,6 +6,7 @@ use super::{
 };
 use crate::{
     binders::{ReadBinder, WriteBinder},
+    connection_stats::ConnectionStats,
     handshake_worker::HandshakeWorker,
     messages::{Message, MessageDeserializer},
     network_event::EventSender,
@@ -22,6 +23,7 @@ use massa_signature::KeyPair;
 use std::{
     collections::{hash_map, HashMap, HashSet},
     net::{IpAddr, SocketAddr},
+    sync::Arc,
 };
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
@@ -60,8 +62,12 @@ pub struct NetworkWorker {
         FuturesUnordered<JoinHandle<(NodeId, Result<ConnectionClosureReason, NetworkError>)>>,
     /// Map of connection to ip, `is_outgoing`.
     pub(crate) active_connections: HashMap<ConnectionId, (IpAddr, bool)>,
+    /// Traffic counters of the connections of active nodes.
+    pub(crate) connection_stats: HashMap<NodeId, Arc<ConnectionStats>>,
     /// Node version
     version: Version,
+    /// Number of in connections refused because their subnet was full
+    pub(crate) subnet_refused_connection_count: u64,
     /// Event sender
     pub(crate) event: EventSender,
 }
@@ -119,7 +125,9 @@ impl NetworkWorker {
             active_nodes: HashMap::new(),
             node_worker_handles: FuturesUnordered::new(),
             active_connections: HashMap::new(),
+            connection_stats: HashMap::new(),
             version,
+            subnet_refused_connection_count: 0,
         }
     }
 
@@ -232,6 +240,7 @@ impl NetworkWorker {
                     let _ = self
                         .event.send(NetworkEvent::ConnectionClosed(node_id))
                         .await;
+                    self.connection_stats.remove(&node_id);
                     if let Some((connection_id, _)) = self
                         .active_nodes
                         .remove(&node_id) {
@@ -271,6 +280,7 @@ impl NetworkWorker {
         // Cleanup of connected nodes.
         // drop sender
         self.event.drop();
+        self.connection_stats.clear();
         for (_, (_, node_tx)) in self.active_nodes.drain() {
             // close opened connection.
             trace!("before sending  NodeCommand::Close(ConnectionClosureReason::Normal) from node_tx in network_worker run_loop");
@@ -379,6 +389,8 @@ impl NetworkWorker {
                             .get(&new_connection_id)
                             .ok_or(NetworkError::ActiveConnectionMissing(new_connection_id))?;
                         self.peer_info_db.peer_alive(ip)?;
+                        self.connection_stats
+                            .insert(new_node_id, socket_reader.stats());
 
                         // spawn node_controller_fn
                         let (node_command_tx, node_command_rx) =
@@ -538,10 +550,16 @@ impl NetworkWorker {
             NetworkCommand::NodeUnbanByIds(ids) => on_node_unban_by_ids_cmd(self, ids).await?,
             NetworkCommand::NodeUnbanByIps(ips) => on_node_unban_by_ips_cmd(self, ips).await?,
             NetworkCommand::GetStats { response_tx } => on_get_stats_cmd(self, response_tx).await,
+            NetworkCommand::GetPeersStats { response_tx } => {
+                on_get_peers_stats_cmd(self, response_tx).await
+            }
             NetworkCommand::Whitelist(ips) => on_whitelist_cmd(self, ips).await?,
             NetworkCommand::RemoveFromWhitelist(ips) => {
                 on_remove_from_whitelist_cmd(self, ips).await?
             }
+            NetworkCommand::NotePeerScoreEvent { node, event } => {
+                on_note_peer_score_event_cmd(self, node, event).await?
+            }
         };
         Ok(())
     }
@@ -620,7 +638,10 @@ impl NetworkWorker {
         match res {
             Ok((reader, writer, remote_addr)) => {
                 match self.peer_info_db.try_new_in_connection(&remote_addr.ip()) {
-                    Ok(_) => {
+                    Ok(evicted_ip) => {
+                        if let Some(evicted_ip) = evicted_ip {
+                            self.evict_in_connection(evicted_ip).await;
+                        }
                         let connection_id = *cur_connection_id;
                         debug!(
                             "inbound connection from addr={} succeeded => connection_id={}",
@@ -638,6 +659,16 @@ impl NetworkWorker {
                     Err(NetworkError::PeerConnectionError(
                         NetworkConnectionErrorType::MaxPeersConnectionReached(_),
                     )) => self.try_send_peer_list_in_handshake(reader, writer, remote_addr),
+                    Err(NetworkError::PeerConnectionError(
+                        NetworkConnectionErrorType::MaxSubnetConnectionReached(_),
+                    )) => {
+                        debug!(
+                            "inbound connection from addr={} refused: subnet is full",
+                            remote_addr
+                        );
+                        massa_trace!("in_connection_refused_subnet_full", {"ip": remote_addr.ip()});
+                        self.subnet_refused_connection_count += 1;
+                    }
                     Err(_) => {
                         debug!("inbound connection from addr={} refused", remote_addr);
                         massa_trace!("in_connection_refused", {"ip": remote_addr.ip()});
@@ -652,6 +683,54 @@ impl NetworkWorker {
         Ok(())
     }
 
+    /// Closes one in connection of `ip` to make room for a better scoring peer.
+    /// The peer database is updated once the connection is actually closed.
+    ///
+    /// # Argument
+    /// * `ip`: ip address of the evicted peer
+    async fn evict_in_connection(&mut self, ip: IpAddr) {
+        let connection_id = match self
+            .active_connections
+            .iter()
+            .find(|(_, (conn_ip, is_outgoing))| *conn_ip == ip && !is_outgoing)
+        {
+            Some((connection_id, _)) => *connection_id,
+            None => {
+                warn!("could not find an in connection to evict for ip={}", ip);
+                return;
+            }
+        };
+        debug!(
+            "evicting in connection connection_id={}, ip={}",
+            connection_id, ip
+        );
+        massa_trace!("network_worker.evict_in_connection", {
+            "connection_id": connection_id,
+            "ip": ip
+        });
+        // a running handshake is closed once it finishes
+        if self.running_handshakes.remove(&connection_id) {
+            return;
+        }
+        if let Some((_, node_command_tx)) = self
+            .active_nodes
+            .values()
+            .find(|(conn_id, _)| *conn_id == connection_id)
+        {
+            if node_command_tx
+                .send(NodeCommand::Close(ConnectionClosureReason::Normal))
+                .await
+                .is_err()
+            {
+                massa_trace!(
+                    "network.network_worker.evict_in_connection", {"err": NetworkError::ChannelError(
+                        "close node command send failed".into(),
+                    ).to_string()}
+                );
+            }
+        }
+    }
+
     /// Start to mock a handshake and try to send a message with a list of
     /// peers.
     /// The function is used while `manage_in_connections()` if the current
@@ -706,7 +785,9 @@ impl NetworkWorker {
             let max_parameters_size = self.cfg.max_parameters_size;
             self.handshake_peer_list_futures
                 .push(tokio::spawn(async move {
-                    let mut writer = WriteBinder::new(writer, max_bytes_read, max_message_size);
+                    let stats = Arc::new(ConnectionStats::new());
+                    let mut writer =
+                        WriteBinder::new(writer, max_bytes_read, max_message_size, stats.clone());
                     let mut reader = ReadBinder::new(
                         reader,
                         max_bytes_write,
@@ -723,6 +804,7 @@ impl NetworkWorker {
                             max_function_name_length,
                             max_parameters_size,
                         ),
+                        stats,
                     );
                     match tokio::time::timeout(
                         timeout,
@@ -20,7 +20,7 @@ use tokio::{
         error::{SendTimeoutError, TrySendError},
         Sender,
     },
-    time::timeout,
+    time::{timeout, Instant},
 };
 use tracing::{debug, trace, warn};
 
@@ -169,6 +169,8 @@ impl NodeWorker {
 
         let mut ask_peer_list_interval =
             tokio::time::interval(self.cfg.ask_peer_list_interval.to_duration());
+        // when the last unanswered peer list request was sent, to measure the round-trip latency
+        let mut ask_peer_list_sent_at: Option<Instant> = None;
         let mut exit_reason = ConnectionClosureReason::Normal;
         'select_loop: loop {
             /*
@@ -229,6 +231,9 @@ impl NodeWorker {
                             }
                             Message::PeerList(pl) =>  {
                                 massa_trace!("node_worker.run_loop. receive Message::PeerList", {"peerlist": pl, "node": self.node_id});
+                                if let Some(sent_at) = ask_peer_list_sent_at.take() {
+                                    self.socket_reader.stats().note_round_trip_latency(sent_at.elapsed());
+                                }
                                 self.send_node_event(NodeEvent(self.node_id, NodeEventType::ReceivedPeerList(pl))).await;
                             }
                             Message::AskPeerList => {
@@ -373,6 +378,7 @@ impl NodeWorker {
                     writer_command_tx.send(Message::AskPeerList).await.map_err(
                         |_| NetworkError::ChannelError("writer send ask peer list failed".into())
                     )?;
+                    ask_peer_list_sent_at = Some(Instant::now());
                     trace!("after sending Message::AskPeerList from writer_command_tx in node_worker run_loop");
                 }
             }
@@ -9,12 +9,13 @@ use massa_network_exports::NetworkConfig;
 use massa_network_exports::NetworkConnectionErrorType;
 use massa_network_exports::NetworkError;
 use massa_network_exports::PeerInfo;
+use massa_network_exports::PeerScoreEvent;
 use massa_network_exports::PeerType;
 use massa_time::MassaTime;
 use serde_json::json;
 use std::cmp::Reverse;
 use std::collections::HashMap;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::path::Path;
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
@@ -38,6 +39,28 @@ pub struct PeerInfoDatabase {
     pub(crate) clock_compensation: i64,
 }
 
+/// Length of the prefix identifying the subnet of an IPv4 address
+const IPV4_SUBNET_PREFIX_LENGTH: u32 = 24;
+
+/// Length of the prefix identifying the subnet of an IPv6 address
+const IPV6_SUBNET_PREFIX_LENGTH: u32 = 48;
+
+/// Returns the subnet of an ip address, as the address with its host bits set to zero
+/// (/24 for IPv4, /48 for IPv6).
+///
+/// # Argument
+/// * `ip`: canonical ip address
+pub(crate) fn get_subnet(ip: &IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(ip) => IpAddr::V4(Ipv4Addr::from(
+            u32::from(*ip) & (u32::MAX << (32 - IPV4_SUBNET_PREFIX_LENGTH)),
+        )),
+        IpAddr::V6(ip) => IpAddr::V6(Ipv6Addr::from(
+            u128::from(*ip) & (u128::MAX << (128 - IPV6_SUBNET_PREFIX_LENGTH)),
+        )),
+    }
+}
+
 /// Saves advertised and non standard peers to a file.
 ///
 /// # Arguments
@@ -58,6 +81,7 @@ async fn dump_peers(
                 "last_alive": peer.last_alive,
                 "last_failure": peer.last_failure,
                 "advertised": peer.advertised,
+                "score": peer.score,
             })
         })
         .collect();
@@ -146,10 +170,16 @@ pub(crate) fn cleanup_peers(
 
     // append new peers to idle_peers
     // stable sort to keep new_peers order,
-    // also prefer existing peers over new ones
+    // also prefer well scoring and existing peers over new ones
     // truncate to max length
     idle_peers.append(&mut res_new_peers);
-    idle_peers.sort_by_key(|&p| (std::cmp::Reverse(p.last_alive), p.last_failure));
+    idle_peers.sort_by_key(|&p| {
+        (
+            std::cmp::Reverse(p.score),
+            std::cmp::Reverse(p.last_alive),
+            p.last_failure,
+        )
+    });
     idle_peers.truncate(cfg.max_idle_peers);
 
     // sort and truncate inactive banned peers
@@ -435,6 +465,14 @@ impl PeerInfoDatabase {
         if !ip.is_global() {
             return Err(NetworkError::InvalidIpError(ip));
         }
+        if self.get_peer_type(&ip).unwrap_or_default() == PeerType::Standard
+            && self.get_subnet_out_connection_count(&ip)
+                >= self.network_settings.max_out_connections_per_subnet
+        {
+            return Err(NetworkError::PeerConnectionError(
+                NetworkConnectionErrorType::MaxSubnetConnectionReached(ip),
+            ));
+        }
         let peer_type = if let Some(peer) = self.peers.get(&ip) {
             if self.can_try_new_out_connection(peer.peer_type) {
                 // Can unwrap because we checked above that there is a peer.
@@ -496,6 +534,29 @@ impl PeerInfoDatabase {
         self.request_dump()
     }
 
+    /// Applies a protocol event to the score of the peer.
+    /// The score is saved with the next dump.
+    ///
+    /// # Arguments
+    /// * ip : ip address of the considered peer.
+    /// * event : event affecting the score of the peer.
+    pub fn peer_score_event(
+        &mut self,
+        ip: &IpAddr,
+        event: PeerScoreEvent,
+    ) -> Result<(), NetworkError> {
+        let ip = ip.to_canonical();
+        self.peers
+            .get_mut(&ip)
+            .ok_or({
+                NetworkError::PeerConnectionError(
+                    NetworkConnectionErrorType::PeerInfoNotFoundError(ip),
+                )
+            })?
+            .apply_score_event(event);
+        Ok(())
+    }
+
     /// Sets that the peer is banned now.
     /// If the peer is not active, the database is cleaned up.
     /// A dump is requested.
@@ -585,6 +646,7 @@ impl PeerInfoDatabase {
                 )
             })?;
             peer.active_in_connections -= 1;
+            peer.pending_eviction = false;
             let peer_type = peer.peer_type;
             if !peer.is_active() && peer.peer_type == PeerType::Standard {
                 self.update()?;
@@ -699,14 +761,18 @@ impl PeerInfoDatabase {
     }
 
     /// An ip has successfully connected to us.
-    /// returns true if some in slots for connections are left.
+    /// returns an error if no in slots for connections are left.
     /// If the corresponding peer exists, it is updated,
     /// otherwise it is created (not advertised).
     /// A dump is requested.
     ///
+    /// When all the in slots of its peer type are taken, the lowest scoring inbound peer
+    /// is evicted if it scores below the incoming one: its ip is returned
+    /// and one of its in connections has to be closed by the caller.
+    ///
     /// # Argument
     /// * ip : ip address of the considered peer.
-    pub fn try_new_in_connection(&mut self, ip: &IpAddr) -> Result<(), NetworkError> {
+    pub fn try_new_in_connection(&mut self, ip: &IpAddr) -> Result<Option<IpAddr>, NetworkError> {
         let ip = ip.to_canonical();
         // try to create a new input connection, return false if no slots
         if !ip.is_global() || self.network_settings.max_in_connections_per_ip == 0 {
@@ -724,19 +790,39 @@ impl PeerInfoDatabase {
             }
         }
 
-        let peer_type = self
-            .peers
-            .entry(ip)
-            .or_insert_with(|| PeerInfo::new(ip, false))
-            .peer_type;
+        let (peer_type, score) = {
+            let peer = self
+                .peers
+                .entry(ip)
+                .or_insert_with(|| PeerInfo::new(ip, false));
+            (peer.peer_type, peer.score)
+        };
 
-        // we need to first check if there is a global slot available
-        if self.is_max_in_connection_count_reached(peer_type) {
+        // standard peers of a same subnet can't take all our slots
+        if peer_type == PeerType::Standard
+            && self.get_subnet_in_connection_count(&ip)
+                >= self.network_settings.max_in_connections_per_subnet
+        {
             return Err(NetworkError::PeerConnectionError(
-                NetworkConnectionErrorType::MaxPeersConnectionReached(ip),
+                NetworkConnectionErrorType::MaxSubnetConnectionReached(ip),
             ));
         }
 
+        // we need to first check if there is a global slot available,
+        // or a lower scoring peer to make room from
+        let evicted_ip = if self.is_max_in_connection_count_reached(peer_type) {
+            match self.get_in_connection_eviction_candidate(peer_type, score) {
+                Some(evicted_ip) => Some(evicted_ip),
+                None => {
+                    return Err(NetworkError::PeerConnectionError(
+                        NetworkConnectionErrorType::MaxPeersConnectionReached(ip),
+                    ))
+                }
+            }
+        } else {
+            None
+        };
+
         let peer_type = {
             let peer = self.peers.get_mut(&ip).ok_or({
                 NetworkError::PeerConnectionError(
@@ -764,16 +850,23 @@ impl PeerInfoDatabase {
             peer.peer_type
         };
 
+        if let Some(evicted_ip) = evicted_ip {
+            massa_trace!("in_connection_evicted", {"ip": evicted_ip, "for_ip": ip});
+            // checked by get_in_connection_eviction_candidate
+            if let Some(evicted_peer) = self.peers.get_mut(&evicted_ip) {
+                evicted_peer.pending_eviction = true;
+            }
+        }
         self.increase_global_active_in_connection_count(peer_type)?;
         self.request_dump()?;
-        Ok(())
+        Ok(evicted_ip)
     }
 
     ////////////////////
     // public getters //
     ////////////////////
 
-    /// Sorts peers by `( last_failure, rev(last_success) )`
+    /// Sorts peers by `( rev(score), last_failure, rev(last_success) )`
     /// and returns as many peers as there are available slots to attempt outgoing connections to.
     pub fn get_out_connection_candidate_ips(&self) -> Result<Vec<IpAddr>, NetworkError> {
         let mut connections = vec![];
@@ -793,6 +886,26 @@ impl PeerInfoDatabase {
         Ok(connections)
     }
 
+    /// Number of distinct subnets among our in connections
+    pub fn get_in_subnet_count(&self) -> u64 {
+        self.peers
+            .values()
+            .filter(|p| p.active_in_connections > 0)
+            .map(|p| get_subnet(&p.ip))
+            .unique()
+            .count() as u64
+    }
+
+    /// Number of distinct subnets among our out connections
+    pub fn get_out_subnet_count(&self) -> u64 {
+        self.peers
+            .values()
+            .filter(|p| p.active_out_connections > 0)
+            .map(|p| get_subnet(&p.ip))
+            .unique()
+            .count() as u64
+    }
+
     /// returns Hashmap of `IpAddrs` -> `PeerInfo`
     pub fn get_peers(&self) -> &HashMap<IpAddr, PeerInfo> {
         &self.peers
@@ -861,14 +974,91 @@ impl PeerInfoDatabase {
             }
             p.is_peer_ready(self.wakeup_interval, now)
         };
-        let mut res: Vec<_> = self
-            .peers
+        let mut res: Vec<_> = self.peers.values().filter(f).collect();
+        res.sort_unstable_by_key(|&p| {
+            (
+                std::cmp::Reverse(p.score),
+                p.last_failure,
+                std::cmp::Reverse(p.last_alive),
+            )
+        });
+        if peer_type != PeerType::Standard {
+            return Ok(res
+                .into_iter()
+                .take(available_slots)
+                .map(|p| p.ip)
+                .collect());
+        }
+
+        // prefer diverse subnets: first pick the candidates from subnets with the fewest
+        // out connections, then from more connected ones, up to the subnet limit
+        let mut subnet_counts: HashMap<IpAddr, usize> = HashMap::new();
+        for peer in self.peers.values() {
+            if peer.peer_type == PeerType::Standard {
+                *subnet_counts.entry(get_subnet(&peer.ip)).or_default() +=
+                    peer.active_out_connections + peer.active_out_connection_attempts;
+            }
+        }
+        let mut selected = Vec::new();
+        let mut remaining: Vec<_> = res.into_iter().map(|p| p.ip).collect();
+        for level in 0..self.network_settings.max_out_connections_per_subnet {
+            remaining.retain(|ip| {
+                if selected.len() >= available_slots {
+                    return false;
+                }
+                let count = subnet_counts.entry(get_subnet(ip)).or_default();
+                if *count > level {
+                    return true;
+                }
+                *count += 1;
+                selected.push(*ip);
+                false
+            });
+        }
+        Ok(selected)
+    }
+
+    /// Count the in connections with standard peers in the subnet of `ip`
+    fn get_subnet_in_connection_count(&self, ip: &IpAddr) -> usize {
+        let subnet = get_subnet(ip);
+        self.peers
             .values()
-            .filter(f)
-            .take(available_slots)
-            .collect();
-        res.sort_unstable_by_key(|&p| (p.last_failure, std::cmp::Reverse(p.last_alive)));
-        Ok(res.into_iter().map(|p| p.ip).collect())
+            .filter(|p| p.peer_type == PeerType::Standard && get_subnet(&p.ip) == subnet)
+            .map(|p| p.active_in_connections)
+            .sum()
+    }
+
+    /// Count the out connections and attempts with standard peers in the subnet of `ip`
+    fn get_subnet_out_connection_count(&self, ip: &IpAddr) -> usize {
+        let subnet = get_subnet(ip);
+        self.peers
+            .values()
+            .filter(|p| p.peer_type == PeerType::Standard && get_subnet(&p.ip) == subnet)
+            .map(|p| p.active_out_connections + p.active_out_connection_attempts)
+            .sum()
+    }
+
+    /// Get the lowest scoring inbound peer of a given type that scores below `score`
+    /// and is not already being evicted
+    ///
+    /// # Arguments
+    /// * `peer_type`: type of the incoming peer
+    /// * `score`: score of the incoming peer
+    fn get_in_connection_eviction_candidate(
+        &self,
+        peer_type: PeerType,
+        score: i64,
+    ) -> Option<IpAddr> {
+        self.peers
+            .values()
+            .filter(|p| {
+                p.peer_type == peer_type
+                    && p.active_in_connections > 0
+                    && !p.pending_eviction
+                    && p.score < score
+            })
+            .min_by_key(|p| (p.score, p.ip))
+            .map(|p| p.ip)
     }
 
     fn get_peer_type(&self, ip: &IpAddr) -> Option<PeerType> {
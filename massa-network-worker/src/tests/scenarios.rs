@@ -2,17 +2,20 @@
 
 // To start alone RUST_BACKTRACE=1 cargo test -- --nocapture --test-threads=1
 use super::tools;
-use crate::messages::{Message, MessageDeserializer};
+use crate::handshake_worker::HandshakeWorker;
+use crate::messages::{Message, MessageDeserializer, MessageSerializer};
 use crate::node_worker::NodeWorker;
 use crate::tests::tools::{get_dummy_block_id, get_transaction};
 use crate::NetworkError;
 use crate::NetworkEvent;
 use crate::{
     binders::{ReadBinder, WriteBinder},
+    connection_stats::ConnectionStats,
     NetworkConfig,
 };
 use enum_map::enum_map;
 use enum_map::EnumMap;
+use massa_cipher::{EphemeralKey, TAG_SIZE};
 use massa_hash::Hash;
 use massa_models::config::{
     ENDORSEMENT_COUNT, MAX_ADVERTISE_LENGTH, MAX_ASK_BLOCKS_PER_MESSAGE,
@@ -24,7 +27,9 @@ use massa_models::{
     block::BlockId,
     endorsement::{Endorsement, EndorsementSerializer},
     node::NodeId,
+    serialization::DeserializeMinBEInt,
     slot::Slot,
+    version::Version,
     wrapped::WrappedContent,
 };
 use massa_network_exports::{settings::PeerTypeConnectionConfig, NodeCommand, NodeEvent};
@@ -32,14 +37,18 @@ use massa_network_exports::{
     AskForBlocksInfo, BlockInfoReply, ConnectionClosureReason, ConnectionId, HandshakeErrorType,
     PeerInfo, PeerType,
 };
+use massa_serialization::Serializer;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serial_test::serial;
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
     time::{Duration, Instant},
 };
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
 use tokio::sync::mpsc;
 use tokio::time::sleep;
 use tracing::trace;
@@ -74,6 +83,7 @@ async fn test_node_worker_shutdown() {
     let network_conf = NetworkConfig::scenarios_default(bind_port, temp_peers_file.path());
     let (duplex_controller, _duplex_mock) = tokio::io::duplex(1);
     let (duplex_mock_read, duplex_mock_write) = tokio::io::split(duplex_controller);
+    let stats = Arc::new(ConnectionStats::new());
     let reader = ReadBinder::new(
         duplex_mock_read,
         f64::INFINITY,
@@ -90,8 +100,9 @@ async fn test_node_worker_shutdown() {
             MAX_FUNCTION_NAME_LENGTH,
             MAX_PARAMETERS_SIZE,
         ),
+        stats.clone(),
     );
-    let writer = WriteBinder::new(duplex_mock_write, f64::INFINITY, MAX_MESSAGE_SIZE);
+    let writer = WriteBinder::new(duplex_mock_write, f64::INFINITY, MAX_MESSAGE_SIZE, stats);
 
     // Note: both channels have size 1.
     let (node_command_tx, node_command_rx) = mpsc::channel::<NodeCommand>(1);
@@ -143,6 +154,7 @@ async fn test_node_worker_operations_message() {
     let network_conf = NetworkConfig::scenarios_default(bind_port, temp_peers_file.path());
     let (duplex_controller, _duplex_mock) = tokio::io::duplex(1);
     let (duplex_mock_read, duplex_mock_write) = tokio::io::split(duplex_controller);
+    let stats = Arc::new(ConnectionStats::new());
     let reader = ReadBinder::new(
         duplex_mock_read,
         f64::INFINITY,
@@ -159,8 +171,9 @@ async fn test_node_worker_operations_message() {
             MAX_FUNCTION_NAME_LENGTH,
             MAX_PARAMETERS_SIZE,
         ),
+        stats.clone(),
     );
-    let writer = WriteBinder::new(duplex_mock_write, f64::INFINITY, MAX_MESSAGE_SIZE);
+    let writer = WriteBinder::new(duplex_mock_write, f64::INFINITY, MAX_MESSAGE_SIZE, stats);
 
     // Note: both channels have size 1.
     let (node_command_tx, node_command_rx) = mpsc::channel::<NodeCommand>(1);
@@ -621,6 +634,8 @@ async fn test_advertised_and_wakeup_interval() {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        score: 0,
+        pending_eviction: false,
     }]);
     let network_conf = NetworkConfig {
         wakeup_interval: MassaTime::from_millis(500),
@@ -654,7 +669,7 @@ async fn test_advertised_and_wakeup_interval() {
                 // wait for the message signalling the closure of the connection
                 tools::wait_network_event(
                     &mut network_event_receiver,
-                    1000.into(),
+                    MassaTime::from_millis(1000),
                     |msg| match msg {
                         NetworkEvent::ConnectionClosed(closed_node_id) => {
                             if closed_node_id == conn2_id {
@@ -709,7 +724,7 @@ async fn test_advertised_and_wakeup_interval() {
             // 4) check that there are no further connection attempts from controller
             if tools::wait_network_event(
                 &mut network_event_receiver,
-                1000.into(),
+                MassaTime::from_millis(1000),
                 |msg| match msg {
                     NetworkEvent::NewConnection(_) => Some(()),
                     _ => None,
@@ -756,6 +771,8 @@ async fn test_block_not_found() {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        score: 0,
+        pending_eviction: false,
     }]);
     let network_conf = NetworkConfig {
         peer_types_config: default_testing_peer_type_enum_map(),
@@ -890,7 +907,7 @@ async fn test_block_not_found() {
             // assert it is sent to protocol
             if tools::wait_network_event(
                 &mut network_event_receiver,
-                1000.into(),
+                MassaTime::from_millis(1000),
                 |msg| match msg {
                     NetworkEvent::AskedForBlocks { list, node } => Some((list, node)),
                     _ => None,
@@ -938,6 +955,8 @@ async fn test_retry_connection_closed() {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        score: 0,
+        pending_eviction: false,
     }]);
     let network_conf = NetworkConfig {
         peer_types_config: default_testing_peer_type_enum_map(),
@@ -1037,6 +1056,8 @@ async fn test_operation_messages() {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        score: 0,
+        pending_eviction: false,
     }]);
     let network_conf = NetworkConfig {
         peer_types_config: default_testing_peer_type_enum_map(),
@@ -1152,6 +1173,8 @@ async fn test_endorsements_messages() {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        score: 0,
+        pending_eviction: false,
     }]);
     let network_conf = NetworkConfig {
         peer_types_config: default_testing_peer_type_enum_map(),
@@ -1265,3 +1288,287 @@ async fn test_endorsements_messages() {
     )
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn test_peers_stats() {
+    // test config
+    let bind_port: u16 = 50_000;
+
+    let mock_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(169, 202, 0, 11)), bind_port);
+    // add advertised peer to controller
+    let temp_peers_file = super::tools::generate_peers_file(&[PeerInfo {
+        ip: mock_addr.ip(),
+        peer_type: PeerType::Bootstrap,
+        last_alive: None,
+        last_failure: None,
+        advertised: true,
+        active_out_connection_attempts: 0,
+        active_out_connections: 0,
+        active_in_connections: 0,
+        banned: false,
+        score: 0,
+        pending_eviction: false,
+    }]);
+    let network_conf = NetworkConfig {
+        peer_types_config: default_testing_peer_type_enum_map(),
+        ..NetworkConfig::scenarios_default(bind_port, temp_peers_file.path())
+    };
+
+    tools::network_test(
+        network_conf.clone(),
+        temp_peers_file,
+        async move |network_command_sender,
+                    mut network_event_receiver,
+                    network_manager,
+                    mut mock_interface| {
+            // accept connection from controller to peer
+            let (conn1_id, conn1_r, mut conn1_w) = tools::full_connection_from_controller(
+                &mut network_event_receiver,
+                &mut mock_interface,
+                mock_addr,
+                1_000u64,
+                1_000u64,
+                1_000u64,
+                ConnectionId(0),
+            )
+            .await;
+            let conn1_drain = tools::incoming_message_drain_start(conn1_r).await;
+
+            let endorsement = Endorsement::new_wrapped(
+                Endorsement {
+                    slot: Slot::new(10, 1),
+                    index: 0,
+                    endorsed_block: BlockId(Hash::compute_from(&[])),
+                },
+                EndorsementSerializer::new(),
+                &KeyPair::generate(),
+            )
+            .unwrap();
+            conn1_w
+                .send(&Message::Endorsements(vec![endorsement]))
+                .await
+                .unwrap();
+
+            // wait for the endorsement to be read by the controller
+            tools::wait_network_event(&mut network_event_receiver, 1000.into(), |msg| match msg {
+                NetworkEvent::ReceivedEndorsements { .. } => Some(()),
+                _ => None,
+            })
+            .await
+            .expect("Timeout while waiting for endorsement event.");
+
+            let peers_stats = network_command_sender.get_peers_stats().await.unwrap();
+            assert_eq!(peers_stats.len(), 1);
+            let peer_stats = &peers_stats[0];
+            assert_eq!(peer_stats.node_id, conn1_id);
+            assert_eq!(peer_stats.ip, mock_addr.ip());
+            assert!(peer_stats.is_outgoing);
+            assert!(peer_stats.bytes_sent > 0);
+            assert!(peer_stats.bytes_received > 0);
+            assert_eq!(
+                peer_stats.messages_sent.get("HandshakeInitiation"),
+                Some(&1)
+            );
+            assert_eq!(
+                peer_stats.messages_received.get("HandshakeInitiation"),
+                Some(&1)
+            );
+            assert_eq!(peer_stats.messages_received.get("Endorsements"), Some(&1));
+
+            (
+                network_event_receiver,
+                network_manager,
+                mock_interface,
+                vec![conn1_drain],
+            )
+        },
+    )
+    .await;
+}
+
+/// Creates a writer and a reader sharing an encrypted session, linked through the returned raw streams:
+/// the frames sent by the writer are read from the first stream, and the reader reads the frames written to the second one.
+fn get_encrypted_binders() -> (WriteBinder, DuplexStream, DuplexStream, ReadBinder) {
+    let writer_key = EphemeralKey::generate();
+    let reader_key = EphemeralKey::generate();
+    let writer_public = writer_key.public_bytes();
+    let reader_public = reader_key.public_bytes();
+    let handshake_hash = Hash::compute_from(b"handshake");
+    let (encrypter, _) = writer_key
+        .into_session(reader_public, &handshake_hash, true)
+        .unwrap()
+        .split();
+    let (_, decrypter) = reader_key
+        .into_session(writer_public, &handshake_hash, false)
+        .unwrap()
+        .split();
+
+    let (writer_stream, raw_in) = tokio::io::duplex(1024);
+    let (raw_out, reader_stream) = tokio::io::duplex(1024);
+    let stats = Arc::new(ConnectionStats::new());
+    let mut writer = WriteBinder::new(
+        tokio::io::split(writer_stream).1,
+        f64::INFINITY,
+        MAX_MESSAGE_SIZE,
+        stats.clone(),
+    );
+    writer.enable_encryption(encrypter);
+    let mut reader = ReadBinder::new(
+        tokio::io::split(reader_stream).0,
+        f64::INFINITY,
+        MAX_MESSAGE_SIZE,
+        MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+        ),
+        stats,
+    );
+    reader.enable_encryption(decrypter);
+    (writer, raw_in, raw_out, reader)
+}
+
+/// Sends `msg` with `writer` and returns the raw encrypted frame, with its size field.
+async fn send_encrypted_frame(
+    writer: &mut WriteBinder,
+    raw_in: &mut DuplexStream,
+    msg: &Message,
+) -> Vec<u8> {
+    let mut serialized = Vec::new();
+    MessageSerializer::new()
+        .serialize(msg, &mut serialized)
+        .unwrap();
+    writer.send(msg).await.unwrap();
+    let mut frame = vec![
+        0u8;
+        u32::be_bytes_min_length(MAX_MESSAGE_SIZE + TAG_SIZE as u32)
+            + serialized.len()
+            + TAG_SIZE
+    ];
+    raw_in.read_exact(&mut frame).await.unwrap();
+    frame
+}
+
+/// Test that an encrypted connection rejects a frame that was altered or replayed.
+#[tokio::test]
+#[serial]
+async fn test_read_binder_rejects_tampered_and_replayed_frames() {
+    // a frame received as sent is decrypted, then the same frame is rejected if it is received again
+    let (mut writer, mut raw_in, mut raw_out, mut reader) = get_encrypted_binders();
+    let frame = send_encrypted_frame(&mut writer, &mut raw_in, &Message::AskPeerList).await;
+    raw_out.write_all(&frame).await.unwrap();
+    assert!(matches!(
+        reader.next().await,
+        Ok(Some((0, Message::AskPeerList)))
+    ));
+    raw_out.write_all(&frame).await.unwrap();
+    assert!(matches!(
+        reader.next().await,
+        Err(NetworkError::CipherError(_))
+    ));
+
+    // a frame with a flipped bit is rejected
+    let (mut writer, mut raw_in, mut raw_out, mut reader) = get_encrypted_binders();
+    let mut frame = send_encrypted_frame(&mut writer, &mut raw_in, &Message::AskPeerList).await;
+    *frame.last_mut().unwrap() ^= 1;
+    raw_out.write_all(&frame).await.unwrap();
+    assert!(matches!(
+        reader.next().await,
+        Err(NetworkError::CipherError(_))
+    ));
+}
+
+/// Forwards the messages read by `reader` to `writer`, disabling the encryption advertised
+/// in the handshake initiations as a man in the middle would.
+async fn relay_downgrading_handshakes(mut reader: ReadBinder, mut writer: WriteBinder) {
+    while let Ok(Some((_, msg))) = reader.next().await {
+        let msg = match msg {
+            Message::HandshakeInitiation {
+                public_key,
+                random_bytes,
+                ..
+            } => Message::HandshakeInitiation {
+                public_key,
+                random_bytes,
+                version: Version::from_str("TEST.14.7").unwrap(),
+                ephemeral_key: None,
+            },
+            msg => msg,
+        };
+        if writer.send(&msg).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Returns plain binders over `stream`, for the relay of `test_handshake_downgrade_is_rejected`.
+fn get_relay_binders(stream: DuplexStream) -> (ReadBinder, WriteBinder) {
+    let (read_half, write_half) = tokio::io::split(stream);
+    let stats = Arc::new(ConnectionStats::new());
+    let reader = ReadBinder::new(
+        read_half,
+        f64::INFINITY,
+        MAX_MESSAGE_SIZE,
+        MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+        ),
+        stats.clone(),
+    );
+    let writer = WriteBinder::new(write_half, f64::INFINITY, MAX_MESSAGE_SIZE, stats);
+    (reader, writer)
+}
+
+/// Test that two nodes supporting encryption refuse a handshake rewritten to disable it.
+#[tokio::test]
+#[serial]
+async fn test_handshake_downgrade_is_rejected() {
+    let (node_a_stream, relay_a_stream) = tokio::io::duplex(MAX_MESSAGE_SIZE as usize);
+    let (node_b_stream, relay_b_stream) = tokio::io::duplex(MAX_MESSAGE_SIZE as usize);
+    let (relay_a_reader, relay_a_writer) = get_relay_binders(relay_a_stream);
+    let (relay_b_reader, relay_b_writer) = get_relay_binders(relay_b_stream);
+    tokio::spawn(relay_downgrading_handshakes(relay_a_reader, relay_b_writer));
+    tokio::spawn(relay_downgrading_handshakes(relay_b_reader, relay_a_writer));
+
+    let spawn_node = |stream: DuplexStream, connection_id: ConnectionId| {
+        let (read_half, write_half) = tokio::io::split(stream);
+        let keypair = KeyPair::generate();
+        HandshakeWorker::spawn(
+            read_half,
+            write_half,
+            NodeId(keypair.get_public_key()),
+            keypair,
+            MassaTime::from_millis(1000),
+            Version::from_str("TEST.14.10").unwrap(),
+            connection_id,
+            f64::INFINITY,
+            f64::INFINITY,
+        )
+    };
+    let node_a = spawn_node(node_a_stream, ConnectionId(0));
+    let node_b = spawn_node(node_b_stream, ConnectionId(1));
+    for node in [node_a, node_b] {
+        assert!(matches!(
+            node.await.unwrap().1,
+            Err(NetworkError::HandshakeError(
+                HandshakeErrorType::HandshakeDowngrade
+            ))
+        ));
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{
-    peer_info_database::{cleanup_peers, PeerInfoDatabase},
+    peer_info_database::{cleanup_peers, get_subnet, PeerInfoDatabase},
     NetworkConfig, NetworkError,
 };
 use enum_map::enum_map;
 use massa_network_exports::{
-    settings::PeerTypeConnectionConfig, NetworkConnectionErrorType, PeerInfo, PeerType,
+    settings::PeerTypeConnectionConfig, NetworkConnectionErrorType, PeerInfo, PeerScoreEvent,
+    PeerType, MIN_PEER_SCORE,
 };
 use massa_time::MassaTime;
 use serial_test::serial;
@@ -806,6 +807,163 @@ async fn test_cleanup_peers() {
     assert!(peers.contains_key(&IpAddr::V4(std::net::Ipv4Addr::new(169, 202, 0, 43))));
 }
 
+#[tokio::test]
+#[serial]
+async fn test_in_connection_eviction() {
+    let peer_types_config = enum_map! {
+        PeerType::Standard => {
+            PeerTypeConnectionConfig {
+                target_out_connections: 5,
+                max_in_connections: 1,
+                max_out_attempts: 5,
+            }
+        },
+        PeerType::Bootstrap => Default::default(),
+        PeerType::WhiteListed => Default::default()
+    };
+    let network_settings = NetworkConfig {
+        peer_types_config,
+        ..Default::default()
+    };
+    let low_ip = IpAddr::V4(std::net::Ipv4Addr::new(169, 202, 0, 21));
+    let high_ip = IpAddr::V4(std::net::Ipv4Addr::new(169, 202, 0, 22));
+    let new_ip = IpAddr::V4(std::net::Ipv4Addr::new(169, 202, 0, 23));
+    let mut peers: HashMap<IpAddr, PeerInfo> = HashMap::new();
+    let mut low_peer = default_peer_info_not_connected(low_ip);
+    low_peer.score = -50;
+    peers.insert(low_ip, low_peer);
+    let mut high_peer = default_peer_info_not_connected(high_ip);
+    high_peer.score = 20;
+    peers.insert(high_ip, high_peer);
+
+    let wakeup_interval = network_settings.wakeup_interval;
+    let (saver_watch_tx, mut saver_watch_rx) = watch::channel(peers.clone());
+    let saver_join_handle =
+        tokio::spawn(async move { while let Ok(()) = saver_watch_rx.changed().await {} });
+    let mut db = PeerInfoDatabase {
+        network_settings,
+        peers,
+        saver_join_handle,
+        saver_watch_tx,
+        wakeup_interval,
+        clock_compensation: 0,
+        peer_types_connection_count: Default::default(),
+    };
+
+    // the only in slot is taken by the low scoring peer
+    assert_eq!(db.try_new_in_connection(&low_ip).unwrap(), None);
+
+    // a better scoring peer evicts it
+    assert_eq!(db.try_new_in_connection(&high_ip).unwrap(), Some(low_ip));
+    assert!(db.peers.get(&low_ip).unwrap().pending_eviction);
+
+    // the evicted peer is not picked twice, and the better scoring peer can't be evicted by a new one
+    if let Err(NetworkError::PeerConnectionError(
+        NetworkConnectionErrorType::MaxPeersConnectionReached(ip_err),
+    )) = db.try_new_in_connection(&new_ip)
+    {
+        assert_eq!(new_ip, ip_err);
+    } else {
+        panic!("MaxPeersConnectionReached error not return");
+    }
+
+    // the eviction is over once the connection is closed
+    db.in_connection_closed(&low_ip).unwrap();
+    assert!(!db.peers.get(&low_ip).unwrap().pending_eviction);
+    assert_eq!(db.get_in_connection_count(), 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_subnet_limits() {
+    let network_settings = NetworkConfig {
+        max_in_connections_per_subnet: 2,
+        max_out_connections_per_subnet: 1,
+        ..Default::default()
+    };
+    let mut peers: HashMap<IpAddr, PeerInfo> = HashMap::new();
+    for ip in [
+        IpAddr::V4(std::net::Ipv4Addr::new(169, 202, 0, 31)),
+        IpAddr::V4(std::net::Ipv4Addr::new(169, 202, 0, 32)),
+        IpAddr::V4(std::net::Ipv4Addr::new(169, 203, 0, 33)),
+    ] {
+        peers.insert(ip, default_peer_info_not_connected(ip));
+    }
+
+    let wakeup_interval = network_settings.wakeup_interval;
+    let (saver_watch_tx, mut saver_watch_rx) = watch::channel(peers.clone());
+    let saver_join_handle =
+        tokio::spawn(async move { while let Ok(()) = saver_watch_rx.changed().await {} });
+    let mut db = PeerInfoDatabase {
+        network_settings,
+        peers,
+        saver_join_handle,
+        saver_watch_tx,
+        wakeup_interval,
+        clock_compensation: 0,
+        peer_types_connection_count: Default::default(),
+    };
+
+    // only one candidate per subnet
+    let candidates = db.get_out_connection_candidate_ips().unwrap();
+    assert_eq!(candidates.len(), 2);
+    assert!(candidates.contains(&IpAddr::V4(std::net::Ipv4Addr::new(169, 203, 0, 33))));
+    assert_eq!(db.get_out_subnet_count(), 0);
+
+    // the in connections of a subnet are capped
+    db.try_new_in_connection(&IpAddr::V4(std::net::Ipv4Addr::new(170, 10, 0, 1)))
+        .expect("in connection not accepted.");
+    db.try_new_in_connection(&IpAddr::V4(std::net::Ipv4Addr::new(170, 10, 0, 2)))
+        .expect("in connection not accepted.");
+    if let Err(NetworkError::PeerConnectionError(
+        NetworkConnectionErrorType::MaxSubnetConnectionReached(ip_err),
+    )) = db.try_new_in_connection(&IpAddr::V4(std::net::Ipv4Addr::new(170, 10, 0, 3)))
+    {
+        assert_eq!(IpAddr::V4(std::net::Ipv4Addr::new(170, 10, 0, 3)), ip_err);
+    } else {
+        panic!("MaxSubnetConnectionReached error not return");
+    }
+    db.try_new_in_connection(&IpAddr::V4(std::net::Ipv4Addr::new(170, 11, 0, 1)))
+        .expect("in connection not accepted.");
+    assert_eq!(db.get_in_subnet_count(), 2);
+
+    // IPv6 subnets are /48
+    assert_eq!(
+        get_subnet(&"2001:db8:1:2::1".parse().unwrap()),
+        "2001:db8:1::".parse::<IpAddr>().unwrap()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_peer_score_event() {
+    let mut peer_db = PeerInfoDatabase::from(5);
+    let ip = *peer_db.peers.keys().next().unwrap();
+
+    peer_db
+        .peer_score_event(&ip, PeerScoreEvent::UsefulBlock)
+        .unwrap();
+    assert_eq!(
+        peer_db.peers.get(&ip).unwrap().score,
+        PeerScoreEvent::UsefulBlock.score_delta()
+    );
+
+    // the score is bounded
+    for _ in 0..100 {
+        peer_db
+            .peer_score_event(&ip, PeerScoreEvent::InvalidHeader)
+            .unwrap();
+    }
+    assert_eq!(peer_db.peers.get(&ip).unwrap().score, MIN_PEER_SCORE);
+
+    peer_db
+        .peer_score_event(
+            &IpAddr::V4(std::net::Ipv4Addr::new(169, 202, 0, 99)),
+            PeerScoreEvent::UsefulBlock,
+        )
+        .expect_err("unknown peer not detected.");
+}
+
 #[tokio::test]
 #[serial]
 async fn test() {
@@ -825,6 +983,8 @@ fn default_peer_info_connected(ip: IpAddr) -> PeerInfo {
         active_out_connections: 1,
         active_in_connections: 0,
         banned: false,
+        score: 0,
+        pending_eviction: false,
     }
 }
 
@@ -839,6 +999,8 @@ fn default_peer_info_not_connected(ip: IpAddr) -> PeerInfo {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        score: 0,
+        pending_eviction: false,
     }
 }
 
@@ -879,6 +1041,8 @@ impl From<u32> for PeerInfoDatabase {
                 active_out_connections: 0,
                 active_in_connections: 0,
                 banned: ip[1] % 5 == 0,
+                score: 0,
+                pending_eviction: false,
             };
             peers.insert(peer.ip, peer);
         }
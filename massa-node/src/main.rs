@@ -4,18 +4,25 @@
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 extern crate massa_logging;
+use crate::metrics::{start_metrics_server, MetricsSources, MetricsStopHandle};
 use crate::settings::SETTINGS;
 
 use dialoguer::Password;
-use massa_api::{APIConfig, Private, Public, RpcServer, StopHandle, API};
+use massa_api::{APIConfig, Private, PubSub, Public, RpcServer, StopHandle, API};
+use massa_archive::{ArchiveConfig, BlockArchive};
 use massa_async_pool::AsyncPoolConfig;
-use massa_bootstrap::{get_state, start_bootstrap_server, BootstrapConfig, BootstrapManager};
+use massa_bootstrap::{
+    export_state_to_snapshot, get_state, get_state_from_snapshot, start_bootstrap_server,
+    BootstrapConfig, BootstrapManager,
+};
 use massa_consensus_exports::ConsensusManager;
 use massa_consensus_exports::{
-    events::ConsensusEvent, settings::ConsensusChannels, ConsensusConfig, ConsensusEventReceiver,
+    events::ConsensusEvent,
+    settings::{ConsensusBroadcasts, ConsensusChannels},
+    ConsensusConfig, ConsensusEventReceiver,
 };
 use massa_consensus_worker::start_consensus_controller;
-use massa_execution_exports::{ExecutionConfig, ExecutionManager};
+use massa_execution_exports::{ExecutionChannels, ExecutionConfig, ExecutionManager};
 use massa_execution_worker::start_execution_worker;
 use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
 use massa_factory_worker::start_factory;
@@ -61,10 +68,13 @@ use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 use tracing_subscriber::filter::{filter_fn, LevelFilter};
 
+mod metrics;
 mod settings;
 
 async fn launch(
     node_wallet: Arc<RwLock<Wallet>>,
+    from_snapshot: Option<PathBuf>,
+    export_snapshot: Option<PathBuf>,
 ) -> (
     ConsensusEventReceiver,
     Option<BootstrapManager>,
@@ -78,6 +88,8 @@ async fn launch(
     mpsc::Receiver<()>,
     StopHandle,
     StopHandle,
+    Option<StopHandle>,
+    Option<MetricsStopHandle>,
 ) {
     info!("Node version : {}", *VERSION);
     if let Some(end) = *END_TIMESTAMP {
@@ -96,6 +108,7 @@ async fn launch(
         disk_ledger_path: SETTINGS.ledger.disk_ledger_path.clone(),
         max_key_length: MAX_DATASTORE_KEY_LENGTH,
         max_ledger_part_size: LEDGER_PART_SIZE_MESSAGE_BYTES,
+        history_length: SETTINGS.ledger.history_length,
     };
     let async_pool_config = AsyncPoolConfig {
         max_length: MAX_ASYNC_POOL_LENGTH,
@@ -157,8 +170,10 @@ async fn launch(
         read_error_timeout: SETTINGS.bootstrap.read_error_timeout,
         write_error_timeout: SETTINGS.bootstrap.write_error_timeout,
         retry_delay: SETTINGS.bootstrap.retry_delay,
+        parallel_bootstrap_servers: SETTINGS.bootstrap.parallel_bootstrap_servers,
         max_ping: SETTINGS.bootstrap.max_ping,
         enable_clock_synchronization: SETTINGS.bootstrap.enable_clock_synchronization,
+        enable_encryption: SETTINGS.bootstrap.enable_encryption,
         cache_duration: SETTINGS.bootstrap.cache_duration,
         max_simultaneous_bootstraps: SETTINGS.bootstrap.max_simultaneous_bootstraps,
         per_ip_min_interval: SETTINGS.bootstrap.per_ip_min_interval,
@@ -185,25 +200,49 @@ async fn launch(
         max_changes_slot_count: SETTINGS.ledger.final_history_length as u32,
     };
 
-    // bootstrap
-    let bootstrap_state = tokio::select! {
-        _ = &mut stop_signal => {
-            info!("interrupt signal received in bootstrap loop");
-            process::exit(0);
-        },
-        res = get_state(
-            &bootstrap_config,
-            final_state.clone(),
-            massa_bootstrap::types::Establisher::new(),
-            *VERSION,
-            *GENESIS_TIMESTAMP,
-            *END_TIMESTAMP,
-        ) => match res {
+    // bootstrap, either from a snapshot file or from the network
+    let bootstrap_state = if let Some(snapshot_path) = from_snapshot {
+        match get_state_from_snapshot(&bootstrap_config, final_state.clone(), &snapshot_path) {
             Ok(vals) => vals,
-            Err(err) => panic!("critical error detected in the bootstrap process: {}", err)
+            Err(err) => panic!(
+                "critical error detected while loading the snapshot: {}",
+                err
+            ),
+        }
+    } else {
+        tokio::select! {
+            _ = &mut stop_signal => {
+                info!("interrupt signal received in bootstrap loop");
+                process::exit(0);
+            },
+            res = get_state(
+                &bootstrap_config,
+                final_state.clone(),
+                massa_bootstrap::types::Establisher::new(),
+                *VERSION,
+                *GENESIS_TIMESTAMP,
+                *END_TIMESTAMP,
+            ) => match res {
+                Ok(vals) => vals,
+                Err(err) => panic!("critical error detected in the bootstrap process: {}", err)
+            }
         }
     };
 
+    // export the bootstrapped final state and graph and exit if requested
+    if let Some(snapshot_path) = export_snapshot {
+        match export_state_to_snapshot(&bootstrap_state, &snapshot_path) {
+            Ok(hash) => info!(
+                "final state at slot {} exported to {} (hash {})",
+                final_state.read().slot,
+                snapshot_path.display(),
+                hash
+            ),
+            Err(err) => panic!("could not export the final state snapshot: {}", err),
+        }
+        process::exit(0);
+    }
+
     let network_config: NetworkConfig = NetworkConfig {
         bind: SETTINGS.network.bind,
         routable_ip: SETTINGS.network.routable_ip,
@@ -215,6 +254,8 @@ async fn launch(
         keypair_file: SETTINGS.network.keypair_file.clone(),
         peer_types_config: SETTINGS.network.peer_types_config.clone(),
         max_in_connections_per_ip: SETTINGS.network.max_in_connections_per_ip,
+        max_in_connections_per_subnet: SETTINGS.network.max_in_connections_per_subnet,
+        max_out_connections_per_subnet: SETTINGS.network.max_out_connections_per_subnet,
         max_idle_peers: SETTINGS.network.max_idle_peers,
         max_banned_peers: SETTINGS.network.max_banned_peers,
         peers_file_dump_interval: SETTINGS.network.peers_file_dump_interval,
@@ -264,6 +305,11 @@ async fn launch(
     // launch execution module
     let execution_config = ExecutionConfig {
         max_final_events: SETTINGS.execution.max_final_events,
+        event_archive_path: SETTINGS.execution.event_archive_path.clone(),
+        address_history_path: SETTINGS.execution.address_history_path.clone(),
+        trace_execution: SETTINGS.execution.trace_execution,
+        max_final_traces: SETTINGS.execution.max_final_traces,
+        max_trace_entries: SETTINGS.execution.max_trace_entries,
         readonly_queue_length: SETTINGS.execution.readonly_queue_length,
         cursor_delay: SETTINGS.execution.cursor_delay,
         clock_compensation: bootstrap_state.compensation_millis,
@@ -281,10 +327,12 @@ async fn launch(
         max_miss_ratio: *POS_MISS_RATE_DEACTIVATION_THRESHOLD,
         max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
     };
+    let execution_channels = ExecutionChannels::new(SETTINGS.api.subscription_buffer_size);
     let (execution_manager, execution_controller) = start_execution_worker(
         execution_config,
         final_state.clone(),
         selector_controller.clone(),
+        execution_channels.clone(),
     );
 
     // launch pool controller
@@ -362,7 +410,21 @@ async fn launch(
         max_gas_per_block: MAX_GAS_PER_BLOCK,
         channel_size: CHANNEL_SIZE,
     };
+    // open the archive of final blocks if the node runs in archive mode
+    let block_archive = SETTINGS.consensus.archive_path.clone().map(|archive_path| {
+        BlockArchive::new(ArchiveConfig {
+            archive_path,
+            thread_count: THREAD_COUNT,
+            endorsement_count: ENDORSEMENT_COUNT,
+            max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+            max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
+            max_parameters_size: MAX_PARAMETERS_SIZE,
+        })
+    });
+
     // launch consensus controller
+    let consensus_broadcasts = ConsensusBroadcasts::new(SETTINGS.api.subscription_buffer_size);
     let (consensus_command_sender, consensus_event_receiver, consensus_manager) =
         start_consensus_controller(
             consensus_config.clone(),
@@ -372,6 +434,8 @@ async fn launch(
                 protocol_event_receiver,
                 pool_command_sender: pool_manager.clone(),
                 selector_controller: selector_controller.clone(),
+                broadcasts: consensus_broadcasts.clone(),
+                archive: block_archive.clone(),
             },
             bootstrap_state.graph,
             shared_storage.clone(),
@@ -413,9 +477,32 @@ async fn launch(
     .await
     .unwrap();
 
+    // spawn metrics server
+    let metrics_handle = SETTINGS.metrics.bind.and_then(|bind| {
+        start_metrics_server(
+            bind,
+            MetricsSources {
+                execution_controller: execution_controller.clone(),
+                consensus_command_sender: consensus_command_sender.clone(),
+                network_command_sender: network_command_sender.clone(),
+                pool_controller: pool_manager.clone(),
+                selector_controller: selector_controller.clone(),
+                storage: shared_storage.clone_without_refs(),
+                final_state: final_state.clone(),
+                bootstrap_session_counters: bootstrap_manager
+                    .as_ref()
+                    .map(|manager| manager.get_session_counters()),
+                compensation_millis: bootstrap_state.compensation_millis,
+            },
+        )
+    });
+
     let api_config: APIConfig = APIConfig {
         bind_private: SETTINGS.api.bind_private,
         bind_public: SETTINGS.api.bind_public,
+        bind_public_ws: SETTINGS.api.bind_public_ws,
+        max_ws_connections: SETTINGS.api.max_ws_connections,
+        max_subscriptions_per_connection: SETTINGS.api.max_subscriptions_per_connection,
         draw_lookahead_period_count: SETTINGS.api.draw_lookahead_period_count,
         max_arguments: SETTINGS.api.max_arguments,
         max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
@@ -448,9 +535,16 @@ async fn launch(
         bootstrap_state.compensation_millis,
         node_id,
         shared_storage.clone(),
+        block_archive,
     );
     let api_public_handle = api_public.serve(&SETTINGS.api.bind_public);
 
+    // spawn public WebSocket subscription API
+    let api_pubsub_handle = SETTINGS.api.bind_public_ws.map(|bind_public_ws| {
+        API::<PubSub>::new(consensus_broadcasts, execution_channels, api_config)
+            .serve(&bind_public_ws)
+    });
+
     #[cfg(feature = "deadlock_detection")]
     {
         // only for #[cfg]
@@ -489,6 +583,8 @@ async fn launch(
         api_private_stop_rx,
         api_private_handle,
         api_public_handle,
+        api_pubsub_handle,
+        metrics_handle,
     )
 }
 
@@ -517,7 +613,14 @@ async fn stop(
     }: Managers,
     api_private_handle: StopHandle,
     api_public_handle: StopHandle,
+    api_pubsub_handle: Option<StopHandle>,
+    metrics_handle: Option<MetricsStopHandle>,
 ) {
+    // stop metrics server
+    if let Some(metrics_handle) = metrics_handle {
+        metrics_handle.stop().await;
+    }
+
     // stop bootstrap
     if let Some(bootstrap_manager) = bootstrap_manager {
         bootstrap_manager
@@ -526,6 +629,11 @@ async fn stop(
             .expect("bootstrap server shutdown failed")
     }
 
+    // stop public WebSocket subscription API
+    if let Some(api_pubsub_handle) = api_pubsub_handle {
+        api_pubsub_handle.stop();
+    }
+
     // stop public API
     api_public_handle.stop();
 
@@ -574,6 +682,22 @@ struct Args {
     /// Wallet password
     #[structopt(short = "p", long = "pwd")]
     password: Option<String>,
+    /// Start from a final state snapshot file instead of bootstrapping from the network
+    #[structopt(long = "from-snapshot", parse(from_os_str))]
+    from_snapshot: Option<PathBuf>,
+    /// Subcommand
+    #[structopt(subcommand)]
+    command: Option<NodeCommand>,
+}
+
+#[derive(StructOpt)]
+enum NodeCommand {
+    /// Bootstrap the node, write its final state and graph to a snapshot file and exit
+    ExportSnapshot {
+        /// Path of the snapshot file
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
 }
 
 /// Load wallet, asking for passwords if necessary
@@ -635,6 +759,12 @@ async fn main(args: Args) -> anyhow::Result<()> {
     // load or create wallet, asking for password if necessary
     let node_wallet = load_wallet(args.password, &SETTINGS.factory.staking_wallet_path)?;
 
+    let export_snapshot = args.command.map(|command| match command {
+        NodeCommand::ExportSnapshot { path } => path,
+    });
+    // the snapshot is only used for the first launch, the node bootstraps from the network on restart
+    let mut from_snapshot = args.from_snapshot;
+
     loop {
         let (
             mut consensus_event_receiver,
@@ -649,7 +779,14 @@ async fn main(args: Args) -> anyhow::Result<()> {
             mut api_private_stop_rx,
             api_private_handle,
             api_public_handle,
-        ) = launch(node_wallet.clone()).await;
+            api_pubsub_handle,
+            metrics_handle,
+        ) = launch(
+            node_wallet.clone(),
+            from_snapshot.take(),
+            export_snapshot.clone(),
+        )
+        .await;
 
         // interrupt signal listener
         let stop_signal = signal::ctrl_c();
@@ -698,6 +835,8 @@ async fn main(args: Args) -> anyhow::Result<()> {
             },
             api_private_handle,
             api_public_handle,
+            api_pubsub_handle,
+            metrics_handle,
         )
         .await;
 
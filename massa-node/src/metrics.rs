@@ -0,0 +1,413 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Optional HTTP server exporting the node stats at `/metrics`,
+//! in the Prometheus text exposition format.
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use massa_bootstrap::BootstrapSessionCounters;
+use massa_consensus_exports::ConsensusCommandSender;
+use massa_execution_exports::ExecutionController;
+use massa_final_state::FinalState;
+use massa_models::{
+    config::{GENESIS_TIMESTAMP, T0, THREAD_COUNT},
+    timeslots::get_latest_block_slot_at_timestamp,
+};
+use massa_network_exports::NetworkCommandSender;
+use massa_pool_exports::PoolController;
+use massa_pos_exports::SelectorController;
+use massa_storage::Storage;
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use tokio::{sync::oneshot, task::JoinHandle};
+use tracing::{debug, error, info, warn};
+
+/// Content type of the Prometheus text exposition format
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Components the exported metrics are read from
+pub(crate) struct MetricsSources {
+    pub execution_controller: Box<dyn ExecutionController>,
+    pub consensus_command_sender: ConsensusCommandSender,
+    pub network_command_sender: NetworkCommandSender,
+    pub pool_controller: Box<dyn PoolController>,
+    pub selector_controller: Box<dyn SelectorController>,
+    /// storage instance that claims no reference
+    pub storage: Storage,
+    pub final_state: Arc<RwLock<FinalState>>,
+    /// set if the bootstrap server is enabled
+    pub bootstrap_session_counters: Option<Arc<BootstrapSessionCounters>>,
+    pub compensation_millis: i64,
+}
+
+/// Handle used to stop the metrics server
+pub(crate) struct MetricsStopHandle {
+    stop_tx: oneshot::Sender<()>,
+    join_handle: JoinHandle<()>,
+}
+
+impl MetricsStopHandle {
+    /// stop the metrics server gracefully
+    pub(crate) async fn stop(self) {
+        let _ = self.stop_tx.send(());
+        if let Err(err) = self.join_handle.await {
+            warn!("metrics server panicked: {}", err);
+        } else {
+            info!("metrics server finished cleanly");
+        }
+    }
+}
+
+/// Starts serving the metrics on `bind`.
+/// Returns `None` if the server could not listen on `bind`: the node runs without metrics in that case.
+pub(crate) fn start_metrics_server(
+    bind: SocketAddr,
+    sources: MetricsSources,
+) -> Option<MetricsStopHandle> {
+    let builder = match Server::try_bind(&bind) {
+        Ok(builder) => builder,
+        Err(err) => {
+            error!("could not start metrics server on {}: {}", bind, err);
+            return None;
+        }
+    };
+    let sources = Arc::new(sources);
+    let make_service = make_service_fn(move |_| {
+        let sources = sources.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| serve_request(sources.clone(), req))) }
+    });
+    let (stop_tx, stop_rx) = oneshot::channel();
+    let server = builder.serve(make_service).with_graceful_shutdown(async {
+        let _ = stop_rx.await;
+    });
+    let join_handle = tokio::spawn(async move {
+        if let Err(err) = server.await {
+            warn!("metrics server error: {}", err);
+        }
+    });
+    info!("metrics server listening on {}", bind);
+    Some(MetricsStopHandle {
+        stop_tx,
+        join_handle,
+    })
+}
+
+/// Answers `GET /metrics`, and 404 to anything else
+async fn serve_request(
+    sources: Arc<MetricsSources>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if req.method() != Method::GET || req.uri().path() != "/metrics" {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap_or_default());
+    }
+    let metrics = collect_metrics(&sources).await;
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, METRICS_CONTENT_TYPE)
+        .body(Body::from(metrics))
+        .unwrap_or_default())
+}
+
+/// Reads the stats of every component and renders them.
+/// Stats that cannot be retrieved are left out of the output.
+async fn collect_metrics(sources: &MetricsSources) -> String {
+    let mut metrics = MetricsWriter::default();
+
+    // execution
+    let execution_stats = sources.execution_controller.get_stats();
+    metrics.gauge(
+        "massa_execution_final_blocks",
+        "Number of final blocks executed in the stats time window",
+        execution_stats.final_block_count as u64,
+    );
+    metrics.gauge(
+        "massa_execution_final_operations",
+        "Number of final operations executed in the stats time window",
+        execution_stats.final_executed_operations_count as u64,
+    );
+    match MassaTime::now(sources.compensation_millis)
+        .map(|now| get_latest_block_slot_at_timestamp(THREAD_COUNT, T0, *GENESIS_TIMESTAMP, now))
+    {
+        Ok(Ok(latest_slot)) => {
+            let slot_lag = latest_slot
+                .and_then(|slot| {
+                    slot.slots_since(&execution_stats.active_cursor, THREAD_COUNT)
+                        .ok()
+                })
+                .unwrap_or(0);
+            metrics.gauge(
+                "massa_execution_slot_lag",
+                "Number of slots between the current slot and the active execution cursor",
+                slot_lag,
+            );
+        }
+        Ok(Err(err)) => debug!("metrics: could not compute the current slot: {}", err),
+        Err(err) => debug!("metrics: could not get the current time: {}", err),
+    }
+    let async_pool_length = sources.final_state.read().async_pool.get_message_count();
+    metrics.gauge(
+        "massa_async_pool_messages",
+        "Number of messages in the final asynchronous pool",
+        async_pool_length as u64,
+    );
+
+    // selector
+    metrics.gauge(
+        "massa_selector_draw_cache_cycles",
+        "Number of cycles whose draws are cached by the selector",
+        sources.selector_controller.get_draw_cache_size() as u64,
+    );
+
+    // pool
+    metrics.gauge(
+        "massa_pool_operations",
+        "Number of operations in the pool",
+        sources.pool_controller.get_operation_count() as u64,
+    );
+    metrics.gauge(
+        "massa_pool_endorsements",
+        "Number of endorsements in the pool",
+        sources.pool_controller.get_endorsement_count() as u64,
+    );
+
+    // storage
+    let storage_stats = sources.storage.get_stats();
+    metrics.labeled_gauge(
+        "massa_storage_objects",
+        "Number of objects held in the shared storage",
+        "kind",
+        &[
+            ("block", storage_stats.block_count as u64),
+            ("operation", storage_stats.operation_count as u64),
+            ("endorsement", storage_stats.endorsement_count as u64),
+        ],
+    );
+    metrics.labeled_gauge(
+        "massa_storage_references",
+        "Number of references claimed on the objects of the shared storage",
+        "kind",
+        &[
+            ("block", storage_stats.block_ref_count as u64),
+            ("operation", storage_stats.operation_ref_count as u64),
+            ("endorsement", storage_stats.endorsement_ref_count as u64),
+        ],
+    );
+
+    // consensus and network
+    let (consensus_stats, network_stats) = tokio::join!(
+        sources.consensus_command_sender.get_stats(),
+        sources.network_command_sender.get_network_stats()
+    );
+    match consensus_stats {
+        Ok(stats) => {
+            metrics.gauge(
+                "massa_consensus_final_blocks",
+                "Number of blocks that became final in the stats time window",
+                stats.final_block_count,
+            );
+            metrics.gauge(
+                "massa_consensus_stale_blocks",
+                "Number of blocks that became stale in the stats time window",
+                stats.stale_block_count,
+            );
+            metrics.gauge(
+                "massa_consensus_cliques",
+                "Number of cliques in the block graph",
+                stats.clique_count,
+            );
+            metrics.labeled_gauge(
+                "massa_block_graph_blocks",
+                "Number of blocks in the block graph, by status",
+                "status",
+                &[
+                    ("active", stats.active_block_count),
+                    ("incoming", stats.incoming_block_count),
+                    ("waiting_for_slot", stats.waiting_for_slot_block_count),
+                    (
+                        "waiting_for_dependencies",
+                        stats.waiting_for_dependencies_block_count,
+                    ),
+                    ("discarded", stats.discarded_block_count),
+                ],
+            );
+        }
+        Err(err) => debug!("metrics: could not get consensus stats: {}", err),
+    }
+    match network_stats {
+        Ok(stats) => {
+            metrics.labeled_gauge(
+                "massa_network_connections",
+                "Number of established connections, by direction",
+                "direction",
+                &[
+                    ("in", stats.in_connection_count),
+                    ("out", stats.out_connection_count),
+                ],
+            );
+            metrics.labeled_gauge(
+                "massa_network_connection_subnets",
+                "Number of distinct subnets among established connections, by direction",
+                "direction",
+                &[
+                    ("in", stats.in_subnet_count),
+                    ("out", stats.out_subnet_count),
+                ],
+            );
+            metrics.gauge(
+                "massa_network_known_peers",
+                "Number of known peers",
+                stats.known_peer_count,
+            );
+            metrics.gauge(
+                "massa_network_banned_peers",
+                "Number of banned peers",
+                stats.banned_peer_count,
+            );
+            metrics.gauge(
+                "massa_network_active_nodes",
+                "Number of nodes we are connected to",
+                stats.active_node_count,
+            );
+            metrics.counter(
+                "massa_network_subnet_refused_connections_total",
+                "Number of in connections refused because their subnet was full",
+                stats.subnet_refused_connection_count,
+            );
+        }
+        Err(err) => debug!("metrics: could not get network stats: {}", err),
+    }
+
+    // bootstrap server
+    if let Some(counters) = &sources.bootstrap_session_counters {
+        metrics.gauge(
+            "massa_bootstrap_server_active_sessions",
+            "Number of bootstrap sessions currently served",
+            counters.get_active_count(),
+        );
+        metrics.counter(
+            "massa_bootstrap_server_sessions_total",
+            "Number of bootstrap sessions started",
+            counters.get_started_count(),
+        );
+        metrics.counter(
+            "massa_bootstrap_server_refused_sessions_total",
+            "Number of bootstrap attempts refused",
+            counters.get_refused_count(),
+        );
+    }
+
+    metrics.0
+}
+
+/// Renders metrics in the Prometheus text exposition format
+#[derive(Default)]
+struct MetricsWriter(String);
+
+impl MetricsWriter {
+    /// Writes a metric and its samples.
+    /// Each sample is given as its label name/value pairs (empty if none) and its value.
+    fn write(&mut self, name: &str, kind: &str, help: &str, samples: &[(Vec<(&str, &str)>, u64)]) {
+        self.0 += &format!(
+            "# HELP {} {}\n# TYPE {} {}\n",
+            name,
+            escape_help(help),
+            name,
+            kind
+        );
+        for (labels, value) in samples {
+            if labels.is_empty() {
+                self.0 += &format!("{} {}\n", name, value);
+            } else {
+                let labels: Vec<String> = labels
+                    .iter()
+                    .map(|(label, value)| format!("{}=\"{}\"", label, escape_label_value(value)))
+                    .collect();
+                self.0 += &format!("{}{{{}}} {}\n", name, labels.join(","), value);
+            }
+        }
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: u64) {
+        self.write(name, "gauge", help, &[(Vec::new(), value)]);
+    }
+
+    /// Writes a gauge with one sample per value of `label`
+    fn labeled_gauge(&mut self, name: &str, help: &str, label: &str, samples: &[(&str, u64)]) {
+        let samples: Vec<_> = samples
+            .iter()
+            .map(|(label_value, value)| (vec![(label, *label_value)], *value))
+            .collect();
+        self.write(name, "gauge", help, &samples);
+    }
+
+    fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.write(name, "counter", help, &[(Vec::new(), value)]);
+    }
+}
+
+/// Escapes backslashes and line feeds in a HELP line
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Escapes backslashes, double quotes and line feeds in a label value
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_writer_format() {
+        let mut metrics = MetricsWriter::default();
+        metrics.gauge("massa_test_gauge", "Test gauge", 3);
+        metrics.counter("massa_test_total", "Test counter", 7);
+        metrics.labeled_gauge(
+            "massa_test_labeled",
+            "Test labeled gauge",
+            "kind",
+            &[("block", 1), ("operation", 2)],
+        );
+        assert_eq!(
+            metrics.0,
+            "# HELP massa_test_gauge Test gauge\n\
+             # TYPE massa_test_gauge gauge\n\
+             massa_test_gauge 3\n\
+             # HELP massa_test_total Test counter\n\
+             # TYPE massa_test_total counter\n\
+             massa_test_total 7\n\
+             # HELP massa_test_labeled Test labeled gauge\n\
+             # TYPE massa_test_labeled gauge\n\
+             massa_test_labeled{kind=\"block\"} 1\n\
+             massa_test_labeled{kind=\"operation\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn test_metrics_writer_escaping() {
+        let mut metrics = MetricsWriter::default();
+        metrics.write(
+            "massa_test",
+            "gauge",
+            "Help with a \\ and a\nline feed",
+            &[(vec![("path", "C:\\dir"), ("name", "a \"quoted\"\nname")], 5)],
+        );
+        assert_eq!(
+            metrics.0,
+            "# HELP massa_test Help with a \\\\ and a\\nline feed\n\
+             # TYPE massa_test gauge\n\
+             massa_test{path=\"C:\\\\dir\",name=\"a \\\"quoted\\\"\\nname\"} 5\n"
+        );
+    }
+}
@@ -24,6 +24,11 @@ pub struct LoggingSettings {
 #[derive(Clone, Debug, Deserialize)]
 pub struct ExecutionSettings {
     pub max_final_events: usize,
+    pub event_archive_path: Option<PathBuf>,
+    pub address_history_path: Option<PathBuf>,
+    pub trace_execution: bool,
+    pub max_final_traces: usize,
+    pub max_trace_entries: usize,
     pub readonly_queue_length: usize,
     pub cursor_delay: MassaTime,
     pub stats_time_window_duration: MassaTime,
@@ -40,6 +45,7 @@ pub struct LedgerSettings {
     pub initial_ledger_path: PathBuf,
     pub disk_ledger_path: PathBuf,
     pub final_history_length: usize,
+    pub history_length: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -54,6 +60,8 @@ pub struct NetworkSettings {
     pub keypair_file: std::path::PathBuf,
     pub peer_types_config: EnumMap<PeerType, PeerTypeConnectionConfig>,
     pub max_in_connections_per_ip: usize,
+    pub max_in_connections_per_subnet: usize,
+    pub max_out_connections_per_subnet: usize,
     pub max_idle_peers: usize,
     pub max_banned_peers: usize,
     pub peers_file_dump_interval: MassaTime,
@@ -79,8 +87,10 @@ pub struct BootstrapSettings {
     pub read_error_timeout: MassaTime,
     pub write_error_timeout: MassaTime,
     pub retry_delay: MassaTime,
+    pub parallel_bootstrap_servers: usize,
     pub max_ping: MassaTime,
     pub enable_clock_synchronization: bool,
+    pub enable_encryption: bool,
     pub cache_duration: MassaTime,
     pub max_simultaneous_bootstraps: u32,
     pub per_ip_min_interval: MassaTime,
@@ -112,7 +122,18 @@ pub struct APISettings {
     pub draw_lookahead_period_count: u64,
     pub bind_private: SocketAddr,
     pub bind_public: SocketAddr,
+    pub bind_public_ws: Option<SocketAddr>,
     pub max_arguments: u64,
+    pub max_ws_connections: usize,
+    pub max_subscriptions_per_connection: usize,
+    pub subscription_buffer_size: usize,
+}
+
+/// Metrics configuration, read from a file configuration
+#[derive(Debug, Deserialize, Clone)]
+pub struct MetricsSettings {
+    /// address on which `/metrics` is served, disabled if not set
+    pub bind: Option<SocketAddr>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -122,6 +143,7 @@ pub struct Settings {
     pub network: NetworkSettings,
     pub consensus: ConsensusSettings,
     pub api: APISettings,
+    pub metrics: MetricsSettings,
     pub bootstrap: BootstrapSettings,
     pub pool: PoolSettings,
     pub execution: ExecutionSettings,
@@ -152,6 +174,8 @@ pub struct ConsensusSettings {
     pub block_db_prune_interval: MassaTime,
     /// max number of items returned while querying
     pub max_item_return_count: usize,
+    /// path to the on-disk archive of all final blocks, enables archive mode if set
+    pub archive_path: Option<PathBuf>,
 }
 
 /// Protocol Configuration, read from toml user config file
@@ -1,7 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
-    block::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    amount::Amount, block::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
 };
 use massa_storage::Storage;
 
@@ -32,6 +32,10 @@ pub trait PoolController: Send + Sync {
     /// Get the number of operations in the pool
     fn get_operation_count(&self) -> usize;
 
+    /// Suggest a fee for an operation of `size` bytes using up to `max_gas` gas, created in `thread`,
+    /// given the operations currently in the pool
+    fn get_suggested_fee(&self, thread: u8, size: usize, max_gas: u64) -> Amount;
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool>;
 
@@ -6,7 +6,7 @@ use std::sync::{
 };
 
 use massa_models::{
-    block::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    amount::Amount, block::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
 };
 use massa_storage::Storage;
 use massa_time::MassaTime;
@@ -57,6 +57,17 @@ pub enum MockPoolControllerMessage {
         /// Response channel
         response_tx: mpsc::Sender<usize>,
     },
+    /// Get a suggested fee
+    GetSuggestedFee {
+        /// thread of the operation
+        thread: u8,
+        /// size of the operation
+        size: usize,
+        /// max gas of the operation
+        max_gas: u64,
+        /// Response channel
+        response_tx: mpsc::Sender<Amount>,
+    },
     /// Contains endorsements
     ContainsEndorsements {
         /// ids to search
@@ -190,6 +201,21 @@ impl PoolController for MockPoolController {
         response_rx.recv().unwrap()
     }
 
+    fn get_suggested_fee(&self, thread: u8, size: usize, max_gas: u64) -> Amount {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::GetSuggestedFee {
+                thread,
+                size,
+                max_gas,
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
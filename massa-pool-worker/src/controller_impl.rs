@@ -1,5 +1,5 @@
 use massa_models::{
-    block::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    amount::Amount, block::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
 };
 use massa_pool_exports::{PoolConfig, PoolController};
 use massa_storage::Storage;
@@ -67,6 +67,13 @@ impl PoolController for PoolControllerImpl {
         self.operation_pool.read().len()
     }
 
+    /// Suggest a fee for an operation of `size` bytes using up to `max_gas` gas, created in `thread`
+    fn get_suggested_fee(&self, thread: u8, size: usize, max_gas: u64) -> Amount {
+        self.operation_pool
+            .read()
+            .get_suggested_fee(thread, size, max_gas)
+    }
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let lck = self.endorsement_pool.read();
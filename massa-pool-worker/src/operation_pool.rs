@@ -171,6 +171,36 @@ impl OperationPool {
         self.storage.drop_operation_refs(&removed);
     }
 
+    /// Suggests a fee for an operation of `size` bytes using up to `max_gas` gas, created in `thread`.
+    ///
+    /// Operations are selected for block creation by decreasing fee per byte,
+    /// as long as there is enough space and gas left in the block,
+    /// so the suggested fee beats the fee per byte of the best operation that would not fit
+    /// in a block along with the new one. It is zero if all pool operations of the thread fit.
+    pub fn get_suggested_fee(&self, thread: u8, size: usize, max_gas: u64) -> Amount {
+        let mut remaining_space = (self.config.max_block_size as usize).saturating_sub(size);
+        let mut remaining_gas = self.config.max_block_gas.saturating_sub(max_gas);
+        for cursor in self.sorted_ops_per_thread[thread as usize].iter() {
+            let op_info = self
+                .operations
+                .get(&cursor.get_id())
+                .expect("the operation should be in self.operations at this point");
+            if op_info.size <= remaining_space && op_info.max_gas <= remaining_gas {
+                remaining_space -= op_info.size;
+                remaining_gas -= op_info.max_gas;
+            } else {
+                // outbid the fee per byte of this operation
+                let raw_fee = (op_info.fee.to_raw() as u128)
+                    .saturating_mul(size as u128)
+                    .checked_div(op_info.size as u128)
+                    .unwrap_or_default()
+                    .saturating_add(1);
+                return Amount::from_raw(u64::try_from(raw_fee).unwrap_or(u64::MAX));
+            }
+        }
+        Amount::zero()
+    }
+
     /// get operations for block creation
     pub fn get_block_operations(&self, slot: &Slot) -> (Vec<OperationId>, Storage) {
         // init list of selected operation IDs
@@ -57,6 +57,92 @@ fn test_add_irrelevant_operation() {
     });
 }
 
+/// Test that the suggested fee outbids the operations that fill a block, and is zero when the pool has room.
+#[test]
+fn test_suggested_fee() {
+    let keypair = KeyPair::generate();
+    let ops: Vec<WrappedOperation> = (0..10)
+        .map(|_| {
+            let content = Operation {
+                fee: Amount::from_str("10").unwrap(),
+                op: OperationType::Transaction {
+                    recipient_address: Address::from_public_key(
+                        &KeyPair::generate().get_public_key(),
+                    ),
+                    amount: Amount::default(),
+                },
+                expire_period: 2,
+            };
+            Operation::new_wrapped(content, OperationSerializer::new(), &keypair).unwrap()
+        })
+        .collect();
+    let op_size = ops[0].serialized_size();
+    let pool_config = PoolConfig {
+        max_block_size: (5 * op_size) as u32,
+        ..PoolConfig::default()
+    };
+    let thread =
+        Address::from_public_key(&keypair.get_public_key()).get_thread(pool_config.thread_count);
+    operation_pool_test(pool_config, |mut operation_pool, mut storage| {
+        assert_eq!(
+            operation_pool.get_suggested_fee(thread, op_size, 0),
+            Amount::zero()
+        );
+        storage.store_operations(ops);
+        operation_pool.add_operations(storage);
+        assert_eq!(
+            operation_pool.get_suggested_fee(thread, op_size, 0),
+            Amount::from_str("10")
+                .unwrap()
+                .saturating_add(Amount::from_raw(1))
+        );
+    });
+}
+
+/// Test that the suggested fee accounts for the gas of the operations that would share a block with the new one.
+#[test]
+fn test_suggested_fee_block_gas() {
+    let keypair = KeyPair::generate();
+    let ops: Vec<WrappedOperation> = (0..4)
+        .map(|_| {
+            let content = Operation {
+                fee: Amount::from_str("10").unwrap(),
+                op: OperationType::ExecuteSC {
+                    data: vec![0; 10],
+                    max_gas: 1000,
+                    coins: Amount::default(),
+                    gas_price: Amount::default(),
+                },
+                expire_period: 2,
+            };
+            Operation::new_wrapped(content, OperationSerializer::new(), &keypair).unwrap()
+        })
+        .collect();
+    let op_size = ops[0].serialized_size();
+    let pool_config = PoolConfig {
+        max_block_gas: 5000,
+        ..PoolConfig::default()
+    };
+    let thread =
+        Address::from_public_key(&keypair.get_public_key()).get_thread(pool_config.thread_count);
+    operation_pool_test(pool_config, |mut operation_pool, mut storage| {
+        storage.store_operations(ops);
+        operation_pool.add_operations(storage);
+        // there is enough gas left in the block for all the pool operations
+        assert_eq!(
+            operation_pool.get_suggested_fee(thread, op_size, 1000),
+            Amount::zero()
+        );
+        // the last pool operation would not get enough gas
+        assert_eq!(
+            operation_pool.get_suggested_fee(thread, op_size, 2000),
+            Amount::from_str("10")
+                .unwrap()
+                .saturating_add(Amount::from_raw(1))
+        );
+    });
+}
+
 fn get_transaction(expire_period: u64, fee: u64) -> WrappedOperation {
     let sender_keypair = KeyPair::generate();
 
@@ -55,6 +55,9 @@ pub trait SelectorController: Send + Sync {
     /// * `slot`: target slot of the selection
     fn get_producer(&self, slot: Slot) -> PosResult<Address>;
 
+    /// Get the number of cycles whose draws are currently cached
+    fn get_draw_cache_size(&self) -> usize;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn SelectorController>`.
     fn clone_box(&self) -> Box<dyn SelectorController>;
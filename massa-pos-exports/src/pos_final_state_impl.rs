@@ -16,7 +16,8 @@ use massa_serialization::U64VarIntDeserializer;
 use tracing::debug;
 
 use crate::{
-    CycleInfo, PoSChanges, PoSFinalState, PosError, PosResult, ProductionStats, SelectorController,
+    CycleInfo, DeferredCredits, PoSChanges, PoSFinalState, PosError, PosResult, ProductionStats,
+    SelectorController,
 };
 
 impl PoSFinalState {
@@ -89,6 +90,14 @@ impl PoSFinalState {
         });
     }
 
+    /// Drop the cycle history and the deferred credits.
+    ///
+    /// Used when a partially bootstrapped state cannot be completed and must be received again.
+    pub fn reset(&mut self) {
+        self.cycle_history.clear();
+        self.deferred_credits = DeferredCredits::default();
+    }
+
     /// Sends the current draw inputs (initial or bootstrapped) to the selector.
     /// Waits for the initial draws to be performed.
     pub fn compute_initial_draws(&mut self) -> PosResult<()> {
@@ -332,6 +341,33 @@ impl PoSFinalState {
             .unwrap_or_default()
     }
 
+    /// Compute a hash committing to the cycle history and the deferred credits.
+    /// Used to check the integrity of a bootstrapped PoS state.
+    ///
+    /// Hash maps are hashed in the order of their keys, and zero deferred credits are ignored.
+    pub fn compute_hash(&self) -> Hash {
+        let mut bytes = Vec::new();
+        for cycle_info in self.cycle_history.iter() {
+            bytes.extend(cycle_info.compute_hash().to_bytes());
+        }
+        for (slot, credits) in self.deferred_credits.0.iter() {
+            let credits: BTreeMap<&Address, &Amount> = credits
+                .iter()
+                .filter(|(_, amount)| !amount.is_zero())
+                .collect();
+            if credits.is_empty() {
+                continue;
+            }
+            bytes.extend(slot.to_bytes_key());
+            bytes.extend((credits.len() as u64).to_be_bytes());
+            for (addr, amount) in credits {
+                bytes.extend(addr.to_bytes());
+                bytes.extend(amount.to_raw().to_be_bytes());
+            }
+        }
+        Hash::compute_from(&bytes)
+    }
+
     /// Retrives the productions statistics for all addresses on a given cycle
     pub fn get_all_production_stats(
         &self,
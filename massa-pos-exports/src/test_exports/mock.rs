@@ -57,6 +57,11 @@ pub enum MockSelectorControllerMessage {
         /// Receiver to send the result to
         response_tx: mpsc::Sender<PosResult<Selection>>,
     },
+    /// Get the number of cycles whose draws are cached
+    GetDrawCacheSize {
+        /// Receiver to send the result to
+        response_tx: mpsc::Sender<usize>,
+    },
     /// Wait for draws
     WaitForDraws {
         /// Cycle to wait for
@@ -166,6 +171,15 @@ impl SelectorController for MockSelectorController {
         response_rx.recv().unwrap()
     }
 
+    fn get_draw_cache_size(&self) -> usize {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .send(MockSelectorControllerMessage::GetDrawCacheSize { response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn clone_box(&self) -> Box<dyn SelectorController> {
         Box::new(self.clone())
     }
@@ -496,6 +496,34 @@ pub struct CycleInfo {
     pub production_stats: PreHashMap<Address, ProductionStats>,
 }
 
+impl CycleInfo {
+    /// Compute a hash committing to the whole cycle information.
+    /// Hash maps are hashed in the order of their keys.
+    ///
+    /// Complete cycles are final, so their hashes can be compared between nodes.
+    pub fn compute_hash(&self) -> Hash {
+        let mut bytes = Vec::new();
+        bytes.extend(self.cycle.to_be_bytes());
+        bytes.push(u8::from(self.complete));
+        bytes.extend((self.roll_counts.len() as u64).to_be_bytes());
+        for (addr, roll_count) in self.roll_counts.iter() {
+            bytes.extend(addr.to_bytes());
+            bytes.extend(roll_count.to_be_bytes());
+        }
+        bytes.extend((self.rng_seed.len() as u64).to_be_bytes());
+        bytes.extend(self.rng_seed.iter().by_vals().map(u8::from));
+        let production_stats: BTreeMap<&Address, &ProductionStats> =
+            self.production_stats.iter().collect();
+        bytes.extend((production_stats.len() as u64).to_be_bytes());
+        for (addr, stats) in production_stats {
+            bytes.extend(addr.to_bytes());
+            bytes.extend(stats.block_success_count.to_be_bytes());
+            bytes.extend(stats.block_failure_count.to_be_bytes());
+        }
+        Hash::compute_from(&bytes)
+    }
+}
+
 /// Block production statistic
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub struct ProductionStats {
@@ -112,6 +112,13 @@ impl SelectorController for SelectorControllerImpl {
             .ok_or(PosError::CycleUnavailable(cycle))
     }
 
+    /// Get the number of cycles whose draws are currently cached
+    fn get_draw_cache_size(&self) -> usize {
+        let (_cache_cv, cache_lock) = &*self.cache;
+        let cache_guard = cache_lock.read();
+        cache_guard.as_ref().map_or(0, |cache| cache.0.len())
+    }
+
     /// Return a list of slots where `address` has been choosen to produce a
     /// block and a list where he is choosen for the endorsements.
     /// Look from the `start` slot to the `end` slot.
@@ -16,10 +16,11 @@ use massa_models::{
     prehash::{CapacityAllocator, PreHashSet},
     wrapped::{Id, Wrapped},
 };
-use massa_network_exports::{AskForBlocksInfo, BlockInfoReply, NetworkEvent};
+use massa_network_exports::{AskForBlocksInfo, BlockInfoReply, NetworkEvent, PeerScoreEvent};
 use massa_protocol_exports::{ProtocolError, ProtocolEvent};
 use massa_serialization::Serializer;
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use tokio::time::{Instant, Sleep};
 use tracing::{info, warn};
 
@@ -110,6 +111,9 @@ impl ProtocolWorker {
                         or a loss of sync between us and the remote node",
                         source_node_id,
                     );
+                    let _ = self
+                        .note_peer_score_event(&source_node_id, PeerScoreEvent::InvalidHeader)
+                        .await;
                     let _ = self.ban_node(&source_node_id).await;
                 }
             }
@@ -261,6 +265,9 @@ impl ProtocolWorker {
                 or a loss of sync between us and the remote node. Err = {}",
                 from_node_id, err
             );
+            let _ = self
+                .note_peer_score_event(&from_node_id, PeerScoreEvent::InvalidHeader)
+                .await;
             let _ = self.ban_node(&from_node_id).await;
             return Ok(());
         };
@@ -493,6 +500,7 @@ impl ProtocolWorker {
                         id: block_id,
                         content: block,
                         serialized_data: content_serialized,
+                        multisig: None,
                     };
 
                     // create block storage (without parents)
@@ -521,6 +529,12 @@ impl ProtocolWorker {
                 return Ok(());
             }
         };
+        if let ProtocolEvent::ReceivedBlock { .. } = protocol_event_full_block {
+            let _ = self
+                .note_peer_score_event(&from_node_id, PeerScoreEvent::UsefulBlock)
+                .await;
+        }
+
         // Send to graph
         self.send_protocol_event(protocol_event_full_block).await;
 
@@ -535,6 +549,17 @@ impl ProtocolWorker {
         block_id: BlockId,
         info: BlockInfoReply,
     ) -> Result<(), ProtocolError> {
+        // note how long the node took to answer our request
+        let ask_time_opt = self
+            .active_nodes
+            .get(&from_node_id)
+            .and_then(|node| node.asked_blocks.get(&block_id).copied());
+        if let Some(ask_time) = ask_time_opt {
+            let latency = MassaTime::try_from(ask_time.elapsed())?;
+            let _ = self
+                .note_peer_score_event(&from_node_id, PeerScoreEvent::Latency(latency))
+                .await;
+        }
         match info {
             BlockInfoReply::Header(header) => {
                 // Verify and Send it consensus
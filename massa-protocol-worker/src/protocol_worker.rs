@@ -15,7 +15,9 @@ use massa_models::{
     operation::{OperationId, WrappedOperation},
     prehash::{CapacityAllocator, PreHashMap, PreHashSet},
 };
-use massa_network_exports::{AskForBlocksInfo, NetworkCommandSender, NetworkEventReceiver};
+use massa_network_exports::{
+    AskForBlocksInfo, NetworkCommandSender, NetworkEventReceiver, PeerScoreEvent,
+};
 use massa_pool_exports::PoolController;
 use massa_protocol_exports::{
     ProtocolCommand, ProtocolCommandSender, ProtocolConfig, ProtocolError, ProtocolEvent,
@@ -519,6 +521,8 @@ impl ProtocolWorker {
         let mut candidate_nodes: PreHashMap<BlockId, Vec<_>> = Default::default();
         let mut ask_block_list: HashMap<NodeId, Vec<(BlockId, AskForBlocksInfo)>> =
             Default::default();
+        // nodes that just timed out on a block request
+        let mut unanswered_nodes: Vec<NodeId> = Vec::new();
 
         // list blocks to re-ask and from whom
         for (hash, block_info) in self.block_wishlist.iter() {
@@ -589,6 +593,7 @@ impl ProtocolWorker {
                                 timeout_at,
                                 self.config.max_node_known_blocks_size,
                             );
+                            unanswered_nodes.push(*node_id);
                             (2u8, ask_time_opt)
                         } else {
                             // told us it has it after a timeout: good candidate again
@@ -616,6 +621,7 @@ impl ProtocolWorker {
                             timeout_at,
                             self.config.max_node_known_blocks_size,
                         );
+                        unanswered_nodes.push(*node_id);
                         (2u8, ask_time_opt)
                     }
                 };
@@ -634,6 +640,12 @@ impl ProtocolWorker {
             }
         }
 
+        // lower the score of the nodes that did not answer in time
+        for node_id in unanswered_nodes {
+            self.note_peer_score_event(&node_id, PeerScoreEvent::UnansweredAskForBlocks)
+                .await?;
+        }
+
         // count active block requests per node
         let mut active_block_req_count: HashMap<NodeId, usize> = self
             .active_nodes
@@ -723,6 +735,21 @@ impl ProtocolWorker {
         Ok(())
     }
 
+    /// Notes a protocol event affecting the score of a node.
+    pub(crate) async fn note_peer_score_event(
+        &mut self,
+        node_id: &NodeId,
+        event: PeerScoreEvent,
+    ) -> Result<(), ProtocolError> {
+        massa_trace!("protocol.protocol_worker.note_peer_score_event", { "node": node_id, "event": event });
+        self.network_command_sender
+            .note_peer_score_event(*node_id, event)
+            .await
+            .map_err(|_| {
+                ProtocolError::ChannelError("note peer score event command send failed".into())
+            })
+    }
+
     /// Perform checks on a header,
     /// and if valid update the node's view of the world.
     ///
@@ -909,7 +936,7 @@ impl ProtocolWorker {
     /// - the sum of all operation's `max_gas`.
     ///
     /// Checks performed:
-    /// - Valid signature
+    /// - Valid signature, or enough valid signatures if the creator is a multisig address
     pub(crate) async fn note_operations_from_node(
         &mut self,
         operations: Vec<WrappedOperation>,
@@ -17,6 +17,7 @@ use massa_models::{
     operation::{OperationPrefixIds, WrappedOperation},
     prehash::{CapacityAllocator, PreHashSet},
 };
+use massa_network_exports::PeerScoreEvent;
 use massa_protocol_exports::ProtocolError;
 use massa_time::TimeError;
 use tokio::time::{sleep_until, Instant, Sleep};
@@ -137,6 +138,15 @@ impl ProtocolWorker {
         node_id: NodeId,
         operations: Vec<WrappedOperation>,
     ) {
+        // operations are only sent on request, so we should not already have them
+        if operations
+            .iter()
+            .any(|op| self.checked_operations.contains(&op.id.prefix()))
+        {
+            let _ = self
+                .note_peer_score_event(&node_id, PeerScoreEvent::DuplicateOperations)
+                .await;
+        }
         if let Err(err) = self.note_operations_from_node(operations, &node_id).await {
             warn!("node {} sent us critically incorrect operation, which may be an attack attempt by the remote node or a loss of sync between us and the remote node. Err = {}", node_id, err);
             let _ = self.ban_node(&node_id).await;
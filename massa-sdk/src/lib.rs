@@ -7,16 +7,21 @@
 use jsonrpc_core_client::transports::http;
 use jsonrpc_core_client::{RpcChannel, RpcError, RpcResult, TypedClient};
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressHistoryPage, AddressHistoryRequest, AddressInfo, BlockInfo, BlockSummary,
+    DatastoreEntryInput, DatastoreEntryOutput, EndorsementInfo, EstimateOperation, EventFilter,
+    LedgerEntryAtSlotInput, LedgerEntryAtSlotOutput, LedgerProofInput, LedgerProofOutput,
+    NodeStatus, OperationInfo, OperationInput, ReadOnlyBytecodeExecution, ReadOnlyCall,
+    TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
-use massa_models::execution::ExecuteReadOnlyResponse;
+use massa_models::execution::{
+    ExecuteReadOnlyResponse, OperationEstimation, OperationTrace, OperationsSimulation,
+};
 use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::{PreHashMap, PreHashSet};
+use massa_models::stats::PeerStats;
 use massa_models::{
     address::Address, block::BlockId, endorsement::EndorsementId, operation::OperationId,
 };
@@ -155,6 +160,12 @@ impl RpcClient {
             .await
     }
 
+    /// traffic stats of every active connection
+    pub async fn get_peers_stats(&self) -> RpcResult<Vec<PeerStats>> {
+        self.call_method("get_peers_stats", "Vec<PeerStats>", ())
+            .await
+    }
+
     ////////////////
     // public-api //
     ////////////////
@@ -235,6 +246,28 @@ impl RpcClient {
             .await
     }
 
+    /// Get a page of the final history of an address
+    pub async fn get_address_history(
+        &self,
+        request: AddressHistoryRequest,
+    ) -> RpcResult<AddressHistoryPage> {
+        self.call_method("get_address_history", "AddressHistoryPage", vec![request])
+            .await
+    }
+
+    /// Get the execution traces of operations
+    pub async fn get_operation_traces(
+        &self,
+        operation_ids: Vec<OperationId>,
+    ) -> RpcResult<Vec<OperationTrace>> {
+        self.call_method(
+            "get_operation_traces",
+            "Vec<OperationTrace>",
+            vec![operation_ids],
+        )
+        .await
+    }
+
     /// Get datastore entries
     pub async fn get_datastore_entries(
         &self,
@@ -248,6 +281,28 @@ impl RpcClient {
         .await
     }
 
+    /// Get final ledger entries as they were at past final slots
+    pub async fn get_ledger_entries_at_slot(
+        &self,
+        input: Vec<LedgerEntryAtSlotInput>,
+    ) -> RpcResult<Vec<LedgerEntryAtSlotOutput>> {
+        self.call_method(
+            "get_ledger_entries_at_slot",
+            "Vec<LedgerEntryAtSlotOutput>",
+            vec![input],
+        )
+        .await
+    }
+
+    /// Get final ledger values along with their Merkle proof against the final ledger root
+    pub async fn get_ledger_proofs(
+        &self,
+        input: Vec<LedgerProofInput>,
+    ) -> RpcResult<Vec<LedgerProofOutput>> {
+        self.call_method("get_ledger_proofs", "Vec<LedgerProofOutput>", vec![input])
+            .await
+    }
+
     // User (interaction with the node)
 
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
@@ -290,4 +345,32 @@ impl RpcClient {
         .pop()
         .ok_or_else(|| RpcError::Client("missing return value on execute_read_only_call".into()))
     }
+
+    /// estimate the gas cost, effects and fee of an operation
+    pub async fn estimate_operation(
+        &self,
+        estimate_operation: EstimateOperation,
+    ) -> RpcResult<OperationEstimation> {
+        self.call_method::<Vec<Vec<EstimateOperation>>, Vec<OperationEstimation>>(
+            "estimate_operation",
+            "Vec<OperationEstimation>",
+            vec![vec![estimate_operation]],
+        )
+        .await?
+        .pop()
+        .ok_or_else(|| RpcError::Client("missing return value on estimate_operation".into()))
+    }
+
+    /// execute signed operations in read-only mode without broadcasting them
+    pub async fn simulate_operations(
+        &self,
+        operations: Vec<OperationInput>,
+    ) -> RpcResult<OperationsSimulation> {
+        self.call_method(
+            "simulate_operations",
+            "OperationsSimulation",
+            vec![operations],
+        )
+        .await
+    }
 }
@@ -32,6 +32,23 @@ use std::fmt::Debug;
 use std::hash::Hash;
 use std::{collections::hash_map, sync::Arc};
 
+/// Number of stored objects and of references claimed on them, across all `Storage` instances
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageStats {
+    /// number of stored blocks
+    pub block_count: usize,
+    /// total number of references claimed on stored blocks
+    pub block_ref_count: usize,
+    /// number of stored operations
+    pub operation_count: usize,
+    /// total number of references claimed on stored operations
+    pub operation_ref_count: usize,
+    /// number of stored endorsements
+    pub endorsement_count: usize,
+    /// total number of references claimed on stored endorsements
+    pub endorsement_ref_count: usize,
+}
+
 /// A storage system for objects (blocks, operations...), shared by various components.
 pub struct Storage {
     /// global block storage
@@ -128,6 +145,30 @@ impl Storage {
         }
     }
 
+    /// Gets the number of stored objects and of references claimed on them, across all instances
+    pub fn get_stats(&self) -> StorageStats {
+        let (block_count, block_ref_count) = {
+            let owners = self.block_owners.read();
+            (owners.len(), owners.values().sum())
+        };
+        let (operation_count, operation_ref_count) = {
+            let owners = self.operation_owners.read();
+            (owners.len(), owners.values().sum())
+        };
+        let (endorsement_count, endorsement_ref_count) = {
+            let owners = self.endorsement_owners.read();
+            (owners.len(), owners.values().sum())
+        };
+        StorageStats {
+            block_count,
+            block_ref_count,
+            operation_count,
+            operation_ref_count,
+            endorsement_count,
+            endorsement_ref_count,
+        }
+    }
+
     /// Efficiently extends the current Storage by consuming the refs of another's.
     pub fn extend(&mut self, mut other: Storage) {
         // Take ownership ot `other`'s references.
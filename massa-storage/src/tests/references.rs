@@ -74,3 +74,21 @@ fn test_retrieve_all_ref_dropped_automatically() {
         assert!(blocks.get(&block.id).is_none());
     };
 }
+
+#[test]
+fn test_stats() {
+    let mut storage = Storage::create_root();
+    let slot = Slot::new(0, 0);
+    let block = create_empty_block(&KeyPair::generate(), &slot);
+
+    storage.store_block(block);
+    let storage2 = storage.clone();
+    let stats = storage.clone_without_refs().get_stats();
+    assert_eq!(stats.block_count, 1);
+    assert_eq!(stats.block_ref_count, 2);
+    assert_eq!(stats.operation_count, 0);
+    assert_eq!(stats.endorsement_count, 0);
+
+    drop(storage2);
+    assert_eq!(storage.get_stats().block_ref_count, 1);
+}
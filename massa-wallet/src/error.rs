@@ -22,4 +22,20 @@ pub enum WalletError {
     MissingKeyError(Address),
     /// `MassaCipher` error: {0}
     MassaCipherError(#[from] massa_cipher::CipherError),
+    /// `MassaSignature` error: {0}
+    MassaSignatureError(#[from] massa_signature::MassaSignatureError),
+    /// Seed phrase error: {0}
+    SeedPhraseError(String),
+    /// The wallet has no seed phrase
+    MissingSeedPhrase,
+    /// The wallet already has a seed phrase
+    SeedPhraseAlreadySet,
+    /// The wallet holds none of the keys of the multisig address
+    MissingMultisigSigner,
+    /// Invalid label {0}: labels cannot be empty, contain whitespace or be an address
+    InvalidLabel(String),
+    /// Label {0} is already used for {1}
+    LabelAlreadyUsed(String, Address),
+    /// {0} is neither an address nor a label of the wallet
+    UnknownAddressOrLabel(String),
 }
@@ -0,0 +1,150 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Hierarchical deterministic accounts:
+//! BIP-39 seed phrases and SLIP-10 ed25519 key derivation.
+
+use crate::WalletError;
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
+use massa_signature::{KeyPair, SECRET_KEY_BYTES_SIZE};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+
+/// Number of words of the generated seed phrases
+pub const SEED_PHRASE_WORD_COUNT: usize = 24;
+
+/// Coin type registered for Massa in SLIP-44
+pub const MASSA_COIN_TYPE: u32 = 632;
+
+/// SLIP-10 only defines hardened derivation for ed25519
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// HMAC key of the SLIP-10 master key generation for ed25519
+const SLIP10_ED25519_CURVE: &[u8] = b"ed25519 seed";
+
+/// BIP-39 seed phrase the HD accounts of a wallet are derived from
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SeedPhrase {
+    /// mnemonic words, separated by single spaces
+    pub mnemonic: String,
+    /// index of the next account to derive
+    pub next_account: u32,
+}
+
+impl SeedPhrase {
+    /// Generates a new random seed phrase of `SEED_PHRASE_WORD_COUNT` words
+    pub fn generate() -> Self {
+        let mut entropy = [0u8; SEED_PHRASE_WORD_COUNT / 3 * 4];
+        rand::thread_rng().fill_bytes(&mut entropy);
+        let mnemonic = Mnemonic::from_entropy(&entropy)
+            .expect("entropy length is valid for a BIP-39 mnemonic");
+        SeedPhrase {
+            mnemonic: mnemonic.to_string(),
+            next_account: 0,
+        }
+    }
+
+    /// Checks an existing seed phrase (word list and checksum) and normalizes it
+    pub fn from_mnemonic(phrase: &str) -> Result<Self, WalletError> {
+        let mnemonic = Mnemonic::parse(phrase.trim().to_lowercase())
+            .map_err(|err| WalletError::SeedPhraseError(err.to_string()))?;
+        Ok(SeedPhrase {
+            mnemonic: mnemonic.to_string(),
+            next_account: 0,
+        })
+    }
+
+    /// Derives the keypair of an account, at the SLIP-10 path given by `account_derivation_path`
+    pub fn derive_account(&self, account: u32) -> Result<KeyPair, WalletError> {
+        let mnemonic = Mnemonic::parse(&self.mnemonic)
+            .map_err(|err| WalletError::SeedPhraseError(err.to_string()))?;
+        let secret_key =
+            derive_ed25519_secret_key(&mnemonic.to_seed(""), &account_derivation_path(account));
+        Ok(KeyPair::from_bytes(&secret_key)?)
+    }
+}
+
+/// Derivation path of an account: `m/44'/632'/account'/0'/0'`.
+/// All levels are hardened.
+pub fn account_derivation_path(account: u32) -> [u32; 5] {
+    [44, MASSA_COIN_TYPE, account, 0, 0]
+}
+
+/// SLIP-10 ed25519 derivation of the secret key at `path` from a BIP-39 seed.
+/// Path indices are hardened by this function.
+fn derive_ed25519_secret_key(seed: &[u8], path: &[u32]) -> [u8; SECRET_KEY_BYTES_SIZE] {
+    let (mut secret_key, mut chain_code) = hmac_sha512(SLIP10_ED25519_CURVE, &[seed]);
+    for index in path {
+        (secret_key, chain_code) = hmac_sha512(
+            &chain_code,
+            &[
+                &[0u8],
+                &secret_key,
+                &(index | HARDENED_OFFSET).to_be_bytes(),
+            ],
+        );
+    }
+    secret_key
+}
+
+/// Computes HMAC-SHA512 of the concatenated `data`, split in a secret key and a chain code
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    for chunk in data {
+        mac.update(chunk);
+    }
+    let output = mac.finalize().into_bytes();
+    let mut secret_key = [0u8; 32];
+    let mut chain_code = [0u8; 32];
+    secret_key.copy_from_slice(&output[..32]);
+    chain_code.copy_from_slice(&output[32..]);
+    (secret_key, chain_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SLIP-10 test vector 1 for ed25519
+    #[test]
+    fn test_slip10_ed25519_vector() {
+        let seed: Vec<u8> = (0u8..16).collect();
+        assert_eq!(
+            derive_ed25519_secret_key(&seed, &[]).to_vec(),
+            hex_decode("2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7")
+        );
+        assert_eq!(
+            derive_ed25519_secret_key(&seed, &[0]).to_vec(),
+            hex_decode("68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3")
+        );
+    }
+
+    #[test]
+    fn test_seed_phrase_restore() {
+        let seed_phrase = SeedPhrase::generate();
+        assert_eq!(
+            seed_phrase.mnemonic.split(' ').count(),
+            SEED_PHRASE_WORD_COUNT
+        );
+        let restored = SeedPhrase::from_mnemonic(&seed_phrase.mnemonic.to_uppercase()).unwrap();
+        assert_eq!(restored.mnemonic, seed_phrase.mnemonic);
+        for account in 0..3 {
+            assert_eq!(
+                restored.derive_account(account).unwrap().to_bytes(),
+                seed_phrase.derive_account(account).unwrap().to_bytes()
+            );
+        }
+        assert_ne!(
+            seed_phrase.derive_account(0).unwrap().to_bytes(),
+            seed_phrase.derive_account(1).unwrap().to_bytes()
+        );
+    }
+
+    fn hex_decode(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}
@@ -6,29 +6,60 @@
 #![feature(map_try_insert)]
 
 pub use error::WalletError;
+pub use hd::{account_derivation_path, SeedPhrase, MASSA_COIN_TYPE, SEED_PHRASE_WORD_COUNT};
 
-use massa_cipher::{decrypt, encrypt};
+pub use massa_cipher::Argon2Params;
+
+use massa_cipher::{decrypt, encrypt_with_params, encryption_params, VERSION};
 use massa_hash::Hash;
 use massa_models::address::Address;
 use massa_models::composite::PubkeySig;
+use massa_models::error::ModelsError;
+use massa_models::multisig::MultisigSignatures;
 use massa_models::operation::{Operation, OperationSerializer, WrappedOperation};
 use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::wrapped::WrappedContent;
+use massa_serialization::Serializer;
 use massa_signature::{KeyPair, PublicKey};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 mod error;
+mod hd;
 
 /// Contains the keypairs created in the wallet.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Wallet {
     /// Keypairs and addresses
     pub keys: PreHashMap<Address, KeyPair>,
+    /// Seed phrase the HD accounts of the wallet are derived from, if any
+    pub seed_phrase: Option<SeedPhrase>,
+    /// Addresses followed by the wallet without their keypair
+    pub watch_only: PreHashSet<Address>,
+    /// Labels given to the addresses of the wallet, usable instead of the addresses
+    pub labels: PreHashMap<Address, String>,
     /// Path to the file containing the keypairs (encrypted)
     pub wallet_path: PathBuf,
     /// Password
     pub password: String,
+    /// Argon2id cost parameters with which the password is hashed to encrypt the wallet file
+    #[serde(default)]
+    pub argon2_params: Argon2Params,
+}
+
+/// Content of the wallet file, before encryption
+#[derive(Deserialize, Serialize)]
+struct WalletFileContent {
+    keys: PreHashMap<Address, KeyPair>,
+    #[serde(default)]
+    seed_phrase: Option<SeedPhrase>,
+    #[serde(default)]
+    watch_only: PreHashSet<Address>,
+    #[serde(default)]
+    labels: PreHashMap<Address, String>,
 }
 
 impl Wallet {
@@ -36,19 +67,45 @@ impl Wallet {
     pub fn new(path: PathBuf, password: String) -> Result<Wallet, WalletError> {
         if path.is_file() {
             let content = &std::fs::read(&path)?[..];
-            let (_version, decrypted_content) = decrypt(&password, content)?;
-            let keys =
-                serde_json::from_slice::<PreHashMap<Address, KeyPair>>(&decrypted_content[..])?;
-            Ok(Wallet {
-                keys,
+            let (version, decrypted_content) = decrypt(&password, content)?;
+            // keep the cost parameters of the file, older versions get the default ones
+            let argon2_params = encryption_params(content)?.unwrap_or_default();
+            let file_content =
+                match serde_json::from_slice::<WalletFileContent>(&decrypted_content[..]) {
+                    Ok(file_content) => file_content,
+                    // wallet files written before seed phrases only contain the keypairs
+                    Err(_) => WalletFileContent {
+                        keys: serde_json::from_slice::<PreHashMap<Address, KeyPair>>(
+                            &decrypted_content[..],
+                        )?,
+                        seed_phrase: None,
+                        watch_only: PreHashSet::default(),
+                        labels: PreHashMap::default(),
+                    },
+                };
+            let wallet = Wallet {
+                keys: file_content.keys,
+                seed_phrase: file_content.seed_phrase,
+                watch_only: file_content.watch_only,
+                labels: file_content.labels,
                 wallet_path: path,
                 password,
-            })
+                argon2_params,
+            };
+            // migrate wallet files encrypted with an older cipher version
+            if version != VERSION {
+                wallet.save()?;
+            }
+            Ok(wallet)
         } else {
             let wallet = Wallet {
                 keys: PreHashMap::default(),
+                seed_phrase: None,
+                watch_only: PreHashSet::default(),
+                labels: PreHashMap::default(),
                 wallet_path: path,
                 password,
+                argon2_params: Argon2Params::default(),
             };
             wallet.save()?;
             Ok(wallet)
@@ -81,6 +138,8 @@ impl Wallet {
         for key in keys {
             let addr = Address::from_public_key(&key.get_public_key());
             if self.keys.try_insert(addr, key).is_ok() {
+                // the address is no longer watch-only once its keypair is known
+                self.watch_only.remove(&addr);
                 changed = true;
             }
             addrs.push(addr);
@@ -91,6 +150,67 @@ impl Wallet {
         Ok(addrs)
     }
 
+    /// Generates a new seed phrase for the wallet, from which `derive_next_account` derives accounts.
+    /// Returns the seed phrase: it must be written down to be able to restore the accounts.
+    /// The wallet file is updated.
+    pub fn generate_seed_phrase(&mut self) -> Result<String, WalletError> {
+        if self.seed_phrase.is_some() {
+            return Err(WalletError::SeedPhraseAlreadySet);
+        }
+        let seed_phrase = SeedPhrase::generate();
+        let mnemonic = seed_phrase.mnemonic.clone();
+        self.seed_phrase = Some(seed_phrase);
+        self.save()?;
+        Ok(mnemonic)
+    }
+
+    /// Derives the next account from the seed phrase of the wallet, adds its keypair and returns its address.
+    /// The wallet file is updated.
+    pub fn derive_next_account(&mut self) -> Result<Address, WalletError> {
+        let seed_phrase = self
+            .seed_phrase
+            .as_mut()
+            .ok_or(WalletError::MissingSeedPhrase)?;
+        let keypair = seed_phrase.derive_account(seed_phrase.next_account)?;
+        seed_phrase.next_account = seed_phrase.next_account.checked_add(1).ok_or_else(|| {
+            WalletError::SeedPhraseError("all the accounts were derived".to_string())
+        })?;
+        let address = Address::from_public_key(&keypair.get_public_key());
+        self.keys.insert(address, keypair);
+        self.watch_only.remove(&address);
+        self.save()?;
+        Ok(address)
+    }
+
+    /// Restores the first `account_count` accounts derived from a seed phrase, returns their addresses.
+    /// The phrase becomes the seed phrase of the wallet: fails if the wallet already has another one.
+    /// The wallet file is updated.
+    pub fn restore_seed_phrase(
+        &mut self,
+        phrase: &str,
+        account_count: u32,
+    ) -> Result<Vec<Address>, WalletError> {
+        let mut seed_phrase = SeedPhrase::from_mnemonic(phrase)?;
+        if let Some(current) = &self.seed_phrase {
+            if current.mnemonic != seed_phrase.mnemonic {
+                return Err(WalletError::SeedPhraseAlreadySet);
+            }
+            seed_phrase.next_account = current.next_account;
+        }
+        seed_phrase.next_account = seed_phrase.next_account.max(account_count);
+        let mut addresses = Vec::with_capacity(account_count as usize);
+        for account in 0..account_count {
+            let keypair = seed_phrase.derive_account(account)?;
+            let address = Address::from_public_key(&keypair.get_public_key());
+            self.keys.insert(address, keypair);
+            self.watch_only.remove(&address);
+            addresses.push(address);
+        }
+        self.seed_phrase = Some(seed_phrase);
+        self.save()?;
+        Ok(addresses)
+    }
+
     /// Removes wallet entries given a list of addresses. Missing entries are ignored.
     /// The wallet file is updated.
     pub fn remove_addresses(&mut self, addresses: &Vec<Address>) -> Result<(), WalletError> {
@@ -99,6 +219,27 @@ impl Wallet {
             if self.keys.remove(address).is_some() {
                 changed = true;
             }
+            if self.watch_only.remove(address) {
+                changed = true;
+            }
+            if self.labels.remove(address).is_some() {
+                changed = true;
+            }
+        }
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Adds addresses to follow without their keypair.
+    /// Addresses whose keypair is in the wallet are ignored. The wallet file is updated.
+    pub fn add_watch_only(&mut self, addresses: &[Address]) -> Result<(), WalletError> {
+        let mut changed = false;
+        for address in addresses {
+            if !self.keys.contains_key(address) && self.watch_only.insert(*address) {
+                changed = true;
+            }
         }
         if changed {
             self.save()?;
@@ -106,6 +247,52 @@ impl Wallet {
         Ok(())
     }
 
+    /// Gives a label to an address of the wallet, replacing its previous label if any.
+    /// Labels are unique, and cannot contain whitespace or be parsed as an address.
+    /// The wallet file is updated.
+    pub fn set_label(&mut self, address: Address, label: String) -> Result<(), WalletError> {
+        if !self.keys.contains_key(&address) && !self.watch_only.contains(&address) {
+            return Err(WalletError::MissingKeyError(address));
+        }
+        if label.is_empty()
+            || label.contains(char::is_whitespace)
+            || Address::from_str(&label).is_ok()
+        {
+            return Err(WalletError::InvalidLabel(label));
+        }
+        if let Some(labeled) = self.find_labeled_address(&label) {
+            if labeled != address {
+                return Err(WalletError::LabelAlreadyUsed(label, labeled));
+            }
+        }
+        self.labels.insert(address, label);
+        self.save()
+    }
+
+    /// Removes the label of an address. The wallet file is updated.
+    pub fn remove_label(&mut self, address: &Address) -> Result<(), WalletError> {
+        if self.labels.remove(address).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Finds the address with the given label
+    pub fn find_labeled_address(&self, label: &str) -> Option<Address> {
+        self.labels
+            .iter()
+            .find(|(_, address_label)| address_label.as_str() == label)
+            .map(|(address, _)| *address)
+    }
+
+    /// Parses an address, or finds the address of a label of the wallet
+    pub fn resolve_address(&self, name: &str) -> Result<Address, WalletError> {
+        if let Some(address) = self.find_labeled_address(name) {
+            return Ok(address);
+        }
+        Address::from_str(name).map_err(|_| WalletError::UnknownAddressOrLabel(name.to_string()))
+    }
+
     /// Finds the keypair associated with given address
     pub fn find_associated_keypair(&self, address: &Address) -> Option<&KeyPair> {
         self.keys.get(address)
@@ -118,17 +305,62 @@ impl Wallet {
             .map(|keypair| keypair.get_public_key())
     }
 
-    /// Get all addresses in the wallet
+    /// Get all addresses in the wallet whose keypair is known
     pub fn get_wallet_address_list(&self) -> PreHashSet<Address> {
         self.keys.keys().copied().collect()
     }
 
+    /// Get all addresses in the wallet, including the watch-only ones
+    pub fn get_all_addresses(&self) -> PreHashSet<Address> {
+        self.keys
+            .keys()
+            .chain(self.watch_only.iter())
+            .copied()
+            .collect()
+    }
+
     /// Save the wallet in json format in a file
-    /// Only the keypair is dumped
+    /// Only the keypairs, the seed phrase, the watch-only addresses and the labels are dumped
     fn save(&self) -> Result<(), WalletError> {
-        let ser_keys = serde_json::to_string(&self.keys)?;
-        let encrypted_content = encrypt(&self.password, ser_keys.as_bytes())?;
-        std::fs::write(&self.wallet_path, encrypted_content)?;
+        let ser_content = serde_json::to_string(&WalletFileContent {
+            keys: self.keys.clone(),
+            seed_phrase: self.seed_phrase.clone(),
+            watch_only: self.watch_only.clone(),
+            labels: self.labels.clone(),
+        })?;
+        let encrypted_content =
+            encrypt_with_params(&self.password, ser_content.as_bytes(), &self.argon2_params)?;
+        // write to a temporary file first, so that the wallet file is never left partially written,
+        // and flush it to the disk before it replaces the wallet file
+        let mut tmp_path = self.wallet_path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let mut tmp_file = File::create(&tmp_path)?;
+        tmp_file.write_all(&encrypted_content)?;
+        tmp_file.sync_all()?;
+        std::fs::rename(&tmp_path, &self.wallet_path)?;
+        sync_parent_dir(&self.wallet_path)?;
+        Ok(())
+    }
+
+    /// Encrypts the wallet file again with a new password.
+    /// The password is left unchanged if the file could not be written.
+    pub fn change_password(&mut self, new_password: String) -> Result<(), WalletError> {
+        let old_password = std::mem::replace(&mut self.password, new_password);
+        if let Err(err) = self.save() {
+            self.password = old_password;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Encrypts the wallet file again with new Argon2id cost parameters.
+    /// The parameters are left unchanged if they are out of range or if the file could not be written.
+    pub fn set_argon2_params(&mut self, argon2_params: Argon2Params) -> Result<(), WalletError> {
+        let old_params = std::mem::replace(&mut self.argon2_params, argon2_params);
+        if let Err(err) = self.save() {
+            self.argon2_params = old_params;
+            return Err(err);
+        }
         Ok(())
     }
 
@@ -148,16 +380,69 @@ impl Wallet {
             .ok_or(WalletError::MissingKeyError(address))?;
         Ok(Operation::new_wrapped(content, OperationSerializer::new(), sender_keypair).unwrap())
     }
+
+    /// Adds to `multisig` the signatures of every signer of its policy whose keypair is in the wallet.
+    /// Returns the addresses of those signers.
+    pub fn sign_multisig_operation(
+        &self,
+        content: &Operation,
+        multisig: &mut MultisigSignatures,
+    ) -> Result<Vec<Address>, WalletError> {
+        let mut serialized_content = Vec::new();
+        OperationSerializer::new()
+            .serialize(content, &mut serialized_content)
+            .map_err(ModelsError::from)?;
+        let hash = MultisigSignatures::compute_signed_hash(
+            &multisig.policy.address(),
+            &serialized_content,
+        );
+        let signers: Vec<Address> = multisig
+            .policy
+            .public_keys
+            .iter()
+            .map(Address::from_public_key)
+            .filter(|address| self.keys.contains_key(address))
+            .collect();
+        if signers.is_empty() {
+            return Err(WalletError::MissingMultisigSigner);
+        }
+        for address in &signers {
+            multisig.sign(&hash, &self.keys[address])?;
+        }
+        Ok(signers)
+    }
+}
+
+/// Flushes the directory entry of a renamed file to the disk, so that the rename survives a crash.
+/// Directories cannot be opened as files on Windows, where this is skipped.
+fn sync_parent_dir(path: &Path) -> Result<(), WalletError> {
+    if cfg!(unix) {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
 }
 
 impl std::fmt::Display for Wallet {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         writeln!(f)?;
         for (addr, keypair) in &self.keys {
+            if let Some(label) = self.labels.get(addr) {
+                writeln!(f, "Label: {}", label)?;
+            }
             writeln!(f, "Secret key: {}", keypair)?;
             writeln!(f, "Public key: {}", keypair.get_public_key())?;
             writeln!(f, "Address: {}", addr)?;
         }
+        for addr in &self.watch_only {
+            if let Some(label) = self.labels.get(addr) {
+                writeln!(f, "Label: {}", label)?;
+            }
+            writeln!(f, "Watch-only address: {}", addr)?;
+        }
         Ok(())
     }
 }